@@ -0,0 +1,806 @@
+use crate::conflict::is_conflict;
+use crate::copy::{apply_view, copy_view, find_unresolved_references, plan_view};
+use crate::exchange::{exchange_to_archi, is_exchange_format};
+use crate::file_descriptor::{read_xml_from, write_xml_into, FileKind, ZipCompression};
+use crate::glossary::display_type;
+use crate::ids::IdGenerator;
+use crate::model::{
+    find_missing_views, find_views_to_import, format_folder_path, is_view_type, serialize_model,
+    MissingElementInfo,
+};
+use crate::report::folder_paths;
+use crate::selection::{find_referenced_views, is_in_folder};
+use crate::view_hash::find_identical_views;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::{Read, Seek, Write};
+use std::sync::Arc;
+
+pub use crate::builder::ModelBuilder;
+pub use crate::concept_type::{Category, ConceptType, Layer};
+pub use crate::conflict::{ConceptConflict, ConflictPolicy};
+pub use crate::copy::{CopyHooks, CopyOptions, NameCollision, ViewEdits, ViewOutcome, ViewPlan};
+pub use crate::error::ImporterError;
+pub use crate::model::ElementInfo;
+pub use crate::model::MatchBy;
+pub use crate::model::{load_model, ArchiModel, Folder, FolderInfo};
+pub use crate::query::Query;
+pub use crate::search::{Search, SearchMatch};
+pub use crate::stats::ModelStats;
+pub use crate::validate::{IssueKind, ValidationIssue};
+pub use xot::Xot;
+
+/// What an import copies and how, set up like the `import` command's
+/// options. Without views or folders every view the target lacks is
+/// imported.
+///
+/// ```no_run
+/// use archi_view_importer::{import, ImportOptions};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let options = ImportOptions::new()
+///     .views(["Default View"])
+///     .include_model_relations(true);
+/// let report = import(&std::fs::read_to_string("source.archimate")?,
+///     &std::fs::read_to_string("target.archimate")?, &options)?;
+/// std::fs::write("target.archimate", report.model)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    views: Vec<String>,
+    folders: Vec<String>,
+    match_by: MatchBy,
+    update: bool,
+    keep_duplicates: bool,
+    recursive: bool,
+    strict: bool,
+    seed: Option<u64>,
+    copy: CopyOptions,
+}
+
+impl ImportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Imports the views with these names or IDs.
+    pub fn views<I, S>(mut self, views: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.views.extend(views.into_iter().map(Into::into));
+        self
+    }
+
+    /// Imports the views under these folder paths, as in `Views > Sales`.
+    pub fn folders<I, S>(mut self, folders: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.folders.extend(folders.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn match_by(mut self, match_by: MatchBy) -> Self {
+        self.match_by = match_by;
+        self
+    }
+
+    /// Also replaces target views that differ from their source view.
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Also imports views identical to a view the target already has.
+    pub fn keep_duplicates(mut self, keep_duplicates: bool) -> Self {
+        self.keep_duplicates = keep_duplicates;
+        self
+    }
+
+    /// Also imports the views that imported views link to.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Seeds the generated IDs, so the same import gives the same model.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// How concepts that differ between the models are settled. `Newer`
+    /// needs the files' modification times and is refused by [`import`].
+    pub fn on_conflict(mut self, policy: ConflictPolicy) -> Self {
+        self.copy.on_conflict = policy;
+        self
+    }
+
+    pub fn include_model_relations(mut self, include_model_relations: bool) -> Self {
+        self.copy.include_model_relations = include_model_relations;
+        self
+    }
+
+    pub fn no_relations(mut self, no_relations: bool) -> Self {
+        self.copy.no_relations = no_relations;
+        self
+    }
+
+    pub fn visual_only(mut self, visual_only: bool) -> Self {
+        self.copy.visual_only = visual_only;
+        self
+    }
+
+    pub fn create_stubs(mut self, create_stubs: bool) -> Self {
+        self.copy.create_stubs = create_stubs;
+        self
+    }
+
+    /// Fails the import, before anything is copied, when a selected view
+    /// shows concepts that neither model has.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Places every new view in this folder path, as in `Views > Imported`.
+    pub fn into_folder(mut self, folder: impl Into<String>) -> Self {
+        self.copy.into_folder = Some(folder.into());
+        self
+    }
+
+    pub fn reuse_by_name(mut self, reuse_by_name: bool) -> Self {
+        self.copy.reuse_by_name = reuse_by_name;
+        self
+    }
+
+    pub fn fresh_ids(mut self, fresh_ids: bool) -> Self {
+        self.copy.fresh_ids = fresh_ids;
+        self
+    }
+
+    pub fn on_name_collision(mut self, policy: NameCollision) -> Self {
+        self.copy.on_name_collision = policy;
+        self
+    }
+
+    /// Sets the `imported-from` property of every copied view and concept.
+    pub fn provenance(mut self, provenance: impl Into<String>) -> Self {
+        self.copy.provenance = Some(provenance.into());
+        self
+    }
+
+    /// Calls `hook` with each selected view once it is copied or has
+    /// failed, with how many of the selected views are done and how many
+    /// there are, to show progress.
+    pub fn on_view_copied(
+        mut self,
+        hook: impl Fn(&ViewOutcome, usize, usize) + Send + Sync + 'static,
+    ) -> Self {
+        self.copy.hooks.on_view_copied = Some(Arc::new(hook));
+        self
+    }
+
+    /// Calls `hook` with each element or relation added to the target.
+    pub fn on_element_copied(
+        mut self,
+        hook: impl Fn(&ElementInfo) + Send + Sync + 'static,
+    ) -> Self {
+        self.copy.hooks.on_element_copied = Some(Arc::new(hook));
+        self
+    }
+
+    /// Lets `hook` settle each conflicting concept, returning
+    /// `Some(ConflictPolicy::Theirs)` to take the source version,
+    /// `Some(ConflictPolicy::Ours)` to keep the target's, or `None` to leave
+    /// it to the `on_conflict` policy.
+    pub fn decide_conflicts(
+        mut self,
+        hook: impl Fn(&ConceptConflict) -> Option<ConflictPolicy> + Send + Sync + 'static,
+    ) -> Self {
+        self.copy.hooks.on_conflict = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets any other copy option the builder has no method for.
+    pub fn copy_options(mut self, copy: CopyOptions) -> Self {
+        self.copy = copy;
+        self
+    }
+}
+
+/// The outcome of an import: the merged model and what happened to each
+/// selected view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// The target model XML with the views imported.
+    pub model: String,
+    pub views: Vec<ViewOutcome>,
+    pub elements: usize,
+    pub relations: usize,
+    /// The seed of the generated IDs, to repeat the import.
+    pub seed: u64,
+}
+
+impl ImportReport {
+    /// How many of the selected views were imported.
+    pub fn imported(&self) -> usize {
+        self.views
+            .iter()
+            .filter(|view| view.error.is_none())
+            .count()
+    }
+
+    /// Writes the merged model to `writer`, as plain XML or, when `zipped`,
+    /// as an archive holding `model.xml` the way Archi saves it.
+    pub fn write_to<W: Write>(&self, writer: W, zipped: bool) -> Result<(), ImporterError> {
+        write_model(&self.model, writer, zipped)
+    }
+}
+
+/// Reads model XML from `reader`, such as a buffer, a network stream or a
+/// database blob, holding plain XML or an archive like those Archi saves;
+/// `password` decrypts an encrypted archive. The XML can be passed to
+/// `import`, `diff` or `load_model`.
+pub fn read_model_xml<R: Read + Seek>(
+    reader: R,
+    password: Option<&str>,
+) -> Result<String, ImporterError> {
+    read_xml_from(reader, password)
+}
+
+/// Reads and loads a model from `reader`, as `read_model_xml` and
+/// `load_model` do.
+pub fn read_model<R: Read + Seek>(reader: R) -> Result<ArchiModel, ImporterError> {
+    load_model(&read_model_xml(reader, None)?)
+}
+
+pub(crate) fn write_model<W: Write>(
+    xml: &str,
+    writer: W,
+    zipped: bool,
+) -> Result<(), ImporterError> {
+    let kind = if zipped {
+        FileKind::ZippedXml
+    } else {
+        FileKind::PlainXml
+    };
+    write_xml_into(writer, kind, None, xml, &[], ZipCompression::Deflate(None))
+}
+
+/// How two models differ, as seen from importing the source into the
+/// target.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelDiff {
+    /// Source views the target lacks, as `import` would offer them.
+    pub missing_views: Vec<DiffItem>,
+    /// Source elements the target lacks.
+    pub missing_elements: Vec<DiffItem>,
+    /// Source relations the target lacks.
+    pub missing_relations: Vec<DiffItem>,
+    /// Concepts both models have with different content.
+    pub conflicts: Vec<ConceptConflict>,
+    /// Folder paths, as in `Views > Sales`, only the source has.
+    pub source_only_folders: Vec<String>,
+    /// Folder paths only the target has.
+    pub target_only_folders: Vec<String>,
+}
+
+impl ModelDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == ModelDiff::default()
+    }
+}
+
+/// A view or concept of one model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffItem {
+    pub id: String,
+    pub name: String,
+    /// The type as ArchiMate names it, as in `Application Component`.
+    #[serde(rename = "type")]
+    pub concept_type: String,
+    pub folder: String,
+}
+
+impl DiffItem {
+    fn new(info: &ElementInfo) -> Self {
+        DiffItem {
+            id: info.id.to_string(),
+            name: info.name.to_string(),
+            concept_type: display_type(&info.xsi_type),
+            folder: format_folder_path(&info.folder_path),
+        }
+    }
+}
+
+/// Compares the `source` model with the `target` model, both given as Archi
+/// model XML, or a source in the Open Exchange Format.
+pub fn diff(source: &str, target: &str, match_by: MatchBy) -> Result<ModelDiff, ImporterError> {
+    let source = if is_exchange_format(source) {
+        exchange_to_archi(source)?
+    } else {
+        source.to_string()
+    };
+    diff_models(&load_model(&source)?, &load_model(target)?, match_by)
+}
+
+/// Compares two loaded models, as `diff` does. Models are `Send` and `Sync`,
+/// so one model can be loaded once and compared with others on several
+/// threads.
+pub fn diff_models(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    match_by: MatchBy,
+) -> Result<ModelDiff, ImporterError> {
+    let mut diff = ModelDiff::default();
+    for view in find_missing_views(source, target, match_by) {
+        if let Some(info) = source.view_map.get(&view.id) {
+            diff.missing_views.push(DiffItem::new(info));
+        }
+    }
+    let mut concepts: Vec<&ElementInfo> = source
+        .element_map
+        .values()
+        .filter(|info| !is_view_type(&info.xsi_type))
+        .collect();
+    concepts.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+    for info in concepts {
+        match target.element_map.get(&info.id) {
+            None if info.is_relationship() => diff.missing_relations.push(DiffItem::new(info)),
+            None => diff.missing_elements.push(DiffItem::new(info)),
+            Some(target_info) if is_conflict(source, target, &info.id)? => {
+                diff.conflicts.push(ConceptConflict::new(info, target_info))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let source_folders: BTreeSet<String> = folder_paths(source).into_values().collect();
+    let target_folders: BTreeSet<String> = folder_paths(target).into_values().collect();
+    diff.source_only_folders = source_folders
+        .difference(&target_folders)
+        .cloned()
+        .collect();
+    diff.target_only_folders = target_folders
+        .difference(&source_folders)
+        .cloned()
+        .collect();
+    Ok(diff)
+}
+
+/// Loads the models of an import, seeding the IDs generated in the target.
+fn load_import_models(
+    source: &str,
+    target: &str,
+    options: &ImportOptions,
+) -> Result<(ArchiModel, ArchiModel), ImporterError> {
+    if options.copy.on_conflict == ConflictPolicy::Newer {
+        return Err(ImporterError::Validation(
+            "on_conflict newer needs file modification times; use ours or theirs".to_string(),
+        ));
+    }
+    let source = if is_exchange_format(source) {
+        exchange_to_archi(source)?
+    } else {
+        source.to_string()
+    };
+    let source = load_model(&source)?;
+    let mut target = load_model(target)?;
+    target.ids = match options.seed {
+        Some(seed) => IdGenerator::new(seed),
+        None => IdGenerator::from_entropy(),
+    };
+    Ok((source, target))
+}
+
+/// The source views `options` select, in the order they are imported,
+/// checked for unresolved references when `strict`.
+fn select_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    options: &ImportOptions,
+) -> Result<Vec<MissingElementInfo>, ImporterError> {
+    let replace_changed = options.update || options.copy.on_conflict == ConflictPolicy::Theirs;
+    let mut missing_views = find_views_to_import(source, target, options.match_by, replace_changed);
+    if !options.keep_duplicates {
+        let identical = find_identical_views(source, target, &missing_views)?;
+        missing_views.retain(|view| !identical.contains_key(&*view.id));
+    }
+
+    let select_all = options.views.is_empty() && options.folders.is_empty();
+    let mut selected_indices: Vec<usize> = missing_views
+        .iter()
+        .enumerate()
+        .filter(|(_, view)| {
+            select_all
+                || options
+                    .views
+                    .iter()
+                    .any(|wanted| **wanted == *view.name || **wanted == *view.id)
+                || options
+                    .folders
+                    .iter()
+                    .any(|folder| is_in_folder(&view.folder_path, folder))
+        })
+        .map(|(i, _)| i + 1)
+        .collect();
+    if options.recursive {
+        let referenced = find_referenced_views(source, target, &missing_views, &selected_indices)?;
+        selected_indices.extend(referenced);
+        selected_indices.sort();
+    }
+    let selected: Vec<MissingElementInfo> = selected_indices
+        .into_iter()
+        .map(|idx| missing_views[idx - 1].clone())
+        .collect();
+
+    if options.strict {
+        let mut unresolved = Vec::new();
+        for view in &selected {
+            unresolved.extend(find_unresolved_references(source, target, &view.id)?);
+        }
+        if !unresolved.is_empty() {
+            unresolved.sort();
+            unresolved.dedup();
+            return Err(ImporterError::Validation(format!(
+                "the selected views show concepts missing from the source: {}",
+                unresolved.join(", ")
+            )));
+        }
+    }
+    Ok(selected)
+}
+
+/// Imports views from the `source` model into the `target` model, both given
+/// as Archi model XML. A source in the Open Exchange Format is converted
+/// first. A view that cannot be copied is reported as failed instead of
+/// failing the import.
+pub fn import(
+    source: &str,
+    target: &str,
+    options: &ImportOptions,
+) -> Result<ImportReport, ImporterError> {
+    let (mut source, mut target) = load_import_models(source, target, options)?;
+    let seed = target.ids.seed();
+    let selected = select_views(&source, &target, options)?;
+
+    let mut views = Vec::new();
+    let mut elements = 0;
+    let mut relations = 0;
+    let total = selected.len();
+    for view in &selected {
+        let error = match copy_view(&mut source, &mut target, view, &options.copy) {
+            Ok((_, element_count, relation_count)) => {
+                elements += element_count;
+                relations += relation_count;
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        };
+        let outcome = ViewOutcome {
+            name: view.name.to_string(),
+            error,
+        };
+        if let Some(on_view_copied) = &options.copy.hooks.on_view_copied {
+            on_view_copied(&outcome, views.len() + 1, total);
+        }
+        views.push(outcome);
+    }
+
+    Ok(ImportReport {
+        model: serialize_model(&target)?,
+        views,
+        elements,
+        relations,
+        seed,
+    })
+}
+
+/// An import worked out by [`plan`] and not yet carried out: the views to
+/// copy, each with the concepts it adds, the conflicts it settles and the
+/// folders it creates. The target is only changed by [`apply`], so the plan
+/// can be shown, edited or dropped first.
+pub struct ImportPlan {
+    /// The views to copy, in order. Each view is planned with the views
+    /// before it applied, so a view relies on the concepts earlier views add;
+    /// leaving out a view should leave out the views after it that show its
+    /// new concepts too.
+    pub views: Vec<ViewPlan>,
+    /// Selected views that cannot be copied, with why.
+    pub failed: Vec<ViewOutcome>,
+    source: ArchiModel,
+    target: ArchiModel,
+    seed: u64,
+    copy: CopyOptions,
+}
+
+impl ImportPlan {
+    /// Whether applying the plan would leave the target as it is.
+    pub fn is_empty(&self) -> bool {
+        self.views.is_empty()
+    }
+}
+
+/// Works out what [`import`] would do with the same arguments, without
+/// changing anything. Each view is planned against a scratch copy of the
+/// target that earlier views are copied into. Conflicts are settled here, so
+/// the `decide_conflicts` hook is called while planning; the other hooks are
+/// called by [`apply`].
+pub fn plan(
+    source: &str,
+    target: &str,
+    options: &ImportOptions,
+) -> Result<ImportPlan, ImporterError> {
+    let (mut scratch_source, mut scratch) = load_import_models(source, target, options)?;
+    let (source, mut target) = load_import_models(source, target, options)?;
+    let seed = scratch.ids.seed();
+    let selected = select_views(&scratch_source, &scratch, options)?;
+
+    let mut scratch_options = options.copy.clone();
+    scratch_options.explain = false;
+    scratch_options.hooks.on_element_copied = None;
+    let mut views = Vec::new();
+    let mut failed = Vec::new();
+    for view in &selected {
+        let planned =
+            plan_view(&scratch_source, &mut scratch, view, &options.copy).and_then(|view_plan| {
+                apply_view(
+                    &mut scratch_source,
+                    &mut scratch,
+                    &view_plan,
+                    &scratch_options,
+                )?;
+                Ok(view_plan)
+            });
+        match planned {
+            Ok(view_plan) => views.push(view_plan),
+            Err(e) => failed.push(ViewOutcome {
+                name: view.name.to_string(),
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+    // IDs drawn while planning are taken, so the target goes on from there
+    target.ids = scratch.ids;
+
+    Ok(ImportPlan {
+        views,
+        failed,
+        source,
+        target,
+        seed,
+        copy: options.copy.clone(),
+    })
+}
+
+/// Carries out `plan`, returning the report [`import`] would. The views that
+/// failed while planning are reported after those copied.
+pub fn apply(plan: ImportPlan) -> Result<ImportReport, ImporterError> {
+    let ImportPlan {
+        views: view_plans,
+        failed,
+        mut source,
+        mut target,
+        seed,
+        copy,
+    } = plan;
+    let mut views = Vec::new();
+    let mut elements = 0;
+    let mut relations = 0;
+    let total = view_plans.len();
+    for view_plan in &view_plans {
+        let error = match apply_view(&mut source, &mut target, view_plan, &copy) {
+            Ok((_, element_count, relation_count)) => {
+                elements += element_count;
+                relations += relation_count;
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        };
+        let outcome = ViewOutcome {
+            name: view_plan.name.clone(),
+            error,
+        };
+        if let Some(on_view_copied) = &copy.hooks.on_view_copied {
+            on_view_copied(&outcome, views.len() + 1, total);
+        }
+        views.push(outcome);
+    }
+    views.extend(failed);
+
+    Ok(ImportReport {
+        model: serialize_model(&target)?,
+        views,
+        elements,
+        relations,
+        seed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_import() -> Result<(), Box<dyn Error>> {
+        let source = std::fs::read_to_string("tests/test1.archimate")?;
+        let target = std::fs::read_to_string("tests/test2.archimate")?;
+
+        let options = ImportOptions::new()
+            .views(["Default View"])
+            .into_folder("Views > Imported")
+            .seed(7);
+        let report = import(&source, &target, &options)?;
+        assert_eq!(report.imported(), 1);
+        assert_eq!(report.views[0].name, "Default View");
+        assert_eq!(report.elements, 2);
+        assert_eq!(report.relations, 1);
+        assert_eq!(report.seed, 7);
+        assert!(report.model.contains("name=\"Imported\""));
+        assert!(report.model.contains("name=\"Default View\""));
+
+        // The import is repeatable and a second one finds nothing to do
+        assert_eq!(import(&source, &target, &options)?.model, report.model);
+        let again = import(&source, &report.model, &options)?;
+        assert!(again.views.is_empty());
+
+        let newer = ImportOptions::new().on_conflict(ConflictPolicy::Newer);
+        assert!(import(&source, &target, &newer).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_hooks() -> Result<(), Box<dyn Error>> {
+        use std::sync::Mutex;
+
+        let source = std::fs::read_to_string("tests/test1.archimate")?;
+        let target = std::fs::read_to_string("tests/test1.archimate")?
+            .replace(r#"name="Application Component""#, r#"name="Renamed""#)
+            .replace(r#"name="Default View""#, r#"name="Other View""#)
+            .replace("id-ea1025087eeb4c608d070157218992bf", "id-other-view");
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (views, elements, conflicts) = (events.clone(), events.clone(), events.clone());
+        let options = ImportOptions::new()
+            .views(["Default View"])
+            .keep_duplicates(true)
+            .on_view_copied(move |view, done, total| {
+                views
+                    .lock()
+                    .unwrap()
+                    .push(format!("view {} {}/{}", view.name, done, total))
+            })
+            .on_element_copied(move |info| {
+                elements
+                    .lock()
+                    .unwrap()
+                    .push(format!("element {}", info.name))
+            })
+            .decide_conflicts(move |conflict| {
+                conflicts
+                    .lock()
+                    .unwrap()
+                    .push(format!("conflict {}", conflict.name));
+                Some(ConflictPolicy::Theirs)
+            });
+        let report = import(&source, &target, &options)?;
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "conflict Application Component".to_string(),
+                "view Default View 1/1".to_string(),
+            ]
+        );
+        // The hook took the source version over the default of keeping ours
+        assert!(!report.model.contains(r#"name="Renamed""#));
+
+        let copied = Arc::new(Mutex::new(Vec::new()));
+        let elements = copied.clone();
+        let options = ImportOptions::new()
+            .views(["Default View"])
+            .on_element_copied(move |info| elements.lock().unwrap().push(info.id.clone()));
+        let target = std::fs::read_to_string("tests/test2.archimate")?;
+        let report = import(&source, &target, &options)?;
+        assert_eq!(
+            copied.lock().unwrap().len(),
+            report.elements + report.relations
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_strict() -> Result<(), Box<dyn Error>> {
+        let source = std::fs::read_to_string("tests/test1.archimate")?.replace(
+            r#"<element xsi:type="archimate:ApplicationComponent" name="Application Component" id="id-a0d52c37910b48b3a80c8b8be102d877"/>"#,
+            "",
+        );
+        let target = std::fs::read_to_string("tests/test2.archimate")?;
+        let options = ImportOptions::new().views(["Default View"]);
+        assert_eq!(import(&source, &target, &options)?.imported(), 1);
+
+        let error = import(&source, &target, &options.strict(true))
+            .err()
+            .ok_or("strict import succeeded")?;
+        assert!(matches!(error, ImporterError::Validation(_)));
+        assert!(error
+            .to_string()
+            .contains("id-a0d52c37910b48b3a80c8b8be102d877"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plan_apply() -> Result<(), Box<dyn Error>> {
+        let source = std::fs::read_to_string("tests/test1.archimate")?;
+        let target = std::fs::read_to_string("tests/test2.archimate")?;
+        let options = ImportOptions::new()
+            .views(["Default View"])
+            .into_folder("Views > Imported")
+            .seed(7);
+
+        let import_plan = plan(&source, &target, &options)?;
+        assert_eq!(import_plan.views.len(), 1);
+        let view_plan = &import_plan.views[0];
+        assert_eq!(view_plan.name, "Default View");
+        assert_eq!(
+            (view_plan.new_elements.len(), view_plan.new_relations.len()),
+            (2, 1)
+        );
+        assert_eq!(view_plan.new_folders, ["Views > Imported"]);
+        // Applying the plan gives what importing does
+        let report = apply(import_plan)?;
+        assert_eq!(report.model, import(&source, &target, &options)?.model);
+        assert_eq!((report.elements, report.relations), (2, 1));
+
+        // The policy settling a conflict can be changed before applying
+        let target = source
+            .replace(r#"name="Application Component""#, r#"name="Renamed""#)
+            .replace(r#"name="Default View""#, r#"name="Other View""#)
+            .replace("id-ea1025087eeb4c608d070157218992bf", "id-other-view");
+        let options = ImportOptions::new()
+            .views(["Default View"])
+            .keep_duplicates(true);
+        let mut import_plan = plan(&source, &target, &options)?;
+        let conflicts = &mut import_plan.views[0].conflicts;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].1, ConflictPolicy::Ours);
+        conflicts[0].1 = ConflictPolicy::Theirs;
+        assert!(!apply(import_plan)?.model.contains(r#"name="Renamed""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff() -> Result<(), Box<dyn Error>> {
+        let source = std::fs::read_to_string("tests/test1.archimate")?;
+        let target = std::fs::read_to_string("tests/test2.archimate")?;
+
+        let diff = diff(&source, &target, MatchBy::Id)?;
+        assert!(diff
+            .missing_views
+            .iter()
+            .any(|view| view.name == "Default View"));
+        assert!(diff.missing_elements.iter().any(|element| element.id
+            == "id-a0d52c37910b48b3a80c8b8be102d877"
+            && element.concept_type == "Application Component"));
+        assert_eq!(diff.missing_relations.len(), 1);
+        assert!(diff.conflicts.is_empty());
+
+        let renamed = target.replace(
+            r#"type="application"/>"#,
+            r#"type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Renamed" id="id-a0d52c37910b48b3a80c8b8be102d877"/>
+  </folder>"#,
+        );
+        let diff = super::diff(&source, &renamed, MatchBy::Id)?;
+        assert_eq!(diff.conflicts.len(), 1);
+        assert_eq!(diff.conflicts[0].name, "Application Component");
+        assert!(diff.conflicts[0].target_version.contains("Renamed"));
+        assert!(super::diff(&source, &source, MatchBy::Id)?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,464 @@
+use crate::api::write_model;
+use crate::concept_type::ConceptType;
+use crate::error::ImporterError;
+use crate::ids::IdGenerator;
+use crate::model::{
+    default_folder_name, extract_elements, serialize_model, ArchiModel, FolderIdCollision,
+    FOLDER_TYPES,
+};
+use crate::namespaces::XSI_NAMESPACE;
+use std::collections::HashMap;
+use std::io::Write;
+use xot::{Node, Xot};
+
+const ARCHIMATE_NAMESPACE: &str = "http://www.archimatetool.com/archimate";
+
+/// What the builder knows of a node it created.
+enum Created {
+    Folder,
+    Concept(ConceptType),
+    View,
+    DiagramObject { view: String, concept: String },
+}
+
+/// Builds a model in code, for tests and generators that would otherwise
+/// write model XML by hand. The model starts with Archi's top-level
+/// folders; each method returns the ID of what it adds, to refer to it in
+/// later calls.
+///
+/// ```
+/// use archi_view_importer::api::{ConceptType, ModelBuilder};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut builder = ModelBuilder::new("Shop");
+/// let crm = builder.element(ConceptType::ApplicationComponent, "CRM", None)?;
+/// let shop = builder.element(ConceptType::ApplicationComponent, "Shop", None)?;
+/// let serving = builder.relationship(ConceptType::ServingRelationship, &crm, &shop, None)?;
+/// let view = builder.view("Overview", None)?;
+/// builder.show(&view, &crm, (20, 20, 120, 55))?;
+/// builder.show(&view, &shop, (220, 20, 120, 55))?;
+/// builder.connect(&view, &serving)?;
+/// let model = builder.build()?;
+/// assert_eq!(model.views().count(), 1);
+/// let xml = model.to_xml()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ModelBuilder {
+    xot: Xot,
+    doc: Node,
+    ids: IdGenerator,
+    nodes: HashMap<String, (Node, Created)>,
+    top_folders: HashMap<&'static str, String>,
+}
+
+impl ModelBuilder {
+    pub fn new(name: &str) -> Self {
+        Self::with_ids(name, IdGenerator::from_entropy())
+    }
+
+    /// A builder whose IDs are generated from `seed`, so that building the
+    /// same model again gives the same XML.
+    pub fn with_seed(name: &str, seed: u64) -> Self {
+        Self::with_ids(name, IdGenerator::new(seed))
+    }
+
+    fn with_ids(name: &str, mut ids: IdGenerator) -> Self {
+        let mut xot = Xot::new();
+        let doc = xot
+            .parse(&format!(
+                r#"<archimate:model xmlns:xsi="{}" xmlns:archimate="{}"/>"#,
+                XSI_NAMESPACE, ARCHIMATE_NAMESPACE
+            ))
+            .expect("the model skeleton is well-formed");
+        let root = xot.document_element(doc).unwrap();
+        set(&mut xot, root, "name", name);
+        let id = ids.next_id();
+        set(&mut xot, root, "id", &id);
+        set(&mut xot, root, "version", "5.0.0");
+
+        let mut builder = ModelBuilder {
+            xot,
+            doc,
+            ids,
+            nodes: HashMap::new(),
+            top_folders: HashMap::new(),
+        };
+        for &folder_type in FOLDER_TYPES {
+            let id = builder.ids.next_id();
+            let folder = new_element(&mut builder.xot, "folder");
+            set(
+                &mut builder.xot,
+                folder,
+                "name",
+                default_folder_name(folder_type),
+            );
+            set(&mut builder.xot, folder, "id", &id);
+            set(&mut builder.xot, folder, "type", folder_type);
+            builder.xot.append(root, folder).unwrap();
+            builder.nodes.insert(id.clone(), (folder, Created::Folder));
+            builder.top_folders.insert(folder_type, id);
+        }
+        builder
+    }
+
+    /// The ID of the top-level folder of a folder type, e.g. `diagrams`.
+    pub fn top_folder(&self, folder_type: &str) -> Option<&str> {
+        self.top_folders.get(folder_type).map(String::as_str)
+    }
+
+    /// Adds a folder named `name` to the folder with the ID `parent`.
+    pub fn folder(&mut self, parent: &str, name: &str) -> Result<String, ImporterError> {
+        let parent = self.folder_node(parent)?;
+        let id = self.ids.next_id();
+        let folder = new_element(&mut self.xot, "folder");
+        set(&mut self.xot, folder, "name", name);
+        set(&mut self.xot, folder, "id", &id);
+        // Archi writes a folder's folders before its concepts
+        let folder_name = self.xot.name("folder");
+        let first_concept = self.xot.children(parent).find(|&child| {
+            self.xot.is_element(child) && Some(self.xot.get_element_name(child)) != folder_name
+        });
+        match first_concept {
+            Some(concept) => self.xot.insert_before(concept, folder)?,
+            None => self.xot.append(parent, folder)?,
+        }
+        self.nodes.insert(id.clone(), (folder, Created::Folder));
+        Ok(id)
+    }
+
+    /// Adds an element to the folder with the ID `folder`, or to the
+    /// top-level folder of its layer.
+    pub fn element(
+        &mut self,
+        concept_type: ConceptType,
+        name: &str,
+        folder: Option<&str>,
+    ) -> Result<String, ImporterError> {
+        if concept_type.is_relationship() {
+            return Err(ImporterError::Validation(format!(
+                "{} is a relationship type; add it with relationship",
+                concept_type
+            )));
+        }
+        let node = self.concept(concept_type, folder)?;
+        set(&mut self.xot, node, "name", name);
+        Ok(self.id_of(node))
+    }
+
+    /// Adds a relationship from the element or relationship `source` to
+    /// `target`, in the folder with the ID `folder` or in `Relations`.
+    pub fn relationship(
+        &mut self,
+        concept_type: ConceptType,
+        source: &str,
+        target: &str,
+        folder: Option<&str>,
+    ) -> Result<String, ImporterError> {
+        if !concept_type.is_relationship() {
+            return Err(ImporterError::Validation(format!(
+                "{} is not a relationship type",
+                concept_type
+            )));
+        }
+        for end in [source, target] {
+            if !matches!(self.nodes.get(end), Some((_, Created::Concept(_)))) {
+                return Err(ImporterError::Validation(format!(
+                    "{} is not an element or relationship of the model",
+                    end
+                )));
+            }
+        }
+        let node = self.concept(concept_type, folder)?;
+        set(&mut self.xot, node, "source", source);
+        set(&mut self.xot, node, "target", target);
+        Ok(self.id_of(node))
+    }
+
+    /// Adds an empty view to the folder with the ID `folder`, or to `Views`.
+    pub fn view(&mut self, name: &str, folder: Option<&str>) -> Result<String, ImporterError> {
+        let parent = match folder {
+            Some(folder) => self.folder_node(folder)?,
+            None => self.folder_node(&self.top_folders["diagrams"])?,
+        };
+        let id = self.ids.next_id();
+        let view = new_element(&mut self.xot, "element");
+        set_xsi_type(&mut self.xot, view, "ArchimateDiagramModel");
+        set(&mut self.xot, view, "name", name);
+        set(&mut self.xot, view, "id", &id);
+        self.xot.append(parent, view)?;
+        self.nodes.insert(id.clone(), (view, Created::View));
+        Ok(id)
+    }
+
+    /// Shows the element `element` on the view `view` within `bounds`, as
+    /// `(x, y, width, height)`, returning the ID of the diagram object.
+    pub fn show(
+        &mut self,
+        view: &str,
+        element: &str,
+        bounds: (i32, i32, i32, i32),
+    ) -> Result<String, ImporterError> {
+        let view_node = match self.nodes.get(view) {
+            Some((node, Created::View)) => *node,
+            _ => {
+                return Err(ImporterError::Validation(format!(
+                    "{} is not a view of the model",
+                    view
+                )))
+            }
+        };
+        match self.nodes.get(element) {
+            Some((_, Created::Concept(concept_type))) if !concept_type.is_relationship() => {}
+            _ => {
+                return Err(ImporterError::Validation(format!(
+                    "{} is not an element of the model",
+                    element
+                )))
+            }
+        }
+        let id = self.ids.next_id();
+        let object = new_element(&mut self.xot, "child");
+        set_xsi_type(&mut self.xot, object, "DiagramObject");
+        set(&mut self.xot, object, "id", &id);
+        set(&mut self.xot, object, "archimateElement", element);
+        let bounds_node = new_element(&mut self.xot, "bounds");
+        let (x, y, width, height) = bounds;
+        for (name, value) in [("x", x), ("y", y), ("width", width), ("height", height)] {
+            set(&mut self.xot, bounds_node, name, &value.to_string());
+        }
+        self.xot.append(object, bounds_node)?;
+        self.xot.append(view_node, object)?;
+        self.nodes.insert(
+            id.clone(),
+            (
+                object,
+                Created::DiagramObject {
+                    view: view.to_string(),
+                    concept: element.to_string(),
+                },
+            ),
+        );
+        Ok(id)
+    }
+
+    /// Draws the relationship `relationship` on the view `view` between the
+    /// diagram objects showing its source and target, returning the ID of
+    /// the connection.
+    pub fn connect(&mut self, view: &str, relationship: &str) -> Result<String, ImporterError> {
+        let relationship_node = match self.nodes.get(relationship) {
+            Some((node, Created::Concept(concept_type))) if concept_type.is_relationship() => *node,
+            _ => {
+                return Err(ImporterError::Validation(format!(
+                    "{} is not a relationship of the model",
+                    relationship
+                )))
+            }
+        };
+        let source = self.attribute(relationship_node, "source");
+        let target = self.attribute(relationship_node, "target");
+        let (source_object, source_id) = self.object_showing(view, &source)?;
+        let (target_object, target_id) = self.object_showing(view, &target)?;
+
+        let id = self.ids.next_id();
+        let connection = new_element(&mut self.xot, "sourceConnection");
+        set_xsi_type(&mut self.xot, connection, "Connection");
+        set(&mut self.xot, connection, "id", &id);
+        set(&mut self.xot, connection, "source", &source_id);
+        set(&mut self.xot, connection, "target", &target_id);
+        set(
+            &mut self.xot,
+            connection,
+            "archimateRelationship",
+            relationship,
+        );
+        self.xot.append(source_object, connection)?;
+        let target_connections = match self.attribute(target_object, "targetConnections") {
+            existing if existing.is_empty() => id.clone(),
+            existing => format!("{} {}", existing, id),
+        };
+        set(
+            &mut self.xot,
+            target_object,
+            "targetConnections",
+            &target_connections,
+        );
+        Ok(id)
+    }
+
+    /// Sets a property of the model, a folder, a concept or a view, given by
+    /// its ID.
+    pub fn property(&mut self, id: &str, key: &str, value: &str) -> Result<(), ImporterError> {
+        let node = match self.nodes.get(id) {
+            Some((node, Created::Folder | Created::Concept(_) | Created::View)) => *node,
+            _ if id == self.attribute(self.root(), "id") => self.root(),
+            _ => {
+                return Err(ImporterError::Validation(format!(
+                    "{} is not in the model",
+                    id
+                )))
+            }
+        };
+        let property = new_element(&mut self.xot, "property");
+        set(&mut self.xot, property, "key", key);
+        set(&mut self.xot, property, "value", value);
+        self.xot.append(node, property)?;
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<ArchiModel, ImporterError> {
+        let root = self.xot.root(self.doc);
+        let mut model = ArchiModel {
+            xot: self.xot,
+            doc: self.doc,
+            root,
+            view_map: HashMap::new(),
+            element_map: HashMap::new(),
+            ids: self.ids,
+            folder_id_collision: FolderIdCollision::default(),
+            repaired: Vec::new(),
+        };
+        extract_elements(&mut model)?;
+        Ok(model)
+    }
+
+    fn root(&self) -> Node {
+        self.xot.document_element(self.doc).unwrap()
+    }
+
+    fn folder_node(&self, id: &str) -> Result<Node, ImporterError> {
+        match self.nodes.get(id) {
+            Some((node, Created::Folder)) => Ok(*node),
+            _ => Err(ImporterError::Validation(format!(
+                "{} is not a folder of the model",
+                id
+            ))),
+        }
+    }
+
+    fn concept(
+        &mut self,
+        concept_type: ConceptType,
+        folder: Option<&str>,
+    ) -> Result<Node, ImporterError> {
+        let folder = match folder {
+            Some(folder) => folder.to_string(),
+            None => self.top_folders[concept_type.folder_type()].clone(),
+        };
+        let parent = self.folder_node(&folder)?;
+        let id = self.ids.next_id();
+        let node = new_element(&mut self.xot, "element");
+        set_xsi_type(&mut self.xot, node, concept_type.name());
+        set(&mut self.xot, node, "id", &id);
+        self.xot.append(parent, node)?;
+        self.nodes
+            .insert(id, (node, Created::Concept(concept_type)));
+        Ok(node)
+    }
+
+    /// The diagram object showing `concept` on `view`, with its ID.
+    fn object_showing(&self, view: &str, concept: &str) -> Result<(Node, String), ImporterError> {
+        self.nodes
+            .iter()
+            .filter_map(|(id, (node, created))| match created {
+                Created::DiagramObject {
+                    view: object_view,
+                    concept: object_concept,
+                } if object_view == view && object_concept == concept => Some((*node, id.clone())),
+                _ => None,
+            })
+            .min_by(|a, b| a.1.cmp(&b.1))
+            .ok_or_else(|| {
+                ImporterError::Validation(format!("{} is not shown on view {}", concept, view))
+            })
+    }
+
+    fn id_of(&self, node: Node) -> String {
+        self.attribute(node, "id")
+    }
+
+    fn attribute(&self, node: Node, name: &str) -> String {
+        self.xot
+            .name(name)
+            .and_then(|name| self.xot.get_attribute(node, name))
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+impl ArchiModel {
+    /// The model as Archi XML.
+    pub fn to_xml(&self) -> Result<String, ImporterError> {
+        Ok(serialize_model(self)?)
+    }
+
+    /// Writes the model to `writer`, as plain XML or, when `zipped`, as an
+    /// archive holding `model.xml`.
+    pub fn write_to<W: Write>(&self, writer: W, zipped: bool) -> Result<(), ImporterError> {
+        write_model(&self.to_xml()?, writer, zipped)
+    }
+}
+
+fn new_element(xot: &mut Xot, name: &str) -> Node {
+    let name = xot.add_name(name);
+    xot.new_element(name)
+}
+
+fn set(xot: &mut Xot, node: Node, name: &str, value: &str) {
+    let name = xot.add_name(name);
+    xot.set_attribute(node, name, value);
+}
+
+fn set_xsi_type(xot: &mut Xot, node: Node, type_name: &str) {
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let name = xot.add_name_ns("type", xsi);
+    xot.set_attribute(node, name, format!("archimate:{}", type_name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_model_builder() -> Result<(), Box<dyn std::error::Error>> {
+        let mut builder = ModelBuilder::with_seed("Shop", 7);
+        let application = builder.top_folder("application").unwrap().to_string();
+        let sales = builder.folder(&application, "Sales")?;
+        let crm = builder.element(ConceptType::ApplicationComponent, "CRM", None)?;
+        let shop = builder.element(ConceptType::ApplicationComponent, "Shop", Some(&sales))?;
+        let serving = builder.relationship(ConceptType::ServingRelationship, &crm, &shop, None)?;
+        builder.property(&shop, "owner", "Sales")?;
+        let view = builder.view("Overview", None)?;
+        builder.show(&view, &crm, (20, 20, 120, 55))?;
+        let shop_object = builder.show(&view, &shop, (220, 20, 120, 55))?;
+        let connection = builder.connect(&view, &serving)?;
+
+        assert!(builder
+            .element(ConceptType::ServingRelationship, "Serves", None)
+            .is_err());
+        assert!(builder
+            .relationship(ConceptType::FlowRelationship, &crm, "missing", None)
+            .is_err());
+        assert!(builder.connect(&view, &crm).is_err());
+
+        let model = builder.build()?;
+        assert_eq!(model.elements().count(), 2);
+        assert_eq!(model.relations().count(), 1);
+        let shop_info = &model.element_map[shop.as_str()];
+        assert_eq!(&*shop_info.folder_path[1].name, "Sales");
+        assert_eq!(shop_info.properties, [("owner".into(), "Sales".into())]);
+
+        let xml = model.to_xml()?;
+        assert!(xml.contains(&format!(
+            r#"id="{}" archimateElement="{}" targetConnections="{}""#,
+            shop_object, shop, connection
+        )));
+        let reloaded = load_model(&xml)?;
+        assert_eq!(reloaded.view_map.len(), 1);
+        assert_eq!(
+            ModelBuilder::with_seed("Shop", 7).build()?.to_xml()?,
+            ModelBuilder::with_seed("Shop", 7).build()?.to_xml()?
+        );
+        Ok(())
+    }
+}
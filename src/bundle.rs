@@ -0,0 +1,96 @@
+use crate::file_descriptor::FileDescriptor;
+use serde::{Deserialize, Serialize};
+
+/// Archive entry of a view bundle describing what it holds.
+pub const MANIFEST_ENTRY: &str = "bundle.json";
+
+/// Extension of view bundles: a zipped model with its images and manifest,
+/// readable as any other zipped model.
+pub const BUNDLE_EXTENSION: &str = "viewpkg";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub timestamp: String,
+    pub tool_version: String,
+    /// The model the views were extracted from.
+    pub source: String,
+    pub views: Vec<BundledView>,
+}
+
+/// A view in a bundle, with the folder path it had in the source model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledView {
+    pub id: String,
+    pub name: String,
+    pub folder_path: String,
+}
+
+impl BundleManifest {
+    pub fn new(source: &str, views: Vec<BundledView>) -> Self {
+        BundleManifest {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            source: source.to_string(),
+            views,
+        }
+    }
+}
+
+/// Reads the manifest of a view bundle, or `None` when the file is not a
+/// bundle.
+pub fn read_manifest(
+    descriptor: &FileDescriptor,
+) -> Result<Option<BundleManifest>, Box<dyn std::error::Error>> {
+    if !descriptor.is_zipped() {
+        return Ok(None);
+    }
+    match descriptor.read_entry(MANIFEST_ENTRY)? {
+        Some(content) => {
+            Ok(Some(serde_json::from_slice(&content).map_err(|e| {
+                format!("Cannot read {}: {}", MANIFEST_ENTRY, e)
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    #[test]
+    fn test_read_manifest() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let manifest = BundleManifest::new(
+            "enterprise.archimate",
+            vec![BundledView {
+                id: "view-1".to_string(),
+                name: "Overview".to_string(),
+                folder_path: "Views > Domain A".to_string(),
+            }],
+        );
+        let bundle_path = dir.path().join("overview.viewpkg");
+        let mut zip = ZipWriter::new(fs::File::create(&bundle_path)?);
+        zip.start_file("model.xml", SimpleFileOptions::default())?;
+        zip.write_all(b"<?xml version=\"1.0\"?><archimate:model/>")?;
+        zip.start_file(MANIFEST_ENTRY, SimpleFileOptions::default())?;
+        zip.write_all(&serde_json::to_vec(&manifest)?)?;
+        zip.finish()?;
+
+        let descriptor = FileDescriptor::from_path(&bundle_path)?;
+        assert_eq!(read_manifest(&descriptor)?, Some(manifest));
+
+        let plain_path = dir.path().join("model.archimate");
+        fs::write(&plain_path, "<?xml version=\"1.0\"?><archimate:model/>")?;
+        assert_eq!(
+            read_manifest(&FileDescriptor::from_path(&plain_path)?)?,
+            None
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use crate::conflict::{ConflictPolicy, DocumentationMerge, PropertyMerge, SyncPolicy};
+use crate::copy::{parse_rename, NameCollision};
+use crate::exchange::{ExportFormat, ExportScope};
+use crate::file_descriptor::ZipCompression;
+use crate::ids::parse_id_prefix;
+use crate::model::{FolderIdCollision, MatchBy};
+use crate::selection::parse_property_filter;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use std::ffi::OsString;
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Import views missing from the target model
+    Import(Box<ImportArgs>),
+    /// List the views of a model
+    List(ListArgs),
+    /// Print statistics of a model: its concepts by layer, type and folder
+    Stats(StatsArgs),
+    /// Show the views of the source model missing from the target model
+    Diff(DiffArgs),
+    /// Copy the views missing from either model into the other
+    Sync(SyncArgs),
+    /// Write a model, or some of its views, in the Open Exchange Format
+    Export(ExportArgs),
+    /// Write views with everything they show into a self-contained bundle
+    Extract(ExtractArgs),
+    /// Serve list, diff and import as Model Context Protocol tools over stdio
+    ServeMcp(ServeMcpArgs),
+    /// Check a model for dangling references, duplicate IDs, missing
+    /// attributes and unknown types
+    Validate(ValidateArgs),
+    /// Revert the last import into a model, from the journal next to it
+    Undo(UndoArgs),
+    /// Remove views from a model, keeping the concepts they show
+    Delete(DeleteArgs),
+    /// Check the tools some options need, and optionally a model
+    Doctor(DoctorArgs),
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    // The source and target files, or only the target when --source-ref
+    // names the source
+    #[arg(required = true, num_args = 1..=2, value_names = ["SOURCE_FILE", "TARGET_FILE"])]
+    pub files: Vec<String>,
+    #[arg(long = "source-ref", value_name = "REVISION:PATH")]
+    pub source_ref: Option<String>,
+    #[arg(long = "source-token")]
+    pub source_token: Option<String>,
+    #[arg(long = "source-password")]
+    pub source_password: Option<String>,
+    #[arg(long = "target-password")]
+    pub target_password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+    #[arg(long = "low-memory", conflicts_with_all = ["prune", "containing_elements"])]
+    pub low_memory: bool,
+    #[arg(long = "memory-stats")]
+    pub memory_stats: bool,
+    #[arg(short = 'v', long = "view", num_args = 1)]
+    pub views: Vec<String>,
+    #[arg(long = "all")]
+    pub all: bool,
+    #[arg(long = "folder", num_args = 1)]
+    pub folders: Vec<String>,
+    #[arg(long = "containing-element", num_args = 1)]
+    pub containing_elements: Vec<String>,
+    #[arg(long = "selection-file", num_args = 1)]
+    pub selection_file: Option<String>,
+    #[arg(long = "save-selection", num_args = 1)]
+    pub save_selection: Option<String>,
+    #[arg(long = "where", num_args = 1, value_parser = parse_property_filter)]
+    pub property_filters: Vec<(String, String)>,
+    #[arg(long = "exclude", num_args = 1)]
+    pub excludes: Vec<String>,
+    #[arg(long = "exclude-pattern", num_args = 1)]
+    pub exclude_patterns: Vec<String>,
+    #[arg(long = "shared-concepts")]
+    pub shared_concepts: Option<String>,
+    #[arg(long = "export-exchange")]
+    pub export_exchange: Option<String>,
+    #[arg(long = "csv-elements")]
+    pub csv_elements: Option<String>,
+    #[arg(long = "export-csv")]
+    pub export_csv: Option<String>,
+    #[arg(long = "csv-scope", default_value_t = ExportScope::Model)]
+    pub csv_scope: ExportScope,
+    #[arg(long = "export-graphml")]
+    pub export_graphml: Option<String>,
+    #[arg(long = "graphml-scope", default_value_t = ExportScope::Model)]
+    pub graphml_scope: ExportScope,
+    #[arg(long = "export-cypher")]
+    pub export_cypher: Option<String>,
+    #[arg(long = "cypher-scope", default_value_t = ExportScope::Model)]
+    pub cypher_scope: ExportScope,
+    #[arg(long = "export-sqlite")]
+    pub export_sqlite: Option<String>,
+    #[arg(long = "emit-jarchi")]
+    pub emit_jarchi: Option<String>,
+    #[arg(long = "export-plantuml")]
+    pub export_plantuml: Option<String>,
+    #[arg(long = "report")]
+    pub report: Option<String>,
+    #[arg(long = "report-md")]
+    pub report_md: Option<String>,
+    #[arg(long = "page-size")]
+    pub page_size: Option<usize>,
+    #[arg(long = "explain")]
+    pub explain: bool,
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+    #[arg(long = "zip-compression", default_value_t = ZipCompression::Keep)]
+    pub zip_compression: ZipCompression,
+    #[arg(long = "match-by", default_value_t = MatchBy::Id)]
+    pub match_by: MatchBy,
+    #[arg(long = "keep-duplicates")]
+    pub keep_duplicates: bool,
+    #[arg(long = "on-conflict", default_value_t = ConflictPolicy::Ours)]
+    pub on_conflict: ConflictPolicy,
+    #[arg(long = "merge-properties")]
+    pub merge_properties: Option<PropertyMerge>,
+    #[arg(long = "merge-documentation")]
+    pub merge_documentation: Option<DocumentationMerge>,
+    #[arg(long = "write-conflicts")]
+    pub write_conflicts: Option<String>,
+    #[arg(long = "resolutions")]
+    pub resolutions: Option<String>,
+    #[arg(long = "base")]
+    pub base: Option<String>,
+    #[arg(long = "base-password")]
+    pub base_password: Option<String>,
+    #[arg(long = "recursive")]
+    pub recursive: bool,
+    #[arg(long = "update")]
+    pub update: bool,
+    #[arg(long = "into-folder", conflicts_with = "snapshot_folder")]
+    pub into_folder: Option<String>,
+    #[arg(long = "folder-id-collision", default_value_t = FolderIdCollision::NewId)]
+    pub folder_id_collision: FolderIdCollision,
+    #[arg(long = "flatten")]
+    pub flatten: bool,
+    #[arg(long = "align-folders")]
+    pub align_folders: bool,
+    #[arg(long = "snapshot-folder")]
+    pub snapshot_folder: bool,
+    #[arg(long = "reuse-by-name")]
+    pub reuse_by_name: bool,
+    #[arg(long = "fresh-ids")]
+    pub fresh_ids: bool,
+    #[arg(long = "rename", num_args = 1, value_parser = parse_rename)]
+    pub renames: Vec<(String, String)>,
+    #[arg(long = "rename-template")]
+    pub rename_template: Option<String>,
+    #[arg(long = "on-name-collision", default_value_t = NameCollision::Keep)]
+    pub on_name_collision: NameCollision,
+    #[arg(long = "visual-only", conflicts_with = "include_model_relations")]
+    pub visual_only: bool,
+    #[arg(long = "no-relations", conflicts_with = "include_model_relations")]
+    pub no_relations: bool,
+    #[arg(long = "include-model-relations")]
+    pub include_model_relations: bool,
+    #[arg(long = "create-stubs")]
+    pub create_stubs: bool,
+    #[arg(long = "strict", conflicts_with = "create_stubs")]
+    pub strict: bool,
+    #[arg(long = "provenance")]
+    pub provenance: bool,
+    #[arg(long = "seed")]
+    pub seed: Option<u64>,
+    #[arg(long = "id-prefix", value_parser = parse_id_prefix)]
+    pub id_prefix: Option<String>,
+    #[arg(long = "prune")]
+    pub prune: Option<String>,
+    #[arg(long = "yes", requires = "prune")]
+    pub yes: bool,
+    #[arg(long = "fail-fast")]
+    pub fail_fast: bool,
+    #[arg(long = "no-history")]
+    pub no_history: bool,
+    #[arg(long = "no-journal")]
+    pub no_journal: bool,
+    #[arg(long = "no-verify")]
+    pub no_verify: bool,
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    pub model_file: String,
+    #[arg(long = "export-dot")]
+    pub export_dot: Option<String>,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+    #[arg(long = "json", conflicts_with = "export_dot")]
+    pub json: bool,
+    #[arg(long = "stats", conflicts_with = "export_dot")]
+    pub stats: bool,
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    pub source_file: String,
+    pub target_file: String,
+    #[arg(long = "match-by", default_value_t = MatchBy::Id)]
+    pub match_by: MatchBy,
+    #[arg(long = "source-password")]
+    pub source_password: Option<String>,
+    #[arg(long = "target-password")]
+    pub target_password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct SyncArgs {
+    pub left_file: String,
+    pub right_file: String,
+    #[arg(long = "left-password")]
+    pub left_password: Option<String>,
+    #[arg(long = "right-password")]
+    pub right_password: Option<String>,
+    #[arg(long = "policy", default_value_t = SyncPolicy::Keep)]
+    pub policy: SyncPolicy,
+    #[arg(long = "match-by", default_value_t = MatchBy::Id)]
+    pub match_by: MatchBy,
+    #[arg(long = "keep-duplicates")]
+    pub keep_duplicates: bool,
+    #[arg(long = "zip-compression", default_value_t = ZipCompression::Keep)]
+    pub zip_compression: ZipCompression,
+    #[arg(long = "seed")]
+    pub seed: Option<u64>,
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    pub model_file: String,
+    pub output_file: String,
+    #[arg(short = 'v', long = "view", num_args = 1)]
+    pub views: Vec<String>,
+    #[arg(long = "folder", num_args = 1)]
+    pub folders: Vec<String>,
+    #[arg(long = "format", default_value_t = ExportFormat::Exchange)]
+    pub format: ExportFormat,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+}
+
+#[derive(Args)]
+pub struct ExtractArgs {
+    pub model_file: String,
+    pub output_file: String,
+    #[arg(short = 'v', long = "view", num_args = 1)]
+    pub views: Vec<String>,
+    #[arg(long = "folder", num_args = 1)]
+    pub folders: Vec<String>,
+    #[arg(long = "zip-compression", default_value_t = ZipCompression::Stored)]
+    pub zip_compression: ZipCompression,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+}
+
+#[derive(Args)]
+pub struct ServeMcpArgs {
+    #[arg(long = "read-only")]
+    pub read_only: bool,
+}
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    pub model_file: String,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct UndoArgs {
+    pub model_file: String,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(long = "force")]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct DeleteArgs {
+    pub model_file: String,
+    #[arg(short = 'v', long = "view", num_args = 1)]
+    pub views: Vec<String>,
+    #[arg(long = "folder", num_args = 1)]
+    pub folders: Vec<String>,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+    #[arg(long = "zip-compression", default_value_t = ZipCompression::Keep)]
+    pub zip_compression: ZipCompression,
+    #[arg(long = "dry-run", conflicts_with = "yes")]
+    pub dry_run: bool,
+    #[arg(long = "yes")]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+pub struct StatsArgs {
+    pub model_file: String,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+    #[arg(long = "lenient-exchange")]
+    pub lenient_exchange: bool,
+    #[arg(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    pub model_file: Option<String>,
+    #[arg(long = "password")]
+    pub password: Option<String>,
+}
+
+/// Parses the command line, treating an invocation without a subcommand as
+/// the deprecated `archi-view-importer <source> <target> ...` form of
+/// `import`.
+pub fn parse() -> Cli {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let args = match normalize_legacy_args(&args) {
+        Some(args) => {
+            eprintln!(
+                "Warning: invoking without a subcommand is deprecated; use `{} import <source_file> <target_file> ...` instead.",
+                env!("CARGO_PKG_NAME")
+            );
+            args
+        }
+        None => args,
+    };
+    Cli::parse_from(args)
+}
+
+/// Returns the arguments with `import` inserted when they use the legacy
+/// invocation, or `None` when they already name a subcommand.
+fn normalize_legacy_args(args: &[OsString]) -> Option<Vec<OsString>> {
+    let first = args.get(1)?.to_str().unwrap_or_default().to_string();
+    let command = Cli::command();
+    let is_subcommand = first == "help"
+        || command
+            .get_subcommands()
+            .any(|subcommand| subcommand.get_name() == first);
+    let is_top_level_flag = matches!(first.as_str(), "-h" | "--help" | "-V" | "--version");
+    if is_subcommand || is_top_level_flag {
+        return None;
+    }
+
+    let mut normalized = args.to_vec();
+    normalized.insert(1, OsString::from("import"));
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_normalize_legacy_args() {
+        let normalized = normalize_legacy_args(&os_args(&["prog", "a.archimate", "b.archimate"]));
+        assert_eq!(
+            normalized,
+            Some(os_args(&["prog", "import", "a.archimate", "b.archimate"]))
+        );
+        assert_eq!(
+            normalize_legacy_args(&os_args(&["prog", "list", "a.archimate"])),
+            None
+        );
+        assert_eq!(normalize_legacy_args(&os_args(&["prog", "--help"])), None);
+        assert_eq!(normalize_legacy_args(&os_args(&["prog"])), None);
+    }
+}
@@ -0,0 +1,754 @@
+use crate::concept_type::ConceptType;
+use crate::model::FOLDER_TYPES;
+use crate::namespaces::{attribute, xsi_type, XSI_NAMESPACE};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use xot::{output, Node, Xot};
+
+/// Directory of a coArchi repository holding the model.
+pub const MODEL_DIRECTORY: &str = "model";
+
+/// File holding the attributes of the folder it is in. The one directly in
+/// the model directory holds the model's own attributes.
+pub const FOLDER_FILE: &str = "folder.xml";
+
+const ARCHIMATE_NAMESPACE: &str = "http://www.archimatetool.com/archimate";
+const CANVAS_NAMESPACE: &str = "http://www.archimatetool.com/canvas";
+
+/// Text features coArchi writes as attributes and Archi as child elements,
+/// in reverse of the order Archi writes them.
+const TEXT_FEATURES: &[&str] = &["content", "hintContent", "purpose", "documentation"];
+
+/// Returns the root of the coArchi repository at `path`, which may be the
+/// repository itself or its model directory.
+pub fn find_repository_root(path: &Path) -> Option<PathBuf> {
+    if path.join(MODEL_DIRECTORY).join(FOLDER_FILE).is_file() {
+        return Some(path.to_path_buf());
+    }
+    let is_model_directory = path.file_name().is_some_and(|name| name == MODEL_DIRECTORY)
+        && path.join(FOLDER_FILE).is_file();
+    is_model_directory
+        .then(|| path.parent().map(Path::to_path_buf))
+        .flatten()
+}
+
+/// Rebuilds the Archi model of a coArchi working tree, where each folder
+/// is a directory and each concept and view a file of its own.
+pub fn read_working_tree(root: &Path) -> Result<String, Box<dyn Error>> {
+    let model_directory = root.join(MODEL_DIRECTORY);
+    let mut xot = Xot::new();
+    let output_doc = xot.parse(&format!(
+        r#"<archimate:model xmlns:xsi="{}" xmlns:archimate="{}"/>"#,
+        XSI_NAMESPACE, ARCHIMATE_NAMESPACE
+    ))?;
+    let output = xot.document_element(output_doc)?;
+    let model = parse_file(&mut xot, &model_directory.join(FOLDER_FILE))?;
+    to_archi_form(&mut xot, model)?;
+    copy_attributes(&mut xot, model, output);
+
+    let mut directories = subdirectories(&model_directory)?;
+    directories.sort_by_key(|directory| {
+        FOLDER_TYPES
+            .iter()
+            .position(|folder_type| directory.ends_with(folder_type))
+            .unwrap_or(FOLDER_TYPES.len())
+    });
+    let mut uses_canvas = false;
+    for directory in directories {
+        let folder = read_folder(&mut xot, &directory, &mut uses_canvas)?;
+        xot.append(output, folder)?;
+    }
+    // Purpose, properties and profiles follow the folders
+    while let Some(child) = xot.first_child(model) {
+        xot.append(output, child)?;
+    }
+    if uses_canvas {
+        let prefix = xot.add_prefix("canvas");
+        let namespace = xot.add_namespace(CANVAS_NAMESPACE);
+        xot.set_namespace(output, prefix, namespace);
+    }
+
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        output_doc,
+    )?)
+}
+
+/// Reads a folder directory with its subfolders, concepts and views.
+fn read_folder(
+    xot: &mut Xot,
+    directory: &Path,
+    uses_canvas: &mut bool,
+) -> Result<Node, Box<dyn Error>> {
+    let folder_file = parse_file(xot, &directory.join(FOLDER_FILE))?;
+    let folder_name = xot.add_name("folder");
+    let folder = xot.new_element(folder_name);
+    to_archi_form(xot, folder_file)?;
+    copy_attributes(xot, folder_file, folder);
+    while let Some(child) = xot.first_child(folder_file) {
+        xot.append(folder, child)?;
+    }
+
+    for subdirectory in subdirectories(directory)? {
+        let subfolder = read_folder(xot, &subdirectory, uses_canvas)?;
+        xot.append(folder, subfolder)?;
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    files.retain(|file| {
+        file.is_file()
+            && file.extension().is_some_and(|extension| extension == "xml")
+            && !file.ends_with(FOLDER_FILE)
+    });
+    files.sort();
+    for file in files {
+        let concept = parse_file(xot, &file)?;
+        let concept_name = xot.get_element_name(concept);
+        let (local_name, namespace) = xot.name_ns_str(concept_name);
+        let prefix = if namespace == CANVAS_NAMESPACE {
+            *uses_canvas = true;
+            "canvas"
+        } else {
+            "archimate"
+        };
+        let xsi_type = format!("{}:{}", prefix, local_name);
+
+        let element_name = xot.add_name("element");
+        let element = xot.new_element(element_name);
+        let xsi = xot.add_namespace(XSI_NAMESPACE);
+        let type_name = xot.add_name_ns("type", xsi);
+        xot.set_attribute(element, type_name, xsi_type);
+        to_archi_form(xot, concept)?;
+        copy_attributes(xot, concept, element);
+        while let Some(child) = xot.first_child(concept) {
+            xot.append(element, child)?;
+        }
+        xot.append(folder, element)?;
+    }
+    Ok(folder)
+}
+
+/// Rewrites the children of a node from coArchi's form into Archi's:
+/// plural child names become singular, references to concepts in other
+/// files become ID attributes, and text attributes become child elements.
+fn to_archi_form(xot: &mut Xot, node: Node) -> Result<(), Box<dyn Error>> {
+    for &feature in TEXT_FEATURES {
+        let name = xot.add_name(feature);
+        if let Some(text) = xot.get_attribute(node, name).map(str::to_string) {
+            xot.remove_attribute(node, name);
+            let element = xot.new_element(name);
+            let text = xot.new_text(&text);
+            xot.append(element, text)?;
+            xot.prepend(node, element)?;
+        }
+    }
+
+    let href_name = xot.add_name("href");
+    let children: Vec<Node> = xot
+        .children(node)
+        .filter(|&child| xot.is_element(child))
+        .collect();
+    for child in children {
+        let local_name = xot.local_name_str(xot.get_element_name(child)).to_string();
+        if let Some(reference) = xot.get_attribute(child, href_name) {
+            // e.g. <archimateElement href="BusinessActor_id-1.xml#id-1"/>
+            let id = reference
+                .rsplit('#')
+                .next()
+                .unwrap_or(reference)
+                .to_string();
+            let attribute_name = xot.add_name(&local_name);
+            let value = match xot.get_attribute(node, attribute_name) {
+                Some(existing) => format!("{} {}", existing, id),
+                None => id,
+            };
+            xot.set_attribute(node, attribute_name, value);
+            // Drop the indentation before the reference along with it
+            if let Some(previous) = xot.previous_sibling(child) {
+                if xot
+                    .text_str(previous)
+                    .is_some_and(|text| text.trim().is_empty())
+                {
+                    xot.remove(previous)?;
+                }
+            }
+            xot.remove(child)?;
+            continue;
+        }
+        let archi_name = match local_name.as_str() {
+            "children" => Some("child"),
+            "sourceConnections" => Some("sourceConnection"),
+            "bendpoints" => Some("bendpoint"),
+            "properties" => Some("property"),
+            "features" => Some("feature"),
+            "profiles" => Some("profile"),
+            _ => None,
+        };
+        if let Some(archi_name) = archi_name {
+            let name = xot.add_name(archi_name);
+            xot.element_mut(child).unwrap().set_name(name);
+        }
+        to_archi_form(xot, child)?;
+    }
+    Ok(())
+}
+
+/// Writes an Archi model into the coArchi working tree at `root`. Only the
+/// fragment files whose content changes are written, and those of folders
+/// and concepts no longer in the model removed, so the change shows up as
+/// a clean diff in the repository.
+pub fn write_working_tree(root: &Path, xml: &str) -> Result<(), Box<dyn Error>> {
+    let model_directory = root.join(MODEL_DIRECTORY);
+    let existing = existing_paths(&model_directory)?;
+    // The current tree is laid out the same way, so unchanged fragments
+    // compare equal however coArchi formatted them
+    let before = layout_fragments(&read_working_tree(root)?, &model_directory, &existing)?;
+    let after = layout_fragments(xml, &model_directory, &existing)?;
+
+    for (path, content) in &after {
+        if before.get(path) != Some(content) {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)
+                .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        }
+    }
+    for path in before.keys().filter(|path| !after.contains_key(*path)) {
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+        // Drop the directories of removed folders
+        let mut directory = path.parent();
+        while let Some(current) = directory {
+            if current == model_directory || fs::read_dir(current)?.next().is_some() {
+                break;
+            }
+            fs::remove_dir(current)?;
+            directory = current.parent();
+        }
+    }
+    Ok(())
+}
+
+/// Where the folders and concepts of a model go in a working tree.
+#[derive(Default)]
+struct Layout {
+    /// The model and its folders, with the directory each is written to
+    directories: Vec<(Node, PathBuf)>,
+    /// Concepts and views, with the file each is written to
+    concepts: Vec<(Node, PathBuf)>,
+    /// Type and file of everything a fragment can refer to, by ID
+    targets: HashMap<String, (Option<String>, PathBuf)>,
+}
+
+/// Renders every fragment file of a model, keyed by path. Folders and
+/// concepts already in the working tree keep their directory or file name.
+fn layout_fragments(
+    xml: &str,
+    model_directory: &Path,
+    existing: &HashMap<String, PathBuf>,
+) -> Result<BTreeMap<PathBuf, String>, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let doc = xot.parse(xml)?;
+    let model = xot.document_element(doc)?;
+    let mut layout = Layout::default();
+    layout
+        .directories
+        .push((model, model_directory.to_path_buf()));
+    for child in xot.children(model).filter(|&child| xot.is_element(child)) {
+        match xot.local_name_str(xot.get_element_name(child)) {
+            "folder" => {
+                let default_name = attribute(&xot, child, "type")
+                    .or_else(|| attribute(&xot, child, "id"))
+                    .unwrap_or_default();
+                let directory =
+                    folder_directory(&xot, child, model_directory, default_name, existing);
+                plan_folder(&xot, child, directory, existing, &mut layout);
+            }
+            "profile" => {
+                if let Some(id) = attribute(&xot, child, "id") {
+                    layout
+                        .targets
+                        .insert(id.to_string(), (None, model_directory.join(FOLDER_FILE)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut fragments = BTreeMap::new();
+    for (node, directory) in &layout.directories {
+        let root_name = if *node == model { "model" } else { "Folder" };
+        let content = render_fragment(&xot, *node, root_name, directory, &layout.targets)?;
+        fragments.insert(directory.join(FOLDER_FILE), content);
+    }
+    for (node, file) in &layout.concepts {
+        let xsi_type = xsi_type(&xot, *node).unwrap_or_default();
+        let directory = file.parent().unwrap_or(model_directory);
+        let content = render_fragment(&xot, *node, xsi_type, directory, &layout.targets)?;
+        fragments.insert(file.clone(), content);
+    }
+    Ok(fragments)
+}
+
+/// Adds a folder, its subfolders and its concepts to the layout.
+fn plan_folder(
+    xot: &Xot,
+    folder: Node,
+    directory: PathBuf,
+    existing: &HashMap<String, PathBuf>,
+    layout: &mut Layout,
+) {
+    for child in xot.children(folder).filter(|&child| xot.is_element(child)) {
+        let Some(id) = attribute(xot, child, "id") else {
+            continue;
+        };
+        match xot.local_name_str(xot.get_element_name(child)) {
+            "folder" => {
+                let subdirectory = folder_directory(xot, child, &directory, id, existing);
+                plan_folder(xot, child, subdirectory, existing, layout);
+            }
+            "element" => {
+                let xsi_type = xsi_type(xot, child).unwrap_or_default();
+                let (_, local_type) = xsi_type.split_once(':').unwrap_or(("", xsi_type));
+                let file_name = existing
+                    .get(id)
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| format!("{}_{}.xml", local_type, id));
+                let file = directory.join(file_name);
+                layout
+                    .targets
+                    .insert(id.to_string(), (Some(xsi_type.to_string()), file.clone()));
+                layout.concepts.push((child, file));
+            }
+            _ => {}
+        }
+    }
+    layout.directories.push((folder, directory));
+}
+
+/// The directory of a folder: the one it already has when it stays in the
+/// same parent, otherwise one named `default_name`.
+fn folder_directory(
+    xot: &Xot,
+    folder: Node,
+    parent: &Path,
+    default_name: &str,
+    existing: &HashMap<String, PathBuf>,
+) -> PathBuf {
+    attribute(xot, folder, "id")
+        .and_then(|id| existing.get(id))
+        .filter(|path| path.parent() == Some(parent))
+        .cloned()
+        .unwrap_or_else(|| parent.join(default_name))
+}
+
+/// Renders one fragment file: `node` without its folders and concepts,
+/// in coArchi's form.
+fn render_fragment(
+    source: &Xot,
+    node: Node,
+    root_type: &str,
+    directory: &Path,
+    targets: &HashMap<String, (Option<String>, PathBuf)>,
+) -> Result<String, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let (prefix, local_type) = root_type
+        .split_once(':')
+        .unwrap_or(("archimate", root_type));
+    let namespace_uri = if prefix == "canvas" {
+        CANVAS_NAMESPACE
+    } else {
+        ARCHIMATE_NAMESPACE
+    };
+    let namespace = xot.add_namespace(namespace_uri);
+    let root_name = xot.add_name_ns(local_type, namespace);
+    let root = xot.new_element(root_name);
+    let doc = xot.new_document_with_element(root)?;
+    let archimate_prefix = xot.add_prefix("archimate");
+    let archimate = xot.add_namespace(ARCHIMATE_NAMESPACE);
+    xot.set_namespace(root, archimate_prefix, archimate);
+    if prefix == "canvas" {
+        let canvas_prefix = xot.add_prefix("canvas");
+        xot.set_namespace(root, canvas_prefix, namespace);
+    }
+    to_coarchi_form(source, node, &mut xot, root, true, directory, targets)?;
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    let uses_xsi = xot
+        .descendants(root)
+        .any(|descendant| xot.get_attribute(descendant, type_name).is_some());
+    if uses_xsi {
+        let xsi_prefix = xot.add_prefix("xsi");
+        xot.set_namespace(root, xsi_prefix, xsi);
+    }
+
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            indentation: Some(Default::default()),
+            ..Default::default()
+        },
+        doc,
+    )?)
+}
+
+/// Copies `from` onto `to` in coArchi's form, the reverse of
+/// `to_archi_form`: child names become plural, ID references to concepts
+/// in other files become `href` children, and text elements attributes.
+fn to_coarchi_form(
+    source: &Xot,
+    from: Node,
+    xot: &mut Xot,
+    to: Node,
+    is_root: bool,
+    directory: &Path,
+    targets: &HashMap<String, (Option<String>, PathBuf)>,
+) -> Result<(), Box<dyn Error>> {
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    let is_relationship = is_root
+        && xsi_type(source, from)
+            .and_then(ConceptType::from_xsi_type)
+            .is_some_and(ConceptType::is_relationship);
+    let mut references = Vec::new();
+    for (name, value) in source.attributes(from).iter() {
+        let (local_name, namespace) = source.name_ns_str(name);
+        if namespace == XSI_NAMESPACE {
+            if local_name == "type" && !is_root {
+                xot.set_attribute(to, type_name, value.clone());
+            }
+            continue;
+        }
+        if !namespace.is_empty() {
+            continue;
+        }
+        let is_reference = matches!(
+            local_name,
+            "archimateElement" | "archimateRelationship" | "model" | "profiles"
+        ) || (is_relationship && matches!(local_name, "source" | "target"));
+        if is_reference && value.split_whitespace().all(|id| targets.contains_key(id)) {
+            references.push((local_name.to_string(), value.clone()));
+            continue;
+        }
+        let attribute_name = xot.add_name(local_name);
+        xot.set_attribute(to, attribute_name, value.clone());
+    }
+
+    for child in source.children(from) {
+        if let Some(text) = source.text_str(child) {
+            if !text.trim().is_empty() {
+                let text = xot.new_text(text);
+                xot.append(to, text)?;
+            }
+            continue;
+        }
+        if !source.is_element(child) {
+            continue;
+        }
+        let local_name = source.local_name_str(source.get_element_name(child));
+        // Folders and concepts have files of their own
+        if is_root && matches!(local_name, "folder" | "element") {
+            continue;
+        }
+        let has_elements = source.children(child).any(|node| source.is_element(node));
+        if TEXT_FEATURES.contains(&local_name) && !has_elements {
+            let text: String = source
+                .children(child)
+                .filter_map(|node| source.text_str(node))
+                .collect();
+            let attribute_name = xot.add_name(local_name);
+            xot.set_attribute(to, attribute_name, text);
+            continue;
+        }
+        let coarchi_name = match local_name {
+            "child" => "children",
+            "sourceConnection" => "sourceConnections",
+            "bendpoint" => "bendpoints",
+            "property" => "properties",
+            "feature" => "features",
+            "profile" => "profiles",
+            other => other,
+        };
+        let name = xot.add_name(coarchi_name);
+        let element = xot.new_element(name);
+        xot.append(to, element)?;
+        to_coarchi_form(source, child, xot, element, false, directory, targets)?;
+    }
+
+    let href_name = xot.add_name("href");
+    for (local_name, value) in references {
+        for id in value.split_whitespace() {
+            let (xsi_type, file) = &targets[id];
+            let name = xot.add_name(&local_name);
+            let element = xot.new_element(name);
+            if let Some(xsi_type) = xsi_type {
+                xot.set_attribute(element, type_name, xsi_type.clone());
+            }
+            xot.set_attribute(
+                element,
+                href_name,
+                format!("{}#{}", relative_path(directory, file), id),
+            );
+            xot.append(to, element)?;
+        }
+    }
+    Ok(())
+}
+
+/// The path of `file` relative to `directory`, with `/` separators.
+fn relative_path(directory: &Path, file: &Path) -> String {
+    let from: Vec<_> = directory.components().collect();
+    let to: Vec<_> = file.components().collect();
+    let common = from
+        .iter()
+        .zip(&to)
+        .take_while(|(left, right)| left == right)
+        .count();
+    std::iter::repeat_n("..".to_string(), from.len() - common)
+        .chain(
+            to[common..]
+                .iter()
+                .map(|component| component.as_os_str().to_string_lossy().to_string()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The IDs of the folders and concepts in a working tree, with the
+/// directory or file each is in.
+fn existing_paths(model_directory: &Path) -> Result<HashMap<String, PathBuf>, Box<dyn Error>> {
+    let mut paths = HashMap::new();
+    let mut xot = Xot::new();
+    let id_name = xot.add_name("id");
+    let mut directories = subdirectories(model_directory)?;
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(&directory)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "xml") || !path.is_file() {
+                continue;
+            }
+            let fragment = parse_file(&mut xot, &path)?;
+            if let Some(id) = xot.get_attribute(fragment, id_name) {
+                let place = if path.ends_with(FOLDER_FILE) {
+                    directory.clone()
+                } else {
+                    path.clone()
+                };
+                paths.insert(id.to_string(), place);
+            }
+        }
+        directories.extend(subdirectories(&directory)?);
+    }
+    Ok(paths)
+}
+
+/// Copies the attributes of `from` onto `to`, after any `to` already has.
+fn copy_attributes(xot: &mut Xot, from: Node, to: Node) {
+    let attributes: Vec<_> = xot
+        .attributes(from)
+        .iter()
+        .map(|(name, value)| (name, value.clone()))
+        .collect();
+    for (name, value) in attributes {
+        xot.set_attribute(to, name, value);
+    }
+}
+
+/// Parses a fragment file and returns its document element.
+fn parse_file(xot: &mut Xot, path: &Path) -> Result<Node, Box<dyn Error>> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let doc = xot
+        .parse(&content)
+        .map_err(|e| format!("Cannot parse {}: {}", path.display(), e))?;
+    Ok(xot.document_element(doc)?)
+}
+
+/// The folder directories directly below `directory`, sorted by name.
+fn subdirectories(directory: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut directories: Vec<PathBuf> = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    directories.retain(|path| path.join(FOLDER_FILE).is_file());
+    directories.sort();
+    Ok(directories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{format_folder_path, load_model};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_read_working_tree() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let model_directory = dir.path().join(MODEL_DIRECTORY);
+        let views_directory = model_directory.join("diagrams");
+        fs::create_dir_all(views_directory.join("id-folder-2"))?;
+        fs::create_dir_all(model_directory.join("application"))?;
+        let namespaces = r#"xmlns:archimate="http://www.archimatetool.com/archimate" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance""#;
+        let files = [
+            (
+                model_directory.join(FOLDER_FILE),
+                format!(
+                    r#"<archimate:model {} name="Repository" id="model-1"/>"#,
+                    namespaces
+                ),
+            ),
+            (
+                model_directory.join("application").join(FOLDER_FILE),
+                format!(
+                    r#"<archimate:Folder {} name="Application" id="folder-1" type="application"/>"#,
+                    namespaces
+                ),
+            ),
+            (
+                model_directory
+                    .join("application")
+                    .join("ApplicationComponent_element-1.xml"),
+                format!(
+                    r#"<archimate:ApplicationComponent {} name="Portal" id="element-1" documentation="Customer facing"><properties key="status" value="live"/></archimate:ApplicationComponent>"#,
+                    namespaces
+                ),
+            ),
+            (
+                views_directory.join(FOLDER_FILE),
+                format!(
+                    r#"<archimate:Folder {} name="Views" id="folder-3" type="diagrams"/>"#,
+                    namespaces
+                ),
+            ),
+            (
+                views_directory.join("id-folder-2").join(FOLDER_FILE),
+                format!(
+                    r#"<archimate:Folder {} name="Team" id="folder-2"/>"#,
+                    namespaces
+                ),
+            ),
+            (
+                views_directory
+                    .join("id-folder-2")
+                    .join("ArchimateDiagramModel_view-1.xml"),
+                format!(
+                    r#"<archimate:ArchimateDiagramModel {} name="Overview" id="view-1"><children xsi:type="archimate:DiagramObject" id="object-1"><bounds x="12" y="12" width="120" height="55"/><archimateElement xsi:type="archimate:ApplicationComponent" href="../../application/ApplicationComponent_element-1.xml#element-1"/></children></archimate:ArchimateDiagramModel>"#,
+                    namespaces
+                ),
+            ),
+        ];
+        for (path, content) in files {
+            fs::write(path, content)?;
+        }
+
+        assert_eq!(
+            find_repository_root(&model_directory),
+            Some(dir.path().to_path_buf())
+        );
+        assert_eq!(find_repository_root(&views_directory), None);
+
+        let xml = read_working_tree(dir.path())?;
+        let model = load_model(&xml)?;
+        let portal = &model.element_map["element-1"];
+        assert_eq!(portal.xsi_type.as_ref(), "archimate:ApplicationComponent");
+        assert_eq!(
+            portal.properties,
+            vec![("status".to_string(), "live".to_string())]
+        );
+        assert!(portal
+            .xml_string
+            .contains("<documentation>Customer facing</documentation>"));
+        let view = &model.view_map["view-1"];
+        assert_eq!(
+            format_folder_path(&view.folder_path),
+            "Views[diagrams] > Team"
+        );
+        assert!(view.xml_string.contains(
+            r#"<child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1">"#
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_working_tree() -> Result<(), Box<dyn Error>> {
+        let dir = tempdir()?;
+        let model_directory = dir.path().join(MODEL_DIRECTORY);
+        fs::create_dir_all(model_directory.join("application"))?;
+        let namespaces = r#"xmlns:archimate="http://www.archimatetool.com/archimate" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance""#;
+        let portal_file = model_directory
+            .join("application")
+            .join("ApplicationComponent_element-1.xml");
+        // Written by coArchi, so formatted differently from what is rendered
+        let portal = format!(
+            r#"<archimate:ApplicationComponent {} name="Portal" id="element-1"/>"#,
+            namespaces
+        );
+        fs::write(
+            model_directory.join(FOLDER_FILE),
+            format!(
+                r#"<archimate:model {} name="Repository" id="model-1"/>"#,
+                namespaces
+            ),
+        )?;
+        fs::write(
+            model_directory.join("application").join(FOLDER_FILE),
+            format!(
+                r#"<archimate:Folder {} name="Application" id="folder-1" type="application"/>"#,
+                namespaces
+            ),
+        )?;
+        fs::write(&portal_file, &portal)?;
+
+        let xml = read_working_tree(dir.path())?;
+        write_working_tree(dir.path(), &xml)?;
+        assert_eq!(fs::read_to_string(&portal_file)?, portal);
+
+        let xml = xml.replace(
+            r#"<element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1"/>"#,
+            r#"<element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1"/>
+    <element xsi:type="archimate:ApplicationService" name="Orders" id="element-2"><documentation>Order intake</documentation></element>
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-2"/>"#,
+        );
+        write_working_tree(dir.path(), &xml)?;
+        assert_eq!(fs::read_to_string(&portal_file)?, portal);
+        let orders = fs::read_to_string(
+            model_directory
+                .join("application")
+                .join("ApplicationService_element-2.xml"),
+        )?;
+        assert!(orders.contains(r#"name="Orders" id="element-2" documentation="Order intake""#));
+        let serving = fs::read_to_string(
+            model_directory
+                .join("application")
+                .join("ServingRelationship_relation-1.xml"),
+        )?;
+        assert!(serving.contains(
+            r#"<source xsi:type="archimate:ApplicationComponent" href="ApplicationComponent_element-1.xml#element-1"/>"#
+        ));
+
+        let xml = read_working_tree(dir.path())?;
+        let model = load_model(&xml)?;
+        assert_eq!(model.element_map.len(), 3);
+        write_working_tree(
+            dir.path(),
+            &xml.replace(r#"<element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1"/>"#, ""),
+        )?;
+        assert!(!portal_file.exists());
+        Ok(())
+    }
+}
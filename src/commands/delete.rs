@@ -0,0 +1,96 @@
+use crate::cli::DeleteArgs;
+use crate::commands::{password, read_model_file, report_repaired, same_file, select_views};
+use crate::error::ImporterError;
+use crate::file_descriptor::{FileDescriptor, TARGET_PASSWORD_VARIABLE};
+use crate::lock::ModelLock;
+use crate::model::{
+    format_folder_path, load_model, serialize_model, sort_views, MissingElementInfo,
+};
+use crate::prune::{keep_linked_views, remove_views};
+use crate::selection::get_input;
+use std::path::Path;
+
+/// Removes the views named, or found under the folders given, from a model
+/// after listing them and asking for confirmation. The concepts they show
+/// stay, and views still linked from views that stay are kept.
+pub fn run(args: DeleteArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.views.is_empty() && args.folders.is_empty() {
+        return Err(ImporterError::Selection(
+            "Name the views to delete with --view or --folder".to_string(),
+        )
+        .into());
+    }
+    let _lock = ModelLock::acquire(Path::new(&args.model_file))?;
+    let _output_lock = match &args.output {
+        Some(output) if !same_file(&args.model_file, output) => {
+            Some(ModelLock::acquire(Path::new(output))?)
+        }
+        _ => None,
+    };
+    let password = password(&args.password, TARGET_PASSWORD_VARIABLE);
+    let (descriptor, content) = read_model_file(&args.model_file, "model", password.as_deref());
+    let mut model = load_model(&content)?;
+    report_repaired(&model, "model");
+
+    let view_ids = select_views(&model, &args.views, &args.folders);
+    let mut views: Vec<_> = view_ids
+        .iter()
+        .map(|id| {
+            let info = &model.view_map[id.as_str()];
+            MissingElementInfo {
+                id: info.id.clone(),
+                name: info.name.clone(),
+                folder_path: info.folder_path.clone(),
+                target_id: None,
+            }
+        })
+        .collect();
+    sort_views(&mut views);
+    keep_linked_views(&model, &mut views)?;
+    if views.is_empty() {
+        return Err(ImporterError::Selection("No views to delete".to_string()).into());
+    }
+
+    println!("Views to delete from {}:", args.model_file);
+    for (i, view) in views.iter().enumerate() {
+        println!(
+            "[{}] {} (in folder: {})",
+            i + 1,
+            view.name,
+            format_folder_path(&view.folder_path)
+        );
+    }
+    if args.dry_run {
+        println!("Dry run: nothing deleted.");
+        return Ok(());
+    }
+    let confirmed = args.yes
+        || get_input(&format!("Delete {} view(s)? [y/N] ", views.len()))?.eq_ignore_ascii_case("y");
+    if !confirmed {
+        println!("Views not deleted.");
+        return Ok(());
+    }
+
+    remove_views(&mut model, &views)?;
+    let xml = serialize_model(&model)?;
+    match &args.output {
+        Some(output) => descriptor.write_xml_to(
+            &FileDescriptor::for_output(output).with_password(password),
+            &xml,
+            &[],
+            args.zip_compression,
+        )?,
+        None => descriptor.write_xml(&xml, &[], args.zip_compression)?,
+    }
+    println!(
+        "Deleted {} view{} from {}{}",
+        views.len(),
+        if views.len() == 1 { "" } else { "s" },
+        args.model_file,
+        match &args.output {
+            Some(output) => format!(", written to {}", output),
+            None => String::new(),
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::api::diff;
+use crate::cli::DiffArgs;
+use crate::commands::{
+    password, print_missing_views, read_model_file, read_source_file, report_repaired,
+};
+use crate::file_descriptor::{SOURCE_PASSWORD_VARIABLE, TARGET_PASSWORD_VARIABLE};
+use crate::model::{find_missing_views, load_model};
+
+pub fn run(args: DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, source_content) = read_source_file(
+        &args.source_file,
+        "source",
+        args.lenient_exchange,
+        password(&args.source_password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let (_, target_content) = read_model_file(
+        &args.target_file,
+        "target",
+        password(&args.target_password, TARGET_PASSWORD_VARIABLE).as_deref(),
+    );
+    if args.json {
+        let diff = diff(&source_content, &target_content, args.match_by)?;
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    let source = load_model(&source_content)?;
+    let target = load_model(&target_content)?;
+    report_repaired(&source, "source");
+    report_repaired(&target, "target");
+
+    let missing_views = find_missing_views(&source, &target, args.match_by);
+    if missing_views.is_empty() {
+        println!("No new views to copy from source to target.");
+        return Ok(());
+    }
+
+    print_missing_views(&source, &target, &missing_views, None)?;
+    println!(
+        "{} view{} missing from target",
+        missing_views.len(),
+        if missing_views.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use crate::cli::DoctorArgs;
+use crate::commands::{password, read_source_file};
+use crate::error::ImporterError;
+use crate::file_descriptor::SOURCE_PASSWORD_VARIABLE;
+use crate::journal::{self, Journal};
+use crate::model::load_model;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The external commands some options run, with the argument printing
+/// their version and what they are needed for.
+const TOOLS: &[(&str, &str, &str)] = &[
+    ("curl", "--version", "importing from a URL"),
+    (
+        "sqlite3",
+        "-version",
+        "SQLite exports other than .sql scripts",
+    ),
+];
+
+/// Reports whether the commands some options need can be run and, given a
+/// model, whether it can be read and merged safely. Fails with the
+/// validation exit code when the model has issues; missing commands are
+/// only reported, as most runs never need them.
+pub fn run(args: DoctorArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    println!("Tools:");
+    for (tool, version_arg, needed_for) in TOOLS {
+        match tool_version(tool, version_arg) {
+            Some(version) => println!(" +- {}: {}", tool, version),
+            None => println!(
+                " +- {}: not found on the PATH; needed for {}",
+                tool, needed_for
+            ),
+        }
+    }
+
+    let Some(model_file) = &args.model_file else {
+        return Ok(());
+    };
+    let (descriptor, content) = read_source_file(
+        model_file,
+        "model",
+        false,
+        password(&args.password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let model = load_model(&content)?;
+    let stats = model.stats();
+    println!("Model: {}", model_file);
+    println!(" +- Format: {}", descriptor.kind());
+    println!(
+        " +- {} element(s), {} relationship(s), {} view(s)",
+        stats.elements, stats.relationships, stats.views
+    );
+    let journal_path = journal::journal_path(Path::new(model_file));
+    if let Ok(journal) = fs::read(&journal_path) {
+        match serde_json::from_slice::<Journal>(&journal) {
+            Ok(journal) => println!(" +- The import of {} can be undone", journal.timestamp),
+            Err(e) => println!(
+                " +- {} cannot be read ({}); undo will fail",
+                journal_path.display(),
+                e
+            ),
+        }
+    }
+    let issues = model.validate();
+    if issues.is_empty() {
+        println!(" +- No issues found");
+        return Ok(());
+    }
+    println!(" +- {} issue(s) found:", issues.len());
+    for issue in &issues {
+        println!("    - {}", issue.message);
+    }
+    Err(ImporterError::Validation(format!(
+        "{} has {} issue(s); run validate for details",
+        model_file,
+        issues.len()
+    ))
+    .into())
+}
+
+/// The version `tool` reports, as in `2.39.5`, or `None` when it cannot be
+/// run.
+fn tool_version(tool: &str, version_arg: &str) -> Option<String> {
+    let output = Command::new(tool).arg(version_arg).output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout);
+    let version = version
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .unwrap_or("version unknown");
+    Some(version.to_string())
+}
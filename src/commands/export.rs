@@ -0,0 +1,89 @@
+use crate::cli::ExportArgs;
+use crate::commands::{password, read_source_file, report_repaired, select_views};
+use crate::cypher::model_to_cypher;
+use crate::exchange::{archi_to_exchange, view_dependencies, ExportFormat};
+use crate::file_descriptor::SOURCE_PASSWORD_VARIABLE;
+use crate::graphml::model_to_graphml;
+use crate::json_model::to_json;
+use crate::model::load_model;
+use crate::plantuml;
+use crate::sqlite_export::{check_sqlite, write_sqlite};
+use std::path::Path;
+
+pub fn run(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    if args.format == ExportFormat::Sqlite {
+        check_sqlite(Path::new(&args.output_file))?;
+    }
+    let (_, content) = read_source_file(
+        &args.model_file,
+        "model",
+        args.lenient_exchange,
+        password(&args.password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let model = load_model(&content)?;
+    report_repaired(&model, "model");
+
+    let selected = if args.views.is_empty() && args.folders.is_empty() {
+        None
+    } else {
+        let view_ids = select_views(&model, &args.views, &args.folders);
+        if view_ids.is_empty() {
+            eprintln!("No views selected for export.");
+            std::process::exit(1);
+        }
+        Some(view_ids)
+    };
+
+    if args.format == ExportFormat::Json && selected.is_some() {
+        eprintln!(
+            "The JSON export writes the whole model; --view and --folder cannot be used with it."
+        );
+        std::process::exit(1);
+    }
+
+    // The selected views' concepts, for formats without views
+    let selected_concepts = || match &selected {
+        Some(view_ids) => view_dependencies(&model, view_ids).map(Some),
+        None => Ok(None),
+    };
+    let content = match args.format {
+        ExportFormat::Exchange => archi_to_exchange(&model, selected.as_ref())?,
+        ExportFormat::GraphMl => model_to_graphml(&model, selected_concepts()?.as_ref())?,
+        ExportFormat::Cypher => model_to_cypher(&model, selected_concepts()?.as_ref())?,
+        ExportFormat::Json => to_json(&model)?,
+        ExportFormat::Sqlite => {
+            write_sqlite(&model, Path::new(&args.output_file), &args.model_file)?;
+            println!(
+                "Exported {} to {} as SQLite",
+                args.model_file, args.output_file
+            );
+            return Ok(());
+        }
+        ExportFormat::PlantUml => {
+            let mut view_ids: Vec<String> = match &selected {
+                Some(view_ids) => view_ids.iter().cloned().collect(),
+                None => model.view_map.keys().map(|id| id.to_string()).collect(),
+            };
+            view_ids.sort();
+            let written = plantuml::write_views(&model, &view_ids, Path::new(&args.output_file))?;
+            println!(
+                "Exported {} view{} to {} as PlantUML",
+                written.len(),
+                if written.len() == 1 { "" } else { "s" },
+                args.output_file
+            );
+            return Ok(());
+        }
+    };
+    std::fs::write(&args.output_file, content)?;
+    match &selected {
+        Some(view_ids) => println!(
+            "Exported {} view{} and the concepts they show to {}",
+            view_ids.len(),
+            if view_ids.len() == 1 { "" } else { "s" },
+            args.output_file
+        ),
+        None => println!("Exported {} to {}", args.model_file, args.output_file),
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::bundle::{BundleManifest, BundledView, MANIFEST_ENTRY};
+use crate::cli::ExtractArgs;
+use crate::commands::{password, read_source_file, report_repaired, select_views};
+use crate::copy::{copy_view, CopyOptions};
+use crate::file_descriptor::SOURCE_PASSWORD_VARIABLE;
+use crate::file_descriptor::{FileDescriptor, FileKind};
+use crate::images::find_image_paths;
+use crate::model::{
+    format_folder_path, load_model, serialize_model, sort_views, MissingElementInfo,
+};
+use std::path::Path;
+use std::process;
+use xot::{output, Xot};
+
+pub fn run(args: ExtractArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (source_descriptor, content) = read_source_file(
+        &args.model_file,
+        "model",
+        args.lenient_exchange,
+        password(&args.password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let mut source = load_model(&content)?;
+    report_repaired(&source, "model");
+
+    let view_ids = select_views(&source, &args.views, &args.folders);
+    if view_ids.is_empty() {
+        eprintln!("No views selected for extraction.");
+        process::exit(1);
+    }
+    let mut views: Vec<MissingElementInfo> = view_ids
+        .iter()
+        .map(|id| {
+            let info = &source.view_map[id.as_str()];
+            MissingElementInfo {
+                id: info.id.clone(),
+                name: info.name.clone(),
+                folder_path: info.folder_path.clone(),
+                target_id: None,
+            }
+        })
+        .collect();
+    sort_views(&mut views);
+
+    let empty = empty_model(&content)?;
+    let mut bundle = load_model(&empty)?;
+    let options = CopyOptions {
+        verbose: args.verbose,
+        ..Default::default()
+    };
+    let (mut elements, mut relations) = (0, 0);
+    for view in &views {
+        let (_, copied_elements, copied_relations) =
+            copy_view(&mut source, &mut bundle, view, &options)?;
+        elements += copied_elements;
+        relations += copied_relations;
+    }
+
+    let mut entries = Vec::new();
+    for path in find_image_paths(&bundle) {
+        match source_descriptor.read_entry(&path)? {
+            Some(content) => entries.push((path, content)),
+            None => println!("Warning: image {} not found in model", path),
+        }
+    }
+    // A bundle carries a manifest; a model file only needs to be zipped
+    // when it holds images, as Archi does
+    let zipped = FileKind::from_extension(&args.output_file) == Some(FileKind::ZippedXml);
+    if zipped {
+        let source_name = Path::new(&args.model_file)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| args.model_file.clone());
+        let bundled_views = views
+            .iter()
+            .map(|view| BundledView {
+                id: view.id.to_string(),
+                name: view.name.to_string(),
+                folder_path: format_folder_path(&view.folder_path),
+            })
+            .collect();
+        let manifest = BundleManifest::new(&source_name, bundled_views);
+        entries.push((
+            MANIFEST_ENTRY.to_string(),
+            serde_json::to_vec_pretty(&manifest)?,
+        ));
+    }
+    let output = if zipped || !entries.is_empty() {
+        FileDescriptor::ZippedXml {
+            zip_path: args.output_file.clone().into(),
+            xml_filename: "model.xml".to_string(),
+            password: None,
+        }
+    } else {
+        FileDescriptor::PlainXml {
+            path: args.output_file.clone().into(),
+        }
+    };
+    let entries: Vec<(&str, &[u8])> = entries
+        .iter()
+        .map(|(name, content)| (name.as_str(), content.as_slice()))
+        .collect();
+    // Nothing is carried over from a file already at the output path
+    FileDescriptor::PlainXml {
+        path: args.output_file.clone().into(),
+    }
+    .write_xml_to(
+        &output,
+        &serialize_model(&bundle)?,
+        &entries,
+        args.zip_compression,
+    )?;
+
+    println!(
+        "Extracted {} view{}, {} element{} and {} relation{} to {}",
+        views.len(),
+        if views.len() == 1 { "" } else { "s" },
+        elements,
+        if elements == 1 { "" } else { "s" },
+        relations,
+        if relations == 1 { "" } else { "s" },
+        args.output_file
+    );
+    Ok(())
+}
+
+/// The model without its concepts, views, subfolders and profiles, keeping
+/// its name, ID, properties and top-level folders.
+fn empty_model(content: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut xot = Xot::new();
+    let doc = xot.parse(content)?;
+    let model = xot.document_element(doc)?;
+    let children: Vec<_> = xot
+        .children(model)
+        .filter(|&child| xot.is_element(child))
+        .collect();
+    for child in children {
+        match xot.local_name_str(xot.get_element_name(child)) {
+            "folder" => {
+                while let Some(grandchild) = xot.first_child(child) {
+                    xot.remove(grandchild)?;
+                }
+            }
+            "profile" => xot.remove(child)?,
+            _ => {}
+        }
+    }
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        doc,
+    )?)
+}
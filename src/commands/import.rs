@@ -0,0 +1,1242 @@
+use crate::bundle;
+use crate::cli::ImportArgs;
+use crate::commands::{
+    password, print_missing_views, read_model_file, read_source, read_source_file, same_file,
+    DEFAULT_PAGE_SIZE,
+};
+use crate::conflict::{
+    find_conflicts, find_skipped_concept, is_view_conflict, read_resolutions,
+    resolve_view_conflict, write_conflicts_file, ConflictPolicy, Resolution,
+};
+use crate::copy::{copy_view, find_unresolved_references, CopyOptions, ViewOutcome};
+use crate::csv_export;
+use crate::csv_import::{import_csv_elements, CsvChanges};
+use crate::cypher::model_to_cypher;
+use crate::download;
+use crate::error::ImporterError;
+use crate::exchange::{archi_to_exchange, view_dependencies, ExportScope};
+use crate::file_descriptor::{
+    FileDescriptor, FileKind, SOURCE_PASSWORD_VARIABLE, TARGET_PASSWORD_VARIABLE,
+};
+use crate::git_revision;
+use crate::graphml::model_to_graphml;
+use crate::history::{self, ImportRecord, ImportedView};
+use crate::ids::IdGenerator;
+use crate::images;
+use crate::jarchi;
+use crate::journal::{self, Journal};
+use crate::lock::ModelLock;
+use crate::merge::{filter_views_with_base, resolve_with_base};
+use crate::model::{
+    extract_elements, find_changed_views, find_views_to_import, format_folder_path, is_view_type,
+    load_model, serialize_model, ArchiModel, MissingElementInfo,
+};
+use crate::plantuml;
+use crate::prune::{find_prunable_views, remove_views};
+use crate::report::{folder_paths, report_html, report_markdown, ImportReport};
+use crate::selection::{
+    find_referenced_views, find_views_containing_element, get_input, has_properties, is_excluded,
+    is_in_folder, parse_selection,
+};
+use crate::selection_file;
+use crate::shared_concepts;
+use crate::sqlite_export::{check_sqlite, write_sqlite};
+use crate::streaming;
+use crate::svg::view_to_svg;
+use crate::validate::ValidationIssue;
+use crate::view_hash::find_identical_views;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, IsTerminal};
+use std::process;
+use std::time::{Duration, Instant};
+
+pub fn run(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let git_source = match &args.source_ref {
+        Some(reference) => Some(git_revision::split_reference(reference)?),
+        None => None,
+    };
+    let (source_file, target_file) = match (git_source, args.files.as_slice()) {
+        (Some((_, path)), [target_file]) => (path, target_file),
+        (None, [source_file, target_file]) => (source_file.as_str(), target_file),
+        (Some(_), _) => {
+            return Err(ImporterError::Validation(
+                "--source-ref names the source; pass only the target file".to_string(),
+            )
+            .into())
+        }
+        (None, _) => {
+            return Err(ImporterError::Validation(
+                "pass the source and the target file, or --source-ref and the target file"
+                    .to_string(),
+            )
+            .into())
+        }
+    };
+
+    println!("-+ Analyzing Archi files");
+    match git_source {
+        Some((revision, path)) => println!(" +- Source: {} at {}", path, revision),
+        None => println!(" +- Source: {}", source_file),
+    }
+    println!(" +- Target: {}", target_file);
+    if let Some(output) = &args.output {
+        println!(" +- Output: {}", output);
+    }
+    // A model has no views of its own to import, and reading and writing
+    // it in one run would only risk it
+    if args.source_ref.is_none() && same_file(source_file, target_file) {
+        return Err(ImporterError::Validation(format!(
+            "{} and {} are the same file; nothing to import (pass --source-ref to import from an earlier revision of it)",
+            source_file, target_file
+        ))
+        .into());
+    }
+    if let Some(path) = &args.export_sqlite {
+        check_sqlite(std::path::Path::new(path))?;
+    }
+    let _lock = ModelLock::acquire(std::path::Path::new(
+        args.output.as_deref().unwrap_or(target_file),
+    ))?;
+
+    let ids = match args.seed {
+        Some(seed) => IdGenerator::new(seed),
+        None => IdGenerator::from_entropy(),
+    };
+    let ids = match &args.id_prefix {
+        Some(prefix) => ids.with_prefix(prefix),
+        None => ids,
+    };
+    let seed = ids.seed();
+    println!(
+        " +- Seed: {} (rerun with --seed {} to reproduce)",
+        seed, seed
+    );
+
+    let source_password = password(&args.source_password, SOURCE_PASSWORD_VARIABLE);
+    let target_password = password(&args.target_password, TARGET_PASSWORD_VARIABLE);
+    // A source from a Git revision or a URL is fetched into memory
+    let fetched = if let Some(reference) = &args.source_ref {
+        Some(git_revision::extract(reference))
+    } else if download::is_url(source_file) {
+        let token = args
+            .source_token
+            .clone()
+            .or_else(|| std::env::var(download::TOKEN_VARIABLE).ok());
+        Some(download::download(source_file, token.as_deref()))
+    } else {
+        None
+    };
+    let (source_descriptor, source_content) = match fetched {
+        Some(Ok(descriptor)) => read_source(
+            descriptor.with_password(source_password.clone()),
+            "source",
+            args.lenient_exchange,
+        ),
+        Some(Err(e)) => {
+            eprintln!("Error reading source file: {}", e);
+            process::exit(1);
+        }
+        None => read_source_file(
+            source_file,
+            "source",
+            args.lenient_exchange,
+            source_password.as_deref(),
+        ),
+    };
+    // Warnings are kept for the report
+    let mut warnings = Vec::new();
+    let manifest = match bundle::read_manifest(&source_descriptor) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            warn(
+                &mut warnings,
+                format!("ignoring the bundle manifest of the source: {}", e),
+            );
+            None
+        }
+    };
+    if let Some(manifest) = &manifest {
+        println!(
+            " +- Bundle of {} view{} extracted from {}",
+            manifest.views.len(),
+            if manifest.views.len() == 1 { "" } else { "s" },
+            manifest.source
+        );
+    }
+    let (target_descriptor, target_content) =
+        read_model_file(target_file, "target", target_password.as_deref());
+    // An output is protected with the target's password
+    let output_override = args
+        .output
+        .as_ref()
+        .map(|output| FileDescriptor::for_output(output).with_password(target_password.clone()));
+    // Without --output the target is updated in place
+    let output_descriptor = output_override.as_ref().unwrap_or(&target_descriptor);
+    verbose_println!(
+        args.verbose,
+        " +- Source format: {}",
+        source_descriptor.kind()
+    );
+    verbose_println!(
+        args.verbose,
+        " +- Target format: {}",
+        target_descriptor.kind()
+    );
+    verbose_println!(
+        args.verbose,
+        " +- Output format: {}",
+        output_descriptor.kind()
+    );
+
+    // Only the views chosen up front, and what they need, are parsed
+    let source_content = if args.low_memory {
+        match reduce_source(&args, source_content) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("Error indexing source file: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        source_content
+    };
+    let started = Instant::now();
+    let mut source = load_model(&source_content)?;
+    // The document holds all that is needed of the text from here on
+    drop(source_content);
+    if args.memory_stats {
+        report_memory_stats(&source, "source", started.elapsed());
+    }
+    warn_repaired(&mut warnings, &source, "source");
+    if let Some(manifest) = &manifest {
+        for view in &manifest.views {
+            if !source.view_map.contains_key(view.id.as_str()) {
+                warn(
+                    &mut warnings,
+                    format!(
+                        "bundle lists view {} ({}) but does not hold it",
+                        view.name, view.id
+                    ),
+                );
+            }
+        }
+    }
+    // Elements from a spreadsheet land before any view is compared, so the
+    // views can use them
+    let started = Instant::now();
+    let mut target = load_model(&target_content)?;
+    drop(target_content);
+    if args.memory_stats {
+        report_memory_stats(&target, "target", started.elapsed());
+    }
+    warn_repaired(&mut warnings, &target, "target");
+    target.ids = ids;
+    let mut csv_changes = CsvChanges::default();
+    if let Some(path) = &args.csv_elements {
+        csv_changes = match import_csv_elements(&mut target, std::path::Path::new(path)) {
+            Ok(changes) => changes,
+            Err(e) => {
+                eprintln!("Error reading CSV elements: {}", e);
+                process::exit(1);
+            }
+        };
+        println!(
+            "Created {} and updated {} element(s) from {}",
+            csv_changes.created, csv_changes.updated, path
+        );
+        extract_elements(&mut target)?;
+    }
+    target.folder_id_collision = args.folder_id_collision;
+    // Issues the target already has are not blamed on the import
+    let target_issues = target.validate();
+
+    let mut pruned_views = 0;
+    if let Some(folder) = &args.prune {
+        let prunable = find_prunable_views(&source, &target, args.match_by, folder)?;
+        if prunable.is_empty() {
+            println!("No target views to prune under {}.", folder);
+        } else {
+            println!(
+                "\nViews in target under {} that don't exist in source:",
+                folder
+            );
+            for (i, view) in prunable.iter().enumerate() {
+                println!(
+                    "[{}] {} (in folder: {})",
+                    i + 1,
+                    view.name,
+                    format_folder_path(&view.folder_path)
+                );
+            }
+            let confirmed = args.yes
+                || get_input(&format!(
+                    "Delete {} view(s) from target? [y/N] ",
+                    prunable.len()
+                ))?
+                .eq_ignore_ascii_case("y");
+            if confirmed {
+                remove_views(&mut target, &prunable)?;
+                println!("Pruned {} view(s)", prunable.len());
+                pruned_views = prunable.len();
+            } else {
+                println!("Views not pruned.");
+            }
+        }
+    }
+
+    let on_conflict = if args.on_conflict == ConflictPolicy::Newer {
+        let source_is_newer = source_descriptor.modified()? > target_descriptor.modified()?;
+        println!(
+            "Conflicts take the {} version, which was modified more recently",
+            if source_is_newer { "source" } else { "target" }
+        );
+        args.on_conflict.resolve_newer(source_is_newer)
+    } else {
+        args.on_conflict
+    };
+    // Views differing from the target view with their ID are only offered
+    // when the source version may win
+    let replace_changed = args.update || on_conflict != ConflictPolicy::Ours;
+    let mut missing_views = find_views_to_import(&source, &target, args.match_by, replace_changed);
+    if !replace_changed {
+        let kept = find_changed_views(&source, &target, args.match_by)
+            .iter()
+            .filter(|view| is_view_conflict(view))
+            .count();
+        if kept > 0 {
+            println!(
+                "Keeping the target version of {} view(s) that differ from the source; use --on-conflict theirs or --update to replace them",
+                kept
+            );
+        }
+    }
+    // A common ancestor turns the one-way copy into a three-way merge
+    let base_password = args.base_password.clone().or(source_password);
+    let base_content = args
+        .base
+        .as_ref()
+        .map(|base_file| read_model_file(base_file, "base", base_password.as_deref()).1);
+    let base = match &base_content {
+        Some(content) => Some(load_model(content)?),
+        None => None,
+    };
+    if let Some(base) = &base {
+        missing_views = filter_views_with_base(base, &source, &target, missing_views)?;
+    }
+    if !args.property_filters.is_empty() {
+        missing_views.retain(|view| {
+            has_properties(
+                &source.view_map.get(&view.id).unwrap().properties,
+                &args.property_filters,
+            )
+        });
+    }
+
+    if !args.keep_duplicates {
+        let identical = find_identical_views(&source, &target, &missing_views)?;
+        missing_views.retain(|view| match identical.get(&*view.id) {
+            Some(target_info) => {
+                println!(
+                    "Skipping view {}: identical to target view {} ({})",
+                    view.name, target_info.name, target_info.id
+                );
+                false
+            }
+            None => true,
+        });
+    }
+
+    if missing_views.is_empty() {
+        println!("No new views to copy from source to target.");
+        return write_target_without_views(
+            &args,
+            &target_descriptor,
+            output_descriptor,
+            &target,
+            &target_issues,
+            pruned_views,
+            &csv_changes,
+        );
+    }
+
+    let interactive = !(args.all
+        || !args.views.is_empty()
+        || !args.folders.is_empty()
+        || !args.containing_elements.is_empty()
+        || args.selection_file.is_some());
+    // Paging only makes sense while someone is reading the listing
+    let page_size = match args.page_size {
+        _ if !interactive => None,
+        Some(0) => None,
+        Some(page_size) => Some(page_size),
+        None if io::stdout().is_terminal() => Some(DEFAULT_PAGE_SIZE),
+        None => None,
+    };
+    print_missing_views(&source, &target, &missing_views, page_size)?;
+
+    let selected_indices = if !interactive {
+        let mut indices = HashSet::new();
+        if args.all {
+            indices.extend(1..=missing_views.len());
+        }
+        for view_name in &args.views {
+            if let Some(pos) = missing_views
+                .iter()
+                .position(|v| *v.name == **view_name || *v.id == **view_name)
+            {
+                indices.insert(pos + 1); // Convert to 1-based index
+            } else {
+                verbose_println!(
+                    args.verbose,
+                    "Warning: View '{}' not found in source or already exists in target",
+                    view_name
+                );
+            }
+        }
+        for folder in &args.folders {
+            let folder_indices: Vec<usize> = missing_views
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| is_in_folder(&v.folder_path, folder))
+                .map(|(i, _)| i + 1)
+                .collect();
+            if folder_indices.is_empty() {
+                verbose_println!(
+                    args.verbose,
+                    "Warning: No missing views found in folder '{}'",
+                    folder
+                );
+            }
+            indices.extend(folder_indices);
+        }
+        for element in &args.containing_elements {
+            let element_indices = find_views_containing_element(&source, &missing_views, element)?;
+            if element_indices.is_empty() {
+                verbose_println!(
+                    args.verbose,
+                    "Warning: No missing views contain element '{}'",
+                    element
+                );
+            }
+            indices.extend(element_indices);
+        }
+        if let Some(selection_file) = &args.selection_file {
+            for view_id in selection_file::read_selection_file(selection_file)? {
+                if let Some(pos) = missing_views.iter().position(|v| *v.id == view_id) {
+                    indices.insert(pos + 1);
+                } else {
+                    verbose_println!(
+                        args.verbose,
+                        "Warning: View '{}' not found in source or already exists in target",
+                        view_id
+                    );
+                }
+            }
+        }
+        let mut indices: Vec<usize> = indices.into_iter().collect();
+        indices.sort();
+        indices
+    } else {
+        let selection = get_input(
+            "\nEnter view numbers to copy (e.g., 1,3,5-7, 'all', 'all - 2,4' or '!2-4'): ",
+        )?;
+        parse_selection(&selection, missing_views.len())?
+    };
+
+    let mut selected_indices: Vec<usize> = selected_indices
+        .into_iter()
+        .filter(|&idx| {
+            let view = &missing_views[idx - 1];
+            let excluded = is_excluded(&view.name, &args.excludes, &args.exclude_patterns);
+            if excluded {
+                verbose_println!(args.verbose, "Excluding view {}", view.name);
+            }
+            !excluded
+        })
+        .collect();
+
+    if let Some(save_selection) = &args.save_selection {
+        let views: Vec<(&str, &str)> = selected_indices
+            .iter()
+            .map(|&idx| {
+                let view = &missing_views[idx - 1];
+                (&*view.id, &*view.name)
+            })
+            .collect();
+        selection_file::write_selection_file(save_selection, &views)?;
+        println!(
+            "Saved selection of {} view(s) to {}",
+            views.len(),
+            save_selection
+        );
+    }
+
+    if args.recursive {
+        let referenced =
+            find_referenced_views(&source, &target, &missing_views, &selected_indices)?;
+        for &idx in &referenced {
+            println!(
+                "Including view {}: linked from a selected view",
+                missing_views[idx - 1].name
+            );
+        }
+        selected_indices.extend(referenced);
+        selected_indices.sort();
+    }
+
+    if on_conflict == ConflictPolicy::Prompt && !args.update {
+        let mut kept = Vec::new();
+        for idx in selected_indices {
+            let view = &missing_views[idx - 1];
+            if !is_view_conflict(view) || resolve_view_conflict(view, on_conflict)? {
+                kept.push(idx);
+            }
+        }
+        selected_indices = kept;
+    }
+
+    if selected_indices.is_empty() {
+        println!("No views selected for copying.");
+        return write_target_without_views(
+            &args,
+            &target_descriptor,
+            output_descriptor,
+            &target,
+            &target_issues,
+            pruned_views,
+            &csv_changes,
+        );
+    }
+
+    let mut conflicts = Vec::new();
+    if args.write_conflicts.is_some() || base.is_some() {
+        let selected_views: Vec<_> = selected_indices
+            .iter()
+            .map(|&idx| &missing_views[idx - 1])
+            .collect();
+        conflicts = find_conflicts(&source, &target, &selected_views)?;
+    }
+    let mut resolutions = HashMap::new();
+    if let Some(base) = &base {
+        conflicts = resolve_with_base(base, &source, &target, conflicts)?;
+        for conflict in &conflicts {
+            match conflict.resolution {
+                Resolution::TakeSource => {
+                    println!("Merge: {} only changed in source since base", conflict.name)
+                }
+                Resolution::KeepTarget => {
+                    println!("Merge: {} only changed in target since base", conflict.name)
+                }
+                _ => continue,
+            }
+            resolutions.insert(conflict.id.clone(), conflict.resolution);
+        }
+        conflicts.retain(|conflict| conflict.resolution == Resolution::Unresolved);
+    }
+
+    if let Some(conflicts_file) = &args.write_conflicts {
+        if !conflicts.is_empty() {
+            write_conflicts_file(conflicts_file, &conflicts)?;
+            println!(
+                "Wrote {} conflict(s) to {}; set their resolutions and rerun with --resolutions {}",
+                conflicts.len(),
+                conflicts_file,
+                conflicts_file
+            );
+            println!("Target file left unchanged.");
+            return Ok(());
+        }
+        println!("No conflicts found.");
+    }
+
+    if let Some(resolutions_file) = &args.resolutions {
+        resolutions.extend(read_resolutions(resolutions_file)?);
+    }
+    if !resolutions.is_empty() {
+        let mut kept = Vec::new();
+        for idx in selected_indices {
+            let view = &missing_views[idx - 1];
+            match find_skipped_concept(&source, view, &resolutions)? {
+                Some(concept_id) => println!(
+                    "Skipping view {}: conflict on {} is resolved as skip",
+                    view.name, concept_id
+                ),
+                None => kept.push(idx),
+            }
+        }
+        selected_indices = kept;
+        if selected_indices.is_empty() {
+            println!("No views selected for copying.");
+            return write_target_without_views(
+                &args,
+                &target_descriptor,
+                output_descriptor,
+                &target,
+                &target_issues,
+                pruned_views,
+                &csv_changes,
+            );
+        }
+    }
+    // Snapshot which target views use each concept before views are added
+    let target_concept_views = match &args.shared_concepts {
+        Some(_) => Some(shared_concepts::find_concept_views(&target)?),
+        None => None,
+    };
+
+    // The reports list the folders and concepts the target did not have,
+    // and every conflict however it was settled
+    let reporting = args.report.is_some() || args.report_md.is_some();
+    let mut report_conflicts = Vec::new();
+    let mut original_concepts = HashSet::new();
+    let mut original_folders = BTreeMap::new();
+    if reporting {
+        let selected_views: Vec<_> = selected_indices
+            .iter()
+            .map(|&idx| &missing_views[idx - 1])
+            .collect();
+        report_conflicts = find_conflicts(&source, &target, &selected_views)?;
+        original_concepts = target.element_map.keys().cloned().collect();
+        original_folders = folder_paths(&target);
+    }
+
+    let mut copied_views = 0;
+    let mut copied_elements = 0;
+    let mut copied_relations = 0;
+    let mut imported_views = Vec::new();
+    let mut updated_view_ids = HashSet::new();
+    let mut outcomes = Vec::new();
+    let source_name = std::path::Path::new(source_file)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| source_file.to_string());
+    let source_name = match git_source {
+        Some((revision, _)) => format!("{}@{}", source_name, revision),
+        // Where a download came from is only known from the whole URL
+        None if download::is_url(source_file) => source_file.to_string(),
+        None => source_name,
+    };
+    let source_name = match &manifest {
+        Some(manifest) => format!("{} (bundle from {})", source_name, manifest.source),
+        None => source_name,
+    };
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let copy_options = CopyOptions {
+        verbose: args.verbose,
+        create_stubs: args.create_stubs,
+        // Explain concepts to whoever is reviewing an interactive import
+        explain: args.explain || interactive,
+        on_conflict,
+        merge_properties: args.merge_properties,
+        merge_documentation: args.merge_documentation,
+        resolutions,
+        into_folder: args.into_folder.clone(),
+        flatten: args.flatten,
+        align_folders: args.align_folders,
+        include_model_relations: args.include_model_relations,
+        no_relations: args.no_relations,
+        visual_only: args.visual_only,
+        snapshot_folder: args.snapshot_folder.then(|| today.clone()),
+        provenance: args
+            .provenance
+            .then(|| format!("{} @ {}", source_name, today)),
+        reuse_by_name: args.reuse_by_name,
+        fresh_ids: args.fresh_ids,
+        renames: args.renames.iter().cloned().collect(),
+        rename_template: args.rename_template.clone(),
+        on_name_collision: args.on_name_collision,
+        hooks: Default::default(),
+    };
+
+    if args.strict {
+        check_references(&source, &target, &missing_views, &selected_indices)?;
+    }
+
+    for &idx in &selected_indices {
+        let view = &missing_views[idx - 1]; // Convert to 0-based index
+        match copy_view(&mut source, &mut target, view, &copy_options) {
+            Ok((view_count, element_count, relation_count)) => {
+                copied_views += view_count;
+                copied_elements += element_count;
+                copied_relations += relation_count;
+                imported_views.push(ImportedView {
+                    id: view.id.to_string(),
+                    name: view.name.to_string(),
+                });
+                updated_view_ids.extend(view.target_id.clone());
+                outcomes.push(ViewOutcome {
+                    name: view.name.to_string(),
+                    error: None,
+                });
+            }
+            Err(e) => {
+                eprintln!("Error copying view {}: {}", view.name, e);
+                if args.fail_fast {
+                    eprintln!("Aborting import; target file left unchanged.");
+                    process::exit(1);
+                }
+                outcomes.push(ViewOutcome {
+                    name: view.name.to_string(),
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    if copied_views == 0 {
+        print_view_outcomes(&outcomes);
+        eprintln!("No views could be imported; target file left unchanged.");
+        process::exit(1);
+    }
+
+    // Images live in archive entries or working tree files, which plain XML
+    // files cannot hold
+    let mut image_entries = Vec::new();
+    if output_descriptor.holds_images() {
+        image_entries = images::import_images(&source_descriptor, &target_descriptor, &mut target)?;
+        if !image_entries.is_empty() {
+            println!("Copied {} image(s)", image_entries.len());
+        }
+    } else if source_descriptor.holds_images() && !images::find_image_paths(&target).is_empty() {
+        warn(
+            &mut warnings,
+            "images are not copied into plain XML files".to_string(),
+        );
+    }
+
+    let imported_view_ids: Vec<String> =
+        imported_views.iter().map(|view| view.id.clone()).collect();
+    let modified_target = serialize_model(&target)?;
+    verify_merged(&args, &target_issues, &modified_target, &mut warnings)?;
+    let original_size = std::fs::metadata(target_descriptor.path())?.len();
+    if let Some(path) = &args.emit_jarchi {
+        if !image_entries.is_empty() {
+            warn(
+                &mut warnings,
+                "images are not carried by jArchi scripts".to_string(),
+            );
+        }
+        write_jarchi_script(path, &target_descriptor, &modified_target)?;
+    } else {
+        let mut history = None;
+        if output_descriptor.is_zipped() && !args.no_history {
+            let record = ImportRecord::new(
+                &source_name,
+                imported_views,
+                copied_elements,
+                copied_relations,
+                seed,
+            );
+            let existing = target_descriptor.read_entry(history::HISTORY_ENTRY)?;
+            history = Some(history::append_record(existing.as_deref(), record)?);
+            verbose_println!(
+                args.verbose,
+                "Recording import in {}",
+                history::HISTORY_ENTRY
+            );
+        }
+        let entries: Vec<(&str, &[u8])> = history
+            .iter()
+            .map(|content| (history::HISTORY_ENTRY, content.as_slice()))
+            .chain(
+                image_entries
+                    .iter()
+                    .map(|(path, content)| (path.as_str(), content.as_slice())),
+            )
+            .collect();
+
+        let journal = record_journal(
+            &args,
+            &target_descriptor,
+            output_descriptor,
+            &modified_target,
+            history.is_some(),
+        )?;
+        let written = match &output_override {
+            Some(output) => target_descriptor.write_xml_to(
+                output,
+                &modified_target,
+                &entries,
+                args.zip_compression,
+            ),
+            None => target_descriptor.write_xml(&modified_target, &entries, args.zip_compression),
+        };
+        match written {
+            Ok(_) => {
+                println!(
+                    "Successfully imported views and elements into {} file.",
+                    if args.output.is_some() {
+                        "output"
+                    } else {
+                        "target"
+                    }
+                );
+                save_journal(output_descriptor, journal.as_ref())?;
+            }
+            Err(e) => {
+                eprintln!("Error writing to target file: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    println!(
+        "Successfully copied:\n- {} view{}\n- {} element{}\n- {} relation{}",
+        copied_views,
+        if copied_views == 1 { "" } else { "s" },
+        copied_elements,
+        if copied_elements == 1 { "" } else { "s" },
+        copied_relations,
+        if copied_relations == 1 { "" } else { "s" }
+    );
+    if args.emit_jarchi.is_none() {
+        if output_descriptor.kind() == FileKind::WorkingTree {
+            println!("Written as {}", output_descriptor.kind());
+        } else {
+            let new_size = std::fs::metadata(output_descriptor.path())?.len();
+            println!(
+                "Written as {}, file size: {} -> {} bytes ({:+})",
+                output_descriptor.kind(),
+                original_size,
+                new_size,
+                new_size as i64 - original_size as i64
+            );
+        }
+    }
+    println!("Seed: {}", seed);
+    if let (Some(path), Some(target_concept_views)) = (&args.shared_concepts, &target_concept_views)
+    {
+        let shared = shared_concepts::find_shared_concepts(
+            &source,
+            &target,
+            target_concept_views,
+            &imported_view_ids,
+        )?;
+        shared_concepts::write_shared_concepts(path, &shared)?;
+        println!("Wrote {} shared concept(s) to {}", shared.len(), path);
+    }
+    if let Some(path) = &args.export_exchange {
+        std::fs::write(path, archi_to_exchange(&target, None)?)?;
+        println!(
+            "Exported the merged model to {} in Open Exchange Format",
+            path
+        );
+    }
+    if args.export_csv.is_some()
+        || args.export_graphml.is_some()
+        || args.export_cypher.is_some()
+        || args.export_sqlite.is_some()
+        || args.export_plantuml.is_some()
+        || reporting
+    {
+        let merged = load_model(&modified_target)?;
+        // Views the import added or replaced, as they are in the merged model
+        let mut imported_views: Vec<String> = merged
+            .view_map
+            .keys()
+            .filter(|id| !target.view_map.contains_key(*id) || updated_view_ids.contains(*id))
+            .map(|id| id.to_string())
+            .collect();
+        imported_views.sort();
+        let scoped_concepts = |scope: ExportScope| match scope {
+            ExportScope::Model => Ok(None),
+            ExportScope::Imported => {
+                view_dependencies(&merged, &imported_views.iter().cloned().collect()).map(Some)
+            }
+        };
+        if let Some(directory) = &args.export_csv {
+            let concept_ids = scoped_concepts(args.csv_scope)?;
+            csv_export::write_csv(
+                &merged,
+                std::path::Path::new(directory),
+                concept_ids.as_ref(),
+            )?;
+            println!(
+                "Exported the {} to {} as CSV",
+                scope_description(args.csv_scope),
+                directory
+            );
+        }
+        if let Some(path) = &args.export_graphml {
+            let concept_ids = scoped_concepts(args.graphml_scope)?;
+            std::fs::write(path, model_to_graphml(&merged, concept_ids.as_ref())?)?;
+            println!(
+                "Exported the {} to {} as GraphML",
+                scope_description(args.graphml_scope),
+                path
+            );
+        }
+        if let Some(path) = &args.export_cypher {
+            let concept_ids = scoped_concepts(args.cypher_scope)?;
+            std::fs::write(path, model_to_cypher(&merged, concept_ids.as_ref())?)?;
+            println!(
+                "Exported the {} to {} as Cypher statements",
+                scope_description(args.cypher_scope),
+                path
+            );
+        }
+        if let Some(path) = &args.export_sqlite {
+            let file = args.output.as_deref().unwrap_or(target_file);
+            write_sqlite(&merged, std::path::Path::new(path), file)?;
+            println!("Exported the merged model to {} as SQLite", path);
+        }
+        if let Some(directory) = &args.export_plantuml {
+            let written =
+                plantuml::write_views(&merged, &imported_views, std::path::Path::new(directory))?;
+            println!(
+                "Exported {} imported view{} to {} as PlantUML",
+                written.len(),
+                if written.len() == 1 { "" } else { "s" },
+                directory
+            );
+        }
+        if reporting {
+            let mut new_folders: Vec<String> = folder_paths(&merged)
+                .into_iter()
+                .filter(|(id, _)| !original_folders.contains_key(id))
+                .map(|(_, path)| path)
+                .collect();
+            new_folders.sort();
+            let new_concepts = merged
+                .element_map
+                .values()
+                .filter(|concept| {
+                    !original_concepts.contains(&concept.id) && !is_view_type(&concept.xsi_type)
+                })
+                .collect();
+            let conflicts = report_conflicts
+                .iter()
+                .map(|conflict| {
+                    let settled = match copy_options.resolutions.get(&conflict.id) {
+                        Some(Resolution::KeepTarget) => "kept target (resolution)".to_string(),
+                        Some(Resolution::TakeSource) => "took source (resolution)".to_string(),
+                        Some(Resolution::Skip) => "skipped (resolution)".to_string(),
+                        _ => match on_conflict {
+                            ConflictPolicy::Theirs => "took source (--on-conflict theirs)",
+                            ConflictPolicy::Prompt => "asked (--on-conflict prompt)",
+                            _ => "kept target (--on-conflict ours)",
+                        }
+                        .to_string(),
+                    };
+                    (conflict, settled)
+                })
+                .collect();
+            let mut diagrams = Vec::new();
+            for id in &imported_views {
+                if let Some(view) = merged.view_map.get(id.as_str()) {
+                    diagrams.push((view.name.to_string(), view_to_svg(&merged, view)?));
+                }
+            }
+            let report = ImportReport {
+                source: &source_name,
+                target: args.output.as_deref().unwrap_or(target_file),
+                seed,
+                views: &outcomes,
+                diagrams,
+                new_concepts,
+                new_folders,
+                conflicts,
+                warnings: &warnings,
+            };
+            if let Some(path) = &args.report {
+                std::fs::write(path, report_html(&report)?)?;
+                println!("Wrote the import report to {}", path);
+            }
+            if let Some(path) = &args.report_md {
+                std::fs::write(path, report_markdown(&report)?)?;
+                println!("Wrote the Markdown import summary to {}", path);
+            }
+        }
+    }
+    print_view_outcomes(&outcomes);
+    if outcomes.iter().any(|outcome| outcome.error.is_some()) {
+        process::exit(2);
+    }
+    Ok(())
+}
+
+/// The source without the concepts and views the views picked by `--all`,
+/// `--view`, `--folder` and `--selection-file` don't need, found by indexing
+/// the source instead of parsing it whole.
+fn reduce_source(args: &ImportArgs, content: String) -> Result<String, Box<dyn std::error::Error>> {
+    if !args.all
+        && args.views.is_empty()
+        && args.folders.is_empty()
+        && args.selection_file.is_none()
+    {
+        return Err(ImporterError::Selection("--low-memory needs the views chosen up front with --all, --view, --folder or --selection-file".to_string()).into());
+    }
+    let selected_ids: HashSet<String> = match &args.selection_file {
+        Some(selection_file) => selection_file::read_selection_file(selection_file)?
+            .into_iter()
+            .collect(),
+        None => HashSet::new(),
+    };
+    let index = streaming::index_model(&content)?;
+    let views: HashSet<String> = index
+        .views()
+        .filter(|view| {
+            args.all
+                || args
+                    .views
+                    .iter()
+                    .any(|selected| *selected == view.name || *selected == view.id)
+                || args
+                    .folders
+                    .iter()
+                    .any(|folder| is_in_folder(&view.folder_path, folder))
+                || selected_ids.contains(&view.id)
+        })
+        .map(|view| view.id.clone())
+        .collect();
+    let needed = index.needed_by(&views);
+    verbose_println!(
+        args.verbose,
+        " +- Low memory: parsing {} of {} source concepts and views",
+        needed.len(),
+        index.concept_count()
+    );
+    Ok(index.reduce(&content, &needed))
+}
+
+/// Prints a warning and keeps it for the report.
+fn warn(warnings: &mut Vec<String>, message: String) {
+    println!("Warning: {}", message);
+    warnings.push(message);
+}
+
+/// Warns about the nodes of the `role` model that were given an ID on
+/// loading, which the report then lists too.
+fn warn_repaired(warnings: &mut Vec<String>, model: &ArchiModel, role: &str) {
+    for issue in &model.repaired {
+        warn(
+            warnings,
+            format!(
+                "{} file: {}; gave it the ID {}",
+                role,
+                issue.message,
+                issue.id.as_deref().unwrap_or_default()
+            ),
+        );
+    }
+}
+
+/// Prints how long the `role` model took to load and what its index holds,
+/// to standard error so reports written to standard output stay clean.
+fn report_memory_stats(model: &ArchiModel, role: &str, elapsed: Duration) {
+    let stats = model.index_stats();
+    eprintln!(
+        "Memory stats: {} loaded in {} ms; its index of {} concepts and views refers to {} strings, stored as {} taking {} bytes ({} bytes unshared)",
+        role,
+        elapsed.as_millis(),
+        stats.entries,
+        stats.strings,
+        stats.distinct_strings,
+        stats.string_bytes,
+        stats.unshared_bytes
+    );
+}
+
+/// What an export written after importing covers, for its message.
+fn scope_description(scope: ExportScope) -> &'static str {
+    match scope {
+        ExportScope::Model => "merged model",
+        ExportScope::Imported => "imported concepts",
+    }
+}
+
+/// Writes the target when views were pruned or elements read from CSV
+/// although no view is imported.
+fn write_target_without_views(
+    args: &ImportArgs,
+    target_descriptor: &FileDescriptor,
+    output_descriptor: &FileDescriptor,
+    target: &ArchiModel,
+    target_issues: &[ValidationIssue],
+    pruned_views: usize,
+    csv_changes: &CsvChanges,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let csv_elements = csv_changes.created + csv_changes.updated;
+    if pruned_views == 0 && csv_elements == 0 {
+        return Ok(());
+    }
+    let modified_target = serialize_model(target)?;
+    verify_merged(args, target_issues, &modified_target, &mut Vec::new())?;
+    if let Some(path) = &args.emit_jarchi {
+        return write_jarchi_script(path, target_descriptor, &modified_target);
+    }
+    let journal = record_journal(
+        args,
+        target_descriptor,
+        output_descriptor,
+        &modified_target,
+        false,
+    )?;
+    target_descriptor.write_xml_to(
+        output_descriptor,
+        &modified_target,
+        &[],
+        args.zip_compression,
+    )?;
+    save_journal(output_descriptor, journal.as_ref())?;
+    let file = if args.output.is_some() {
+        "output"
+    } else {
+        "target"
+    };
+    if pruned_views > 0 {
+        println!(
+            "Removed {} view{} from {} file.",
+            pruned_views,
+            if pruned_views == 1 { "" } else { "s" },
+            file
+        );
+    }
+    if csv_elements > 0 {
+        println!(
+            "Wrote {} element{} from CSV to {} file.",
+            csv_elements,
+            if csv_elements == 1 { "" } else { "s" },
+            file
+        );
+    }
+    Ok(())
+}
+
+/// Fails before anything is copied when a selected view shows concepts
+/// that neither model has, listing them, as `--strict` asks.
+fn check_references(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    missing_views: &[MissingElementInfo],
+    selected_indices: &[usize],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut broken = 0;
+    for &idx in selected_indices {
+        let view = &missing_views[idx - 1];
+        let unresolved = find_unresolved_references(source, target, &view.id)?;
+        if !unresolved.is_empty() {
+            eprintln!(
+                "View {} shows concepts missing from the source: {}",
+                view.name,
+                unresolved.join(", ")
+            );
+            broken += 1;
+        }
+    }
+    if broken == 0 {
+        return Ok(());
+    }
+    Err(ImporterError::Validation(format!(
+        "{} view{} {} unresolved references (--strict); target file left unchanged",
+        broken,
+        if broken == 1 { "" } else { "s" },
+        if broken == 1 { "has" } else { "have" }
+    ))
+    .into())
+}
+
+/// Checks that the merged model reparses and has no issues the target did
+/// not have before, such as references the import left dangling or IDs it
+/// used twice, refusing to write it otherwise unless `--no-verify` is given.
+fn verify_merged(
+    args: &ImportArgs,
+    target_issues: &[ValidationIssue],
+    modified_target: &str,
+    warnings: &mut Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let merged = load_model(modified_target).map_err(|e| {
+        ImporterError::Validation(format!(
+            "the merged model does not parse again ({}); target file left unchanged",
+            e
+        ))
+    })?;
+    let introduced: Vec<ValidationIssue> = merged
+        .validate()
+        .into_iter()
+        .filter(|issue| !target_issues.contains(issue))
+        .collect();
+    if introduced.is_empty() {
+        return Ok(());
+    }
+    println!("The merged model has issues the target did not have:");
+    for issue in &introduced {
+        println!("- {}", issue);
+    }
+    let count = format!(
+        "{} issue{}",
+        introduced.len(),
+        if introduced.len() == 1 { "" } else { "s" }
+    );
+    if args.no_verify {
+        warn(
+            warnings,
+            format!("writing the merged model despite {} (--no-verify)", count),
+        );
+        return Ok(());
+    }
+    Err(ImporterError::Validation(format!(
+        "the import introduced {}; target file left unchanged (pass --no-verify to write it anyway)",
+        count
+    ))
+    .into())
+}
+
+/// Records what the import changes in the target, for `undo`. Working
+/// trees are left to Git.
+fn record_journal(
+    args: &ImportArgs,
+    target_descriptor: &FileDescriptor,
+    output_descriptor: &FileDescriptor,
+    modified_target: &str,
+    history: bool,
+) -> Result<Option<Journal>, Box<dyn std::error::Error>> {
+    if args.no_journal || output_descriptor.kind() == FileKind::WorkingTree {
+        return Ok(None);
+    }
+    let mut journal = journal::record(&target_descriptor.read_xml()?, modified_target)?;
+    journal.history = history;
+    Ok(Some(journal))
+}
+
+/// Writes the journal next to the file written, replacing the journal of
+/// an earlier import.
+fn save_journal(
+    output_descriptor: &FileDescriptor,
+    journal: Option<&Journal>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(journal) = journal {
+        let path = journal::journal_path(output_descriptor.path());
+        std::fs::write(&path, serde_json::to_vec_pretty(journal)?)?;
+        println!("Recorded the changes in {} for undo", path.display());
+    }
+    Ok(())
+}
+
+/// Writes a jArchi script making the import's changes to the target in
+/// Archi, instead of writing the target itself.
+fn write_jarchi_script(
+    path: &str,
+    target_descriptor: &FileDescriptor,
+    modified_target: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let original_target = target_descriptor.read_xml()?;
+    std::fs::write(
+        path,
+        jarchi::change_script(&original_target, modified_target)?,
+    )?;
+    println!(
+        "Wrote the changes to {} as a jArchi script; the target file is left unchanged.",
+        path
+    );
+    Ok(())
+}
+
+fn print_view_outcomes(outcomes: &[ViewOutcome]) {
+    println!("View results:");
+    for outcome in outcomes {
+        match &outcome.error {
+            None => println!("- [ok] {}", outcome.name),
+            Some(reason) => println!("- [failed] {}: {}", outcome.name, reason),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::cli::ListArgs;
+use crate::commands::stats::print_stats;
+use crate::commands::{password, read_source_file, report_repaired};
+use crate::dot::view_graph;
+use crate::file_descriptor::SOURCE_PASSWORD_VARIABLE;
+use crate::model::{compare_views, format_folder_path, load_model, view_kind};
+
+pub fn run(args: ListArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, content) = read_source_file(
+        &args.model_file,
+        "model",
+        args.lenient_exchange,
+        password(&args.password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let model = load_model(&content)?;
+    report_repaired(&model, "model");
+    if args.stats {
+        print_stats(&args.model_file, &model.stats(), args.json)?;
+        return Ok(());
+    }
+    if args.json {
+        let views: Vec<_> = model.views().collect();
+        println!("{}", serde_json::to_string_pretty(&views)?);
+        return Ok(());
+    }
+
+    let mut views: Vec<_> = model.view_map.values().collect();
+    views.sort_by(|a, b| {
+        compare_views(
+            (&a.folder_path, &a.name, &a.id),
+            (&b.folder_path, &b.name, &b.id),
+        )
+    });
+
+    println!("Views in {}:", args.model_file);
+    for (i, view) in views.iter().enumerate() {
+        let folder_path = format_folder_path(&view.folder_path);
+        let kind = view_kind(&view.xsi_type)
+            .map(|kind| format!(" [{}]", kind))
+            .unwrap_or_default();
+        if args.verbose {
+            println!(
+                "[{}] {}{} (in folder: {}) [{}]",
+                i + 1,
+                view.name,
+                kind,
+                folder_path,
+                view.id
+            );
+        } else {
+            println!(
+                "[{}] {}{} (in folder: {})",
+                i + 1,
+                view.name,
+                kind,
+                folder_path
+            );
+        }
+    }
+    println!(
+        "{} view{}, {} element{}",
+        views.len(),
+        if views.len() == 1 { "" } else { "s" },
+        model.element_map.len(),
+        if model.element_map.len() == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+    if let Some(path) = &args.export_dot {
+        std::fs::write(path, view_graph(&model)?)?;
+        println!(
+            "Wrote the views and the elements they show to {} as a Graphviz graph",
+            path
+        );
+    }
+    Ok(())
+}
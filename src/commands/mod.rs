@@ -0,0 +1,240 @@
+pub mod delete;
+pub mod diff;
+pub mod doctor;
+pub mod export;
+pub mod extract;
+pub mod import;
+pub mod list;
+pub mod serve_mcp;
+pub mod stats;
+pub mod sync;
+pub mod undo;
+pub mod validate;
+
+use crate::copy::find_view_dependencies;
+use crate::error::ImporterError;
+use crate::exchange::{
+    exchange_to_archi, exchange_to_archi_lenient, is_exchange_format, is_lenient_exchange_format,
+};
+use crate::file_descriptor::FileDescriptor;
+use crate::json_model::from_json;
+use crate::model::{format_folder_path, view_kind, ArchiModel, MissingElementInfo};
+use crate::selection::{get_input, is_in_folder};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process;
+
+/// Opens a model file and reads its XML, exiting with an error message that
+/// names the file's `role` (e.g. "source") when it cannot be read. The
+/// `password` decrypts a password-protected archive.
+pub fn read_model_file(path: &str, role: &str, password: Option<&str>) -> (FileDescriptor, String) {
+    let descriptor = match FileDescriptor::from_path(path) {
+        Ok(file_descriptor) => file_descriptor.with_password(password.map(str::to_string)),
+        Err(e) => exit_reading(role, e),
+    };
+
+    let content = match descriptor.read_xml() {
+        Ok(content) => content,
+        Err(e) => exit_reading(role, e),
+    };
+
+    (descriptor, content)
+}
+
+/// The password given by an option, or else by the environment variable
+/// `variable`, such as `ARCHI_SOURCE_PASSWORD`.
+pub fn password(option: &Option<String>, variable: &str) -> Option<String> {
+    option.clone().or_else(|| std::env::var(variable).ok())
+}
+
+/// Warns about the nodes of a model that had no ID in its `role` file and
+/// were given one on loading.
+pub fn report_repaired(model: &ArchiModel, role: &str) {
+    for issue in &model.repaired {
+        eprintln!(
+            "Warning: {} file: {}; gave it the ID {}",
+            role,
+            issue.message,
+            issue.id.as_deref().unwrap_or_default()
+        );
+    }
+}
+
+/// Whether `a` and `b` are the same file, reached through a link or
+/// another relative path. Paths that don't exist, such as URLs, never are.
+pub fn same_file(a: &str, b: &str) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Exits with the exit code of the error's kind, naming the file's `role`.
+fn exit_reading(role: &str, e: impl Into<ImporterError>) -> ! {
+    let e = e.into();
+    eprintln!("Error reading {} file: {}", role, e);
+    process::exit(e.exit_code());
+}
+
+/// Reads a model that is only read from, translating an Open Exchange
+/// Format or JSON model into the Archi representation. With `lenient`,
+/// exchange models from other tools are read despite their deviations from
+/// the format, warning about each.
+pub fn read_source_file(
+    path: &str,
+    role: &str,
+    lenient: bool,
+    password: Option<&str>,
+) -> (FileDescriptor, String) {
+    let descriptor = if is_json(Path::new(path)) {
+        FileDescriptor::PlainXml { path: path.into() }
+    } else {
+        match FileDescriptor::from_path(path) {
+            Ok(descriptor) => descriptor.with_password(password.map(str::to_string)),
+            Err(e) => exit_reading(role, e),
+        }
+    };
+    read_source(descriptor, role, lenient)
+}
+
+/// Like `read_source_file`, for a model already described, such as one
+/// fetched into memory.
+pub fn read_source(
+    descriptor: FileDescriptor,
+    role: &str,
+    lenient: bool,
+) -> (FileDescriptor, String) {
+    let content = match descriptor.read_xml() {
+        Ok(content) => content,
+        Err(e) => exit_reading(role, e),
+    };
+    if is_json(descriptor.path()) {
+        return match from_json(&content) {
+            Ok(content) => (descriptor, content),
+            Err(e) => {
+                eprintln!("Error reading {} file as JSON: {}", role, e);
+                process::exit(1);
+            }
+        };
+    }
+    if lenient && is_lenient_exchange_format(&content) {
+        return match exchange_to_archi_lenient(&content) {
+            Ok((content, warnings)) => {
+                for warning in warnings {
+                    println!("Warning: {} file: {}", role, warning);
+                }
+                (descriptor, content)
+            }
+            Err(e) => {
+                eprintln!("Error reading {} file as Open Exchange Format: {}", role, e);
+                process::exit(1);
+            }
+        };
+    }
+    if !is_exchange_format(&content) {
+        return (descriptor, content);
+    }
+    match exchange_to_archi(&content) {
+        Ok(content) => (descriptor, content),
+        Err(e) => {
+            eprintln!("Error reading {} file as Open Exchange Format: {}", role, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// The IDs of the views named, by name or ID, in `views` or found under
+/// one of `folders`, warning about those that match nothing.
+pub fn select_views(model: &ArchiModel, views: &[String], folders: &[String]) -> HashSet<String> {
+    let mut view_ids = HashSet::new();
+    for view_name in views {
+        let matching: Vec<_> = model
+            .view_map
+            .values()
+            .filter(|info| *info.name == **view_name || *info.id == **view_name)
+            .map(|info| info.id.to_string())
+            .collect();
+        if matching.is_empty() {
+            eprintln!("Warning: View '{}' not found in model", view_name);
+        }
+        view_ids.extend(matching);
+    }
+    for folder in folders {
+        let matching: Vec<_> = model
+            .view_map
+            .values()
+            .filter(|info| is_in_folder(&info.folder_path, folder))
+            .map(|info| info.id.to_string())
+            .collect();
+        if matching.is_empty() {
+            eprintln!("Warning: No views found in folder '{}'", folder);
+        }
+        view_ids.extend(matching);
+    }
+    view_ids
+}
+
+pub const DEFAULT_PAGE_SIZE: usize = 40;
+
+/// Prints the numbered missing-view listing. With a `page_size`, the listing
+/// pauses after each page until Enter is pressed, or stops on `q`.
+pub fn print_missing_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    missing_views: &[MissingElementInfo],
+    page_size: Option<usize>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if missing_views.iter().any(|view| view.target_id.is_some()) {
+        println!("\nViews in source that don't exist in target or differ from it:");
+    } else {
+        println!("\nViews in source that don't exist in target:");
+    }
+    for (i, view) in missing_views.iter().enumerate() {
+        if let Some(page_size) = page_size {
+            if i > 0 && i % page_size == 0 {
+                let answer = get_input(&format!(
+                    "-- {} of {} listed, Enter for more, q to stop listing -- ",
+                    i,
+                    missing_views.len()
+                ))?;
+                if answer.eq_ignore_ascii_case("q") {
+                    break;
+                }
+            }
+        }
+        let folder_path = format_folder_path(&view.folder_path);
+        let dependencies = find_view_dependencies(source, target, &view.id)?;
+        // Views that already exist in the target are only listed in update mode
+        let update = if view.target_id.is_some() {
+            " [update]"
+        } else {
+            ""
+        };
+        let kind = source
+            .view_map
+            .get(&view.id)
+            .and_then(|info| view_kind(&info.xsi_type))
+            .map(|kind| format!(" [{}]", kind))
+            .unwrap_or_default();
+        println!(
+            "[{}] {}{}{} (in folder: {}) - {} element{} ({} in target), {} relation{} ({} in target)",
+            i + 1,
+            view.name,
+            kind,
+            update,
+            folder_path,
+            dependencies.elements,
+            if dependencies.elements == 1 { "" } else { "s" },
+            dependencies.existing_elements,
+            dependencies.relations,
+            if dependencies.relations == 1 { "" } else { "s" },
+            dependencies.existing_relations
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,405 @@
+use crate::cli::ServeMcpArgs;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::process::{Command, Stdio};
+
+/// The protocol revisions this server speaks, newest last.
+const PROTOCOL_VERSIONS: [&str; 3] = ["2024-11-05", "2025-03-26", "2025-06-18"];
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const MATCH_BY: &[&str] = &["id", "name"];
+
+/// Serves `list`, `diff` and `import` as Model Context Protocol tools,
+/// reading JSON-RPC messages from standard input and writing the responses
+/// to standard output, one per line.
+pub fn run(args: ServeMcpArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&line, &args) {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to a message, or `None` for a notification.
+fn handle_message(line: &str, args: &ServeMcpArgs) -> Option<Value> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+    };
+    let id = message.get("id").cloned()?;
+    let params = &message["params"];
+    let result = match message["method"].as_str().unwrap_or_default() {
+        "initialize" => Ok(initialize(params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools(args.read_only) })),
+        "tools/call" => call_tool(params, args),
+        method => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn initialize(params: &Value) -> Value {
+    // The client's revision when this server speaks it, else the newest
+    let requested = params["protocolVersion"].as_str().unwrap_or_default();
+    let version = PROTOCOL_VERSIONS
+        .iter()
+        .find(|&&version| version == requested)
+        .unwrap_or(&PROTOCOL_VERSIONS[PROTOCOL_VERSIONS.len() - 1]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+fn tools(read_only: bool) -> Vec<Value> {
+    let match_by = json!({
+        "type": "string",
+        "enum": ["id", "name"],
+        "description": "Whether views and concepts are the same in both models when their IDs or their names match. Defaults to id.",
+    });
+    let mut tools = vec![
+        json!({
+            "name": "list_views",
+            "description": "List the views of an Archi model with their folders and IDs.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "model": { "type": "string", "description": "Path of the model file." },
+                },
+                "required": ["model"],
+            },
+        }),
+        json!({
+            "name": "diff_models",
+            "description": "Compare two Archi models: the views, elements and relations of the source missing from the target, concepts that differ between them, and folders only one of them has. Returns JSON.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "source": { "type": "string", "description": "Path of the source model file." },
+                    "target": { "type": "string", "description": "Path of the target model file." },
+                    "match_by": match_by,
+                },
+                "required": ["source", "target"],
+            },
+        }),
+    ];
+    if !read_only {
+        tools.push(json!({
+            "name": "import_views",
+            "description": "Import views missing from the target model, with the elements and relations they show, from the source model. Writes the target, or the output file when given.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "source": { "type": "string", "description": "Path of the source model file." },
+                    "target": { "type": "string", "description": "Path of the target model file." },
+                    "output": { "type": "string", "description": "Path to write the merged model to instead of the target." },
+                    "views": { "type": "array", "items": { "type": "string" }, "description": "Names of the views to import." },
+                    "folders": { "type": "array", "items": { "type": "string" }, "description": "Folder paths, as in 'Views > Sales', whose views to import." },
+                    "all": { "type": "boolean", "description": "Import every view missing from the target." },
+                    "match_by": match_by,
+                    "on_conflict": {
+                        "type": "string",
+                        "enum": ["ours", "theirs", "newer"],
+                        "description": "Which model's version of a concept that differs between them is kept. Defaults to ours, the target's.",
+                    },
+                    "include_model_relations": { "type": "boolean", "description": "Also import the relations between imported elements that the views don't show." },
+                    "recursive": { "type": "boolean", "description": "Also import the views the imported views link to." },
+                    "update": { "type": "boolean", "description": "Also import views that changed in the source." },
+                    "seed": { "type": "integer", "minimum": 0, "description": "Seed of the generated IDs, to repeat an import." },
+                },
+                "required": ["source", "target"],
+            },
+        }));
+    }
+    tools
+}
+
+/// Runs a tool as a command of this program, so that what the command
+/// prints can't mix with the protocol on standard output and it can't read
+/// standard input. Paths follow `--` and option values are joined to their
+/// flag, so no argument of a client is taken for an option.
+fn call_tool(params: &Value, args: &ServeMcpArgs) -> Result<Value, (i64, String)> {
+    let arguments = &params["arguments"];
+    let command_args = match params["name"].as_str().unwrap_or_default() {
+        "list_views" => vec![
+            "list".to_string(),
+            "--verbose".to_string(),
+            "--".to_string(),
+            path_argument(arguments, "model")?,
+        ],
+        "diff_models" => {
+            let mut command_args = vec!["diff".to_string(), "--json".to_string()];
+            push_choice(
+                &mut command_args,
+                arguments,
+                "match_by",
+                "--match-by",
+                MATCH_BY,
+            )?;
+            command_args.extend([
+                "--".to_string(),
+                path_argument(arguments, "source")?,
+                path_argument(arguments, "target")?,
+            ]);
+            command_args
+        }
+        "import_views" if !args.read_only => import_args(arguments)?,
+        name => return Err((INVALID_PARAMS, format!("unknown tool '{}'", name))),
+    };
+
+    let output = std::env::current_exe().and_then(|program| {
+        Command::new(program)
+            .args(&command_args)
+            .stdin(Stdio::null())
+            .output()
+    });
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => return Ok(tool_result(format!("Error running the tool: {}", e), true)),
+    };
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    let errors = String::from_utf8_lossy(&output.stderr);
+    if !errors.trim().is_empty() {
+        text.push_str(&errors);
+    }
+    Ok(tool_result(text, !output.status.success()))
+}
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+fn import_args(arguments: &Value) -> Result<Vec<String>, (i64, String)> {
+    let mut command_args = vec!["import".to_string()];
+    let views = string_list_argument(arguments, "views")?;
+    let folders = string_list_argument(arguments, "folders")?;
+    let all = bool_argument(arguments, "all")?;
+    // Without a selection the command would ask which views to import
+    if views.is_empty() && folders.is_empty() && !all {
+        return Err((
+            INVALID_PARAMS,
+            "name the views to import with views, folders or all".to_string(),
+        ));
+    }
+    for view in views {
+        command_args.push(format!("--view={}", view));
+    }
+    for folder in folders {
+        command_args.push(format!("--folder={}", folder));
+    }
+    if all {
+        command_args.push("--all".to_string());
+    }
+    if !arguments["output"].is_null() {
+        command_args.push(format!("--output={}", path_argument(arguments, "output")?));
+    }
+    push_choice(
+        &mut command_args,
+        arguments,
+        "match_by",
+        "--match-by",
+        MATCH_BY,
+    )?;
+    // A prompt would wait for an answer nobody can give
+    push_choice(
+        &mut command_args,
+        arguments,
+        "on_conflict",
+        "--on-conflict",
+        &["ours", "theirs", "newer"],
+    )?;
+    for (name, flag) in [
+        ("include_model_relations", "--include-model-relations"),
+        ("recursive", "--recursive"),
+        ("update", "--update"),
+    ] {
+        if bool_argument(arguments, name)? {
+            command_args.push(flag.to_string());
+        }
+    }
+    match &arguments["seed"] {
+        Value::Null => {}
+        seed => match seed.as_u64() {
+            Some(seed) => command_args.push(format!("--seed={}", seed)),
+            None => {
+                return Err((
+                    INVALID_PARAMS,
+                    "seed must be a positive integer".to_string(),
+                ))
+            }
+        },
+    }
+    command_args.extend([
+        "--".to_string(),
+        path_argument(arguments, "source")?,
+        path_argument(arguments, "target")?,
+    ]);
+    Ok(command_args)
+}
+
+fn string_argument(arguments: &Value, name: &str) -> Result<String, (i64, String)> {
+    arguments[name]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| (INVALID_PARAMS, format!("{} must be a string", name)))
+}
+
+fn string_list_argument(arguments: &Value, name: &str) -> Result<Vec<String>, (i64, String)> {
+    match &arguments[name] {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| (INVALID_PARAMS, format!("{} must be strings", name)))
+            })
+            .collect(),
+        _ => Err((
+            INVALID_PARAMS,
+            format!("{} must be a list of strings", name),
+        )),
+    }
+}
+
+fn bool_argument(arguments: &Value, name: &str) -> Result<bool, (i64, String)> {
+    match &arguments[name] {
+        Value::Null => Ok(false),
+        Value::Bool(value) => Ok(*value),
+        _ => Err((INVALID_PARAMS, format!("{} must be true or false", name))),
+    }
+}
+
+/// A path given by the client, which must not look like an option.
+fn path_argument(arguments: &Value, name: &str) -> Result<String, (i64, String)> {
+    let path = string_argument(arguments, name)?;
+    if path.starts_with('-') {
+        return Err((
+            INVALID_PARAMS,
+            format!("{} must be a path, not start with '-'", name),
+        ));
+    }
+    Ok(path)
+}
+
+/// Adds `flag` with the string argument `name` when it is given, which must
+/// be one of `choices`.
+fn push_choice(
+    command_args: &mut Vec<String>,
+    arguments: &Value,
+    name: &str,
+    flag: &str,
+    choices: &[&str],
+) -> Result<(), (i64, String)> {
+    if arguments[name].is_null() {
+        return Ok(());
+    }
+    let value = string_argument(arguments, name)?;
+    if !choices.contains(&value.as_str()) {
+        return Err((
+            INVALID_PARAMS,
+            format!("{} must be one of {}", name, choices.join(", ")),
+        ));
+    }
+    command_args.push(format!("{}={}", flag, value));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_message() {
+        let args = ServeMcpArgs { read_only: true };
+        let response = handle_message(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#,
+            &args,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], "2025-03-26");
+        assert!(handle_message(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            &args
+        )
+        .is_none());
+
+        let response =
+            handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#, &args).unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["list_views", "diff_models"]);
+
+        let response = handle_message(
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"import_views","arguments":{}}}"#,
+            &args,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(
+            handle_message("not json", &args).unwrap()["error"]["code"],
+            PARSE_ERROR
+        );
+
+        let arguments = json!({ "source": "a.archimate", "target": "b.archimate" });
+        assert!(import_args(&arguments).is_err());
+        let arguments = json!({
+            "source": "a.archimate",
+            "target": "b.archimate",
+            "views": ["Overview"],
+            "on_conflict": "theirs",
+            "recursive": true,
+            "seed": 42,
+        });
+        assert_eq!(
+            import_args(&arguments).unwrap(),
+            [
+                "import",
+                "--view=Overview",
+                "--on-conflict=theirs",
+                "--recursive",
+                "--seed=42",
+                "--",
+                "a.archimate",
+                "b.archimate"
+            ]
+        );
+
+        // Nothing a client sends is taken for an option
+        let arguments =
+            json!({ "source": "--output=/tmp/x", "target": "b.archimate", "all": true });
+        assert!(import_args(&arguments).is_err());
+        let arguments = json!({ "source": "a.archimate", "target": "b.archimate", "all": true, "output": "-o" });
+        assert!(import_args(&arguments).is_err());
+        let arguments = json!({ "source": "a.archimate", "target": "b.archimate", "all": true, "on_conflict": "prompt" });
+        assert!(import_args(&arguments).is_err());
+        let arguments =
+            json!({ "source": "a.archimate", "target": "b.archimate", "views": ["--all"] });
+        assert_eq!(import_args(&arguments).unwrap()[1], "--view=--all");
+    }
+}
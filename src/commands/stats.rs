@@ -0,0 +1,59 @@
+use crate::cli::StatsArgs;
+use crate::commands::{password, read_source_file, report_repaired};
+use crate::file_descriptor::SOURCE_PASSWORD_VARIABLE;
+use crate::glossary::display_type;
+use crate::model::load_model;
+use crate::stats::ModelStats;
+
+pub fn run(args: StatsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, content) = read_source_file(
+        &args.model_file,
+        "model",
+        args.lenient_exchange,
+        password(&args.password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let model = load_model(&content)?;
+    report_repaired(&model, "model");
+    print_stats(&args.model_file, &model.stats(), args.json)
+}
+
+/// Prints the statistics of `model_file`, as text or, with `json`, as
+/// `ModelStats` serializes them.
+pub fn print_stats(
+    model_file: &str,
+    stats: &ModelStats,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(stats)?);
+        return Ok(());
+    }
+    println!("Statistics of {}:", model_file);
+    let plural = |count: usize| if count == 1 { "" } else { "s" };
+    println!(
+        "{} element{}, {} relationship{}, {} view{}",
+        stats.elements,
+        plural(stats.elements),
+        stats.relationships,
+        plural(stats.relationships),
+        stats.views,
+        plural(stats.views)
+    );
+    println!(
+        "Relationship density: {:.2} relationships per element",
+        stats.relationship_density
+    );
+    println!("Elements by layer:");
+    for (layer, count) in &stats.by_layer {
+        println!("- {}: {}", layer, count);
+    }
+    println!("Concepts by type:");
+    for (concept_type, count) in &stats.by_type {
+        println!("- {}: {}", display_type(concept_type), count);
+    }
+    println!("Concepts and views by folder:");
+    for (folder, count) in &stats.by_folder {
+        println!("- {}: {}", folder, count);
+    }
+    Ok(())
+}
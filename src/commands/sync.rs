@@ -0,0 +1,207 @@
+use crate::cli::SyncArgs;
+use crate::commands::{password, read_model_file, report_repaired, same_file};
+use crate::conflict::{ConflictPolicy, SyncPolicy};
+use crate::copy::{copy_view, CopyOptions};
+use crate::file_descriptor::{SOURCE_PASSWORD_VARIABLE, TARGET_PASSWORD_VARIABLE};
+use crate::ids::IdGenerator;
+use crate::images;
+use crate::lock::ModelLock;
+use crate::model::{
+    find_missing_views, format_folder_path, load_model, serialize_model, ArchiModel,
+    MissingElementInfo,
+};
+use crate::view_hash::find_identical_views;
+use std::path::Path;
+use std::process;
+
+pub fn run(args: SyncArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let left_file = &args.left_file;
+    let right_file = &args.right_file;
+
+    println!("-+ Syncing Archi files");
+    println!(" +- Left: {}", left_file);
+    println!(" +- Right: {}", right_file);
+    let ids = match args.seed {
+        Some(seed) => IdGenerator::new(seed),
+        None => IdGenerator::from_entropy(),
+    };
+    let seed = ids.seed();
+    println!(
+        " +- Seed: {} (rerun with --seed {} to reproduce)",
+        seed, seed
+    );
+
+    // Both models are written, so both are locked; one file is locked once
+    let _left_lock = ModelLock::acquire(Path::new(left_file))?;
+    let _right_lock = if same_file(left_file, right_file) {
+        None
+    } else {
+        Some(ModelLock::acquire(Path::new(right_file))?)
+    };
+    let (left_descriptor, left_content) = read_model_file(
+        left_file,
+        "left",
+        password(&args.left_password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let (right_descriptor, right_content) = read_model_file(
+        right_file,
+        "right",
+        password(&args.right_password, TARGET_PASSWORD_VARIABLE).as_deref(),
+    );
+    let mut left = load_model(&left_content)?;
+    let mut right = load_model(&right_content)?;
+    report_repaired(&left, "left");
+    report_repaired(&right, "right");
+
+    // Both directions are found before either model changes
+    let mut to_right = find_missing_views(&left, &right, args.match_by);
+    let mut to_left = find_missing_views(&right, &left, args.match_by);
+    if !args.keep_duplicates {
+        skip_identical_views(&left, &right, &mut to_right)?;
+        skip_identical_views(&right, &left, &mut to_left)?;
+    }
+    if to_right.is_empty() && to_left.is_empty() {
+        println!("No views to sync; both models have the same views.");
+        return Ok(());
+    }
+
+    let left_is_newer = args.policy == SyncPolicy::Newer
+        && std::fs::metadata(left_descriptor.path())?.modified()?
+            > std::fs::metadata(right_descriptor.path())?.modified()?;
+    let (to_right_policy, to_left_policy) = args.policy.conflict_policies(left_is_newer);
+
+    right.ids = ids;
+    let (copied_to_right, failed_to_right) = copy_views(
+        &mut left,
+        &mut right,
+        &to_right,
+        left_file,
+        right_file,
+        to_right_policy,
+        args.verbose,
+    );
+    // Continue the ID stream so the two models never get the same new IDs
+    left.ids = right.ids.clone();
+    let (copied_to_left, failed_to_left) = copy_views(
+        &mut right,
+        &mut left,
+        &to_left,
+        right_file,
+        left_file,
+        to_left_policy,
+        args.verbose,
+    );
+
+    for (copied, descriptor, source_descriptor, model) in [
+        (
+            copied_to_right,
+            &right_descriptor,
+            &left_descriptor,
+            &mut right,
+        ),
+        (
+            copied_to_left,
+            &left_descriptor,
+            &right_descriptor,
+            &mut left,
+        ),
+    ] {
+        if copied == 0 {
+            continue;
+        }
+        let image_entries = if descriptor.holds_images() && source_descriptor.holds_images() {
+            images::import_images(source_descriptor, descriptor, model)?
+        } else {
+            Vec::new()
+        };
+        let entries: Vec<(&str, &[u8])> = image_entries
+            .iter()
+            .map(|(path, content)| (path.as_str(), content.as_slice()))
+            .collect();
+        if let Err(e) =
+            descriptor.write_xml(&serialize_model(model)?, &entries, args.zip_compression)
+        {
+            eprintln!("Error writing to {}: {}", descriptor.path().display(), e);
+            process::exit(1);
+        }
+    }
+
+    println!(
+        "Synced:\n- {} view{} from {} to {}\n- {} view{} from {} to {}",
+        copied_to_right,
+        if copied_to_right == 1 { "" } else { "s" },
+        left_file,
+        right_file,
+        copied_to_left,
+        if copied_to_left == 1 { "" } else { "s" },
+        right_file,
+        left_file
+    );
+    println!("Seed: {}", seed);
+    if failed_to_right + failed_to_left > 0 {
+        process::exit(2);
+    }
+    Ok(())
+}
+
+/// Leaves out views whose content the other model already has under
+/// another ID.
+fn skip_identical_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    views: &mut Vec<MissingElementInfo>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let identical = find_identical_views(source, target, views)?;
+    views.retain(|view| match identical.get(&*view.id) {
+        Some(target_info) => {
+            println!(
+                "Skipping view {}: identical to view {} ({})",
+                view.name, target_info.name, target_info.id
+            );
+            false
+        }
+        None => true,
+    });
+    Ok(())
+}
+
+/// Copies views from one model to the other, returning how many were copied
+/// and how many failed.
+fn copy_views(
+    source: &mut ArchiModel,
+    target: &mut ArchiModel,
+    views: &[MissingElementInfo],
+    source_file: &str,
+    target_file: &str,
+    on_conflict: ConflictPolicy,
+    verbose: bool,
+) -> (usize, usize) {
+    if views.is_empty() {
+        return (0, 0);
+    }
+    println!("\nViews in {} missing from {}:", source_file, target_file);
+    for (i, view) in views.iter().enumerate() {
+        println!(
+            "[{}] {} (in folder: {})",
+            i + 1,
+            view.name,
+            format_folder_path(&view.folder_path)
+        );
+    }
+    let options = CopyOptions {
+        verbose,
+        on_conflict,
+        ..Default::default()
+    };
+    let (mut copied, mut failed) = (0, 0);
+    for view in views {
+        match copy_view(source, target, view, &options) {
+            Ok(_) => copied += 1,
+            Err(e) => {
+                eprintln!("Error copying view {} to {}: {}", view.name, target_file, e);
+                failed += 1;
+            }
+        }
+    }
+    (copied, failed)
+}
@@ -0,0 +1,59 @@
+use crate::cli::UndoArgs;
+use crate::commands::{password, read_model_file};
+use crate::file_descriptor::{ZipCompression, TARGET_PASSWORD_VARIABLE};
+use crate::history;
+use crate::journal;
+use crate::lock::ModelLock;
+use std::fs;
+use std::path::Path;
+
+/// Reverts the last import into a model from the journal it left, then
+/// removes the journal so the import cannot be undone twice.
+pub fn run(args: UndoArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let password = password(&args.password, TARGET_PASSWORD_VARIABLE);
+    let _lock = ModelLock::acquire(Path::new(&args.model_file))?;
+    let journal_path = journal::journal_path(Path::new(&args.model_file));
+    let journal = match fs::read(&journal_path) {
+        Ok(content) => journal::parse(&content, &journal_path)?,
+        Err(_) => {
+            return Err(format!(
+                "No import to undo in {}: {} not found",
+                args.model_file,
+                journal_path.display()
+            )
+            .into())
+        }
+    };
+
+    let (descriptor, content) = read_model_file(&args.model_file, "model", password.as_deref());
+    if journal::content_hash(&content) != journal.written_hash && !args.force {
+        return Err(format!(
+            "{} was changed after the import of {}; pass --force to undo it anyway",
+            args.model_file, journal.timestamp
+        )
+        .into());
+    }
+    let restored = journal::undo(&journal, &content)?;
+
+    let mut entries = Vec::new();
+    if journal.history {
+        if let Some(existing) = descriptor.read_entry(history::HISTORY_ENTRY)? {
+            entries.push(history::remove_last_record(&existing)?);
+        }
+    }
+    let entries: Vec<(&str, &[u8])> = entries
+        .iter()
+        .map(|content| (history::HISTORY_ENTRY, content.as_slice()))
+        .collect();
+    descriptor.write_xml(&restored, &entries, ZipCompression::default())?;
+    fs::remove_file(&journal_path)?;
+    println!(
+        "Undid the import of {} into {}: removed {} added and restored {} changed node{}",
+        journal.timestamp,
+        args.model_file,
+        journal.added.len(),
+        journal.replaced.len(),
+        if journal.replaced.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
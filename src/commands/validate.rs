@@ -0,0 +1,38 @@
+use crate::cli::ValidateArgs;
+use crate::commands::{password, read_source_file};
+use crate::error::ImporterError;
+use crate::file_descriptor::SOURCE_PASSWORD_VARIABLE;
+use crate::model::load_model;
+
+/// Prints the issues of a model, failing when there are any so that the
+/// command can gate a merge.
+pub fn run(args: ValidateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, content) = read_source_file(
+        &args.model_file,
+        "model",
+        args.lenient_exchange,
+        password(&args.password, SOURCE_PASSWORD_VARIABLE).as_deref(),
+    );
+    let model = load_model(&content)?;
+    let issues = model.validate();
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&issues)?);
+    } else if issues.is_empty() {
+        println!("No issues found in {}", args.model_file);
+    } else {
+        println!("Issues in {}:", args.model_file);
+        for issue in &issues {
+            println!("- {}", issue);
+        }
+    }
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(ImporterError::Validation(format!(
+            "{} issue{} found",
+            issues.len(),
+            if issues.len() == 1 { "" } else { "s" }
+        ))
+        .into())
+    }
+}
@@ -0,0 +1,270 @@
+//! The ArchiMate 3.2 concept types an Archi model holds, with the layer and
+//! category of each, so that concepts can be told apart without matching
+//! on their `xsi:type` names.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The layer of an element type. Locations, groupings and junctions belong
+/// to `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Layer {
+    Strategy,
+    Business,
+    Application,
+    Technology,
+    Physical,
+    Motivation,
+    Implementation,
+    Other,
+}
+
+impl Layer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Strategy => "Strategy",
+            Layer::Business => "Business",
+            Layer::Application => "Application",
+            Layer::Technology => "Technology",
+            Layer::Physical => "Physical",
+            Layer::Motivation => "Motivation",
+            Layer::Implementation => "Implementation",
+            Layer::Other => "Other",
+        }
+    }
+
+    /// The top-level folder Archi keeps the layer's elements in.
+    pub fn folder_type(self) -> &'static str {
+        match self {
+            Layer::Strategy => "strategy",
+            Layer::Business => "business",
+            Layer::Application => "application",
+            Layer::Technology | Layer::Physical => "technology",
+            Layer::Motivation => "motivation",
+            Layer::Implementation => "implementation_migration",
+            Layer::Other => "other",
+        }
+    }
+}
+
+impl FromStr for Layer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strategy" => Ok(Layer::Strategy),
+            "business" => Ok(Layer::Business),
+            "application" => Ok(Layer::Application),
+            "technology" => Ok(Layer::Technology),
+            "physical" => Ok(Layer::Physical),
+            "motivation" => Ok(Layer::Motivation),
+            "implementation" | "implementation_migration" => Ok(Layer::Implementation),
+            "other" => Ok(Layer::Other),
+            _ => Err(format!(
+                "Invalid layer: {}. Use strategy, business, application, technology, physical, motivation, implementation or other",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Category {
+    Element,
+    Relationship,
+}
+
+macro_rules! concept_types {
+    ($($layer:ident => [$($element:ident),* $(,)?]),*; Relationship => [$($relationship:ident),* $(,)?]) => {
+        /// An ArchiMate concept type, named as in Archi's `xsi:type`
+        /// without the `archimate:` prefix.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum ConceptType {
+            $($($element,)*)*
+            $($relationship,)*
+        }
+
+        impl ConceptType {
+            /// Every concept type, elements by layer and then relationships.
+            pub const ALL: &'static [ConceptType] = &[
+                $($(ConceptType::$element,)*)*
+                $(ConceptType::$relationship,)*
+            ];
+
+            /// The `xsi:type` name without the `archimate:` prefix.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($(ConceptType::$element => stringify!($element),)*)*
+                    $(ConceptType::$relationship => stringify!($relationship),)*
+                }
+            }
+
+            /// The layer of an element type, or `None` for a relationship.
+            pub fn layer(self) -> Option<Layer> {
+                match self {
+                    $($(ConceptType::$element => Some(Layer::$layer),)*)*
+                    $(ConceptType::$relationship => None,)*
+                }
+            }
+        }
+    };
+}
+
+concept_types! {
+    Strategy => [Resource, Capability, ValueStream, CourseOfAction],
+    Business => [
+        BusinessActor,
+        BusinessRole,
+        BusinessCollaboration,
+        BusinessInterface,
+        BusinessProcess,
+        BusinessFunction,
+        BusinessInteraction,
+        BusinessEvent,
+        BusinessService,
+        BusinessObject,
+        Contract,
+        Representation,
+        Product,
+    ],
+    Application => [
+        ApplicationComponent,
+        ApplicationCollaboration,
+        ApplicationInterface,
+        ApplicationFunction,
+        ApplicationInteraction,
+        ApplicationProcess,
+        ApplicationEvent,
+        ApplicationService,
+        DataObject,
+    ],
+    Technology => [
+        Node,
+        Device,
+        SystemSoftware,
+        TechnologyCollaboration,
+        TechnologyInterface,
+        Path,
+        CommunicationNetwork,
+        TechnologyFunction,
+        TechnologyProcess,
+        TechnologyInteraction,
+        TechnologyEvent,
+        TechnologyService,
+        Artifact,
+    ],
+    Physical => [Equipment, Facility, DistributionNetwork, Material],
+    Motivation => [
+        Stakeholder,
+        Driver,
+        Assessment,
+        Goal,
+        Outcome,
+        Principle,
+        Requirement,
+        Constraint,
+        Meaning,
+        Value,
+    ],
+    Implementation => [WorkPackage, Deliverable, ImplementationEvent, Plateau, Gap],
+    Other => [Location, Grouping, Junction];
+    Relationship => [
+        CompositionRelationship,
+        AggregationRelationship,
+        AssignmentRelationship,
+        RealizationRelationship,
+        ServingRelationship,
+        AccessRelationship,
+        InfluenceRelationship,
+        TriggeringRelationship,
+        FlowRelationship,
+        SpecializationRelationship,
+        AssociationRelationship,
+    ]
+}
+
+impl ConceptType {
+    /// The concept type of an `xsi:type`, with or without the `archimate:`
+    /// prefix, or `None` for views, diagram objects and unknown types.
+    pub fn from_xsi_type(xsi_type: &str) -> Option<Self> {
+        let name = xsi_type.strip_prefix("archimate:").unwrap_or(xsi_type);
+        ConceptType::ALL
+            .iter()
+            .copied()
+            .find(|concept_type| concept_type.name() == name)
+    }
+
+    pub fn category(self) -> Category {
+        match self.layer() {
+            Some(_) => Category::Element,
+            None => Category::Relationship,
+        }
+    }
+
+    pub fn is_relationship(self) -> bool {
+        self.category() == Category::Relationship
+    }
+
+    /// Whether this is a junction, which joins relationships of one type.
+    pub fn is_junction(self) -> bool {
+        self == ConceptType::Junction
+    }
+
+    /// The top-level folder Archi keeps concepts of this type in.
+    pub fn folder_type(self) -> &'static str {
+        self.layer().map_or("relations", Layer::folder_type)
+    }
+}
+
+impl FromStr for ConceptType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ConceptType::from_xsi_type(s)
+            .ok_or_else(|| format!("Invalid ArchiMate concept type: {}", s))
+    }
+}
+
+impl fmt::Display for ConceptType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concept_types() {
+        assert_eq!(ConceptType::ALL.len(), 72);
+        let equipment = ConceptType::from_xsi_type("archimate:Equipment").unwrap();
+        assert_eq!(equipment, ConceptType::Equipment);
+        assert_eq!(equipment.layer(), Some(Layer::Physical));
+        assert_eq!(equipment.folder_type(), "technology");
+        assert_eq!(equipment.category(), Category::Element);
+        assert!(!equipment.is_junction());
+        assert!(ConceptType::Junction.is_junction());
+
+        let serving: ConceptType = "ServingRelationship".parse().unwrap();
+        assert!(serving.is_relationship());
+        assert_eq!(serving.layer(), None);
+        assert_eq!(serving.folder_type(), "relations");
+        assert_eq!(serving.to_string(), "ServingRelationship");
+
+        assert_eq!(
+            ConceptType::from_xsi_type("archimate:ArchimateDiagramModel"),
+            None
+        );
+        assert!("Widget".parse::<ConceptType>().is_err());
+        assert_eq!("application".parse::<Layer>(), Ok(Layer::Application));
+        assert!("data".parse::<Layer>().is_err());
+    }
+}
@@ -0,0 +1,909 @@
+use crate::copy::find_view_references;
+use crate::error::ImporterError;
+use crate::glossary::display_type;
+use crate::model::{
+    extract_properties, find_node_by_id, model_name, ArchiModel, ElementInfo, MissingElementInfo,
+};
+use crate::namespaces::{normalize_namespaces, xsi_type};
+use crate::selection::get_input;
+use crate::view_hash::{concept_content_hash, concept_hash_without};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use xot::{Node, Xot};
+
+const CONFLICTS_FILE_HEADER: &str =
+    "# archi-view-importer conflicts: set each resolution to keep-target, take-source\n\
+# or skip (skip leaves out every view using the concept), then rerun with\n\
+# --resolutions <this file>\n";
+
+/// Which version wins when a concept or view exists in both models but
+/// differs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConflictPolicy {
+    /// Keep the target version.
+    #[default]
+    Ours,
+    /// Take the source version.
+    Theirs,
+    /// Take the version from the most recently modified model file.
+    Newer,
+    /// Ask for each conflicting concept and view.
+    Prompt,
+}
+
+impl ConflictPolicy {
+    /// Turns `Newer` into `Ours` or `Theirs` for the given model files.
+    pub fn resolve_newer(self, source_is_newer: bool) -> Self {
+        match self {
+            ConflictPolicy::Newer if source_is_newer => ConflictPolicy::Theirs,
+            ConflictPolicy::Newer => ConflictPolicy::Ours,
+            policy => policy,
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ours" => Ok(ConflictPolicy::Ours),
+            "theirs" => Ok(ConflictPolicy::Theirs),
+            "newer" => Ok(ConflictPolicy::Newer),
+            "prompt" => Ok(ConflictPolicy::Prompt),
+            _ => Err(format!(
+                "Invalid conflict policy: {} (expected ours, theirs, newer or prompt)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictPolicy::Ours => write!(f, "ours"),
+            ConflictPolicy::Theirs => write!(f, "theirs"),
+            ConflictPolicy::Newer => write!(f, "newer"),
+            ConflictPolicy::Prompt => write!(f, "prompt"),
+        }
+    }
+}
+
+/// Which model's version of a differing concept wins when `sync` copies views
+/// both ways.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SyncPolicy {
+    /// Each model keeps its own version.
+    #[default]
+    Keep,
+    /// Concepts used by copied views take the left model's version.
+    Left,
+    /// Concepts used by copied views take the right model's version.
+    Right,
+    /// Concepts used by copied views take the version from the most
+    /// recently modified file.
+    Newer,
+}
+
+impl SyncPolicy {
+    /// The conflict policies for copying from left to right and from right
+    /// to left.
+    pub fn conflict_policies(self, left_is_newer: bool) -> (ConflictPolicy, ConflictPolicy) {
+        match self {
+            SyncPolicy::Keep => (ConflictPolicy::Ours, ConflictPolicy::Ours),
+            SyncPolicy::Newer if left_is_newer => (ConflictPolicy::Theirs, ConflictPolicy::Ours),
+            SyncPolicy::Left => (ConflictPolicy::Theirs, ConflictPolicy::Ours),
+            SyncPolicy::Right | SyncPolicy::Newer => (ConflictPolicy::Ours, ConflictPolicy::Theirs),
+        }
+    }
+}
+
+impl FromStr for SyncPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "keep" => Ok(SyncPolicy::Keep),
+            "left" => Ok(SyncPolicy::Left),
+            "right" => Ok(SyncPolicy::Right),
+            "newer" => Ok(SyncPolicy::Newer),
+            _ => Err(format!(
+                "Invalid sync policy: {} (expected keep, left, right or newer)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SyncPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncPolicy::Keep => write!(f, "keep"),
+            SyncPolicy::Left => write!(f, "left"),
+            SyncPolicy::Right => write!(f, "right"),
+            SyncPolicy::Newer => write!(f, "newer"),
+        }
+    }
+}
+
+/// How the properties of a concept in both models are merged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyMerge {
+    /// Keep every property of either model.
+    Union,
+    /// Take the source value of properties both models have.
+    PreferSource,
+    /// Keep the target value of properties both models have.
+    PreferTarget,
+    /// Ask for each property whose value differs.
+    Prompt,
+}
+
+impl FromStr for PropertyMerge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "union" => Ok(PropertyMerge::Union),
+            "prefer-source" => Ok(PropertyMerge::PreferSource),
+            "prefer-target" => Ok(PropertyMerge::PreferTarget),
+            "prompt" => Ok(PropertyMerge::Prompt),
+            _ => Err(format!(
+                "Invalid property merge policy: {} (expected union, prefer-source, prefer-target or prompt)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PropertyMerge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyMerge::Union => write!(f, "union"),
+            PropertyMerge::PreferSource => write!(f, "prefer-source"),
+            PropertyMerge::PreferTarget => write!(f, "prefer-target"),
+            PropertyMerge::Prompt => write!(f, "prompt"),
+        }
+    }
+}
+
+/// How differing documentation of a concept in both models is merged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentationMerge {
+    /// Append the source text to the target text after a marker line.
+    Append,
+    /// Keep the longer of the two texts.
+    Longer,
+    /// Keep the target text and report the difference.
+    Keep,
+}
+
+impl FromStr for DocumentationMerge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "append" => Ok(DocumentationMerge::Append),
+            "longer" => Ok(DocumentationMerge::Longer),
+            "keep" => Ok(DocumentationMerge::Keep),
+            _ => Err(format!(
+                "Invalid documentation merge policy: {} (expected append, longer or keep)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DocumentationMerge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentationMerge::Append => write!(f, "append"),
+            DocumentationMerge::Longer => write!(f, "longer"),
+            DocumentationMerge::Keep => write!(f, "keep"),
+        }
+    }
+}
+
+/// A reviewer's decision for one conflict in a conflicts file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Resolution {
+    KeepTarget,
+    TakeSource,
+    Skip,
+    Unresolved,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictRecord {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub xsi_type: String,
+    /// Names of the selected views that use the concept.
+    pub views: Vec<String>,
+    pub target_version: String,
+    pub source_version: String,
+    pub resolution: Resolution,
+}
+
+/// A concept whose name, properties, documentation or endpoints differ
+/// between the models, with the XML of each version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConceptConflict {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub concept_type: String,
+    pub source_version: String,
+    pub target_version: String,
+}
+
+impl ConceptConflict {
+    pub fn new(source_info: &ElementInfo, target_info: &ElementInfo) -> Self {
+        ConceptConflict {
+            id: source_info.id.to_string(),
+            name: source_info.name.to_string(),
+            concept_type: display_type(&source_info.xsi_type),
+            source_version: source_info.xml_string.trim().to_string(),
+            target_version: target_info.xml_string.trim().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConflictFile {
+    conflicts: Vec<ConflictRecord>,
+}
+
+/// Returns true when the concept exists in both models with different
+/// content.
+pub fn is_conflict(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    element_id: &str,
+) -> Result<bool, ImporterError> {
+    match (
+        source.element_map.get(element_id),
+        target.element_map.get(element_id),
+    ) {
+        (Some(source_info), Some(target_info)) => {
+            Ok(concept_content_hash(&source_info.xml_string)?
+                != concept_content_hash(&target_info.xml_string)?)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Like `is_conflict`, but ignores the child elements named in `skipped`,
+/// such as `property`, which are merged on their own.
+pub fn is_conflict_besides(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    element_id: &str,
+    skipped: &[&str],
+) -> Result<bool, ImporterError> {
+    match (
+        source.element_map.get(element_id),
+        target.element_map.get(element_id),
+    ) {
+        (Some(source_info), Some(target_info)) => {
+            Ok(concept_hash_without(&source_info.xml_string, skipped)?
+                != concept_hash_without(&target_info.xml_string, skipped)?)
+        }
+        _ => Ok(false),
+    }
+}
+
+fn concept_label(element_id: &str, info: &ElementInfo) -> String {
+    if info.name.is_empty() {
+        format!("{} [{}]", element_id, display_type(&info.xsi_type))
+    } else {
+        format!("{} [{}]", info.name, display_type(&info.xsi_type))
+    }
+}
+
+/// Merges the source properties of a concept into `target_properties`, the
+/// target's properties before any conflict was resolved. Properties are
+/// paired by key; identical properties are kept once.
+fn merged_properties(
+    label: &str,
+    target_properties: &[(String, String)],
+    source_properties: &[(String, String)],
+    policy: PropertyMerge,
+) -> Result<Vec<(String, String)>, io::Error> {
+    let mut merged = Vec::new();
+    for (key, value) in target_properties {
+        let source_value = source_properties
+            .iter()
+            .find(|(source_key, _)| source_key == key)
+            .map(|(_, source_value)| source_value);
+        let value = match source_value {
+            Some(source_value) if source_value != value => match policy {
+                PropertyMerge::Union | PropertyMerge::PreferTarget => value,
+                PropertyMerge::PreferSource => source_value,
+                PropertyMerge::Prompt => {
+                    let answer = get_input(&format!(
+                        "Property {} of {} differs: keep (t)arget value \"{}\" or take (s)ource value \"{}\"? [t] ",
+                        key, label, value, source_value
+                    ))?;
+                    if answer.eq_ignore_ascii_case("s") || answer.eq_ignore_ascii_case("source") {
+                        source_value
+                    } else {
+                        value
+                    }
+                }
+            },
+            _ => value,
+        };
+        merged.push((key.clone(), value.clone()));
+    }
+    for (key, value) in source_properties {
+        let in_target = target_properties
+            .iter()
+            .any(|(target_key, _)| target_key == key);
+        let kept = merged
+            .iter()
+            .any(|(merged_key, merged_value)| merged_key == key && merged_value == value);
+        if !in_target || (policy == PropertyMerge::Union && !kept) {
+            merged.push((key.clone(), value.clone()));
+        }
+    }
+    Ok(merged)
+}
+
+/// Replaces the properties of a target concept with the merge of
+/// `target_properties` and the source's properties. Returns true when the
+/// target changed.
+pub fn merge_properties(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    element_id: &str,
+    target_properties: &[(String, String)],
+    policy: PropertyMerge,
+) -> Result<bool, ImporterError> {
+    let source_info = source.element_map.get(element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in source", element_id))
+    })?;
+    let node = find_node_by_id(target, element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in target", element_id))
+    })?;
+    let label = concept_label(element_id, source_info);
+    let merged = merged_properties(&label, target_properties, &source_info.properties, policy)?;
+    if merged == extract_properties(&target.xot, node) {
+        return Ok(false);
+    }
+
+    let xot = &mut target.xot;
+    let property_name = xot.add_name("property");
+    let key_name = xot.add_name("key");
+    let value_name = xot.add_name("value");
+    let existing: Vec<_> = xot
+        .children(node)
+        .filter(|&child| xot.is_element(child) && xot.get_element_name(child) == property_name)
+        .collect();
+    for property in existing {
+        xot.remove(property)?;
+    }
+    for (key, value) in &merged {
+        let property = xot.new_element(property_name);
+        xot.set_attribute(property, key_name, key.as_str());
+        xot.set_attribute(property, value_name, value.as_str());
+        xot.append(node, property)?;
+    }
+    let xml_string = xot.serialize_xml_string(Default::default(), node)?;
+    if let Some(target_info) = target.element_map.get_mut(element_id) {
+        target_info.xml_string = xml_string;
+        target_info.properties = merged;
+    }
+    println!("Merged properties of {} ({})", label, policy);
+    Ok(true)
+}
+
+/// Returns the text of a concept's `documentation` child, if it has one.
+pub fn documentation_text(xot: &Xot, node: Node) -> Option<String> {
+    let documentation_name = xot.name("documentation")?;
+    xot.children(node)
+        .find(|&child| xot.is_element(child) && xot.get_element_name(child) == documentation_name)
+        .map(|child| xot.text_content_str(child).unwrap_or_default().to_string())
+}
+
+/// Replaces the `documentation` child of a concept, which Archi writes
+/// before any other child.
+pub fn set_documentation(xot: &mut Xot, node: Node, text: &str) -> Result<(), ImporterError> {
+    let documentation_name = xot.add_name("documentation");
+    let existing: Vec<_> = xot
+        .children(node)
+        .filter(|&child| xot.is_element(child) && xot.get_element_name(child) == documentation_name)
+        .collect();
+    for documentation in existing {
+        xot.remove(documentation)?;
+    }
+    let documentation = xot.new_element(documentation_name);
+    xot.append_text(documentation, text)?;
+    xot.prepend(node, documentation)?;
+    Ok(())
+}
+
+/// Works out the documentation of a concept from `target_text`, the
+/// target's documentation before any conflict was resolved, and the source's.
+/// Returns `None` when the target's documentation stays as it is.
+fn merged_documentation(
+    target_text: Option<&str>,
+    source_text: &str,
+    marker: &str,
+    policy: DocumentationMerge,
+) -> Option<String> {
+    match target_text {
+        None | Some("") => Some(source_text.to_string()),
+        Some(target_text) if target_text == source_text => None,
+        Some(target_text) => match policy {
+            // Appended earlier, so appending again would repeat the text
+            DocumentationMerge::Append if target_text.contains(source_text) => None,
+            DocumentationMerge::Append => {
+                Some(format!("{}\n\n{}\n{}", target_text, marker, source_text))
+            }
+            DocumentationMerge::Longer
+                if source_text.chars().count() > target_text.chars().count() =>
+            {
+                Some(source_text.to_string())
+            }
+            DocumentationMerge::Longer | DocumentationMerge::Keep => None,
+        },
+    }
+}
+
+/// Merges the documentation of a concept in both models into the target.
+/// Returns true when the target changed.
+pub fn merge_documentation(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    element_id: &str,
+    target_text: Option<&str>,
+    policy: DocumentationMerge,
+) -> Result<bool, ImporterError> {
+    let source_info = source.element_map.get(element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in source", element_id))
+    })?;
+    let source_node = find_node_by_id(source, element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in source", element_id))
+    })?;
+    let Some(source_text) = documentation_text(&source.xot, source_node) else {
+        return Ok(false);
+    };
+    let label = concept_label(element_id, source_info);
+    let model_name = model_name(source);
+    let marker = if model_name.is_empty() {
+        "--- Imported from source ---".to_string()
+    } else {
+        format!("--- Imported from {} ---", model_name)
+    };
+    let node = find_node_by_id(target, element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in target", element_id))
+    })?;
+    // Without a merged text the target's own text stays, even where a
+    // resolved conflict took the source version
+    let text = match merged_documentation(target_text, &source_text, &marker, policy) {
+        Some(text) => text,
+        None => {
+            let target_text = target_text.unwrap_or_default();
+            if policy == DocumentationMerge::Keep && target_text != source_text {
+                println!(
+                    "Warning: documentation of {} differs between models, keeping target text",
+                    label
+                );
+            }
+            target_text.to_string()
+        }
+    };
+    if documentation_text(&target.xot, node).as_deref() == Some(text.as_str()) {
+        return Ok(false);
+    }
+
+    let xot = &mut target.xot;
+    set_documentation(xot, node, &text)?;
+    let xml_string = xot.serialize_xml_string(Default::default(), node)?;
+    if let Some(target_info) = target.element_map.get_mut(element_id) {
+        target_info.xml_string = xml_string;
+    }
+    println!("Merged documentation of {} ({})", label, policy);
+    Ok(true)
+}
+
+/// Applies `policy` to a conflicting concept, replacing the target version
+/// in place when the source wins. Returns true when the target changed.
+pub fn resolve_conflict(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    element_id: &str,
+    policy: ConflictPolicy,
+) -> Result<bool, ImporterError> {
+    let source_info = source.element_map.get(element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in source", element_id))
+    })?;
+    let label = concept_label(element_id, source_info);
+
+    let take_source = match policy {
+        ConflictPolicy::Theirs => true,
+        ConflictPolicy::Ours | ConflictPolicy::Newer => false,
+        ConflictPolicy::Prompt => {
+            let answer = get_input(&format!(
+                "Conflict: {} differs between models. Keep (t)arget or take (s)ource version? [t] ",
+                label
+            ))?;
+            answer.eq_ignore_ascii_case("s") || answer.eq_ignore_ascii_case("source")
+        }
+    };
+    if !take_source {
+        println!(
+            "Conflict: {} differs between models, keeping target version",
+            label
+        );
+        return Ok(false);
+    }
+
+    let existing = find_node_by_id(target, element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in target", element_id))
+    })?;
+    let cloned_node = target.xot.parse(source_info.xml_string.as_str())?;
+    let cloned_element = target.xot.document_element(cloned_node)?;
+    target.xot.replace(existing, cloned_element)?;
+    let renamespaced = normalize_namespaces(&mut target.xot, cloned_element);
+    if let Some(target_info) = target.element_map.get_mut(element_id) {
+        // The concept stays in its target folder
+        target_info.name = source_info.name.clone();
+        target_info.xsi_type = source_info.xsi_type.clone();
+        target_info.xml_string = source_info.xml_string.clone();
+        if renamespaced {
+            if let Some(xsi_type) = xsi_type(&target.xot, cloned_element) {
+                target_info.xsi_type = xsi_type.into();
+            }
+            target_info.xml_string = target
+                .xot
+                .serialize_xml_string(Default::default(), cloned_element)?;
+        }
+        target_info.properties = source_info.properties.clone();
+    }
+    println!(
+        "Conflict: {} differs between models, took source version",
+        label
+    );
+    Ok(true)
+}
+
+/// Whether `view` is a source view that differs from the target view with
+/// its ID, which `policy` decides between. Views paired by name replace
+/// their target view regardless.
+pub fn is_view_conflict(view: &MissingElementInfo) -> bool {
+    view.target_id.as_ref() == Some(&view.id)
+}
+
+/// Applies `policy` to a conflicting view, asking when it is `Prompt`.
+/// Returns true when the source version is to replace the target's.
+pub fn resolve_view_conflict(
+    view: &MissingElementInfo,
+    policy: ConflictPolicy,
+) -> Result<bool, ImporterError> {
+    let take_source = match policy {
+        ConflictPolicy::Theirs => true,
+        ConflictPolicy::Ours | ConflictPolicy::Newer => false,
+        ConflictPolicy::Prompt => {
+            let answer = get_input(&format!(
+                "Conflict: view {} differs between models. Keep (t)arget or take (s)ource version? [t] ",
+                view.name
+            ))?;
+            answer.eq_ignore_ascii_case("s") || answer.eq_ignore_ascii_case("source")
+        }
+    };
+    if !take_source {
+        println!(
+            "Conflict: view {} differs between models, keeping target version",
+            view.name
+        );
+    }
+    Ok(take_source)
+}
+
+/// Collects the conflicting concepts used by `views`, sorted by ID, with
+/// their resolution left open.
+pub fn find_conflicts(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    views: &[&MissingElementInfo],
+) -> Result<Vec<ConflictRecord>, ImporterError> {
+    let mut concept_views: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for view in views {
+        let Some(view_info) = source.view_map.get(&view.id) else {
+            continue;
+        };
+        let (elements, relations) = find_view_references(&view_info.xml_string)?;
+        for id in elements.into_iter().chain(relations) {
+            concept_views
+                .entry(id)
+                .or_default()
+                .insert(view.name.to_string());
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (id, view_names) in concept_views {
+        if !is_conflict(source, target, &id)? {
+            continue;
+        }
+        let source_info = source.element_map.get(id.as_str()).unwrap();
+        let target_info = target.element_map.get(id.as_str()).unwrap();
+        conflicts.push(ConflictRecord {
+            name: source_info.name.to_string(),
+            xsi_type: display_type(&source_info.xsi_type),
+            views: view_names.into_iter().collect(),
+            target_version: target_info.xml_string.trim().to_string(),
+            source_version: source_info.xml_string.trim().to_string(),
+            resolution: Resolution::Unresolved,
+            id,
+        });
+    }
+    Ok(conflicts)
+}
+
+/// Returns the first concept used by `view` that the resolutions mark as
+/// skip, if any.
+pub fn find_skipped_concept(
+    source: &ArchiModel,
+    view: &MissingElementInfo,
+    resolutions: &HashMap<String, Resolution>,
+) -> Result<Option<String>, ImporterError> {
+    let Some(view_info) = source.view_map.get(&view.id) else {
+        return Ok(None);
+    };
+    let (elements, relations) = find_view_references(&view_info.xml_string)?;
+    let mut skipped: Vec<String> = elements
+        .into_iter()
+        .chain(relations)
+        .filter(|id| resolutions.get(id) == Some(&Resolution::Skip))
+        .collect();
+    skipped.sort();
+    Ok(skipped.into_iter().next())
+}
+
+pub fn write_conflicts_file<P: AsRef<Path>>(
+    path: P,
+    conflicts: &[ConflictRecord],
+) -> Result<(), ImporterError> {
+    let file = ConflictFile {
+        conflicts: conflicts.to_vec(),
+    };
+    let yaml = serde_yaml::to_string(&file).map_err(|e| ImporterError::Other(e.to_string()))?;
+    let content = format!("{}{}", CONFLICTS_FILE_HEADER, yaml);
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Reads the decisions from an edited conflicts file, failing when any
+/// conflict is still unresolved.
+pub fn read_resolutions<P: AsRef<Path>>(
+    path: P,
+) -> Result<HashMap<String, Resolution>, ImporterError> {
+    let content = fs::read_to_string(path)?;
+    let file: ConflictFile = serde_yaml::from_str(&content)
+        .map_err(|e| ImporterError::Validation(format!("Invalid conflicts file: {}", e)))?;
+    let unresolved: Vec<&str> = file
+        .conflicts
+        .iter()
+        .filter(|conflict| conflict.resolution == Resolution::Unresolved)
+        .map(|conflict| conflict.id.as_str())
+        .collect();
+    if !unresolved.is_empty() {
+        return Err(ImporterError::Validation(format!(
+            "Unresolved conflicts: {}",
+            unresolved.join(", ")
+        )));
+    }
+    Ok(file
+        .conflicts
+        .into_iter()
+        .map(|conflict| (conflict.id, conflict.resolution))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{load_model, serialize_model};
+    use std::error::Error;
+
+    #[test]
+    fn test_resolve_conflict() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Apps' id='folder-9'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Customer Portal'/>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Backend'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let mut target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Backend'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+
+        assert!(is_conflict(&source, &target, "element-1")?);
+        assert!(!is_conflict(&source, &target, "element-2")?);
+
+        assert!(!resolve_conflict(
+            &source,
+            &mut target,
+            "element-1",
+            ConflictPolicy::Ours
+        )?);
+        assert!(serialize_model(&target)?.contains("name=\"Portal\""));
+
+        assert!(resolve_conflict(
+            &source,
+            &mut target,
+            "element-1",
+            ConflictPolicy::Theirs
+        )?);
+        let xml = serialize_model(&target)?;
+        assert!(xml.contains("name=\"Customer Portal\""));
+        assert!(!xml.contains("name=\"Portal\""));
+        assert_eq!(
+            target.element_map.get("element-1").unwrap().folder_path[0].name,
+            "Application".into()
+        );
+        assert!(!is_conflict(&source, &target, "element-1")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_conflict_policy() {
+        assert_eq!("theirs".parse(), Ok(ConflictPolicy::Theirs));
+        assert_eq!(
+            "newer"
+                .parse::<ConflictPolicy>()
+                .unwrap()
+                .resolve_newer(true),
+            ConflictPolicy::Theirs
+        );
+        assert_eq!(
+            ConflictPolicy::Newer.resolve_newer(false),
+            ConflictPolicy::Ours
+        );
+        assert!("mine".parse::<ConflictPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_conflicts_file_round_trip() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("conflicts.yaml");
+        let conflict = ConflictRecord {
+            id: "element-1".to_string(),
+            name: "Portal".to_string(),
+            xsi_type: "Application Component".to_string(),
+            views: vec!["Landscape".to_string()],
+            target_version: "<element id=\"element-1\" name=\"Portal\"/>".to_string(),
+            source_version: "<element id=\"element-1\" name=\"Customer Portal\"/>".to_string(),
+            resolution: Resolution::Unresolved,
+        };
+        write_conflicts_file(&path, std::slice::from_ref(&conflict))?;
+        let content = fs::read_to_string(&path)?;
+        assert!(content.starts_with("# archi-view-importer conflicts"));
+        assert!(content.contains("resolution: unresolved"));
+        assert!(read_resolutions(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("element-1"));
+
+        fs::write(
+            &path,
+            content.replace("resolution: unresolved", "resolution: take-source"),
+        )?;
+        let resolutions = read_resolutions(&path)?;
+        assert_eq!(resolutions.get("element-1"), Some(&Resolution::TakeSource));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_policy_conflict_policies() {
+        use ConflictPolicy::{Ours, Theirs};
+        assert_eq!(SyncPolicy::Keep.conflict_policies(true), (Ours, Ours));
+        assert_eq!(SyncPolicy::Left.conflict_policies(false), (Theirs, Ours));
+        assert_eq!(SyncPolicy::Right.conflict_policies(true), (Ours, Theirs));
+        assert_eq!(SyncPolicy::Newer.conflict_policies(true), (Theirs, Ours));
+        assert_eq!(SyncPolicy::Newer.conflict_policies(false), (Ours, Theirs));
+        assert_eq!("Left".parse::<SyncPolicy>(), Ok(SyncPolicy::Left));
+        assert!("both".parse::<SyncPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_merged_properties() -> Result<(), Box<dyn Error>> {
+        let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        let target = pairs(&[("owner", "Team B"), ("status", "draft")]);
+        let source = pairs(&[("status", "approved"), ("tier", "1"), ("owner", "Team B")]);
+
+        assert_eq!(
+            merged_properties("Portal", &target, &source, PropertyMerge::Union)?,
+            pairs(&[
+                ("owner", "Team B"),
+                ("status", "draft"),
+                ("status", "approved"),
+                ("tier", "1")
+            ])
+        );
+        assert_eq!(
+            merged_properties("Portal", &target, &source, PropertyMerge::PreferSource)?,
+            pairs(&[("owner", "Team B"), ("status", "approved"), ("tier", "1")])
+        );
+        assert_eq!(
+            merged_properties("Portal", &target, &source, PropertyMerge::PreferTarget)?,
+            pairs(&[("owner", "Team B"), ("status", "draft"), ("tier", "1")])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_merged_documentation() {
+        let marker = "--- Imported from upstream ---";
+        assert_eq!(
+            merged_documentation(
+                Some("Local notes"),
+                "Upstream notes",
+                marker,
+                DocumentationMerge::Append
+            ),
+            Some("Local notes\n\n--- Imported from upstream ---\nUpstream notes".to_string())
+        );
+        // Appending again after a repeated import changes nothing
+        assert_eq!(
+            merged_documentation(
+                Some("Local notes\n\n--- Imported from upstream ---\nUpstream notes"),
+                "Upstream notes",
+                marker,
+                DocumentationMerge::Append
+            ),
+            None
+        );
+        assert_eq!(
+            merged_documentation(
+                Some("Short"),
+                "Much longer",
+                marker,
+                DocumentationMerge::Longer
+            ),
+            Some("Much longer".to_string())
+        );
+        assert_eq!(
+            merged_documentation(
+                Some("Much longer"),
+                "Short",
+                marker,
+                DocumentationMerge::Longer
+            ),
+            None
+        );
+        assert_eq!(
+            merged_documentation(Some("Local"), "Upstream", marker, DocumentationMerge::Keep),
+            None
+        );
+        assert_eq!(
+            merged_documentation(None, "Upstream", marker, DocumentationMerge::Keep),
+            Some("Upstream".to_string())
+        );
+    }
+}
@@ -0,0 +1,2101 @@
+use crate::conflict::{
+    documentation_text, is_conflict, is_conflict_besides, merge_documentation, merge_properties,
+    resolve_conflict, ConceptConflict, ConflictPolicy, DocumentationMerge, PropertyMerge,
+    Resolution,
+};
+use crate::error::ImporterError;
+use crate::glossary::{describe, display_type};
+use crate::model::{
+    extract_properties, find_node_by_id, find_or_create_folder, format_folder_path,
+    into_folder_path, is_view_type, model_name, recursive_find_or_create_folder_path,
+    snapshot_folder_path, ArchiModel, ElementInfo, FolderInfo, MissingElementInfo,
+};
+use crate::namespaces::{normalize_namespaces, xsi_type, XSI_NAMESPACE};
+use crate::profiles::import_profiles;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use xot::{Node, Xot};
+
+/// Property recording where copied content was imported from.
+pub const PROVENANCE_KEY: &str = "imported-from";
+
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    pub verbose: bool,
+    pub create_stubs: bool,
+    pub explain: bool,
+    /// Label of the `Imports` subfolder new views are placed under.
+    pub snapshot_folder: Option<String>,
+    /// Folder path, as in `Views > Imported`, all new views are placed in.
+    pub into_folder: Option<String>,
+    /// Leave out the source folders below the top-level folders.
+    pub flatten: bool,
+    /// Move concepts the target already has into their source folders.
+    pub align_folders: bool,
+    /// Also copy source relations the view leaves out whose endpoints are
+    /// both in the target.
+    pub include_model_relations: bool,
+    /// Leave out relations the target lacks, along with their connections.
+    pub no_relations: bool,
+    /// Copy only the view, pointing it at concepts the target already has.
+    pub visual_only: bool,
+    pub on_conflict: ConflictPolicy,
+    /// Merges the properties of concepts in both models instead of treating
+    /// differing properties as a conflict.
+    pub merge_properties: Option<PropertyMerge>,
+    /// Merges the documentation of concepts in both models instead of
+    /// treating differing documentation as a conflict.
+    pub merge_documentation: Option<DocumentationMerge>,
+    /// Decisions read from a conflicts file, overriding `on_conflict`.
+    pub resolutions: HashMap<String, Resolution>,
+    /// Reuse target elements with the same name and type instead of
+    /// copying source elements the target lacks by ID.
+    pub reuse_by_name: bool,
+    /// Give every copied view, diagram object and concept a new ID.
+    pub fresh_ids: bool,
+    /// New names for views, by source view name.
+    pub renames: HashMap<String, String>,
+    /// Names views not listed in `renames`, with `{name}` standing for the
+    /// source view name.
+    pub rename_template: Option<String>,
+    pub on_name_collision: NameCollision,
+    /// Value of the `imported-from` property set on every copied view and
+    /// concept.
+    pub provenance: Option<String>,
+    pub hooks: CopyHooks,
+}
+
+/// Called with each selected view once it is copied or has failed, with
+/// how many of the selected views are done and how many there are.
+pub type ViewHook = Arc<dyn Fn(&ViewOutcome, usize, usize) + Send + Sync>;
+/// Called with each element or relation added to the target.
+pub type ElementHook = Arc<dyn Fn(&ElementInfo) + Send + Sync>;
+/// Decides a conflicting concept: `Some(ConflictPolicy::Theirs)` takes the
+/// source version, `Some(ConflictPolicy::Ours)` keeps the target's, and
+/// `None` leaves it to `on_conflict`.
+pub type ConflictHook = Arc<dyn Fn(&ConceptConflict) -> Option<ConflictPolicy> + Send + Sync>;
+
+/// Callbacks following a copy as it runs, for programs embedding the
+/// importer to show progress or take decisions.
+#[derive(Clone, Default)]
+pub struct CopyHooks {
+    pub on_view_copied: Option<ViewHook>,
+    pub on_element_copied: Option<ElementHook>,
+    pub on_conflict: Option<ConflictHook>,
+}
+
+impl fmt::Debug for CopyHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyHooks")
+            .field("on_view_copied", &self.on_view_copied.is_some())
+            .field("on_element_copied", &self.on_element_copied.is_some())
+            .field("on_conflict", &self.on_conflict.is_some())
+            .finish()
+    }
+}
+
+/// How a new view is named when the target already has a view by its name.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NameCollision {
+    /// Keep the name, leaving two views with the same name.
+    #[default]
+    Keep,
+    /// Append the first free number, as in `View (2)`.
+    Number,
+    /// Append the source model's name, numbered if that is taken too.
+    ModelName,
+}
+
+impl FromStr for NameCollision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "keep" => Ok(NameCollision::Keep),
+            "number" => Ok(NameCollision::Number),
+            "model-name" => Ok(NameCollision::ModelName),
+            _ => Err(format!(
+                "Invalid name collision policy: {} (expected keep, number or model-name)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for NameCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameCollision::Keep => write!(f, "keep"),
+            NameCollision::Number => write!(f, "number"),
+            NameCollision::ModelName => write!(f, "model-name"),
+        }
+    }
+}
+
+/// Whether the target has a view named `name`, counting views already
+/// imported in this run.
+fn has_view_named(target: &ArchiModel, name: &str) -> bool {
+    target
+        .view_map
+        .values()
+        .chain(
+            target
+                .element_map
+                .values()
+                .filter(|info| is_view_type(&info.xsi_type)),
+        )
+        .any(|info| &*info.name == name)
+}
+
+/// Picks a name for a new view that no target view has yet.
+fn unique_view_name(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    name: &str,
+    policy: NameCollision,
+) -> Option<String> {
+    if policy == NameCollision::Keep || !has_view_named(target, name) {
+        return None;
+    }
+    let base_name = match policy {
+        NameCollision::ModelName => {
+            let source_name = model_name(source);
+            let candidate = format!("{} ({})", name, source_name);
+            if source_name.is_empty() {
+                name.to_string()
+            } else if !has_view_named(target, &candidate) {
+                return Some(candidate);
+            } else {
+                candidate
+            }
+        }
+        _ => name.to_string(),
+    };
+    (2..)
+        .map(|number| format!("{} ({})", base_name, number))
+        .find(|candidate| !has_view_named(target, candidate))
+}
+
+impl CopyOptions {
+    /// Returns the name a view gets in the target if it is renamed.
+    pub fn renamed_view(&self, name: &str) -> Option<String> {
+        if let Some(new_name) = self.renames.get(name) {
+            return Some(new_name.clone());
+        }
+        self.rename_template
+            .as_ref()
+            .map(|template| template.replace("{name}", name))
+    }
+}
+
+/// Parses a `--rename` argument of the form `Old Name=New Name`.
+pub fn parse_rename(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((old_name, new_name))
+            if !old_name.trim().is_empty() && !new_name.trim().is_empty() =>
+        {
+            Ok((old_name.trim().to_string(), new_name.trim().to_string()))
+        }
+        _ => Err(format!(
+            "Invalid rename: {} (expected Old Name=New Name)",
+            input
+        )),
+    }
+}
+
+/// Attributes of a view and its diagram objects that hold IDs.
+const VIEW_ID_ATTRIBUTES: &[&str] = &[
+    "id",
+    "archimateElement",
+    "archimateRelationship",
+    "source",
+    "target",
+    "targetConnections",
+];
+
+/// Attributes of an element or relation that hold IDs.
+const CONCEPT_ID_ATTRIBUTES: &[&str] = &["id", "source", "target"];
+
+/// Changes made to a view as it is copied into the target.
+#[derive(Debug, Clone, Default)]
+pub struct ViewEdits {
+    /// IDs replaced throughout the view.
+    pub remapped: HashMap<String, String>,
+    /// Name replacing the source view's name.
+    pub new_name: Option<String>,
+    /// Relations whose connections are removed from the view.
+    pub dropped_relations: HashSet<String>,
+    /// Value of the `imported-from` property set on the view.
+    pub provenance: Option<String>,
+}
+
+impl ViewEdits {
+    fn is_empty(&self) -> bool {
+        self.remapped.is_empty()
+            && self.new_name.is_none()
+            && self.dropped_relations.is_empty()
+            && self.provenance.is_none()
+    }
+
+    fn apply(&self, xot: &mut Xot, view: Node) -> Result<(), ImporterError> {
+        remove_connections(xot, view, &self.dropped_relations)?;
+        remap_references(xot, view, VIEW_ID_ATTRIBUTES, &self.remapped);
+        if let Some(new_name) = &self.new_name {
+            let name_name = xot.add_name("name");
+            xot.set_attribute(view, name_name, new_name.as_str());
+        }
+        if let Some(provenance) = &self.provenance {
+            set_property(xot, view, PROVENANCE_KEY, provenance)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViewDependencies {
+    pub elements: usize,
+    pub existing_elements: usize,
+    pub relations: usize,
+    pub existing_relations: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewOutcome {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+pub fn extract_references(
+    xot: &Xot,
+    node: Node,
+    elements: &mut HashSet<String>,
+    relations: &mut HashSet<String>,
+    verbose: bool,
+) {
+    let attribute = |name: &str| {
+        xot.name(name)
+            .and_then(|name| xot.get_attribute(node, name))
+    };
+    if let Some(element_ref) = attribute("archimateElement") {
+        verbose_println!(verbose, ".found element: {}", element_ref);
+        elements.insert(element_ref.to_string());
+    }
+    if let Some(relation_ref) = attribute("archimateRelationship") {
+        verbose_println!(verbose, ".found relation: {}", relation_ref);
+        relations.insert(relation_ref.to_string());
+    }
+    for child in xot.children(node).filter(|&n| xot.is_element(n)) {
+        extract_references(xot, child, elements, relations, verbose);
+    }
+}
+
+/// Returns the element and relation IDs referenced by a serialized view.
+pub fn find_view_references(
+    xml_string: &str,
+) -> Result<(HashSet<String>, HashSet<String>), ImporterError> {
+    let mut xot = Xot::new();
+    xot.add_name("archimateElement");
+    xot.add_name("archimateRelationship");
+    let view_node = xot.parse_fragment(xml_string)?;
+    let mut referenced_elements = HashSet::new();
+    let mut referenced_relations = HashSet::new();
+    extract_references(
+        &xot,
+        view_node,
+        &mut referenced_elements,
+        &mut referenced_relations,
+        false,
+    );
+    Ok((referenced_elements, referenced_relations))
+}
+
+/// The concepts a source view shows that neither model has, sorted, which
+/// copying the view would leave it pointing at.
+pub fn find_unresolved_references(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    view_id: &str,
+) -> Result<Vec<String>, ImporterError> {
+    let Some(info) = source.view_map.get(view_id) else {
+        return Ok(Vec::new());
+    };
+    let (elements, relations) = find_view_references(&info.xml_string)?;
+    let mut unresolved: Vec<String> = elements
+        .into_iter()
+        .chain(relations)
+        .filter(|id| {
+            !source.element_map.contains_key(id.as_str())
+                && !target.element_map.contains_key(id.as_str())
+        })
+        .collect();
+    unresolved.sort();
+    Ok(unresolved)
+}
+
+/// Returns the IDs of the views a serialized view links to through diagram
+/// model references.
+pub fn find_diagram_references(xml_string: &str) -> Result<HashSet<String>, ImporterError> {
+    let mut xot = Xot::new();
+    let model_name = xot.add_name("model");
+    let view_node = xot.parse_fragment(xml_string)?;
+    Ok(xot
+        .descendants(view_node)
+        .filter_map(|node| xot.get_attribute(node, model_name))
+        .map(|id| id.to_string())
+        .collect())
+}
+
+/// Counts the concepts a source view would pull in and how many of them the
+/// target already has.
+pub fn find_view_dependencies(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    view_id: &str,
+) -> Result<ViewDependencies, ImporterError> {
+    let view_info = source
+        .view_map
+        .get(view_id)
+        .ok_or_else(|| ImporterError::Selection(format!("View {} not found in source", view_id)))?;
+    let (elements, relations) = find_view_references(&view_info.xml_string)?;
+    let existing = |ids: &HashSet<String>| {
+        ids.iter()
+            .filter(|id| target.element_map.contains_key(id.as_str()))
+            .count()
+    };
+    Ok(ViewDependencies {
+        elements: elements.len(),
+        existing_elements: existing(&elements),
+        relations: relations.len(),
+        existing_relations: existing(&relations),
+    })
+}
+
+pub fn collect_relation_endpoints(
+    xot: &Xot,
+    node: Node,
+    object_elements: &mut HashMap<String, String>,
+    connections: &mut Vec<(String, String, String)>,
+) {
+    let attribute = |name: &str| {
+        xot.name(name)
+            .and_then(|name| xot.get_attribute(node, name))
+            .map(|value| value.to_string())
+    };
+    if let (Some(object_id), Some(element_ref)) = (attribute("id"), attribute("archimateElement")) {
+        object_elements.insert(object_id, element_ref);
+    }
+    if let (Some(relation_ref), Some(source), Some(target)) = (
+        attribute("archimateRelationship"),
+        attribute("source"),
+        attribute("target"),
+    ) {
+        connections.push((relation_ref, source, target));
+    }
+    for child in xot.children(node).filter(|&n| xot.is_element(n)) {
+        collect_relation_endpoints(xot, child, object_elements, connections);
+    }
+}
+
+/// Maps each new source element to the target element with the same name
+/// and type, taking the lowest ID when several match. Unnamed elements are
+/// never matched.
+pub fn find_reusable_elements(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    element_ids: &[String],
+) -> HashMap<String, String> {
+    let mut reusable = HashMap::new();
+    for element_id in element_ids {
+        let Some(source_info) = source.element_map.get(element_id.as_str()) else {
+            continue;
+        };
+        if source_info.name.is_empty() {
+            continue;
+        }
+        let target_id = target
+            .element_map
+            .values()
+            .filter(|info| info.name == source_info.name && info.xsi_type == source_info.xsi_type)
+            .map(|info| &info.id)
+            .min();
+        if let Some(target_id) = target_id {
+            reusable.insert(element_id.clone(), target_id.to_string());
+        }
+    }
+    reusable
+}
+
+/// Returns the `source` and `target` IDs of a serialized relation.
+pub fn relation_endpoints(xml_string: &str) -> Result<Option<(String, String)>, ImporterError> {
+    let mut xot = Xot::new();
+    let source_name = xot.add_name("source");
+    let target_name = xot.add_name("target");
+    let node = xot.parse_fragment(xml_string)?;
+    let Some(relation) = xot.first_child(node) else {
+        return Ok(None);
+    };
+    Ok(
+        match (
+            xot.get_attribute(relation, source_name),
+            xot.get_attribute(relation, target_name),
+        ) {
+            (Some(source), Some(target)) => Some((source.to_string(), target.to_string())),
+            _ => None,
+        },
+    )
+}
+
+/// Finds the target relation with the same type as a source relation that
+/// joins the same endpoints, once they are `remapped`, taking the lowest ID
+/// when several match.
+pub fn find_matching_relation(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    relation_id: &str,
+    remapped: &HashMap<String, String>,
+) -> Result<Option<String>, ImporterError> {
+    let Some(info) = source.element_map.get(relation_id) else {
+        return Ok(None);
+    };
+    let Some((source_id, target_id)) = relation_endpoints(&info.xml_string)? else {
+        return Ok(None);
+    };
+    let endpoints = (
+        remapped.get(&source_id).cloned().unwrap_or(source_id),
+        remapped.get(&target_id).cloned().unwrap_or(target_id),
+    );
+    let mut candidates: Vec<&ElementInfo> = target
+        .element_map
+        .values()
+        .filter(|candidate| candidate.xsi_type == info.xsi_type)
+        .collect();
+    candidates.sort_by(|a, b| a.id.cmp(&b.id));
+    for candidate in candidates {
+        if relation_endpoints(&candidate.xml_string)?.as_ref() == Some(&endpoints) {
+            return Ok(Some(candidate.id.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the endpoints of `relations` that neither the target nor the
+/// concepts about to be copied include, following endpoints that are
+/// relations themselves. Returns the elements and relations to copy as well.
+pub fn find_missing_endpoints(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    elements: &[String],
+    relations: &[String],
+) -> Result<(Vec<String>, Vec<String>), ImporterError> {
+    let mut present: HashSet<String> = elements.iter().chain(relations).cloned().collect();
+    let mut queue: Vec<String> = relations.to_vec();
+    let mut missing_elements = Vec::new();
+    let mut missing_relations = Vec::new();
+    while let Some(relation_id) = queue.pop() {
+        let Some(info) = source.element_map.get(relation_id.as_str()) else {
+            continue;
+        };
+        let Some((source_id, target_id)) = relation_endpoints(&info.xml_string)? else {
+            continue;
+        };
+        for endpoint in [source_id, target_id] {
+            if target.element_map.contains_key(endpoint.as_str()) || present.contains(&endpoint) {
+                continue;
+            }
+            match source.element_map.get(endpoint.as_str()) {
+                Some(endpoint_info) if endpoint_info.is_relationship() => {
+                    queue.push(endpoint.clone());
+                    missing_relations.push(endpoint.clone());
+                }
+                Some(_) => missing_elements.push(endpoint.clone()),
+                None => {
+                    println!(
+                        "Warning: relation {} references {}, found in neither model",
+                        relation_id, endpoint
+                    );
+                }
+            }
+            present.insert(endpoint);
+        }
+    }
+    Ok((missing_elements, missing_relations))
+}
+
+/// Finds the source relations connected to the junctions in `elements` that
+/// neither the target nor `relations` include, so a copied junction keeps
+/// every leg. Returns each relation with the junction it connects.
+pub fn find_junction_legs(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    elements: &[String],
+    relations: &[String],
+) -> Result<Vec<(String, String)>, ImporterError> {
+    let junctions: HashSet<&String> = elements
+        .iter()
+        .filter(|id| {
+            source
+                .element_map
+                .get(id.as_str())
+                .is_some_and(ElementInfo::is_junction)
+        })
+        .collect();
+    if junctions.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut candidates: Vec<&ElementInfo> = source
+        .element_map
+        .values()
+        .filter(|info| {
+            info.is_relationship()
+                && !target.element_map.contains_key(&info.id)
+                && !relations.iter().any(|id| **id == *info.id)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut legs = Vec::new();
+    for info in candidates {
+        if let Some((source_id, target_id)) = relation_endpoints(&info.xml_string)? {
+            if let Some(junction_id) = [source_id, target_id]
+                .into_iter()
+                .find(|endpoint| junctions.contains(endpoint))
+            {
+                legs.push((info.id.to_string(), junction_id));
+            }
+        }
+    }
+    Ok(legs)
+}
+
+/// Finds the source relations missing from the target whose endpoints are
+/// both in the target or in `present`, the concepts about to be copied.
+/// Relations between such relations are found too; each one found is added
+/// to `present`.
+pub fn find_model_relations(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    present: &mut HashSet<String>,
+) -> Result<Vec<String>, ImporterError> {
+    let mut candidates = Vec::new();
+    for info in source.element_map.values() {
+        if !info.is_relationship()
+            || target.element_map.contains_key(&info.id)
+            || present.contains(&*info.id)
+        {
+            continue;
+        }
+        if let Some(endpoints) = relation_endpoints(&info.xml_string)? {
+            candidates.push((info.id.to_string(), endpoints));
+        }
+    }
+    candidates.sort();
+
+    let mut found = Vec::new();
+    loop {
+        let is_present =
+            |id: &String| target.element_map.contains_key(id.as_str()) || present.contains(id);
+        let (ready, waiting): (Vec<_>, Vec<_>) =
+            candidates
+                .into_iter()
+                .partition(|(_, (source_id, target_id))| {
+                    is_present(source_id) && is_present(target_id)
+                });
+        if ready.is_empty() {
+            break;
+        }
+        for (relation_id, _) in ready {
+            present.insert(relation_id.clone());
+            found.push(relation_id);
+        }
+        candidates = waiting;
+    }
+    Ok(found)
+}
+
+/// Replaces each remapped ID held by the `attributes` of `node` and its
+/// descendants. Attributes may hold several space-separated IDs.
+pub fn remap_references(
+    xot: &mut Xot,
+    node: Node,
+    attributes: &[&str],
+    remapped: &HashMap<String, String>,
+) {
+    for attribute in attributes {
+        let name = xot.add_name(attribute);
+        let descendants: Vec<Node> = xot.descendants(node).collect();
+        for descendant in descendants {
+            let Some(value) = xot.get_attribute(descendant, name) else {
+                continue;
+            };
+            let ids: Vec<&str> = value.split_whitespace().collect();
+            if !ids.iter().any(|id| remapped.contains_key(*id)) {
+                continue;
+            }
+            let new_value = ids
+                .iter()
+                .map(|id| remapped.get(*id).map(String::as_str).unwrap_or(id))
+                .collect::<Vec<_>>()
+                .join(" ");
+            xot.set_attribute(descendant, name, new_value);
+        }
+    }
+}
+
+/// Removes the connections drawn for `relations` from a view, along with
+/// connections attached to them, and their `targetConnections` entries.
+pub fn remove_connections(
+    xot: &mut Xot,
+    view: Node,
+    relations: &HashSet<String>,
+) -> Result<(), ImporterError> {
+    if relations.is_empty() {
+        return Ok(());
+    }
+    let id_name = xot.add_name("id");
+    let relation_name = xot.add_name("archimateRelationship");
+    let source_name = xot.add_name("source");
+    let target_name = xot.add_name("target");
+    let target_connections_name = xot.add_name("targetConnections");
+
+    let mut removed_ids = HashSet::new();
+    loop {
+        let removed: Vec<Node> = xot
+            .descendants(view)
+            .filter(|&node| {
+                let attribute = |name| xot.get_attribute(node, name).unwrap_or("");
+                relations.contains(attribute(relation_name))
+                    || removed_ids.contains(attribute(source_name))
+                    || removed_ids.contains(attribute(target_name))
+            })
+            .filter(|&node| !removed_ids.contains(xot.get_attribute(node, id_name).unwrap_or("")))
+            .collect();
+        if removed.is_empty() {
+            break;
+        }
+        for node in removed {
+            if let Some(id) = xot.get_attribute(node, id_name) {
+                removed_ids.insert(id.to_string());
+            }
+            xot.remove(node)?;
+        }
+    }
+
+    let descendants: Vec<Node> = xot.descendants(view).collect();
+    for node in descendants {
+        let Some(value) = xot.get_attribute(node, target_connections_name) else {
+            continue;
+        };
+        let kept: Vec<&str> = value
+            .split_whitespace()
+            .filter(|id| !removed_ids.contains(*id))
+            .collect();
+        if kept.is_empty() {
+            xot.remove_attribute(node, target_connections_name);
+        } else {
+            let kept = kept.join(" ");
+            xot.set_attribute(node, target_connections_name, kept);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the IDs of `node` and its descendants in document order.
+fn collect_ids(xot: &mut Xot, node: Node) -> Vec<String> {
+    let id_name = xot.add_name("id");
+    xot.descendants(node)
+        .filter_map(|descendant| xot.get_attribute(descendant, id_name))
+        .map(|id| id.to_string())
+        .collect()
+}
+
+/// Maps each relation drawn on the view to the elements its connection joins.
+pub fn find_relation_endpoints(xot: &Xot, view_node: Node) -> HashMap<String, (String, String)> {
+    let mut object_elements = HashMap::new();
+    let mut connections = Vec::new();
+    collect_relation_endpoints(xot, view_node, &mut object_elements, &mut connections);
+    connections
+        .into_iter()
+        .filter_map(|(relation_ref, source, target)| {
+            let source_element = object_elements.get(&source)?.clone();
+            let target_element = object_elements.get(&target)?.clone();
+            Some((relation_ref, (source_element, target_element)))
+        })
+        .collect()
+}
+
+/// What copying one view will do, worked out by `plan_view` before any
+/// concept is added or changed, and carried out by `apply_view`. Callers
+/// may change it in between, such as the policy settling a conflict.
+#[derive(Debug, Clone)]
+pub struct ViewPlan {
+    /// The ID of the source view.
+    pub view_id: String,
+    /// The name of the source view.
+    pub name: String,
+    /// The target view this one replaces in update mode.
+    pub target_id: Option<String>,
+    /// Elements the target lacks, by source ID, copied in this order.
+    pub new_elements: Vec<String>,
+    /// Relations the target lacks, by source ID, copied in this order.
+    pub new_relations: Vec<String>,
+    /// Concepts the view shows that the target already has, whose
+    /// properties and documentation are merged and folders aligned.
+    pub existing_concepts: Vec<String>,
+    /// Existing concepts that differ from their source version, with the
+    /// policy settling each.
+    pub conflicts: Vec<(String, ConflictPolicy)>,
+    /// Folder paths, as in `Views > Sales`, created in the target for the
+    /// view and its new concepts.
+    pub new_folders: Vec<String>,
+    /// The folder the view is placed in, or `None` for its source folder,
+    /// or the folder of the view it replaces.
+    pub folder_path: Option<Vec<FolderInfo>>,
+    /// Changes made to the view and the IDs of its new concepts.
+    pub edits: ViewEdits,
+    /// The endpoints of the relations the view draws, for stubs.
+    relation_endpoints: HashMap<String, (String, String)>,
+}
+
+pub fn copy_view(
+    source: &mut ArchiModel,
+    target: &mut ArchiModel,
+    view: &MissingElementInfo,
+    options: &CopyOptions,
+) -> Result<(usize, usize, usize), ImporterError> {
+    let plan = plan_view(source, target, view, options)?;
+    apply_view(source, target, &plan, options)
+}
+
+/// Works out what copying `view` into `target` does, without adding or
+/// changing concepts. Only fresh IDs are drawn from the target, and the
+/// top-level views folder is created when a folder below it is asked for.
+pub fn plan_view(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    view: &MissingElementInfo,
+    options: &CopyOptions,
+) -> Result<ViewPlan, ImporterError> {
+    let verbose = options.verbose;
+    let source_info = source
+        .view_map
+        .get(&view.id)
+        .ok_or_else(|| ImporterError::Selection(format!("View {} not found in source", view.id)))?;
+    let view_node = target.xot.parse_fragment(source_info.xml_string.as_str())?;
+    let mut source_folder_path = source_info.folder_path.to_vec();
+    if options.flatten {
+        source_folder_path.truncate(1);
+    }
+    if view.target_id.is_some() {
+        println!("Updating view {}", view.name);
+    } else {
+        println!("Creating view {}", view.name);
+    }
+
+    // Extract referenced elements and relations from the view
+    let mut referenced_elements = HashSet::new();
+    let mut referenced_relations = HashSet::new();
+
+    // Extract all referenced elements and relations from the view
+    extract_references(
+        &target.xot,
+        view_node,
+        &mut referenced_elements,
+        &mut referenced_relations,
+        verbose,
+    );
+    let relation_endpoints = find_relation_endpoints(&target.xot, view_node);
+
+    // Sorted so concepts are appended to the target in a reproducible order
+    let mut new_elements: Vec<_> = referenced_elements
+        .iter()
+        .filter(|id| !target.element_map.contains_key(id.as_str()))
+        .cloned()
+        .collect();
+    new_elements.sort();
+
+    let mut new_relations: Vec<_> = referenced_relations
+        .iter()
+        .filter(|id| !target.element_map.contains_key(id.as_str()))
+        .cloned()
+        .collect();
+    new_relations.sort();
+
+    let mut remapped = HashMap::new();
+    let mut dropped_relations = HashSet::new();
+    // Visual-only imports point the view at concepts the target already has
+    if options.visual_only {
+        remapped = find_reusable_elements(source, target, &new_elements);
+        let unmatched: Vec<&str> = new_elements
+            .iter()
+            .filter(|id| !remapped.contains_key(*id))
+            .map(String::as_str)
+            .collect();
+        if !unmatched.is_empty() {
+            return Err(ImporterError::Validation(format!(
+                "No target element matches {}",
+                unmatched.join(", ")
+            )));
+        }
+        new_elements.clear();
+        for relation_id in std::mem::take(&mut new_relations) {
+            match find_matching_relation(source, target, &relation_id, &remapped)? {
+                Some(target_id) => {
+                    remapped.insert(relation_id, target_id);
+                }
+                None => {
+                    println!(
+                        "Skipping relation {}: no matching relation in target",
+                        relation_id
+                    );
+                    dropped_relations.insert(relation_id);
+                }
+            }
+        }
+    }
+    if options.no_relations {
+        for relation_id in &new_relations {
+            let relation_type = source
+                .element_map
+                .get(relation_id.as_str())
+                .map(|info| display_type(&info.xsi_type))
+                .unwrap_or_default();
+            println!(
+                "Skipping relation {} [{}]: not in target",
+                relation_id, relation_type
+            );
+        }
+        dropped_relations.extend(new_relations.drain(..));
+    }
+
+    // Relations may point at concepts the view does not show, and copied
+    // junctions need all their legs, whose endpoints may be junctions again.
+    // Without relations there are no endpoints or legs to follow.
+    if !options.no_relations {
+        loop {
+            let (endpoint_elements, endpoint_relations) =
+                find_missing_endpoints(source, target, &new_elements, &new_relations)?;
+            new_elements.extend(endpoint_elements);
+            new_elements.sort();
+            new_relations.extend(endpoint_relations);
+            let legs = find_junction_legs(source, target, &new_elements, &new_relations)?;
+            if legs.is_empty() {
+                new_relations.sort();
+                break;
+            }
+            for (relation_id, junction_id) in legs {
+                let relation_type = source
+                    .element_map
+                    .get(relation_id.as_str())
+                    .map(|info| display_type(&info.xsi_type))
+                    .unwrap_or_default();
+                println!(
+                    "Including relation {} [{}]: connects junction {}",
+                    relation_id, relation_type, junction_id
+                );
+                new_relations.push(relation_id);
+            }
+        }
+    }
+
+    // Elements the target already has under another ID are reused
+    if options.reuse_by_name {
+        remapped.extend(find_reusable_elements(source, target, &new_elements));
+        for element_id in &new_elements {
+            if let (Some(target_id), Some(info)) = (
+                remapped.get(element_id),
+                source.element_map.get(element_id.as_str()),
+            ) {
+                println!(
+                    "Reusing target element {} [{}] ({}) for {}",
+                    info.name,
+                    display_type(&info.xsi_type),
+                    target_id,
+                    element_id
+                );
+            }
+        }
+        new_elements.retain(|id| !remapped.contains_key(id));
+    }
+
+    if options.include_model_relations {
+        let mut present: HashSet<String> = new_elements
+            .iter()
+            .chain(&new_relations)
+            .chain(remapped.keys())
+            .cloned()
+            .collect();
+        for relation_id in find_model_relations(source, target, &mut present)? {
+            verbose_println!(verbose, ".model relation {}", relation_id);
+            new_relations.push(relation_id);
+        }
+    }
+
+    if options.fresh_ids {
+        let view_ids = collect_ids(&mut target.xot, view_node);
+        for id in new_elements.iter().chain(&new_relations).chain(&view_ids) {
+            // An updated view keeps the ID of the target view it replaces
+            if view.target_id.is_some() && **id == *view.id {
+                continue;
+            }
+            let new_id = target.ids.next_id();
+            remapped.insert(id.clone(), new_id);
+        }
+    }
+
+    // Concepts the target already has are checked for divergence
+    let mut existing_concepts: Vec<_> = referenced_elements
+        .iter()
+        .chain(referenced_relations.iter())
+        .filter(|id| target.element_map.contains_key(id.as_str()))
+        .cloned()
+        .collect();
+    existing_concepts.sort();
+    // Properties and documentation with a merge policy are not conflicts
+    let merged_children: Vec<&str> = [
+        options.merge_properties.map(|_| "property"),
+        options.merge_documentation.map(|_| "documentation"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let mut conflicts = Vec::new();
+    for element_id in &existing_concepts {
+        let conflict = if merged_children.is_empty() {
+            is_conflict(source, target, element_id)?
+        } else {
+            is_conflict_besides(source, target, element_id, &merged_children)?
+        };
+        if conflict {
+            let decided = match (
+                &options.hooks.on_conflict,
+                options.resolutions.get(element_id),
+            ) {
+                (Some(on_conflict), None) => {
+                    let conflict = ConceptConflict::new(
+                        &source.element_map[element_id.as_str()],
+                        &target.element_map[element_id.as_str()],
+                    );
+                    on_conflict(&conflict)
+                }
+                _ => None,
+            };
+            let policy = match options.resolutions.get(element_id) {
+                Some(Resolution::TakeSource) => ConflictPolicy::Theirs,
+                Some(_) => ConflictPolicy::Ours,
+                None => decided.unwrap_or(options.on_conflict),
+            };
+            conflicts.push((element_id.clone(), policy));
+        }
+    }
+
+    let mut new_name = options.renamed_view(&view.name);
+    if let Some(new_name) = &new_name {
+        println!("Renaming view {} to {}", view.name, new_name);
+    }
+    if view.target_id.is_none() {
+        let name = new_name.as_deref().unwrap_or(&view.name);
+        if let Some(unique_name) = unique_view_name(source, target, name, options.on_name_collision)
+        {
+            println!(
+                "View name {} is taken in target, importing as {}",
+                name, unique_name
+            );
+            new_name = Some(unique_name);
+        }
+    }
+    // Updated views stay in the folder of the target view they replace
+    let folder_path = match (&options.into_folder, &options.snapshot_folder) {
+        _ if view.target_id.is_some() => None,
+        (Some(path), _) => Some(into_folder_path(target, path)?),
+        (None, Some(label)) => Some(snapshot_folder_path(target, &source_folder_path, label)),
+        (None, None) if options.flatten => Some(source_folder_path.clone()),
+        (None, None) => None,
+    };
+
+    // Folders are matched by name as they are created
+    let name_path = |path: &[FolderInfo]| -> String {
+        path.iter()
+            .map(|folder| &*folder.name)
+            .collect::<Vec<_>>()
+            .join(" > ")
+    };
+    let target_folders: HashSet<String> = target
+        .folders()
+        .map(|folder| {
+            let mut path = folder.folder_path.to_vec();
+            path.push(FolderInfo {
+                id: folder.id.into(),
+                name: folder.name.into(),
+                folder_type: folder.folder_type,
+            });
+            name_path(&path)
+        })
+        .collect();
+    let mut placed: Vec<&[FolderInfo]> = new_elements
+        .iter()
+        .chain(&new_relations)
+        .filter_map(|id| source.element_map.get(id.as_str()))
+        .map(|info| concept_folder_path(&info.folder_path, options.flatten))
+        .collect();
+    if view.target_id.is_none() {
+        placed.push(folder_path.as_deref().unwrap_or(&source_folder_path));
+    }
+    let mut new_folders = Vec::new();
+    for path in placed {
+        for depth in 1..=path.len() {
+            let prefix = name_path(&path[..depth]);
+            if !target_folders.contains(&prefix) && !new_folders.contains(&prefix) {
+                new_folders.push(prefix);
+            }
+        }
+    }
+    new_folders.sort();
+
+    Ok(ViewPlan {
+        view_id: view.id.to_string(),
+        name: view.name.to_string(),
+        target_id: view.target_id.as_deref().map(str::to_string),
+        new_elements,
+        new_relations,
+        existing_concepts,
+        conflicts,
+        new_folders,
+        folder_path,
+        edits: ViewEdits {
+            remapped,
+            new_name,
+            dropped_relations,
+            provenance: options.provenance.clone(),
+        },
+        relation_endpoints,
+    })
+}
+
+/// Copies a view into `target` as `plan` lays out, returning the number of
+/// views, elements and relations copied. On an error the target is restored
+/// to how it was before, so a view that fails halfway leaves nothing behind.
+pub fn apply_view(
+    source: &mut ArchiModel,
+    target: &mut ArchiModel,
+    plan: &ViewPlan,
+    options: &CopyOptions,
+) -> Result<(usize, usize, usize), ImporterError> {
+    let snapshot = target.clone();
+    let result = apply_planned_view(source, target, plan, options);
+    if result.is_err() {
+        *target = snapshot;
+    }
+    result
+}
+
+fn apply_planned_view(
+    source: &mut ArchiModel,
+    target: &mut ArchiModel,
+    plan: &ViewPlan,
+    options: &CopyOptions,
+) -> Result<(usize, usize, usize), ImporterError> {
+    let verbose = options.verbose;
+    let remapped = &plan.edits.remapped;
+    for element_id in &plan.existing_concepts {
+        // Taken before a source version can replace the target's
+        let target_properties = target
+            .element_map
+            .get(element_id.as_str())
+            .map(|info| info.properties.clone())
+            .unwrap_or_default();
+        let target_documentation = match options.merge_documentation {
+            Some(_) => find_node_by_id(target, element_id)
+                .and_then(|node| documentation_text(&target.xot, node)),
+            None => None,
+        };
+        if let Some((_, policy)) = plan.conflicts.iter().find(|(id, _)| id == element_id) {
+            resolve_conflict(source, target, element_id, *policy)?;
+        }
+        if let Some(policy) = options.merge_properties {
+            merge_properties(source, target, element_id, &target_properties, policy)?;
+        }
+        if let Some(policy) = options.merge_documentation {
+            merge_documentation(
+                source,
+                target,
+                element_id,
+                target_documentation.as_deref(),
+                policy,
+            )?;
+        }
+    }
+    if options.align_folders {
+        for element_id in &plan.existing_concepts {
+            align_folder(source, target, element_id, options.flatten)?;
+        }
+    }
+
+    for element_id in &plan.new_elements {
+        verbose_println!(verbose, ".new elements {}", element_id);
+        let target_element_id = remapped.get(element_id).unwrap_or(element_id);
+        if options.create_stubs && !source.element_map.contains_key(element_id.as_str()) {
+            insert_stub_element(target, target_element_id, None)?;
+        } else {
+            insert_new_element(
+                source,
+                target,
+                element_id,
+                remapped,
+                options.flatten,
+                options.provenance.as_deref(),
+                verbose,
+            )?;
+        }
+        element_copied(target, target_element_id, options);
+    }
+    for element_id in &plan.new_relations {
+        verbose_println!(verbose, ".new relations {}", element_id);
+        let target_element_id = remapped.get(element_id).unwrap_or(element_id);
+        if options.create_stubs && !source.element_map.contains_key(element_id.as_str()) {
+            let endpoints = plan.relation_endpoints.get(element_id).ok_or_else(|| {
+                ImporterError::Validation(format!(
+                    "Cannot resolve endpoints of relation {}",
+                    element_id
+                ))
+            })?;
+            let endpoints = (
+                remapped.get(&endpoints.0).unwrap_or(&endpoints.0).clone(),
+                remapped.get(&endpoints.1).unwrap_or(&endpoints.1).clone(),
+            );
+            insert_stub_element(target, target_element_id, Some(&endpoints))?;
+        } else {
+            insert_new_element(
+                source,
+                target,
+                element_id,
+                remapped,
+                options.flatten,
+                options.provenance.as_deref(),
+                verbose,
+            )?;
+        }
+        element_copied(target, target_element_id, options);
+    }
+    insert_new_view(
+        source,
+        target,
+        &plan.view_id,
+        plan.target_id.as_deref(),
+        plan.folder_path.as_deref(),
+        &plan.edits,
+    )?;
+    Ok((1, plan.new_elements.len(), plan.new_relations.len()))
+}
+
+/// Explains a concept added to the target and hands it to the hook.
+fn element_copied(target: &ArchiModel, element_id: &str, options: &CopyOptions) {
+    if options.explain {
+        explain_element(target, element_id);
+    }
+    if let Some(on_element_copied) = &options.hooks.on_element_copied {
+        if let Some(info) = target.element_map.get(element_id) {
+            on_element_copied(info);
+        }
+    }
+}
+
+/// Prints a copied concept with a short description of its ArchiMate type.
+fn explain_element(target: &ArchiModel, element_id: &str) {
+    if let Some(info) = target.element_map.get(element_id) {
+        let name = if info.name.is_empty() {
+            "(unnamed)"
+        } else {
+            &info.name
+        };
+        match describe(&info.xsi_type) {
+            Some(description) => println!(
+                "  + {} [{}]: {}",
+                name,
+                display_type(&info.xsi_type),
+                description
+            ),
+            None => println!("  + {} [{}]", name, display_type(&info.xsi_type)),
+        }
+    }
+}
+
+pub fn insert_new_element(
+    source: &mut ArchiModel,
+    target: &mut ArchiModel,
+    element_id: &String,
+    remapped: &HashMap<String, String>,
+    flatten: bool,
+    provenance: Option<&str>,
+    verbose: bool,
+) -> Result<(), ImporterError> {
+    if !source.element_map.contains_key(element_id.as_str()) {
+        verbose_println!(verbose, ".Not found in source {}", element_id);
+    }
+    if let Some(source_element_info) = source.element_map.get(element_id.as_str()) {
+        let folder_path = concept_folder_path(&source_element_info.folder_path, flatten);
+        let target_element_folder = recursive_find_or_create_folder_path(target, folder_path)?;
+
+        verbose_println!(
+            verbose,
+            "creating element {}",
+            source_element_info.xml_string
+        );
+        let cloned_node = target.xot.parse(source_element_info.xml_string.as_str())?;
+        let cloned_element = target.xot.document_element(cloned_node)?;
+        target.xot.append(target_element_folder, cloned_element)?;
+        let renamespaced = normalize_namespaces(&mut target.xot, cloned_element);
+        let profiles_changed = import_profiles(source, target, cloned_element)?;
+        let mut element_info = source_element_info.clone();
+        if renamespaced {
+            if let Some(xsi_type) = xsi_type(&target.xot, cloned_element) {
+                element_info.xsi_type = xsi_type.into();
+            }
+        }
+        if let Some(provenance) = provenance {
+            set_property(&mut target.xot, cloned_element, PROVENANCE_KEY, provenance)?;
+            element_info.properties = extract_properties(&target.xot, cloned_element);
+        }
+        if !remapped.is_empty() || profiles_changed || provenance.is_some() || renamespaced {
+            remap_references(
+                &mut target.xot,
+                cloned_element,
+                CONCEPT_ID_ATTRIBUTES,
+                remapped,
+            );
+            element_info.id = remapped
+                .get(element_id)
+                .unwrap_or(element_id)
+                .as_str()
+                .into();
+            element_info.xml_string = target
+                .xot
+                .serialize_xml_string(Default::default(), cloned_element)?;
+        }
+        target
+            .element_map
+            .insert(element_info.id.clone(), element_info);
+    }
+    Ok(())
+}
+
+/// The folder path a copied concept is placed in.
+fn concept_folder_path(folder_path: &[FolderInfo], flatten: bool) -> &[FolderInfo] {
+    if flatten {
+        &folder_path[..folder_path.len().min(1)]
+    } else {
+        folder_path
+    }
+}
+
+/// Moves a concept the target already has into the folder it has in the
+/// source, creating the folder as needed. Folders are compared by name.
+fn align_folder(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    element_id: &str,
+    flatten: bool,
+) -> Result<(), ImporterError> {
+    let (Some(source_info), Some(target_info)) = (
+        source.element_map.get(element_id),
+        target.element_map.get(element_id),
+    ) else {
+        return Ok(());
+    };
+    let folder_path = concept_folder_path(&source_info.folder_path, flatten);
+    let same_folder = folder_path.len() == target_info.folder_path.len()
+        && folder_path
+            .iter()
+            .zip(target_info.folder_path.iter())
+            .all(|(a, b)| a.name == b.name);
+    if same_folder {
+        return Ok(());
+    }
+    println!(
+        "Moving {} [{}] from {} to {}",
+        target_info.name,
+        display_type(&target_info.xsi_type),
+        format_folder_path(&target_info.folder_path),
+        format_folder_path(folder_path)
+    );
+    let node = find_node_by_id(target, element_id).ok_or_else(|| {
+        ImporterError::Validation(format!("Concept {} not found in target", element_id))
+    })?;
+    let folder = recursive_find_or_create_folder_path(target, folder_path)?;
+    target.xot.detach(node)?;
+    target.xot.append(folder, node)?;
+    if let Some(target_info) = target.element_map.get_mut(element_id) {
+        target_info.folder_path = folder_path.into();
+    }
+    Ok(())
+}
+
+/// Sets the property `key` of a concept or view, replacing its value if the
+/// property is already there.
+pub fn set_property(
+    xot: &mut Xot,
+    node: Node,
+    key: &str,
+    value: &str,
+) -> Result<(), ImporterError> {
+    let property_name = xot.add_name("property");
+    let key_name = xot.add_name("key");
+    let value_name = xot.add_name("value");
+    let existing = xot.children(node).find(|&child| {
+        xot.is_element(child)
+            && xot.get_element_name(child) == property_name
+            && xot.get_attribute(child, key_name) == Some(key)
+    });
+    let property = match existing {
+        Some(property) => property,
+        None => {
+            let property = xot.new_element(property_name);
+            xot.set_attribute(property, key_name, key);
+            xot.append(node, property)?;
+            property
+        }
+    };
+    xot.set_attribute(property, value_name, value);
+    Ok(())
+}
+
+/// Creates a placeholder for a concept found in neither model so the view
+/// still renders. Relations are stubbed as associations between `endpoints`.
+pub fn insert_stub_element(
+    target: &mut ArchiModel,
+    element_id: &str,
+    endpoints: Option<&(String, String)>,
+) -> Result<(), ImporterError> {
+    let folder_type = if endpoints.is_some() {
+        "relations"
+    } else {
+        "other"
+    };
+    let stub_folder = find_or_create_folder(target, folder_type)?;
+
+    let xot = &mut target.xot;
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    let element_name = xot.add_name("element");
+    let name_name = xot.add_name("name");
+    let id_name = xot.add_name("id");
+    let source_name = xot.add_name("source");
+    let target_name = xot.add_name("target");
+    let property_name = xot.add_name("property");
+    let key_name = xot.add_name("key");
+    let value_name = xot.add_name("value");
+
+    let stub = xot.new_element(element_name);
+    let name = format!("{} [missing]", element_id);
+    let stub_type = match endpoints {
+        Some(_) => "archimate:AssociationRelationship",
+        None => "archimate:Grouping",
+    };
+    xot.set_attribute(stub, type_name, stub_type);
+    xot.set_attribute(stub, name_name, name.as_str());
+    xot.set_attribute(stub, id_name, element_id);
+    if let Some((source_id, target_id)) = endpoints {
+        xot.set_attribute(stub, source_name, source_id.as_str());
+        xot.set_attribute(stub, target_name, target_id.as_str());
+    }
+    let property = xot.new_element(property_name);
+    xot.set_attribute(property, key_name, "stub");
+    xot.set_attribute(property, value_name, "true");
+    xot.append(stub, property)?;
+    xot.append(stub_folder, stub)?;
+
+    println!("Created stub for missing concept {}", element_id);
+    let xml_string = xot.serialize_xml_string(Default::default(), stub)?;
+    let folder_path = vec![FolderInfo {
+        id: xot.get_attribute(stub_folder, id_name).unwrap_or("").into(),
+        name: xot
+            .get_attribute(stub_folder, name_name)
+            .unwrap_or("")
+            .into(),
+        folder_type: Some(folder_type.to_string()),
+    }];
+    let id: Arc<str> = element_id.into();
+    target.element_map.insert(
+        id.clone(),
+        ElementInfo {
+            id,
+            name: name.into(),
+            xsi_type: stub_type.into(),
+            xml_string,
+            folder_path: folder_path.into(),
+            properties: vec![("stub".to_string(), "true".to_string())],
+        },
+    );
+    Ok(())
+}
+
+/// Copies a source view into the target. With a `replaced_id`, the target
+/// view with that ID is overwritten in place and keeps its ID and folder;
+/// otherwise it goes to `folder_path`, or to the folder it has in the source.
+/// The copy is changed as described by `edits`.
+pub fn insert_new_view(
+    source: &mut ArchiModel,
+    target: &mut ArchiModel,
+    element_id: &String,
+    replaced_id: Option<&str>,
+    folder_path: Option<&[FolderInfo]>,
+    edits: &ViewEdits,
+) -> Result<(), ImporterError> {
+    if let Some(source_element_info) = source.view_map.get(element_id.as_str()) {
+        if let Some(replaced_id) = replaced_id {
+            let existing_view = find_node_by_id(target, replaced_id).ok_or_else(|| {
+                ImporterError::Validation(format!("View {} not found in target", replaced_id))
+            })?;
+            let cloned_node = target.xot.parse(source_element_info.xml_string.as_str())?;
+            let cloned_element = target.xot.document_element(cloned_node)?;
+            let id_name = target.xot.add_name("id");
+            target
+                .xot
+                .set_attribute(cloned_element, id_name, replaced_id.to_string());
+            edits.apply(&mut target.xot, cloned_element)?;
+            target.xot.replace(existing_view, cloned_element)?;
+            normalize_namespaces(&mut target.xot, cloned_element);
+
+            let xml_string = target
+                .xot
+                .serialize_xml_string(Default::default(), cloned_element)?;
+            if let Some(target_view_info) = target.view_map.get_mut(replaced_id) {
+                target_view_info.name = edits
+                    .new_name
+                    .clone()
+                    .unwrap_or_else(|| source_element_info.name.to_string())
+                    .into();
+                target_view_info.xml_string = xml_string;
+                target_view_info.properties = extract_properties(&target.xot, cloned_element);
+            }
+            return Ok(());
+        }
+        let folder_path = folder_path.unwrap_or(&source_element_info.folder_path);
+        let target_element_folder = recursive_find_or_create_folder_path(target, folder_path)?;
+
+        println!("Creating view {}", source_element_info.xml_string);
+        let cloned_node = target.xot.parse(source_element_info.xml_string.as_str())?;
+        let cloned_element = target.xot.document_element(cloned_node)?;
+        target.xot.append(target_element_folder, cloned_element)?;
+        let renamespaced = normalize_namespaces(&mut target.xot, cloned_element);
+
+        let mut view_info = source_element_info.clone();
+        if !edits.is_empty() {
+            edits.apply(&mut target.xot, cloned_element)?;
+            view_info.id = edits
+                .remapped
+                .get(element_id)
+                .unwrap_or(element_id)
+                .as_str()
+                .into();
+            if let Some(new_name) = &edits.new_name {
+                view_info.name = new_name.as_str().into();
+            }
+            view_info.properties = extract_properties(&target.xot, cloned_element);
+        }
+        if renamespaced || !edits.is_empty() {
+            if let Some(xsi_type) = xsi_type(&target.xot, cloned_element) {
+                view_info.xsi_type = xsi_type.into();
+            }
+            view_info.xml_string = target
+                .xot
+                .serialize_xml_string(Default::default(), cloned_element)?;
+        }
+        target.element_map.insert(view_info.id.clone(), view_info);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{find_changed_views, load_model, serialize_model, MatchBy};
+    use std::error::Error;
+
+    /// Portal and Portal API composed by relation-1, both shown on view-1.
+    const PORTAL_VIEW: &str = r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+            </folder>
+            <folder type='relations' name='Relations' id='folder-2'>
+                <element xsi:type='archimate:CompositionRelationship' id='relation-1' source='element-1' target='element-2'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'>
+                        <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-2' archimateRelationship='relation-1'/>
+                    </child>
+                    <child xsi:type='archimate:DiagramObject' id='object-2' targetConnections='connection-1' archimateElement='element-2'/>
+                </element>
+            </folder>"#;
+
+    /// Loads a model holding the given top-level folders.
+    fn model(folders: &str) -> Result<ArchiModel, Box<dyn Error>> {
+        Ok(load_model(&format!(
+            "<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+            {}
+            </archimate:model>",
+            folders
+        ))?)
+    }
+
+    /// The source's view-1, as found missing from the target.
+    fn missing_view(name: &str) -> MissingElementInfo {
+        MissingElementInfo {
+            id: "view-1".into(),
+            name: name.into(),
+            folder_path: Vec::new().into(),
+            target_id: None,
+        }
+    }
+
+    #[test]
+    fn test_copy_view_missing_in_source() -> Result<(), Box<dyn Error>> {
+        let views = "<folder type='diagrams' name='Views' id='folder-1'/>";
+        let mut source = model(views)?;
+        let mut target = model(views)?;
+        let view = missing_view("Gone");
+
+        let result = copy_view(&mut source, &mut target, &view, &CopyOptions::default());
+        let error = result.unwrap_err();
+        assert!(matches!(error, ImporterError::Selection(_)));
+        assert!(error.to_string().contains("not found in source"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_view_dependencies() -> Result<(), Box<dyn Error>> {
+        let source = model(
+            r#"<folder type='diagrams' name='Views' id='folder-1'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'>
+                        <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-2' archimateRelationship='relation-1'/>
+                    </child>
+                    <child xsi:type='archimate:DiagramObject' id='object-2' archimateElement='element-2'/>
+                </element>
+            </folder>"#,
+        )?;
+        let target = model(
+            r#"<folder type='application' name='Application' id='folder-2'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Existing'/>
+            </folder>"#,
+        )?;
+
+        let dependencies = find_view_dependencies(&source, &target, "view-1")?;
+        assert_eq!(
+            dependencies,
+            ViewDependencies {
+                elements: 2,
+                existing_elements: 1,
+                relations: 1,
+                existing_relations: 0,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_creates_stubs() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='diagrams' name='Views' id='folder-1'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Broken View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'>
+                        <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-2' archimateRelationship='relation-1'/>
+                    </child>
+                    <child xsi:type='archimate:DiagramObject' id='object-2' archimateElement='element-2'/>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(r#"<folder type='diagrams' name='Views' id='folder-1'/>"#)?;
+        let view = missing_view("Broken View");
+        let options = CopyOptions {
+            create_stubs: true,
+            ..Default::default()
+        };
+
+        copy_view(&mut source, &mut target, &view, &options)?;
+
+        let relation = target.element_map.get("relation-1").unwrap();
+        assert!(relation
+            .xml_string
+            .contains("archimate:AssociationRelationship"));
+        assert!(relation.xml_string.contains(r#"source="element-1""#));
+        assert!(relation.xml_string.contains(r#"target="element-2""#));
+        let element = target.element_map.get("element-1").unwrap();
+        assert_eq!(&*element.name, "element-1 [missing]");
+        assert!(element.xml_string.contains(r#"key="stub" value="true""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_updates_existing_view() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-2'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Renamed'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(
+            r#"<folder type='diagrams' name='Views' id='folder-3'>
+                <folder name='Team' id='folder-4'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Original'/>
+                </folder>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Untouched'/>
+            </folder>"#,
+        )?;
+
+        let changed = find_changed_views(&source, &target, MatchBy::Id);
+        assert_eq!(changed.len(), 1);
+        let counts = copy_view(
+            &mut source,
+            &mut target,
+            &changed[0],
+            &CopyOptions::default(),
+        )?;
+        assert_eq!(counts, (1, 1, 0));
+
+        let xml = serialize_model(&target)?;
+        assert_eq!(xml.matches("id=\"view-1\"").count(), 1);
+        let view_node = find_node_by_id(&target, "view-1").unwrap();
+        let folder_node = target.xot.parent(view_node).unwrap();
+        assert_eq!(
+            target
+                .xot
+                .get_attribute(folder_node, target.xot.name("id").unwrap()),
+            Some("folder-4")
+        );
+        assert!(xml.contains("name=\"Renamed\""));
+        assert!(xml.contains("name=\"Untouched\""));
+        assert!(find_changed_views(&source, &target, MatchBy::Id).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_view_leaves_target_unchanged() -> Result<(), Box<dyn Error>> {
+        let mut source = model(PORTAL_VIEW)?;
+        let mut target = model(
+            r#"<folder type='diagrams' name='Views' id='folder-3'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'/>
+            </folder>"#,
+        )?;
+        let view = MissingElementInfo {
+            target_id: Some("view-1".into()),
+            ..missing_view("View")
+        };
+        let plan = plan_view(&source, &mut target, &view, &CopyOptions::default())?;
+        // Gone by the time the plan is applied, after the concepts are added
+        let view_node = find_node_by_id(&target, "view-1").unwrap();
+        target.xot.remove(view_node)?;
+        let before = serialize_model(&target)?;
+
+        let result = apply_view(&mut source, &mut target, &plan, &CopyOptions::default());
+        let error = result.unwrap_err();
+        assert!(matches!(error, ImporterError::Validation(_)));
+        assert!(error.to_string().contains("not found in target"));
+        assert_eq!(serialize_model(&target)?, before);
+        assert!(!target.element_map.contains_key("element-1"));
+        assert!(!target.element_map.contains_key("relation-1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_reuses_elements_by_name() -> Result<(), Box<dyn Error>> {
+        let mut source = model(PORTAL_VIEW)?;
+        let mut target = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-9' name='Portal'/>
+                <element xsi:type='archimate:ApplicationCollaboration' id='element-8' name='Portal API'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'/>"#,
+        )?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            reuse_by_name: true,
+            ..Default::default()
+        };
+
+        let counts = copy_view(&mut source, &mut target, &view, &options)?;
+        assert_eq!(counts, (1, 1, 1));
+
+        let xml = serialize_model(&target)?;
+        assert!(!xml.contains(r#"id="element-1""#));
+        assert!(xml.contains(r#"id="element-2""#));
+        assert!(xml.contains(r#"archimateElement="element-9""#));
+        assert!(xml.contains(r#"source="element-9" target="element-2""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_with_fresh_ids() -> Result<(), Box<dyn Error>> {
+        let mut source = model(PORTAL_VIEW)?;
+        let mut target = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'/>"#,
+        )?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            fresh_ids: true,
+            ..Default::default()
+        };
+
+        copy_view(&mut source, &mut target, &view, &options)?;
+        copy_view(&mut source, &mut target, &view, &options)?;
+
+        let xml = serialize_model(&target)?;
+        for id in [
+            "view-1",
+            "object-1",
+            "connection-1",
+            "element-1",
+            "relation-1",
+        ] {
+            assert!(!xml.contains(&format!("\"{}\"", id)), "{} was kept", id);
+        }
+        assert_eq!(xml.matches("name=\"Portal\"").count(), 2);
+        assert_eq!(xml.matches("name=\"Portal API\"").count(), 1);
+        assert_eq!(xml.matches("archimateElement=\"element-2\"").count(), 2);
+
+        // References inside each copy still line up
+        let relations: Vec<&ElementInfo> = target
+            .element_map
+            .values()
+            .filter(|info| info.xsi_type.ends_with("CompositionRelationship"))
+            .collect();
+        assert_eq!(relations.len(), 2);
+        for relation in relations {
+            assert!(xml.contains(&format!("archimateRelationship=\"{}\"", relation.id)));
+            assert!(relation.xml_string.contains("target=\"element-2\""));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unique_view_name() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' name='Upstream'/>"#,
+        )?;
+        let target = model(
+            r#"<folder type='diagrams' name='Views' id='folder-1'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'/>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='View (2)'/>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='View (Upstream)'/>
+            </folder>"#,
+        )?;
+
+        let unique = |name, policy| unique_view_name(&source, &target, name, policy);
+        assert_eq!(unique("View", NameCollision::Keep), None);
+        assert_eq!(unique("Other", NameCollision::Number), None);
+        assert_eq!(
+            unique("View", NameCollision::Number).as_deref(),
+            Some("View (3)")
+        );
+        assert_eq!(
+            unique("View", NameCollision::ModelName).as_deref(),
+            Some("View (Upstream) (2)")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_flattened() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <folder name='Domain' id='folder-2'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                </folder>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'>
+                <folder name='Team' id='folder-4'>
+                    <folder name='Drafts' id='folder-5'>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                            <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                        </element>
+                    </folder>
+                </folder>
+            </folder>"#,
+        )?;
+        let mut target = model(
+            r#"<folder type='application' name='Application' id='folder-6'/>
+            <folder type='diagrams' name='Views' id='folder-7'/>"#,
+        )?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            flatten: true,
+            ..Default::default()
+        };
+
+        copy_view(&mut source, &mut target, &view, &options)?;
+
+        let parent_id = |id: &str| {
+            let node = find_node_by_id(&target, id).unwrap();
+            let parent = target.xot.parent(node).unwrap();
+            target
+                .xot
+                .get_attribute(parent, target.xot.name("id").unwrap())
+                .map(|id| id.to_string())
+        };
+        assert_eq!(parent_id("view-1").as_deref(), Some("folder-7"));
+        assert_eq!(parent_id("element-1").as_deref(), Some("folder-6"));
+        assert!(!serialize_model(&target)?.contains("Team"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_includes_model_relations() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+                <element xsi:type='archimate:ApplicationService' id='element-3' name='Search'/>
+            </folder>
+            <folder type='relations' name='Relations' id='folder-2'>
+                <element xsi:type='archimate:CompositionRelationship' id='relation-1' source='element-1' target='element-2'/>
+                <element xsi:type='archimate:AssociationRelationship' id='relation-2' source='relation-1' target='element-1'/>
+                <element xsi:type='archimate:RealizationRelationship' id='relation-3' source='element-1' target='element-3'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'/>"#,
+        )?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            include_model_relations: true,
+            ..Default::default()
+        };
+
+        let counts = copy_view(&mut source, &mut target, &view, &options)?;
+        assert_eq!(counts, (1, 1, 2));
+        assert!(target.element_map.contains_key("relation-1"));
+        assert!(target.element_map.contains_key("relation-2"));
+        assert!(!target.element_map.contains_key("relation-3"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_missing_endpoints() -> Result<(), Box<dyn Error>> {
+        let source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+            </folder>
+            <folder type='other' name='Other' id='folder-2'>
+                <element xsi:type='archimate:Junction' id='junction-1'/>
+            </folder>
+            <folder type='relations' name='Relations' id='folder-3'>
+                <element xsi:type='archimate:CompositionRelationship' id='relation-1' source='element-2' target='junction-1'/>
+                <element xsi:type='archimate:AssociationRelationship' id='relation-2' source='element-1' target='relation-1'/>
+                <element xsi:type='archimate:AssociationRelationship' id='relation-3' source='element-1' target='ghost-1'/>
+            </folder>"#,
+        )?;
+        let target = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+            </folder>"#,
+        )?;
+
+        let (elements, relations) = find_missing_endpoints(
+            &source,
+            &target,
+            &["element-1".to_string()],
+            &["relation-2".to_string(), "relation-3".to_string()],
+        )?;
+        assert_eq!(elements, vec!["junction-1".to_string()]);
+        assert_eq!(relations, vec!["relation-1".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_without_relations() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                <element xsi:type='archimate:ApplicationInterface' id='element-2' name='Portal API'/>
+            </folder>
+            <folder type='relations' name='Relations' id='folder-2'>
+                <element xsi:type='archimate:CompositionRelationship' id='relation-1' source='element-1' target='element-2'/>
+                <element xsi:type='archimate:ServingRelationship' id='relation-2' source='element-2' target='element-1'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' targetConnections='connection-2' archimateElement='element-1'>
+                        <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-2' archimateRelationship='relation-1'/>
+                    </child>
+                    <child xsi:type='archimate:DiagramObject' id='object-2' targetConnections='connection-1' archimateElement='element-2'>
+                        <sourceConnection xsi:type='archimate:Connection' id='connection-2' source='object-2' target='object-1' archimateRelationship='relation-2'/>
+                    </child>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(
+            r#"<folder type='relations' name='Relations' id='folder-2'>
+                <element xsi:type='archimate:ServingRelationship' id='relation-2' source='element-2' target='element-1'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-3'/>"#,
+        )?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            no_relations: true,
+            ..Default::default()
+        };
+
+        let counts = copy_view(&mut source, &mut target, &view, &options)?;
+        assert_eq!(counts, (1, 2, 0));
+
+        let xml = serialize_model(&target)?;
+        assert!(!xml.contains("relation-1"));
+        assert!(!xml.contains("connection-1"));
+        assert!(xml.contains(r#"id="connection-2""#));
+        assert!(xml.contains(r#"targetConnections="connection-2""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_tags_provenance() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'>
+                    <property key='imported-from' value='older.archimate @ 2020-01-01'/>
+                </element>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-2'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(r#"<folder type='diagrams' name='Views' id='folder-2'/>"#)?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            provenance: Some("source.archimate @ 2024-06-01".to_string()),
+            ..Default::default()
+        };
+
+        copy_view(&mut source, &mut target, &view, &options)?;
+
+        let provenance = (
+            PROVENANCE_KEY.to_string(),
+            "source.archimate @ 2024-06-01".to_string(),
+        );
+        let element = target.element_map.get("element-1").unwrap();
+        assert_eq!(element.properties, vec![provenance.clone()]);
+        let view = target.element_map.get("view-1").unwrap();
+        assert_eq!(view.properties, vec![provenance]);
+        let xml = serialize_model(&target)?;
+        assert_eq!(xml.matches("imported-from").count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_aligns_folders() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <folder name='Portals' id='folder-3'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                </folder>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-2'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-2'/>"#,
+        )?;
+        let view = missing_view("View");
+        let options = CopyOptions {
+            align_folders: true,
+            ..Default::default()
+        };
+
+        copy_view(&mut source, &mut target, &view, &options)?;
+
+        let element = target.element_map.get("element-1").unwrap();
+        assert_eq!(
+            format_folder_path(&element.folder_path),
+            "Application[application] > Portals"
+        );
+        let xml = serialize_model(&target)?;
+        assert!(xml.contains(
+            r#"<folder name="Portals" id="folder-3"><element xsi:type="archimate:ApplicationComponent" id="element-1" name="Portal"/></folder>"#
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_view_completes_junctions() -> Result<(), Box<dyn Error>> {
+        let mut source = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Billing'/>
+                <element xsi:type='archimate:ApplicationComponent' id='element-3' name='Shipping'/>
+            </folder>
+            <folder type='other' name='Other' id='folder-2'>
+                <element xsi:type='archimate:Junction' id='junction-1'/>
+                <element xsi:type='archimate:Junction' id='junction-2' type='or'/>
+            </folder>
+            <folder type='relations' name='Relations' id='folder-3'>
+                <element xsi:type='archimate:FlowRelationship' id='relation-1' source='element-1' target='junction-1'/>
+                <element xsi:type='archimate:FlowRelationship' id='relation-2' source='junction-1' target='element-2'/>
+                <element xsi:type='archimate:FlowRelationship' id='relation-3' source='junction-1' target='junction-2'/>
+                <element xsi:type='archimate:FlowRelationship' id='relation-4' source='junction-2' target='element-3'/>
+                <element xsi:type='archimate:FlowRelationship' id='relation-5' source='element-2' target='element-3'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-4'>
+                <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Flows'>
+                    <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'>
+                        <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-2' archimateRelationship='relation-1'/>
+                    </child>
+                    <child xsi:type='archimate:DiagramObject' id='object-2' targetConnections='connection-1' archimateElement='junction-1'/>
+                </element>
+            </folder>"#,
+        )?;
+        let mut target = model(
+            r#"<folder type='application' name='Application' id='folder-1'>
+                <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+            </folder>
+            <folder type='diagrams' name='Views' id='folder-4'/>"#,
+        )?;
+        let view = missing_view("Flows");
+
+        let (_, elements, relations) =
+            copy_view(&mut source, &mut target, &view, &CopyOptions::default())?;
+
+        // Both junctions with all their legs, but not relations between
+        // elements that only came along as leg endpoints
+        assert_eq!((elements, relations), (4, 4));
+        for id in [
+            "junction-2",
+            "element-2",
+            "element-3",
+            "relation-3",
+            "relation-4",
+        ] {
+            assert!(target.element_map.contains_key(id), "{} not copied", id);
+        }
+        assert!(!target.element_map.contains_key("relation-5"));
+        let junction = target.element_map.get("junction-1").unwrap();
+        assert_eq!(format_folder_path(&junction.folder_path), "Other[other]");
+        Ok(())
+    }
+}
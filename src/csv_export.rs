@@ -0,0 +1,219 @@
+use crate::concept_type::ConceptType;
+use crate::model::{is_view_type, ArchiModel};
+use crate::namespaces::{attribute, child_text, children, xsi_type};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use xot::{Node, Xot};
+
+pub const ELEMENTS_FILE: &str = "elements.csv";
+pub const RELATIONS_FILE: &str = "relations.csv";
+pub const PROPERTIES_FILE: &str = "properties.csv";
+
+/// The rows of the three CSV files, headers included.
+struct CsvFiles {
+    elements: Vec<Vec<String>>,
+    relations: Vec<Vec<String>>,
+    properties: Vec<Vec<String>>,
+}
+
+/// Writes the concepts of a model to `directory` as the elements, relations
+/// and properties files Archi's CSV import reads. With `concept_ids`, only
+/// those concepts are written; the model's own row always is.
+pub fn write_csv(
+    model: &ArchiModel,
+    directory: &Path,
+    concept_ids: Option<&HashSet<String>>,
+) -> Result<(), Box<dyn Error>> {
+    let xot = &model.xot;
+    let root = xot.document_element(model.doc)?;
+    let mut files = CsvFiles {
+        elements: vec![row(&[
+            "ID",
+            "Type",
+            "Name",
+            "Documentation",
+            "Specialization",
+        ])],
+        relations: vec![row(&[
+            "ID",
+            "Type",
+            "Name",
+            "Documentation",
+            "Source",
+            "Target",
+            "Specialization",
+        ])],
+        properties: vec![row(&["ID", "Key", "Value"])],
+    };
+
+    let model_id = attribute(xot, root, "id").unwrap_or_default();
+    files.elements.push(row(&[
+        model_id,
+        "ArchimateModel",
+        attribute(xot, root, "name").unwrap_or_default(),
+        child_text(xot, root, "purpose").unwrap_or_default(),
+        "",
+    ]));
+    append_properties(xot, root, model_id, &mut files.properties);
+
+    let mut profile_names = HashMap::new();
+    for profile in children(xot, root, "profile") {
+        if let (Some(id), Some(name)) = (
+            attribute(xot, profile, "id"),
+            attribute(xot, profile, "name"),
+        ) {
+            profile_names.insert(id, name);
+        }
+    }
+
+    for node in xot.descendants(root) {
+        if !xot.is_element(node) || xot.local_name_str(xot.get_element_name(node)) != "element" {
+            continue;
+        }
+        let (Some(id), Some(xsi_type)) = (attribute(xot, node, "id"), xsi_type(xot, node)) else {
+            continue;
+        };
+        let Some(concept_type) = xsi_type.strip_prefix("archimate:") else {
+            continue;
+        };
+        if is_view_type(concept_type) || concept_ids.is_some_and(|ids| !ids.contains(id)) {
+            continue;
+        }
+        let concept_type = match concept_type {
+            "Junction" if attribute(xot, node, "type") == Some("or") => "OrJunction",
+            "Junction" => "AndJunction",
+            other => other,
+        };
+        let name = attribute(xot, node, "name").unwrap_or_default();
+        let documentation = child_text(xot, node, "documentation").unwrap_or_default();
+        let specialization = attribute(xot, node, "profiles")
+            .and_then(|profiles| profiles.split_whitespace().next())
+            .and_then(|profile| profile_names.get(profile).copied())
+            .unwrap_or_default();
+        if ConceptType::from_xsi_type(concept_type).is_some_and(ConceptType::is_relationship) {
+            files.relations.push(row(&[
+                id,
+                concept_type,
+                name,
+                documentation,
+                attribute(xot, node, "source").unwrap_or_default(),
+                attribute(xot, node, "target").unwrap_or_default(),
+                specialization,
+            ]));
+        } else {
+            files.elements.push(row(&[
+                id,
+                concept_type,
+                name,
+                documentation,
+                specialization,
+            ]));
+        }
+        append_properties(xot, node, id, &mut files.properties);
+    }
+
+    fs::create_dir_all(directory)?;
+    for (file_name, rows) in [
+        (ELEMENTS_FILE, &files.elements),
+        (RELATIONS_FILE, &files.relations),
+        (PROPERTIES_FILE, &files.properties),
+    ] {
+        let path = directory.join(file_name);
+        fs::write(&path, format_csv(rows))
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+fn row(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn append_properties(xot: &Xot, node: Node, id: &str, properties: &mut Vec<Vec<String>>) {
+    for property in children(xot, node, "property") {
+        properties.push(row(&[
+            id,
+            attribute(xot, property, "key").unwrap_or_default(),
+            attribute(xot, property, "value").unwrap_or_default(),
+        ]));
+    }
+}
+
+/// Formats rows the way Archi writes them: every value quoted, quotes
+/// doubled, and lines ending in CRLF.
+fn format_csv(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| {
+            let values: Vec<String> = row
+                .iter()
+                .map(|value| format!("\"{}\"", value.replace('"', "\"\"")))
+                .collect();
+            values.join(",") + "\r\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_csv() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal &quot;new&quot;" id="element-1" profiles="profile-1">
+      <documentation>Customer facing</documentation>
+      <property key="status" value="live"/>
+    </element>
+    <element xsi:type="archimate:ApplicationService" name="Orders" id="element-2"/>
+  </folder>
+  <folder name="Other" id="folder-2" type="other">
+    <element xsi:type="archimate:Junction" id="junction-1" type="or"/>
+  </folder>
+  <folder name="Relations" id="folder-3" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-2"/>
+  </folder>
+  <folder name="Views" id="folder-4" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1"/>
+  </folder>
+  <purpose>Reviews</purpose>
+  <property key="owner" value="EA team"/>
+  <profile name="Portal" id="profile-1" conceptType="ApplicationComponent"/>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+        let dir = tempdir()?;
+
+        write_csv(&model, dir.path(), None)?;
+        assert_eq!(
+            fs::read_to_string(dir.path().join(ELEMENTS_FILE))?,
+            "\"ID\",\"Type\",\"Name\",\"Documentation\",\"Specialization\"\r\n\
+             \"model-1\",\"ArchimateModel\",\"Enterprise\",\"Reviews\",\"\"\r\n\
+             \"element-1\",\"ApplicationComponent\",\"Portal \"\"new\"\"\",\"Customer facing\",\"Portal\"\r\n\
+             \"element-2\",\"ApplicationService\",\"Orders\",\"\",\"\"\r\n\
+             \"junction-1\",\"OrJunction\",\"\",\"\",\"\"\r\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join(RELATIONS_FILE))?,
+            "\"ID\",\"Type\",\"Name\",\"Documentation\",\"Source\",\"Target\",\"Specialization\"\r\n\
+             \"relation-1\",\"ServingRelationship\",\"\",\"\",\"element-1\",\"element-2\",\"\"\r\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join(PROPERTIES_FILE))?,
+            "\"ID\",\"Key\",\"Value\"\r\n\
+             \"model-1\",\"owner\",\"EA team\"\r\n\
+             \"element-1\",\"status\",\"live\"\r\n"
+        );
+
+        let subset = HashSet::from(["element-2".to_string()]);
+        write_csv(&model, dir.path(), Some(&subset))?;
+        let elements = fs::read_to_string(dir.path().join(ELEMENTS_FILE))?;
+        assert!(elements.contains("\"element-2\""));
+        assert!(!elements.contains("\"element-1\""));
+        Ok(())
+    }
+}
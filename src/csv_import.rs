@@ -0,0 +1,300 @@
+use crate::conflict::{documentation_text, set_documentation};
+use crate::copy::set_property;
+use crate::model::{
+    element_folder_type, find_node_by_id, find_or_create_folder, folder_path_below,
+    recursive_find_or_create_folder_path, ArchiModel,
+};
+use crate::namespaces::XSI_NAMESPACE;
+use crate::profiles::find_or_create_profile;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Columns of an elements CSV file with a meaning of their own; any other
+/// column is a property, keyed by its header.
+const KNOWN_COLUMNS: &[&str] = &[
+    "id",
+    "type",
+    "name",
+    "documentation",
+    "folder",
+    "specialization",
+];
+
+/// How many elements a CSV file created and updated.
+#[derive(Debug, Default, PartialEq)]
+pub struct CsvChanges {
+    pub created: usize,
+    pub updated: usize,
+}
+
+/// Creates or updates the elements listed in a CSV file. The header row
+/// names the columns: `Type` and `Name` are required, `ID`,
+/// `Documentation`, `Folder` (a path such as `Application > Reference
+/// Data`) and `Specialization` are optional, and every other column is a
+/// property. A row updates the element with its ID or, without one, the
+/// element with its name and type; otherwise it creates an element, which
+/// needs a name. Archi's own `elements.csv` can be read as is.
+pub fn import_csv_elements(
+    model: &mut ArchiModel,
+    path: &Path,
+) -> Result<CsvChanges, Box<dyn Error>> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut rows = parse_csv(content.trim_start_matches('\u{feff}')).into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| format!("{} is empty", path.display()))?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|title| title.trim().eq_ignore_ascii_case(name))
+    };
+    let (Some(type_column), Some(name_column)) = (column("type"), column("name")) else {
+        return Err(format!("{} needs Type and Name columns", path.display()).into());
+    };
+    let id_column = column("id");
+    let documentation_column = column("documentation");
+    let folder_column = column("folder");
+    let specialization_column = column("specialization");
+    let property_columns: Vec<(usize, &str)> = header
+        .iter()
+        .enumerate()
+        .filter(|(_, title)| !KNOWN_COLUMNS.contains(&title.trim().to_lowercase().as_str()))
+        .map(|(index, title)| (index, title.trim()))
+        .collect();
+
+    let xsi = model.xot.add_namespace(XSI_NAMESPACE);
+    let type_name = model.xot.add_name_ns("type", xsi);
+    let name_name = model.xot.add_name("name");
+    let id_name = model.xot.add_name("id");
+    let element_name = model.xot.add_name("element");
+    let profiles_name = model.xot.add_name("profiles");
+    let mut changes = CsvChanges::default();
+    // Line numbers count the header as line 1
+    for (line, row) in rows.enumerate().map(|(index, row)| (index + 2, row)) {
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|index| row.get(index))
+                .map(|value| value.trim())
+                .unwrap_or_default()
+        };
+        let element_type = cell(Some(type_column));
+        let element_type = element_type
+            .strip_prefix("archimate:")
+            .unwrap_or(element_type);
+        let name = cell(Some(name_column));
+        if row.iter().all(|value| value.trim().is_empty()) || element_type == "ArchimateModel" {
+            continue;
+        }
+        let Some(folder_type) = element_folder_type(element_type) else {
+            println!(
+                "Warning: {} line {}: {} is not an element type, row skipped",
+                path.display(),
+                line,
+                element_type
+            );
+            continue;
+        };
+        let xsi_type = format!("archimate:{}", element_type);
+        let id = cell(id_column);
+
+        // Searched in the tree, so rows repeating an element created by an
+        // earlier row update it
+        let existing = if id.is_empty() {
+            model.xot.descendants(model.root).find(|&node| {
+                model.xot.is_element(node)
+                    && model.xot.get_element_name(node) == element_name
+                    && model.xot.get_attribute(node, type_name) == Some(xsi_type.as_str())
+                    && model.xot.get_attribute(node, name_name) == Some(name)
+            })
+        } else {
+            find_node_by_id(model, id)
+        };
+        let node = match existing {
+            Some(node) => {
+                if model.xot.get_attribute(node, type_name) != Some(xsi_type.as_str()) {
+                    println!(
+                        "Warning: {} line {}: {} is not a {}, row skipped",
+                        path.display(),
+                        line,
+                        id,
+                        element_type
+                    );
+                    continue;
+                }
+                changes.updated += 1;
+                node
+            }
+            None if name.is_empty() => {
+                println!(
+                    "Warning: {} line {}: {} has no name, row skipped",
+                    path.display(),
+                    line,
+                    element_type
+                );
+                continue;
+            }
+            None => {
+                let node = model.xot.new_element(element_name);
+                model.xot.set_attribute(node, type_name, xsi_type.as_str());
+                model.xot.set_attribute(node, name_name, name);
+                let id = if id.is_empty() {
+                    model.ids.next_id()
+                } else {
+                    id.to_string()
+                };
+                model.xot.set_attribute(node, id_name, id);
+                changes.created += 1;
+                node
+            }
+        };
+        if !name.is_empty() {
+            model.xot.set_attribute(node, name_name, name);
+        }
+        let documentation = cell(documentation_column);
+        if documentation_column.is_some()
+            && documentation_text(&model.xot, node).unwrap_or_default() != documentation
+        {
+            set_documentation(&mut model.xot, node, documentation)?;
+        }
+        let specialization = cell(specialization_column);
+        if !specialization.is_empty() {
+            let profile_id = find_or_create_profile(model, specialization, element_type)?;
+            model.xot.set_attribute(node, profiles_name, profile_id);
+        }
+        for &(index, key) in &property_columns {
+            let value = cell(Some(index));
+            if !value.is_empty() {
+                set_property(&mut model.xot, node, key, value)?;
+            }
+        }
+
+        // New elements go to the folder of their type unless a folder is given
+        let folder = cell(folder_column);
+        let folder_node = if !folder.is_empty() {
+            let folder_path = folder_path_below(model, folder_type, folder)?;
+            Some(recursive_find_or_create_folder_path(model, &folder_path)?)
+        } else if model.xot.parent(node).is_none() {
+            Some(find_or_create_folder(model, folder_type)?)
+        } else {
+            None
+        };
+        if let Some(folder_node) = folder_node {
+            if model.xot.parent(node) != Some(folder_node) {
+                if model.xot.parent(node).is_some() {
+                    model.xot.detach(node)?;
+                }
+                model.xot.append(folder_node, node)?;
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// Splits CSV text into rows of values. Values may be quoted, with doubled
+/// quotes standing for quotes and line breaks kept as they are.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut value = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                value.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut value)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut value));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => value.push(c),
+        }
+    }
+    if !value.is_empty() || !row.is_empty() {
+        row.push(value);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{format_folder_path, load_model, serialize_model};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_csv() {
+        assert_eq!(
+            parse_csv("\"ID\",Name\r\n\"a\",\"Say \"\"hi\"\"\nagain\"\r\nb,\n"),
+            vec![
+                vec!["ID".to_string(), "Name".to_string()],
+                vec!["a".to_string(), "Say \"hi\"\nagain".to_string()],
+                vec!["b".to_string(), String::new()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_csv_elements() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1">
+      <property key="status" value="planned"/>
+    </element>
+  </folder>
+</archimate:model>"#;
+        let dir = tempdir()?;
+        let csv_path = dir.path().join("elements.csv");
+        fs::write(
+            &csv_path,
+            "\u{feff}Type,Name,Documentation,Folder,Specialization,status\r\n\
+             ApplicationComponent,Portal,Customer facing,,Cloud Service,live\r\n\
+             archimate:DataObject,Customer,Master data,Application > Reference Data,,\r\n\
+             ServingRelationship,Serves,,,,\r\n\
+             ApplicationComponent,,Nameless,,,\r\n",
+        )?;
+        let mut model = load_model(xml)?;
+
+        let changes = import_csv_elements(&mut model, &csv_path)?;
+        assert_eq!(
+            changes,
+            CsvChanges {
+                created: 1,
+                updated: 1
+            }
+        );
+        let xml = serialize_model(&model)?;
+        let model = load_model(&xml)?;
+        let portal = &model.element_map["element-1"];
+        assert_eq!(
+            portal.properties,
+            vec![("status".to_string(), "live".to_string())]
+        );
+        assert!(portal
+            .xml_string
+            .contains("<documentation>Customer facing</documentation>"));
+        let customer = model
+            .element_map
+            .values()
+            .find(|info| &*info.name == "Customer")
+            .unwrap();
+        assert_eq!(customer.xsi_type.as_ref(), "archimate:DataObject");
+        assert_eq!(
+            format_folder_path(&customer.folder_path),
+            "Application[application] > Reference Data"
+        );
+        assert!(!xml.contains("Serves"));
+        assert!(!xml.contains("Nameless"));
+        assert!(portal.xml_string.contains(r#"profiles="id-"#));
+        assert!(xml.contains(r#"<profile name="Cloud Service" id="id-"#));
+        Ok(())
+    }
+}
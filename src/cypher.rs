@@ -0,0 +1,214 @@
+use crate::concept_type::ConceptType;
+use crate::model::{element_layer, ArchiModel};
+use crate::namespaces::{attribute, children, xsi_type};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Write as _;
+use xot::{Node, Xot};
+
+/// Writes the concepts of a model as Cypher statements that load them into
+/// Neo4j: each element becomes an `Element` node also labelled with its
+/// type, and each relation a relationship typed after it (`SERVING` for a
+/// serving relation). Statements `MERGE` on the concept IDs, so running
+/// them again updates the graph instead of duplicating it. With
+/// `concept_ids`, only those concepts are written. Relations on relations
+/// have no relationship form and are left out.
+pub fn model_to_cypher(
+    model: &ArchiModel,
+    concept_ids: Option<&HashSet<String>>,
+) -> Result<String, Box<dyn Error>> {
+    let xot = &model.xot;
+    let root = xot.document_element(model.doc)?;
+    let mut elements = Vec::new();
+    let mut relations = Vec::new();
+    for node in xot.descendants(root) {
+        if !xot.is_element(node) || xot.local_name_str(xot.get_element_name(node)) != "element" {
+            continue;
+        }
+        let (Some(id), Some(concept_type)) = (
+            attribute(xot, node, "id"),
+            xsi_type(xot, node).and_then(|xsi_type| xsi_type.strip_prefix("archimate:")),
+        ) else {
+            continue;
+        };
+        if concept_ids.is_some_and(|ids| !ids.contains(id)) {
+            continue;
+        }
+        if ConceptType::from_xsi_type(concept_type).is_some_and(ConceptType::is_relationship) {
+            relations.push(node);
+        } else if element_layer(concept_type).is_some() {
+            elements.push(node);
+        }
+    }
+
+    let mut text = String::new();
+    writeln!(
+        text,
+        "CREATE CONSTRAINT archimate_element_id IF NOT EXISTS FOR (n:Element) REQUIRE n.id IS UNIQUE;"
+    )?;
+    let mut element_ids = HashSet::new();
+    for &element in &elements {
+        let id = attribute(xot, element, "id").unwrap_or_default();
+        let archi_type = xsi_type(xot, element)
+            .and_then(|xsi_type| xsi_type.strip_prefix("archimate:"))
+            .unwrap_or_default();
+        let element_type = match archi_type {
+            "Junction" if attribute(xot, element, "type") == Some("or") => "OrJunction",
+            "Junction" => "AndJunction",
+            other => other,
+        };
+        let mut assignments = vec![
+            format!("n.type = {}", string(element_type)),
+            format!(
+                "n.layer = {}",
+                string(element_layer(archi_type).unwrap_or_default())
+            ),
+        ];
+        assignments.extend(concept_assignments(xot, element, "n"));
+        writeln!(
+            text,
+            "MERGE (n:Element {{id: {}}}) SET n:{}, {};",
+            string(id),
+            element_type,
+            assignments.join(", ")
+        )?;
+        element_ids.insert(id);
+    }
+    for &relation in &relations {
+        let (Some(id), Some(source_id), Some(target_id)) = (
+            attribute(xot, relation, "id"),
+            attribute(xot, relation, "source"),
+            attribute(xot, relation, "target"),
+        ) else {
+            continue;
+        };
+        if !element_ids.contains(source_id) || !element_ids.contains(target_id) {
+            continue;
+        }
+        let relation_type = xsi_type(xot, relation)
+            .and_then(|xsi_type| xsi_type.strip_prefix("archimate:"))
+            .and_then(|xsi_type| xsi_type.strip_suffix("Relationship"))
+            .unwrap_or_default();
+        let mut assignments = vec![format!("r.type = {}", string(relation_type))];
+        if relation_type == "Access" {
+            // Archi leaves out its default access type, Write
+            let access_type = match attribute(xot, relation, "accessType") {
+                Some("1") => "Read",
+                Some("2") => "Access",
+                Some("3") => "ReadWrite",
+                _ => "Write",
+            };
+            assignments.push(format!("r.accessType = {}", string(access_type)));
+        }
+        assignments.extend(concept_assignments(xot, relation, "r"));
+        writeln!(
+            text,
+            "MATCH (s:Element {{id: {}}}), (t:Element {{id: {}}}) MERGE (s)-[r:{} {{id: {}}}]->(t) SET {};",
+            string(source_id),
+            string(target_id),
+            upper_snake_case(relation_type),
+            string(id),
+            assignments.join(", ")
+        )?;
+    }
+    Ok(text)
+}
+
+/// The `SET` assignments of a concept's name, documentation and
+/// properties to `variable`.
+fn concept_assignments(xot: &Xot, concept: Node, variable: &str) -> Vec<String> {
+    let mut assignments = Vec::new();
+    if let Some(name) = attribute(xot, concept, "name") {
+        assignments.push(format!("{}.name = {}", variable, string(name)));
+    }
+    let documentation = children(xot, concept, "documentation")
+        .first()
+        .and_then(|&child| xot.text_content_str(child));
+    if let Some(documentation) = documentation {
+        assignments.push(format!(
+            "{}.documentation = {}",
+            variable,
+            string(documentation)
+        ));
+    }
+    // A property can only be set once per statement; the first with a key wins
+    let mut keys = HashSet::new();
+    for property in children(xot, concept, "property") {
+        let Some(key) = attribute(xot, property, "key") else {
+            continue;
+        };
+        if !keys.insert(key) || ["id", "type", "layer", "name", "documentation"].contains(&key) {
+            continue;
+        }
+        assignments.push(format!(
+            "{}.`{}` = {}",
+            variable,
+            key.replace('`', "``"),
+            string(attribute(xot, property, "value").unwrap_or_default())
+        ));
+    }
+    assignments
+}
+
+/// A Cypher string literal.
+fn string(value: &str) -> String {
+    format!(
+        "'{}'",
+        value
+            .replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('\r', "\\r")
+            .replace('\n', "\\n")
+    )
+}
+
+/// `UPPER_SNAKE_CASE` for a `CamelCase` name, as Neo4j names relationship
+/// types.
+fn upper_snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            result.push('_');
+        }
+        result.push(c.to_ascii_uppercase());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_model_to_cypher() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Bob's Portal" id="element-1">
+      <documentation>Customer
+facing</documentation>
+      <property key="owner team" value="Sales"/>
+    </element>
+    <element xsi:type="archimate:DataObject" name="Order" id="element-2"/>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations">
+    <element xsi:type="archimate:AccessRelationship" id="relation-1" source="element-1" target="element-2" accessType="3"/>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+
+        assert_eq!(
+            model_to_cypher(&model, None)?,
+            "CREATE CONSTRAINT archimate_element_id IF NOT EXISTS FOR (n:Element) REQUIRE n.id IS UNIQUE;\n\
+             MERGE (n:Element {id: 'element-1'}) SET n:ApplicationComponent, n.type = 'ApplicationComponent', n.layer = 'Application', n.name = 'Bob\\'s Portal', n.documentation = 'Customer\\nfacing', n.`owner team` = 'Sales';\n\
+             MERGE (n:Element {id: 'element-2'}) SET n:DataObject, n.type = 'DataObject', n.layer = 'Application', n.name = 'Order';\n\
+             MATCH (s:Element {id: 'element-1'}), (t:Element {id: 'element-2'}) MERGE (s)-[r:ACCESS {id: 'relation-1'}]->(t) SET r.type = 'Access', r.accessType = 'ReadWrite';\n"
+        );
+        let subset = HashSet::from(["element-2".to_string()]);
+        let cypher = model_to_cypher(&model, Some(&subset))?;
+        assert!(cypher.contains("'element-2'"));
+        assert!(!cypher.contains("'element-1'"));
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::copy::{find_diagram_references, find_view_references};
+use crate::model::ArchiModel;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::Write as _;
+
+/// Renders which views show which elements, and which views link to other
+/// views, as a Graphviz digraph. Views are boxes and elements ellipses;
+/// links between views are dashed. Elements shown by several views are
+/// where the views are coupled.
+pub fn view_graph(model: &ArchiModel) -> Result<String, Box<dyn Error>> {
+    let mut views: Vec<_> = model.view_map.values().collect();
+    views.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let mut text = String::new();
+    writeln!(text, "digraph views {{")?;
+    writeln!(text, "  rankdir=LR;")?;
+    writeln!(text, "  node [fontname=\"Helvetica\"];")?;
+    let mut elements = BTreeSet::new();
+    let mut edges = Vec::new();
+    for view in &views {
+        writeln!(
+            text,
+            "  {} [shape=box, style=filled, fillcolor=\"#e0e0e0\", label={}];",
+            quote(&view.id),
+            quote(&view.name)
+        )?;
+        let (element_ids, _) = find_view_references(&view.xml_string)?;
+        let element_ids: BTreeSet<String> = element_ids
+            .into_iter()
+            .filter(|id| model.element_map.contains_key(id.as_str()))
+            .collect();
+        for element_id in element_ids {
+            edges.push(format!("  {} -> {};", quote(&view.id), quote(&element_id)));
+            elements.insert(element_id);
+        }
+        let linked: BTreeSet<String> = find_diagram_references(&view.xml_string)?
+            .into_iter()
+            .filter(|id| model.view_map.contains_key(id.as_str()))
+            .collect();
+        for linked_id in linked {
+            edges.push(format!(
+                "  {} -> {} [style=dashed];",
+                quote(&view.id),
+                quote(&linked_id)
+            ));
+        }
+    }
+    for element_id in &elements {
+        let element = &model.element_map[element_id.as_str()];
+        let element_type = element
+            .xsi_type
+            .strip_prefix("archimate:")
+            .unwrap_or(&element.xsi_type);
+        writeln!(
+            text,
+            "  {} [shape=ellipse, label={}];",
+            quote(element_id),
+            quote(&format!("{}\n({})", element.name, element_type))
+        )?;
+    }
+    for edge in edges {
+        writeln!(text, "{}", edge)?;
+    }
+    writeln!(text, "}}")?;
+    Ok(text)
+}
+
+/// A quoted DOT identifier or label.
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace("\r\n", "\\n")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_view_graph() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal &quot;web&quot;" id="element-1"/>
+  </folder>
+  <folder name="Views" id="folder-2" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1"/>
+      <child xsi:type="archimate:DiagramModelReference" id="object-2" model="view-2"/>
+    </element>
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Detail" id="view-2">
+      <child xsi:type="archimate:DiagramObject" id="object-3" archimateElement="element-1"/>
+    </element>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+
+        assert_eq!(
+            view_graph(&model)?,
+            "digraph views {\n\
+             \x20 rankdir=LR;\n\
+             \x20 node [fontname=\"Helvetica\"];\n\
+             \x20 \"view-1\" [shape=box, style=filled, fillcolor=\"#e0e0e0\", label=\"Overview\"];\n\
+             \x20 \"view-2\" [shape=box, style=filled, fillcolor=\"#e0e0e0\", label=\"Detail\"];\n\
+             \x20 \"element-1\" [shape=ellipse, label=\"Portal \\\"web\\\"\\n(ApplicationComponent)\"];\n\
+             \x20 \"view-1\" -> \"element-1\";\n\
+             \x20 \"view-1\" -> \"view-2\" [style=dashed];\n\
+             \x20 \"view-2\" -> \"element-1\";\n\
+             }\n"
+        );
+        Ok(())
+    }
+}
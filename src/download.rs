@@ -0,0 +1,93 @@
+use crate::file_descriptor::FileDescriptor;
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+/// Environment variable holding the bearer token sent with downloads when
+/// `--source-token` is not given.
+pub const TOKEN_VARIABLE: &str = "ARCHI_SOURCE_TOKEN";
+
+/// Whether a source argument names a model to download rather than a file.
+pub fn is_url(source: &str) -> bool {
+    let lowercase = source.to_ascii_lowercase();
+    lowercase.starts_with("https://") || lowercase.starts_with("http://")
+}
+
+/// Downloads the model at `url` into memory, sending `token` as a bearer
+/// token when given. Uses `curl`, which must be on the `PATH` and follows
+/// redirects and fails on HTTP errors. The model counts as modified when it
+/// was downloaded.
+pub fn download(url: &str, token: Option<&str>) -> Result<FileDescriptor, Box<dyn Error>> {
+    let mut command = Command::new("curl");
+    command
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // The header is passed on standard input to keep the token out of the
+    // process list
+    if token.is_some() {
+        command.args(["--header", "@-"]);
+    }
+    command.arg(url);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Cannot run curl, which downloads need on the PATH: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(token) = token {
+            writeln!(stdin, "Authorization: Bearer {}", token)?;
+        }
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Cannot download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(FileDescriptor::fetched(
+        file_name(url),
+        output.stdout,
+        SystemTime::now(),
+    )?)
+}
+
+/// The last path segment of a URL, or `model.archimate` when it has none.
+fn file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    match path.split_once('/') {
+        Some((_, path)) => path
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .unwrap_or("model.archimate")
+            .to_string(),
+        None => "model.archimate".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://models.example.com/enterprise.archimate"));
+        assert!(is_url("HTTP://models.example.com/enterprise.archimate"));
+        assert!(!is_url("models/enterprise.archimate"));
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("https://models.example.com/releases/enterprise.archimate?version=3"),
+            "enterprise.archimate"
+        );
+        assert_eq!(file_name("https://models.example.com"), "model.archimate");
+        assert_eq!(file_name("https://models.example.com/"), "model.archimate");
+    }
+}
@@ -0,0 +1,1721 @@
+use crate::concept_type::ConceptType;
+use crate::conflict::documentation_text;
+use crate::copy::{find_view_references, relation_endpoints};
+use crate::ids::stable_hash;
+use crate::model::{
+    default_folder_name, element_folder_type, extract_properties, model_name, view_kind,
+    ArchiModel, FOLDER_TYPES,
+};
+use crate::namespaces::{archi_type, XSI_NAMESPACE};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use xot::{output, Node, Xot};
+
+/// Namespace of the ArchiMate 3 Open Exchange Format.
+pub const EXCHANGE_NAMESPACE: &str = "http://www.opengroup.org/xsd/archimate/3.0/";
+
+/// What the `export` command writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ExportFormat {
+    /// One Open Exchange Format file.
+    #[default]
+    Exchange,
+    /// A directory with a PlantUML diagram per view.
+    PlantUml,
+    /// One GraphML file of elements and relations.
+    GraphMl,
+    /// One file of Cypher statements loading elements and relations into
+    /// Neo4j.
+    Cypher,
+    /// The model as JSON.
+    Json,
+    /// The model in a SQLite database.
+    Sqlite,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "exchange" => Ok(ExportFormat::Exchange),
+            "plantuml" => Ok(ExportFormat::PlantUml),
+            "graphml" => Ok(ExportFormat::GraphMl),
+            "cypher" => Ok(ExportFormat::Cypher),
+            "json" => Ok(ExportFormat::Json),
+            "sqlite" => Ok(ExportFormat::Sqlite),
+            _ => Err(format!(
+                "Invalid export format: {} (expected exchange, plantuml, graphml, cypher, json or sqlite)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Exchange => write!(f, "exchange"),
+            ExportFormat::PlantUml => write!(f, "plantuml"),
+            ExportFormat::GraphMl => write!(f, "graphml"),
+            ExportFormat::Cypher => write!(f, "cypher"),
+            ExportFormat::Json => write!(f, "json"),
+            ExportFormat::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+/// Which concepts an export written after importing covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ExportScope {
+    /// Every concept of the merged model.
+    #[default]
+    Model,
+    /// Only the imported views' concepts.
+    Imported,
+}
+
+impl FromStr for ExportScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "model" => Ok(ExportScope::Model),
+            "imported" => Ok(ExportScope::Imported),
+            _ => Err(format!(
+                "Invalid export scope: {} (expected model or imported)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportScope::Model => write!(f, "model"),
+            ExportScope::Imported => write!(f, "imported"),
+        }
+    }
+}
+
+/// The relationship types of the exchange format.
+const RELATIONSHIP_TYPES: &[&str] = &[
+    "Composition",
+    "Aggregation",
+    "Assignment",
+    "Realization",
+    "Serving",
+    "Access",
+    "Influence",
+    "Triggering",
+    "Flow",
+    "Specialization",
+    "Association",
+];
+
+/// Whether the document element of `xml` is an Open Exchange Format model.
+pub fn is_exchange_format(xml: &str) -> bool {
+    document_element_tag(xml).is_some_and(|tag| tag.contains(EXCHANGE_NAMESPACE))
+}
+
+/// Whether `xml` looks like an Open Exchange Format model from any tool:
+/// its document element is in some version of the exchange namespace, or
+/// is a `model` in no namespace at all.
+pub fn is_lenient_exchange_format(xml: &str) -> bool {
+    document_element_tag(xml).is_some_and(|tag| {
+        tag.contains("http://www.opengroup.org/xsd/archimate")
+            || (tag.starts_with("<model") && !tag.contains("xmlns="))
+    })
+}
+
+/// The start tag of the document element of `xml`.
+pub fn document_element_tag(xml: &str) -> Option<&str> {
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        // Skip the XML declaration, comments and the doctype
+        if !rest[1..].starts_with(['?', '!']) {
+            return Some(&rest[..end]);
+        }
+        rest = &rest[end..];
+    }
+    None
+}
+
+/// What reading an exchange model leniently put up with.
+struct Deviations {
+    lenient: bool,
+    warnings: Vec<String>,
+}
+
+impl Deviations {
+    /// Records a deviation from the exchange format, or fails on it when
+    /// not reading leniently.
+    fn tolerate(&mut self, message: String) -> Result<(), Box<dyn Error>> {
+        if !self.lenient {
+            return Err(message.into());
+        }
+        self.warnings.push(message);
+        Ok(())
+    }
+
+    /// A stable ID for a concept or diagram part written without one.
+    fn generated_id(&self) -> String {
+        stable_id(&["generated", &self.warnings.len().to_string()])
+    }
+}
+
+/// Translates an Open Exchange Format model into an Archi model, keeping
+/// the exchange identifiers. Concepts go to the top-level folder of their
+/// type, below the organization folders they are listed in.
+pub fn exchange_to_archi(xml: &str) -> Result<String, Box<dyn Error>> {
+    convert_exchange(
+        xml,
+        &mut Deviations {
+            lenient: false,
+            warnings: Vec::new(),
+        },
+    )
+}
+
+/// Like `exchange_to_archi`, but puts up with what other tools get wrong:
+/// another version of the exchange namespace, prefixed or unknown types,
+/// missing identifiers and sizes, and references to concepts or diagram
+/// objects the model does not have. Each deviation is returned as a
+/// warning, and what cannot be converted is left out.
+pub fn exchange_to_archi_lenient(xml: &str) -> Result<(String, Vec<String>), Box<dyn Error>> {
+    let mut deviations = Deviations {
+        lenient: true,
+        warnings: Vec::new(),
+    };
+    let converted = convert_exchange(xml, &mut deviations)?;
+    Ok((converted, deviations.warnings))
+}
+
+fn convert_exchange(xml: &str, deviations: &mut Deviations) -> Result<String, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let doc = xot.parse(xml)?;
+    let model = xot.document_element(doc)?;
+    if deviations.lenient {
+        normalize_namespace(&mut xot, model, deviations);
+    }
+    let output_doc = xot.parse(&format!(
+        r#"<archimate:model xmlns:xsi="{}" xmlns:archimate="http://www.archimatetool.com/archimate" name="" id="" version="5.0.0"/>"#,
+        XSI_NAMESPACE
+    ))?;
+    let output = xot.document_element(output_doc)?;
+
+    let model_name = child_text(&mut xot, model, "name").unwrap_or_default();
+    set(&mut xot, output, "name", &model_name);
+    let model_id = attribute(&mut xot, model, "identifier").unwrap_or_default();
+    set(&mut xot, output, "id", &model_id);
+
+    let mut property_names = HashMap::new();
+    for definitions in children_named(&mut xot, model, "propertyDefinitions") {
+        for definition in children_named(&mut xot, definitions, "propertyDefinition") {
+            if let Some(id) = attribute(&mut xot, definition, "identifier") {
+                let name = child_text(&mut xot, definition, "name").unwrap_or_default();
+                property_names.insert(id, name);
+            }
+        }
+    }
+
+    let mut organization_paths = HashMap::new();
+    for organizations in children_named(&mut xot, model, "organizations") {
+        collect_organizations(
+            &mut xot,
+            organizations,
+            &mut Vec::new(),
+            &mut organization_paths,
+        );
+    }
+
+    let mut folders = HashMap::new();
+    for &folder_type in FOLDER_TYPES {
+        let folder = new_element(&mut xot, "folder");
+        set(&mut xot, folder, "name", default_folder_name(folder_type));
+        set(&mut xot, folder, "id", &stable_id(&[folder_type]));
+        set(&mut xot, folder, "type", folder_type);
+        xot.append(output, folder)?;
+        folders.insert(folder_type, folder);
+    }
+    let place = |xot: &mut Xot, folder_type: &str, id: &str, node: Node| {
+        let labels = organization_paths.get(id).cloned().unwrap_or_default();
+        let folder = find_or_create_subfolder(xot, folders[folder_type], folder_type, &labels)?;
+        xot.append(folder, node)
+    };
+
+    let mut concepts = HashSet::new();
+    for elements in children_named(&mut xot, model, "elements") {
+        for element in children_named(&mut xot, elements, "element") {
+            let exchange_type = exchange_type(&mut xot, element, deviations);
+            let (archi_type, junction_type) = match exchange_type.as_str() {
+                "AndJunction" => ("Junction".to_string(), None),
+                "OrJunction" => ("Junction".to_string(), Some("or")),
+                _ => (exchange_type.clone(), None),
+            };
+            if deviations.lenient && element_folder_type(&archi_type).is_none() {
+                deviations.warnings.push(format!(
+                    "left out element {} of unknown type {}",
+                    describe_part(&mut xot, element),
+                    exchange_type
+                ));
+                continue;
+            }
+            let (id, converted) = convert_concept(&mut xot, element, &archi_type, deviations)?;
+            concepts.insert(id.clone());
+            if let Some(junction_type) = junction_type {
+                set(&mut xot, converted, "type", junction_type);
+            }
+            copy_annotations(&mut xot, element, converted, &property_names)?;
+            place(
+                &mut xot,
+                element_folder_type(&archi_type).unwrap_or("other"),
+                &id,
+                converted,
+            )?;
+        }
+    }
+
+    // Relationships may connect relationships listed after them
+    let mut relationship_ids = HashSet::new();
+    for relationships in children_named(&mut xot, model, "relationships") {
+        for relationship in children_named(&mut xot, relationships, "relationship") {
+            relationship_ids.extend(attribute(&mut xot, relationship, "identifier"));
+        }
+    }
+    for relationships in children_named(&mut xot, model, "relationships") {
+        for relationship in children_named(&mut xot, relationships, "relationship") {
+            let mut exchange_type = exchange_type(&mut xot, relationship, deviations);
+            if deviations.lenient {
+                if let Some(stripped) = exchange_type.strip_suffix("Relationship") {
+                    deviations.warnings.push(format!(
+                        "relationship {} has the type {} instead of {}",
+                        describe_part(&mut xot, relationship),
+                        exchange_type,
+                        stripped
+                    ));
+                    exchange_type = stripped.to_string();
+                }
+                if !RELATIONSHIP_TYPES.contains(&exchange_type.as_str()) {
+                    deviations.warnings.push(format!(
+                        "left out relationship {} of unknown type {}",
+                        describe_part(&mut xot, relationship),
+                        exchange_type
+                    ));
+                    continue;
+                }
+                let missing_end = ["source", "target"].into_iter().find_map(|end| {
+                    let value = attribute(&mut xot, relationship, end).unwrap_or_default();
+                    (!concepts.contains(&value) && !relationship_ids.contains(&value))
+                        .then_some(value)
+                });
+                if let Some(missing_end) = missing_end {
+                    deviations.warnings.push(format!(
+                        "left out relationship {} to unknown concept {}",
+                        describe_part(&mut xot, relationship),
+                        missing_end
+                    ));
+                    continue;
+                }
+            }
+            let (id, converted) = convert_concept(
+                &mut xot,
+                relationship,
+                &format!("{}Relationship", exchange_type),
+                deviations,
+            )?;
+            concepts.insert(id.clone());
+            for endpoint in ["source", "target"] {
+                let value = attribute(&mut xot, relationship, endpoint).unwrap_or_default();
+                set(&mut xot, converted, endpoint, &value);
+            }
+            // Write is Archi's default access type and is left out
+            let access_type = match attribute(&mut xot, relationship, "accessType").as_deref() {
+                Some("Read") => Some("1"),
+                Some("Access") => Some("2"),
+                Some("ReadWrite") => Some("3"),
+                _ => None,
+            };
+            if let Some(access_type) = access_type {
+                set(&mut xot, converted, "accessType", access_type);
+            }
+            if attribute(&mut xot, relationship, "isDirected").as_deref() == Some("true") {
+                set(&mut xot, converted, "directed", "true");
+            }
+            if let Some(modifier) = attribute(&mut xot, relationship, "modifier") {
+                set(&mut xot, converted, "strength", &modifier);
+            }
+            copy_annotations(&mut xot, relationship, converted, &property_names)?;
+            place(&mut xot, "relations", &id, converted)?;
+        }
+    }
+
+    for views in children_named(&mut xot, model, "views") {
+        for diagrams in children_named(&mut xot, views, "diagrams") {
+            for view in children_named(&mut xot, diagrams, "view") {
+                let (id, converted) =
+                    convert_concept(&mut xot, view, "ArchimateDiagramModel", deviations)?;
+                if let Some(viewpoint) = attribute(&mut xot, view, "viewpoint") {
+                    // Spaces and the "and" of two-part names become underscores
+                    let viewpoint = viewpoint
+                        .to_lowercase()
+                        .replace(" and ", " ")
+                        .replace(' ', "_");
+                    set(&mut xot, converted, "viewpoint", &viewpoint);
+                }
+                copy_annotations(&mut xot, view, converted, &property_names)?;
+                convert_diagram(&mut xot, view, converted, &concepts, deviations)?;
+                place(&mut xot, "diagrams", &id, converted)?;
+            }
+        }
+    }
+
+    if let Some(purpose) = child_text(&mut xot, model, "documentation") {
+        let purpose_node = new_element(&mut xot, "purpose");
+        let text = xot.new_text(&purpose);
+        xot.append(purpose_node, text)?;
+        xot.append(output, purpose_node)?;
+    }
+    append_properties(&mut xot, model, output, &property_names)?;
+
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        output_doc,
+    )?)
+}
+
+/// Moves the elements of an exchange model written with another version of
+/// the exchange namespace, or with none, into the namespace this reads.
+fn normalize_namespace(xot: &mut Xot, model: Node, deviations: &mut Deviations) {
+    let (_, namespace) = xot.name_ns_str(xot.get_element_name(model));
+    if namespace == EXCHANGE_NAMESPACE {
+        return;
+    }
+    let namespace = namespace.to_string();
+    deviations.warnings.push(format!(
+        "the model is in the namespace \"{}\" instead of {}",
+        namespace, EXCHANGE_NAMESPACE
+    ));
+    let exchange_namespace = xot.add_namespace(EXCHANGE_NAMESPACE);
+    let nodes: Vec<Node> = xot
+        .descendants(model)
+        .filter(|&node| {
+            xot.is_element(node) && xot.name_ns_str(xot.get_element_name(node)).1 == namespace
+        })
+        .collect();
+    for node in nodes {
+        let local_name = xot.local_name_str(xot.get_element_name(node)).to_string();
+        let name = xot.add_name_ns(&local_name, exchange_namespace);
+        if let Some(element) = xot.element_mut(node) {
+            element.set_name(name);
+        }
+    }
+}
+
+/// The `xsi:type` of an exchange concept. Read leniently, a namespace
+/// prefix some tools write is dropped.
+fn exchange_type(xot: &mut Xot, node: Node, deviations: &mut Deviations) -> String {
+    let exchange_type = xsi_type(xot, node).unwrap_or_default();
+    match exchange_type.split_once(':') {
+        Some((_, local_type)) if deviations.lenient => {
+            deviations.warnings.push(format!(
+                "{} has the prefixed type {}",
+                describe_part(xot, node),
+                exchange_type
+            ));
+            local_type.to_string()
+        }
+        _ => exchange_type,
+    }
+}
+
+/// A concept or diagram part for a warning: its identifier, or its name
+/// when it has none.
+fn describe_part(xot: &mut Xot, node: Node) -> String {
+    match attribute(xot, node, "identifier") {
+        Some(id) => id,
+        None => format!(
+            "'{}'",
+            child_text(xot, node, "name")
+                .or_else(|| child_text(xot, node, "label"))
+                .unwrap_or_default()
+        ),
+    }
+}
+
+/// Records the organization labels leading to each listed concept. A
+/// concept listed more than once stays in the first place it is listed.
+fn collect_organizations(
+    xot: &mut Xot,
+    node: Node,
+    labels: &mut Vec<String>,
+    paths: &mut HashMap<String, Vec<String>>,
+) {
+    for item in children_named(xot, node, "item") {
+        match attribute(xot, item, "identifierRef") {
+            Some(reference) => {
+                paths.entry(reference).or_insert_with(|| labels.clone());
+            }
+            None => {
+                labels.push(child_text(xot, item, "label").unwrap_or_default());
+                collect_organizations(xot, item, labels, paths);
+                labels.pop();
+            }
+        }
+    }
+}
+
+/// Finds or creates the subfolders named by `labels` below a top-level
+/// folder. A first label naming the top-level folder itself is skipped, as
+/// tools exporting Archi models list the top-level folders as organizations.
+fn find_or_create_subfolder(
+    xot: &mut Xot,
+    top_folder: Node,
+    folder_type: &str,
+    labels: &[String],
+) -> Result<Node, xot::Error> {
+    let labels = match labels.split_first() {
+        Some((first, rest)) if first == default_folder_name(folder_type) => rest,
+        _ => labels,
+    };
+    let folder_name = xot.add_name("folder");
+    let name_name = xot.add_name("name");
+    let mut current = top_folder;
+    for (depth, label) in labels.iter().enumerate() {
+        let existing = xot.children(current).find(|&child| {
+            xot.is_element(child)
+                && xot.get_element_name(child) == folder_name
+                && xot.get_attribute(child, name_name) == Some(label.as_str())
+        });
+        current = match existing {
+            Some(folder) => folder,
+            None => {
+                let folder = new_element(xot, "folder");
+                set(xot, folder, "name", label);
+                let path: Vec<&str> = std::iter::once(folder_type)
+                    .chain(labels[..=depth].iter().map(|label| label.as_str()))
+                    .collect();
+                set(xot, folder, "id", &stable_id(&path));
+                xot.append(current, folder)?;
+                folder
+            }
+        };
+    }
+    Ok(current)
+}
+
+/// A stable ID for a folder the exchange format has no identifier for, or
+/// a part written without one, so converting the same model twice gives
+/// the same IDs.
+fn stable_id(path: &[&str]) -> String {
+    // Parts are separated by a byte no name holds
+    format!("id-{:016x}", stable_hash(path.join("\0").as_bytes()))
+}
+
+/// Creates the Archi element for an element, relationship or view, with its
+/// type, name and ID.
+fn convert_concept(
+    xot: &mut Xot,
+    node: Node,
+    archi_type: &str,
+    deviations: &mut Deviations,
+) -> Result<(String, Node), Box<dyn Error>> {
+    let id = match attribute(xot, node, "identifier") {
+        Some(id) => id,
+        None => {
+            let id = deviations.generated_id();
+            deviations.tolerate(format!(
+                "Open Exchange {} without an identifier",
+                xsi_type(xot, node).unwrap_or_else(|| archi_type.to_string())
+            ))?;
+            id
+        }
+    };
+    let converted = new_element(xot, "element");
+    set_xsi_type(xot, converted, archi_type);
+    if let Some(name) = child_text(xot, node, "name") {
+        set(xot, converted, "name", &name);
+    }
+    set(xot, converted, "id", &id);
+    Ok((id, converted))
+}
+
+/// Copies the documentation and properties of an exchange concept.
+fn copy_annotations(
+    xot: &mut Xot,
+    node: Node,
+    converted: Node,
+    property_names: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(documentation) = child_text(xot, node, "documentation") {
+        let documentation_node = new_element(xot, "documentation");
+        let text = xot.new_text(&documentation);
+        xot.append(documentation_node, text)?;
+        xot.append(converted, documentation_node)?;
+    }
+    append_properties(xot, node, converted, property_names)
+}
+
+fn append_properties(
+    xot: &mut Xot,
+    node: Node,
+    converted: Node,
+    property_names: &HashMap<String, String>,
+) -> Result<(), Box<dyn Error>> {
+    for properties in children_named(xot, node, "properties") {
+        for property in children_named(xot, properties, "property") {
+            let reference = attribute(xot, property, "propertyDefinitionRef").unwrap_or_default();
+            let key = property_names.get(&reference).unwrap_or(&reference).clone();
+            let value = child_text(xot, property, "value").unwrap_or_default();
+            let property_node = new_element(xot, "property");
+            set(xot, property_node, "key", &key);
+            set(xot, property_node, "value", &value);
+            xot.append(converted, property_node)?;
+        }
+    }
+    Ok(())
+}
+
+/// Absolute bounds of the diagram objects of a view, by ID.
+type DiagramBounds = HashMap<String, (i64, i64, i64, i64)>;
+
+/// The converted diagram objects and connections of a view by ID, with the
+/// bounds of the objects.
+#[derive(Default)]
+struct DiagramObjects {
+    nodes: HashMap<String, Node>,
+    bounds: DiagramBounds,
+}
+
+/// Converts the nodes and connections of an exchange view into diagram
+/// children of `converted`.
+fn convert_diagram(
+    xot: &mut Xot,
+    view: Node,
+    converted: Node,
+    concepts: &HashSet<String>,
+    deviations: &mut Deviations,
+) -> Result<(), Box<dyn Error>> {
+    let mut objects = DiagramObjects::default();
+    for node in children_named(xot, view, "node") {
+        convert_diagram_node(
+            xot,
+            node,
+            converted,
+            (0, 0),
+            &mut objects,
+            concepts,
+            deviations,
+        )?;
+    }
+
+    // Connections are created first, as a connection may start or end at
+    // another connection
+    let mut connections = Vec::new();
+    for connection in children_named(xot, view, "connection") {
+        let id = match attribute(xot, connection, "identifier") {
+            Some(id) => id,
+            None => {
+                let id = deviations.generated_id();
+                deviations
+                    .tolerate("Open Exchange connection without an identifier".to_string())?;
+                id
+            }
+        };
+        let source = attribute(xot, connection, "source").unwrap_or_default();
+        let target = attribute(xot, connection, "target").unwrap_or_default();
+        let relationship = attribute(xot, connection, "relationshipRef");
+        if let Some(relationship) = &relationship {
+            if deviations.lenient && !concepts.contains(relationship) {
+                deviations.warnings.push(format!(
+                    "left out connection {} of unknown relationship {}",
+                    id, relationship
+                ));
+                continue;
+            }
+        }
+        let child = new_element(xot, "sourceConnection");
+        match relationship {
+            Some(relationship) => {
+                set_xsi_type(xot, child, "Connection");
+                set(xot, child, "id", &id);
+                copy_colors(xot, connection, child);
+                set(xot, child, "source", &source);
+                set(xot, child, "target", &target);
+                set(xot, child, "archimateRelationship", &relationship);
+            }
+            None => {
+                set_xsi_type(xot, child, "DiagramModelConnection");
+                set(xot, child, "id", &id);
+                if let Some(label) = child_text(xot, connection, "label") {
+                    set(xot, child, "name", &label);
+                }
+                copy_colors(xot, connection, child);
+                set(xot, child, "source", &source);
+                set(xot, child, "target", &target);
+            }
+        }
+        // Archi keeps bendpoints as offsets from the centers of both ends
+        let center = |id: &str| {
+            objects
+                .bounds
+                .get(id)
+                .map(|&(x, y, width, height)| (x + width / 2, y + height / 2))
+                .unwrap_or((0, 0))
+        };
+        let (source_x, source_y) = center(&source);
+        let (target_x, target_y) = center(&target);
+        for bendpoint in children_named(xot, connection, "bendpoint") {
+            let x = number(xot, bendpoint, "x");
+            let y = number(xot, bendpoint, "y");
+            let bendpoint_node = new_element(xot, "bendpoint");
+            for (name, value) in [
+                ("startX", x - source_x),
+                ("startY", y - source_y),
+                ("endX", x - target_x),
+                ("endY", y - target_y),
+            ] {
+                if value != 0 {
+                    set(xot, bendpoint_node, name, &value.to_string());
+                }
+            }
+            xot.append(child, bendpoint_node)?;
+        }
+        objects.nodes.insert(id.clone(), child);
+        connections.push((id, source, target, child));
+    }
+
+    let child_name = xot.add_name("child");
+    let target_connections_name = xot.add_name("targetConnections");
+    for (id, source, target, child) in connections {
+        let (Some(&source_node), Some(&target_node)) =
+            (objects.nodes.get(&source), objects.nodes.get(&target))
+        else {
+            deviations.tolerate(format!(
+                "Open Exchange connection {} joins unknown diagram objects {} and {}",
+                id, source, target
+            ))?;
+            continue;
+        };
+        // Connections go before nested diagram objects, as Archi writes them
+        let first_nested = xot
+            .children(source_node)
+            .find(|&node| xot.is_element(node) && xot.get_element_name(node) == child_name);
+        match first_nested {
+            Some(nested) => xot.insert_before(nested, child)?,
+            None => xot.append(source_node, child)?,
+        }
+        let target_connections = match xot.get_attribute(target_node, target_connections_name) {
+            Some(existing) => format!("{} {}", existing, id),
+            None => id,
+        };
+        xot.set_attribute(target_node, target_connections_name, target_connections);
+    }
+    Ok(())
+}
+
+/// Converts a diagram node and the nodes nested in it. Exchange coordinates
+/// are absolute while Archi's are relative to the parent object.
+fn convert_diagram_node(
+    xot: &mut Xot,
+    node: Node,
+    parent: Node,
+    origin: (i64, i64),
+    objects: &mut DiagramObjects,
+    concepts: &HashSet<String>,
+    deviations: &mut Deviations,
+) -> Result<(), Box<dyn Error>> {
+    let id = match attribute(xot, node, "identifier") {
+        Some(id) => id,
+        None => {
+            let id = deviations.generated_id();
+            deviations.tolerate("Open Exchange diagram node without an identifier".to_string())?;
+            id
+        }
+    };
+    let element = attribute(xot, node, "elementRef");
+    if let Some(element) = &element {
+        if deviations.lenient && !concepts.contains(element) {
+            deviations.warnings.push(format!(
+                "left out diagram node {} of unknown element {}",
+                id, element
+            ));
+            return Ok(());
+        }
+    }
+    let child = new_element(xot, "child");
+    let label = child_text(xot, node, "label");
+    match element {
+        Some(element) => {
+            set_xsi_type(xot, child, "DiagramObject");
+            set(xot, child, "id", &id);
+            copy_colors(xot, node, child);
+            set(xot, child, "archimateElement", &element);
+        }
+        None if xsi_type(xot, node).as_deref() == Some("Container") => {
+            set_xsi_type(xot, child, "DiagramModelGroup");
+            set(xot, child, "name", label.as_deref().unwrap_or_default());
+            set(xot, child, "id", &id);
+            copy_colors(xot, node, child);
+        }
+        None => {
+            set_xsi_type(xot, child, "Note");
+            set(xot, child, "id", &id);
+            copy_colors(xot, node, child);
+        }
+    }
+
+    let (x, y) = (number(xot, node, "x"), number(xot, node, "y"));
+    let width = size(xot, node, "w", deviations);
+    let height = size(xot, node, "h", deviations);
+    let bounds_node = new_element(xot, "bounds");
+    set(xot, bounds_node, "x", &(x - origin.0).to_string());
+    set(xot, bounds_node, "y", &(y - origin.1).to_string());
+    set(xot, bounds_node, "width", &width.to_string());
+    set(xot, bounds_node, "height", &height.to_string());
+    xot.append(child, bounds_node)?;
+    if xsi_type(xot, child).as_deref() == Some("archimate:Note") {
+        let content = new_element(xot, "content");
+        let text = xot.new_text(label.as_deref().unwrap_or_default());
+        xot.append(content, text)?;
+        xot.append(child, content)?;
+    }
+    xot.append(parent, child)?;
+    objects.nodes.insert(id.clone(), child);
+    objects.bounds.insert(id, (x, y, width, height));
+
+    for nested in children_named(xot, node, "node") {
+        convert_diagram_node(xot, nested, child, (x, y), objects, concepts, deviations)?;
+    }
+    Ok(())
+}
+
+/// Copies the fill, line and font colors of an exchange diagram part.
+fn copy_colors(xot: &mut Xot, node: Node, converted: Node) {
+    let Some(&style) = children_named(xot, node, "style").first() else {
+        return;
+    };
+    for (exchange_name, archi_name) in [("fillColor", "fillColor"), ("lineColor", "lineColor")] {
+        if let Some(&color) = children_named(xot, style, exchange_name).first() {
+            let value = hex_color(xot, color);
+            set(xot, converted, archi_name, &value);
+        }
+    }
+    if let Some(&font) = children_named(xot, style, "font").first() {
+        if let Some(&color) = children_named(xot, font, "color").first() {
+            let value = hex_color(xot, color);
+            set(xot, converted, "fontColor", &value);
+        }
+    }
+}
+
+fn hex_color(xot: &mut Xot, color: Node) -> String {
+    let [r, g, b] = ["r", "g", "b"].map(|channel| number(xot, color, channel).clamp(0, 255));
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// The width or height of a diagram node. Read leniently, a missing one
+/// leaves the size to Archi.
+fn size(xot: &mut Xot, node: Node, name: &str, deviations: &mut Deviations) -> i64 {
+    if deviations.lenient && attribute(xot, node, name).is_none() {
+        deviations.warnings.push(format!(
+            "diagram node {} has no {} attribute",
+            describe_part(xot, node),
+            name
+        ));
+        return -1;
+    }
+    number(xot, node, name)
+}
+
+fn number(xot: &mut Xot, node: Node, name: &str) -> i64 {
+    attribute(xot, node, name)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Serializes a model to the Open Exchange Format, with organizations
+/// following its folder tree. With `view_ids`, only those views are written,
+/// along with the elements and relations they show and the endpoints of
+/// those relations. Sketch and canvas views have no exchange form and are
+/// left out.
+pub fn archi_to_exchange(
+    model: &ArchiModel,
+    view_ids: Option<&HashSet<String>>,
+) -> Result<String, Box<dyn Error>> {
+    let views: HashSet<String> = model
+        .view_map
+        .values()
+        .filter(|info| view_kind(&info.xsi_type).is_none())
+        .filter(|info| view_ids.is_none_or(|ids| ids.contains(&*info.id)))
+        .map(|info| info.id.to_string())
+        .collect();
+    let concepts = match view_ids {
+        Some(_) => view_dependencies(model, &views)?,
+        None => model.element_map.keys().map(|id| id.to_string()).collect(),
+    };
+
+    let source = &model.xot;
+    let root = source.document_element(model.root)?;
+    let mut xot = Xot::new();
+    let doc = xot.parse(&format!(
+        r#"<model xmlns="{}" xmlns:xsi="{}" identifier=""/>"#,
+        EXCHANGE_NAMESPACE, XSI_NAMESPACE
+    ))?;
+    let output = xot.document_element(doc)?;
+    set(
+        &mut xot,
+        output,
+        "identifier",
+        archi_attribute(source, root, "id").unwrap_or_default(),
+    );
+    append_text(&mut xot, output, "name", &model_name(model))?;
+    let purpose = archi_children(source, root, "purpose")
+        .first()
+        .and_then(|&purpose| source.text_content_str(purpose));
+    if let Some(purpose) = purpose {
+        append_text(&mut xot, output, "documentation", purpose)?;
+    }
+    let mut definitions = Vec::new();
+    append_exchange_properties(
+        &mut xot,
+        output,
+        &extract_properties(source, root),
+        &mut definitions,
+    )?;
+
+    let mut collected = CollectedConcepts::default();
+    let organizations = new_exchange_element(&mut xot, "organizations");
+    for folder in archi_children(source, root, "folder") {
+        let item = organization_item(
+            source,
+            &mut xot,
+            folder,
+            (&concepts, &views),
+            &mut collected,
+        )?;
+        if let Some(item) = item {
+            xot.append(organizations, item)?;
+        }
+    }
+
+    let elements = new_exchange_element(&mut xot, "elements");
+    for &node in &collected.elements {
+        let archi_type = archi_type(source, node).unwrap_or_default();
+        let exchange_type = match archi_type {
+            "Junction" if archi_attribute(source, node, "type") == Some("or") => "OrJunction",
+            "Junction" => "AndJunction",
+            _ => archi_type,
+        };
+        let converted = write_concept(
+            source,
+            &mut xot,
+            node,
+            "element",
+            exchange_type,
+            &mut definitions,
+        )?;
+        xot.append(elements, converted)?;
+    }
+
+    let relationships = new_exchange_element(&mut xot, "relationships");
+    for &node in &collected.relationships {
+        let archi_type = archi_type(source, node).unwrap_or_default();
+        let exchange_type = archi_type
+            .strip_suffix("Relationship")
+            .unwrap_or(archi_type);
+        let converted = write_concept(
+            source,
+            &mut xot,
+            node,
+            "relationship",
+            exchange_type,
+            &mut definitions,
+        )?;
+        for endpoint in ["source", "target"] {
+            let value = archi_attribute(source, node, endpoint).unwrap_or_default();
+            set(&mut xot, converted, endpoint, value);
+        }
+        if exchange_type == "Access" {
+            // Archi leaves out its default access type, Write
+            let access_type = match archi_attribute(source, node, "accessType") {
+                Some("1") => "Read",
+                Some("2") => "Access",
+                Some("3") => "ReadWrite",
+                _ => "Write",
+            };
+            set(&mut xot, converted, "accessType", access_type);
+        }
+        if archi_attribute(source, node, "directed") == Some("true") {
+            set(&mut xot, converted, "isDirected", "true");
+        }
+        if let Some(strength) = archi_attribute(source, node, "strength") {
+            set(&mut xot, converted, "modifier", strength);
+        }
+        xot.append(relationships, converted)?;
+    }
+
+    let diagrams = new_exchange_element(&mut xot, "diagrams");
+    for &node in &collected.views {
+        let converted = write_concept(source, &mut xot, node, "view", "Diagram", &mut definitions)?;
+        if let Some(viewpoint) = archi_attribute(source, node, "viewpoint") {
+            set(
+                &mut xot,
+                converted,
+                "viewpoint",
+                &exchange_viewpoint(viewpoint),
+            );
+        }
+        write_diagram(model, &mut xot, node, converted)?;
+        xot.append(diagrams, converted)?;
+    }
+
+    // Sections go in the order the exchange schema requires, leaving out
+    // empty ones
+    for section in [elements, relationships, organizations] {
+        if xot.first_child(section).is_some() {
+            xot.append(output, section)?;
+        }
+    }
+    if !definitions.is_empty() {
+        let property_definitions = new_exchange_element(&mut xot, "propertyDefinitions");
+        for (index, key) in definitions.iter().enumerate() {
+            let definition = new_exchange_element(&mut xot, "propertyDefinition");
+            set(
+                &mut xot,
+                definition,
+                "identifier",
+                &property_definition_id(index),
+            );
+            set(&mut xot, definition, "type", "string");
+            append_text(&mut xot, definition, "name", key)?;
+            xot.append(property_definitions, definition)?;
+        }
+        xot.append(output, property_definitions)?;
+    }
+    if xot.first_child(diagrams).is_some() {
+        let views_node = new_exchange_element(&mut xot, "views");
+        xot.append(views_node, diagrams)?;
+        xot.append(output, views_node)?;
+    }
+
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            // Indented like the exports of other modeling tools
+            indentation: Some(Default::default()),
+            ..Default::default()
+        },
+        doc,
+    )?)
+}
+
+/// The elements and relations the views show, with the endpoints of those
+/// relations, following relations on relations and junctions.
+pub fn view_dependencies(
+    model: &ArchiModel,
+    views: &HashSet<String>,
+) -> Result<HashSet<String>, Box<dyn Error>> {
+    let mut concepts = HashSet::new();
+    for id in views {
+        let (elements, relations) = find_view_references(&model.view_map[id.as_str()].xml_string)?;
+        concepts.extend(elements);
+        concepts.extend(relations);
+    }
+    let mut pending: Vec<String> = concepts.iter().cloned().collect();
+    while let Some(id) = pending.pop() {
+        let Some(info) = model.element_map.get(id.as_str()) else {
+            continue;
+        };
+        if !info.is_relationship() {
+            continue;
+        }
+        if let Some((source, target)) = relation_endpoints(&info.xml_string)? {
+            for endpoint in [source, target] {
+                if concepts.insert(endpoint.clone()) {
+                    pending.push(endpoint);
+                }
+            }
+        }
+    }
+    concepts.retain(|id| model.element_map.contains_key(id.as_str()));
+    Ok(concepts)
+}
+
+/// The Archi nodes written to each exchange section, in folder order.
+#[derive(Default)]
+struct CollectedConcepts {
+    elements: Vec<Node>,
+    relationships: Vec<Node>,
+    views: Vec<Node>,
+}
+
+/// Builds the organization item for a folder, collecting the concepts and
+/// views in it that are written. Returns `None` for a folder with nothing
+/// written below it.
+fn organization_item(
+    source: &Xot,
+    xot: &mut Xot,
+    folder: Node,
+    (concepts, views): (&HashSet<String>, &HashSet<String>),
+    collected: &mut CollectedConcepts,
+) -> Result<Option<Node>, Box<dyn Error>> {
+    let item = new_exchange_element(xot, "item");
+    let name = archi_attribute(source, folder, "name").unwrap_or_default();
+    append_text(xot, item, "label", name)?;
+    let mut is_empty = true;
+    for child in source.children(folder).filter(|&n| source.is_element(n)) {
+        let nested = match source.local_name_str(source.node_name(child).unwrap()) {
+            "folder" => organization_item(source, xot, child, (concepts, views), collected)?,
+            "element" => {
+                let id = archi_attribute(source, child, "id").unwrap_or_default();
+                let is_relationship = archi_type(source, child)
+                    .and_then(ConceptType::from_xsi_type)
+                    .is_some_and(ConceptType::is_relationship);
+                if views.contains(id) {
+                    collected.views.push(child);
+                } else if concepts.contains(id) && is_relationship {
+                    collected.relationships.push(child);
+                } else if concepts.contains(id) {
+                    collected.elements.push(child);
+                } else {
+                    continue;
+                }
+                let reference = new_exchange_element(xot, "item");
+                set(xot, reference, "identifierRef", id);
+                Some(reference)
+            }
+            _ => None,
+        };
+        if let Some(nested) = nested {
+            xot.append(item, nested)?;
+            is_empty = false;
+        }
+    }
+    Ok((!is_empty).then_some(item))
+}
+
+/// Creates the exchange element for an element, relationship or view, with
+/// its identifier, type, name, documentation and properties.
+fn write_concept(
+    source: &Xot,
+    xot: &mut Xot,
+    node: Node,
+    tag: &str,
+    exchange_type: &str,
+    definitions: &mut Vec<String>,
+) -> Result<Node, Box<dyn Error>> {
+    let converted = new_exchange_element(xot, tag);
+    set(
+        xot,
+        converted,
+        "identifier",
+        archi_attribute(source, node, "id").unwrap_or_default(),
+    );
+    set_exchange_type(xot, converted, exchange_type);
+    // Names are required of elements and views but not of relationships
+    match archi_attribute(source, node, "name") {
+        Some(name) if !name.is_empty() || tag != "relationship" => {
+            append_text(xot, converted, "name", name)?
+        }
+        None if tag != "relationship" => append_text(xot, converted, "name", "")?,
+        _ => {}
+    }
+    if let Some(documentation) = documentation_text(source, node) {
+        append_text(xot, converted, "documentation", &documentation)?;
+    }
+    append_exchange_properties(
+        xot,
+        converted,
+        &extract_properties(source, node),
+        definitions,
+    )?;
+    Ok(converted)
+}
+
+fn append_exchange_properties(
+    xot: &mut Xot,
+    converted: Node,
+    properties: &[(String, String)],
+    definitions: &mut Vec<String>,
+) -> Result<(), Box<dyn Error>> {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    let properties_node = new_exchange_element(xot, "properties");
+    for (key, value) in properties {
+        let index = match definitions.iter().position(|definition| definition == key) {
+            Some(index) => index,
+            None => {
+                definitions.push(key.clone());
+                definitions.len() - 1
+            }
+        };
+        let property = new_exchange_element(xot, "property");
+        set(
+            xot,
+            property,
+            "propertyDefinitionRef",
+            &property_definition_id(index),
+        );
+        append_text(xot, property, "value", value)?;
+        xot.append(properties_node, property)?;
+    }
+    xot.append(converted, properties_node)?;
+    Ok(())
+}
+
+fn property_definition_id(index: usize) -> String {
+    format!("propid-{}", index + 1)
+}
+
+/// The exchange name of an Archi viewpoint, e.g. `Application Cooperation`
+/// for `application_cooperation`.
+fn exchange_viewpoint(viewpoint: &str) -> String {
+    let words: Vec<String> = viewpoint
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    match words.as_slice() {
+        [first, second] if first == "Implementation" => format!("{} and {}", first, second),
+        _ => words.join(" "),
+    }
+}
+
+/// Writes the diagram objects and connections of an Archi view into an
+/// exchange view, with coordinates made absolute.
+fn write_diagram(
+    model: &ArchiModel,
+    xot: &mut Xot,
+    view: Node,
+    converted: Node,
+) -> Result<(), Box<dyn Error>> {
+    let mut bounds = DiagramBounds::new();
+    let mut connections = Vec::new();
+    write_diagram_nodes(
+        model,
+        xot,
+        view,
+        converted,
+        (0, 0),
+        &mut bounds,
+        &mut connections,
+    )?;
+
+    let source = &model.xot;
+    let written: HashSet<&str> = bounds
+        .keys()
+        .map(|id| id.as_str())
+        .chain(
+            connections
+                .iter()
+                .filter_map(|&connection| archi_attribute(source, connection, "id")),
+        )
+        .collect();
+    let center = |id: &str| {
+        bounds
+            .get(id)
+            .map(|&(x, y, width, height)| (x + width / 2, y + height / 2))
+    };
+    for &connection in &connections {
+        let id = archi_attribute(source, connection, "id").unwrap_or_default();
+        let source_id = archi_attribute(source, connection, "source").unwrap_or_default();
+        let target_id = archi_attribute(source, connection, "target").unwrap_or_default();
+        if !written.contains(source_id) || !written.contains(target_id) {
+            continue;
+        }
+        let converted_connection = new_exchange_element(xot, "connection");
+        set(xot, converted_connection, "identifier", id);
+        match archi_attribute(source, connection, "archimateRelationship") {
+            Some(relationship) => {
+                set(xot, converted_connection, "relationshipRef", relationship);
+                set_exchange_type(xot, converted_connection, "Relationship");
+            }
+            None => set_exchange_type(xot, converted_connection, "Line"),
+        }
+        set(xot, converted_connection, "source", source_id);
+        set(xot, converted_connection, "target", target_id);
+        if let Some(name) = archi_attribute(source, connection, "name") {
+            if archi_attribute(source, connection, "archimateRelationship").is_none() {
+                append_text(xot, converted_connection, "label", name)?;
+            }
+        }
+        write_style(source, xot, connection, converted_connection)?;
+        // Archi keeps bendpoints as offsets from the centers of both ends
+        for bendpoint in archi_children(source, connection, "bendpoint") {
+            let offset = |name: &str| archi_number(source, bendpoint, name).unwrap_or(0);
+            let position = match (center(source_id), center(target_id)) {
+                (Some((x, y)), _) => (x + offset("startX"), y + offset("startY")),
+                (None, Some((x, y))) => (x + offset("endX"), y + offset("endY")),
+                (None, None) => continue,
+            };
+            let converted_bendpoint = new_exchange_element(xot, "bendpoint");
+            set(xot, converted_bendpoint, "x", &position.0.to_string());
+            set(xot, converted_bendpoint, "y", &position.1.to_string());
+            xot.append(converted_connection, converted_bendpoint)?;
+        }
+        xot.append(converted, converted_connection)?;
+    }
+    Ok(())
+}
+
+/// Writes the diagram objects below `parent` as exchange nodes, collecting
+/// their connections. Objects with no exchange form, such as images, are
+/// left out with everything in them.
+fn write_diagram_nodes(
+    model: &ArchiModel,
+    xot: &mut Xot,
+    parent: Node,
+    converted_parent: Node,
+    origin: (i64, i64),
+    bounds: &mut DiagramBounds,
+    connections: &mut Vec<Node>,
+) -> Result<(), Box<dyn Error>> {
+    let source = &model.xot;
+    for child in archi_children(source, parent, "child") {
+        let id = archi_attribute(source, child, "id").unwrap_or_default();
+        let node = new_exchange_element(xot, "node");
+        set(xot, node, "identifier", id);
+        let label = match archi_type(source, child).unwrap_or_default() {
+            "DiagramObject" => {
+                let element = archi_attribute(source, child, "archimateElement");
+                set(xot, node, "elementRef", element.unwrap_or_default());
+                set_exchange_type(xot, node, "Element");
+                None
+            }
+            "DiagramModelGroup" => {
+                set_exchange_type(xot, node, "Container");
+                Some(
+                    archi_attribute(source, child, "name")
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            }
+            "Note" => {
+                set_exchange_type(xot, node, "Label");
+                Some(
+                    archi_children(source, child, "content")
+                        .first()
+                        .and_then(|&content| source.text_content_str(content))
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            }
+            "DiagramModelReference" => {
+                set_exchange_type(xot, node, "Label");
+                let reference = archi_attribute(source, child, "model").unwrap_or_default();
+                Some(
+                    model
+                        .view_map
+                        .get(reference)
+                        .map(|info| info.name.to_string())
+                        .unwrap_or_default(),
+                )
+            }
+            _ => continue,
+        };
+
+        // Archi stores -1 for objects drawn at their default size
+        let child_bounds = archi_children(source, child, "bounds").first().copied();
+        let dimension = |name: &str, default: i64| {
+            child_bounds
+                .and_then(|node| archi_number(source, node, name))
+                .filter(|&value| value >= 0)
+                .unwrap_or(default)
+        };
+        let x = origin.0 + dimension("x", 0);
+        let y = origin.1 + dimension("y", 0);
+        let (width, height) = (dimension("width", 120), dimension("height", 55));
+        for (name, value) in [("x", x), ("y", y), ("w", width), ("h", height)] {
+            set(xot, node, name, &value.to_string());
+        }
+        if let Some(label) = label {
+            append_text(xot, node, "label", &label)?;
+        }
+        write_style(source, xot, child, node)?;
+        bounds.insert(id.to_string(), (x, y, width, height));
+        collect_connections(source, child, connections);
+
+        write_diagram_nodes(model, xot, child, node, (x, y), bounds, connections)?;
+        xot.append(converted_parent, node)?;
+    }
+    Ok(())
+}
+
+/// Collects the connections starting at a diagram part and at the
+/// connections attached to them.
+fn collect_connections(source: &Xot, node: Node, connections: &mut Vec<Node>) {
+    for connection in archi_children(source, node, "sourceConnection") {
+        connections.push(connection);
+        collect_connections(source, connection, connections);
+    }
+}
+
+/// Writes the fill, line and font colors of an Archi diagram part as an
+/// exchange style.
+fn write_style(
+    source: &Xot,
+    xot: &mut Xot,
+    node: Node,
+    converted: Node,
+) -> Result<(), Box<dyn Error>> {
+    let color = |name: &str| archi_attribute(source, node, name).and_then(parse_hex_color);
+    let (fill, line, font) = (color("fillColor"), color("lineColor"), color("fontColor"));
+    if fill.is_none() && line.is_none() && font.is_none() {
+        return Ok(());
+    }
+    let style = new_exchange_element(xot, "style");
+    for (name, value) in [("fillColor", fill), ("lineColor", line)] {
+        if let Some(rgb) = value {
+            let color_node = new_exchange_element(xot, name);
+            set_rgb(xot, color_node, rgb);
+            xot.append(style, color_node)?;
+        }
+    }
+    if let Some(rgb) = font {
+        let font_node = new_exchange_element(xot, "font");
+        let color_node = new_exchange_element(xot, "color");
+        set_rgb(xot, color_node, rgb);
+        xot.append(font_node, color_node)?;
+        xot.append(style, font_node)?;
+    }
+    xot.append(converted, style)?;
+    Ok(())
+}
+
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn set_rgb(xot: &mut Xot, node: Node, [r, g, b]: [u8; 3]) {
+    for (name, value) in [("r", r), ("g", g), ("b", b)] {
+        set(xot, node, name, &value.to_string());
+    }
+}
+
+fn archi_attribute<'a>(xot: &'a Xot, node: Node, name: &str) -> Option<&'a str> {
+    xot.name(name)
+        .and_then(|name| xot.get_attribute(node, name))
+}
+
+fn archi_number(xot: &Xot, node: Node, name: &str) -> Option<i64> {
+    archi_attribute(xot, node, name).and_then(|value| value.parse().ok())
+}
+
+fn archi_children(xot: &Xot, node: Node, name: &str) -> Vec<Node> {
+    let Some(name) = xot.name(name) else {
+        return Vec::new();
+    };
+    xot.children(node)
+        .filter(|&child| xot.is_element(child) && xot.get_element_name(child) == name)
+        .collect()
+}
+
+fn new_exchange_element(xot: &mut Xot, local_name: &str) -> Node {
+    let namespace = xot.add_namespace(EXCHANGE_NAMESPACE);
+    let name = xot.add_name_ns(local_name, namespace);
+    xot.new_element(name)
+}
+
+fn append_text(
+    xot: &mut Xot,
+    parent: Node,
+    local_name: &str,
+    text: &str,
+) -> Result<(), Box<dyn Error>> {
+    let node = new_exchange_element(xot, local_name);
+    let text = xot.new_text(text);
+    xot.append(node, text)?;
+    xot.append(parent, node)?;
+    Ok(())
+}
+
+fn set_exchange_type(xot: &mut Xot, node: Node, exchange_type: &str) {
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    xot.set_attribute(node, type_name, exchange_type);
+}
+
+/// Child elements of `node` with the exchange-format name `local_name`.
+fn children_named(xot: &mut Xot, node: Node, local_name: &str) -> Vec<Node> {
+    let namespace = xot.add_namespace(EXCHANGE_NAMESPACE);
+    let name = xot.add_name_ns(local_name, namespace);
+    xot.children(node)
+        .filter(|&child| xot.is_element(child) && xot.get_element_name(child) == name)
+        .collect()
+}
+
+/// The text of the first child named `local_name`, as exchange names,
+/// labels and documentation come in one element per language.
+fn child_text(xot: &mut Xot, node: Node, local_name: &str) -> Option<String> {
+    let child = *children_named(xot, node, local_name).first()?;
+    Some(xot.text_content_str(child).unwrap_or_default().to_string())
+}
+
+fn attribute(xot: &mut Xot, node: Node, name: &str) -> Option<String> {
+    let name = xot.add_name(name);
+    xot.get_attribute(node, name).map(str::to_string)
+}
+
+fn xsi_type(xot: &mut Xot, node: Node) -> Option<String> {
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    xot.get_attribute(node, type_name).map(str::to_string)
+}
+
+fn set_xsi_type(xot: &mut Xot, node: Node, archi_type: &str) {
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    xot.set_attribute(node, type_name, format!("archimate:{}", archi_type));
+}
+
+fn new_element(xot: &mut Xot, name: &str) -> Node {
+    let name = xot.add_name(name);
+    xot.new_element(name)
+}
+
+fn set(xot: &mut Xot, node: Node, name: &str, value: &str) {
+    let name = xot.add_name(name);
+    xot.set_attribute(node, name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{format_folder_path, load_model};
+
+    #[test]
+    fn test_stable_id() {
+        // Fixed values, so converting in a later release gives the same IDs
+        assert_eq!(stable_id(&["Views", "Archive"]), "id-5265bc8ff841b47b");
+        assert_ne!(
+            stable_id(&["Views", "Archive"]),
+            stable_id(&["ViewsArchive"])
+        );
+    }
+
+    const EXCHANGE_MODEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <model xmlns="http://www.opengroup.org/xsd/archimate/3.0/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" identifier="model-1">
+            <name xml:lang="en">Exchange</name>
+            <elements>
+                <element identifier="element-1" xsi:type="ApplicationComponent">
+                    <name xml:lang="en">Portal</name>
+                    <documentation xml:lang="en">Customer facing</documentation>
+                    <properties>
+                        <property propertyDefinitionRef="pd-1"><value xml:lang="en">Sales</value></property>
+                    </properties>
+                </element>
+                <element identifier="element-2" xsi:type="DataObject"><name xml:lang="en">Order</name></element>
+                <element identifier="element-3" xsi:type="OrJunction"/>
+            </elements>
+            <relationships>
+                <relationship identifier="relation-1" source="element-1" target="element-2" xsi:type="Access" accessType="Read"/>
+            </relationships>
+            <organizations>
+                <item>
+                    <label xml:lang="en">Application</label>
+                    <item>
+                        <label xml:lang="en">Sales</label>
+                        <item identifierRef="element-1"/>
+                    </item>
+                </item>
+                <item>
+                    <label xml:lang="en">Views</label>
+                    <item identifierRef="view-1"/>
+                </item>
+            </organizations>
+            <propertyDefinitions>
+                <propertyDefinition identifier="pd-1" type="string"><name xml:lang="en">Owner</name></propertyDefinition>
+            </propertyDefinitions>
+            <views>
+                <diagrams>
+                    <view identifier="view-1" xsi:type="Diagram" viewpoint="Application Cooperation">
+                        <name xml:lang="en">Overview</name>
+                        <node identifier="node-1" xsi:type="Container" x="10" y="10" w="300" h="200">
+                            <label xml:lang="en">Group</label>
+                            <node identifier="node-2" elementRef="element-1" xsi:type="Element" x="30" y="40" w="120" h="55">
+                                <style><fillColor r="181" g="255" b="255"/></style>
+                            </node>
+                        </node>
+                        <node identifier="node-3" elementRef="element-2" xsi:type="Element" x="400" y="40" w="120" h="55"/>
+                        <connection identifier="connection-1" relationshipRef="relation-1" xsi:type="Relationship" source="node-2" target="node-3">
+                            <bendpoint x="90" y="150"/>
+                        </connection>
+                    </view>
+                </diagrams>
+            </views>
+        </model>"#;
+
+    #[test]
+    fn test_is_exchange_format() {
+        assert!(is_exchange_format(EXCHANGE_MODEL));
+        assert!(!is_exchange_format(
+            r#"<?xml version="1.0"?><archimate:model xmlns:archimate="http://www.archimatetool.com/archimate"><documentation>http://www.opengroup.org/xsd/archimate/3.0/</documentation></archimate:model>"#
+        ));
+    }
+
+    #[test]
+    fn test_exchange_to_archi() -> Result<(), Box<dyn Error>> {
+        let xml = exchange_to_archi(EXCHANGE_MODEL)?;
+        let model = load_model(&xml)?;
+
+        let portal = &model.element_map["element-1"];
+        assert_eq!(&*portal.name, "Portal");
+        assert_eq!(portal.xsi_type.as_ref(), "archimate:ApplicationComponent");
+        assert_eq!(
+            format_folder_path(&portal.folder_path),
+            "Application[application] > Sales"
+        );
+        assert_eq!(
+            portal.properties,
+            vec![("Owner".to_string(), "Sales".to_string())]
+        );
+        assert!(xml.contains("<documentation>Customer facing</documentation>"));
+        assert!(xml.contains(r#"xsi:type="archimate:Junction" id="element-3" type="or""#));
+        assert!(xml.contains(r#"source="element-1" target="element-2" accessType="1""#));
+
+        let view = &model.view_map["view-1"];
+        assert_eq!(&*view.name, "Overview");
+        assert_eq!(format_folder_path(&view.folder_path), "Views[diagrams]");
+        assert!(view
+            .xml_string
+            .contains(r#"viewpoint="application_cooperation""#));
+        // The nested object is placed relative to its group
+        assert!(view
+            .xml_string
+            .contains(r#"<bounds x="20" y="30" width="120" height="55"/>"#));
+        assert!(view.xml_string.contains(r##"fillColor="#b5ffff""##));
+        assert!(view.xml_string.contains(
+            r#"source="node-2" target="node-3" archimateRelationship="relation-1"><bendpoint startY="83" endX="-370" endY="83"/>"#
+        ));
+        assert!(view
+            .xml_string
+            .contains(r#"targetConnections="connection-1""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_archi_to_exchange_round_trip() -> Result<(), Box<dyn Error>> {
+        let xml = exchange_to_archi(EXCHANGE_MODEL)?;
+        let model = load_model(&xml)?;
+
+        let exchange = archi_to_exchange(&model, None)?;
+        assert!(exchange.contains(r#"xsi:type="OrJunction""#));
+        assert!(exchange.contains(
+            r#"xsi:type="Access" source="element-1" target="element-2" accessType="Read""#
+        ));
+        assert!(exchange.contains(r#"viewpoint="Application Cooperation""#));
+        assert!(exchange.contains(r#"<propertyDefinition identifier="propid-1" type="string">"#));
+        let round_trip_xml = exchange_to_archi(&exchange)?;
+        let round_trip = load_model(&round_trip_xml)?;
+        assert_eq!(
+            format_folder_path(&round_trip.element_map["element-1"].folder_path),
+            "Application[application] > Sales"
+        );
+        assert_eq!(
+            round_trip.element_map["element-1"].properties,
+            vec![("Owner".to_string(), "Sales".to_string())]
+        );
+        assert_eq!(
+            round_trip.view_map["view-1"].xml_string,
+            model.view_map["view-1"].xml_string
+        );
+
+        // A selected view brings only the concepts it shows
+        let selected = HashSet::from(["view-1".to_string()]);
+        let exchange = archi_to_exchange(&model, Some(&selected))?;
+        assert!(exchange.contains(r#"identifier="element-2""#));
+        assert!(!exchange.contains(r#"identifier="element-3""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exchange_to_archi_lenient() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <model xmlns="http://www.opengroup.org/xsd/archimate/3.1/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:vendor="http://example.com/vendor" identifier="model-1" vendor:revision="7">
+            <name xml:lang="en">Vendor</name>
+            <elements>
+                <element identifier="element-1" xsi:type="archimate:ApplicationComponent"><name xml:lang="en">Portal</name></element>
+                <element identifier="element-2" xsi:type="DataObject"><name xml:lang="en">Order</name></element>
+                <element xsi:type="Node"><name xml:lang="en">Server</name></element>
+                <element identifier="element-4" xsi:type="VendorWidget"><name xml:lang="en">Widget</name></element>
+            </elements>
+            <relationships>
+                <relationship identifier="relation-1" source="element-1" target="element-2" xsi:type="AccessRelationship"/>
+                <relationship identifier="relation-2" source="element-1" target="element-4" xsi:type="Serving"/>
+            </relationships>
+            <views>
+                <diagrams>
+                    <view identifier="view-1" xsi:type="Diagram">
+                        <name xml:lang="en">Overview</name>
+                        <node identifier="node-1" elementRef="element-1" xsi:type="Element" x="10" y="10"/>
+                        <node identifier="node-2" elementRef="element-4" xsi:type="Element" x="200" y="10" w="120" h="55"/>
+                        <connection identifier="connection-1" relationshipRef="relation-2" xsi:type="Relationship" source="node-1" target="node-2"/>
+                    </view>
+                </diagrams>
+            </views>
+        </model>"#;
+        assert!(!is_exchange_format(xml));
+        assert!(is_lenient_exchange_format(xml));
+
+        let (converted, warnings) = exchange_to_archi_lenient(xml)?;
+        assert_eq!(
+            warnings,
+            vec![
+                "the model is in the namespace \"http://www.opengroup.org/xsd/archimate/3.1/\" instead of http://www.opengroup.org/xsd/archimate/3.0/",
+                "element-1 has the prefixed type archimate:ApplicationComponent",
+                "Open Exchange Node without an identifier",
+                "left out element element-4 of unknown type VendorWidget",
+                "relationship relation-1 has the type AccessRelationship instead of Access",
+                "left out relationship relation-2 to unknown concept element-4",
+                "diagram node node-1 has no w attribute",
+                "diagram node node-1 has no h attribute",
+                "left out diagram node node-2 of unknown element element-4",
+                "left out connection connection-1 of unknown relationship relation-2",
+            ]
+        );
+        let model = load_model(&converted)?;
+        assert_eq!(
+            model.element_map["element-1"].xsi_type.as_ref(),
+            "archimate:ApplicationComponent"
+        );
+        assert!(model
+            .element_map
+            .values()
+            .any(|element| &*element.name == "Server"));
+        assert!(!model.element_map.contains_key("element-4"));
+        assert!(model.element_map["relation-1"]
+            .xsi_type
+            .ends_with(":AccessRelationship"));
+        assert!(model.view_map["view-1"]
+            .xml_string
+            .contains(r#"<bounds x="10" y="10" width="-1" height="-1"/>"#));
+
+        // Read strictly, a missing identifier is an error
+        let strict = xml.replace("/3.1/", "/3.0/");
+        assert!(exchange_to_archi(&strict).is_err());
+        Ok(())
+    }
+}
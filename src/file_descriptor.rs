@@ -1,30 +1,192 @@
+use crate::bundle::BUNDLE_EXTENSION;
+use crate::coarchi;
+use crate::error::ImporterError;
+use crate::exchange::document_element_tag;
 use encoding_rs::UTF_8;
+use std::fmt;
 use std::fs;
-use std::io::{Cursor, Read, Write};
+use std::io::{Cursor, Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+use zip::read::ZipFile;
+use zip::result::{ZipError, ZipResult};
 use zip::write::FileOptions;
-use zip::CompressionMethod;
+use zip::{AesMode, CompressionMethod};
 use zip::{ZipArchive, ZipWriter};
 
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+const ARCHIMATE_NAMESPACE: &str = "http://www.archimatetool.com/archimate";
+
+/// Environment variables holding the passwords of encrypted archives when
+/// `--source-password` or `--target-password` is not given.
+pub const SOURCE_PASSWORD_VARIABLE: &str = "ARCHI_SOURCE_PASSWORD";
+pub const TARGET_PASSWORD_VARIABLE: &str = "ARCHI_TARGET_PASSWORD";
+
 #[derive(Debug)]
 pub enum FileDescriptor {
     PlainXml {
         path: PathBuf,
     },
+    /// A zip archive holding the model, with the password its entries are
+    /// encrypted with, if any.
     ZippedXml {
         zip_path: PathBuf,
         xml_filename: String,
+        password: Option<String>,
+    },
+    /// A coArchi repository, keeping each folder, concept and view in a
+    /// file of its own.
+    WorkingTree {
+        path: PathBuf,
     },
+    /// A model fetched into memory rather than read from disk, such as a
+    /// download or a file from a Git revision, under the name it was
+    /// fetched as. Zipped content names its model entry.
+    Fetched {
+        name: PathBuf,
+        content: Vec<u8>,
+        xml_filename: Option<String>,
+        password: Option<String>,
+        modified: SystemTime,
+    },
+}
+
+/// How a model file is stored on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    PlainXml,
+    ZippedXml,
+    WorkingTree,
+}
+
+impl FileKind {
+    /// Guesses the kind from the file name: `.zip` (including
+    /// `.archimate.zip`) and view bundles mean zipped, `.xml` and
+    /// `.archimate` mean plain XML.
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_string_lossy().to_lowercase();
+        match extension.as_str() {
+            "zip" | BUNDLE_EXTENSION => Some(FileKind::ZippedXml),
+            "xml" | "archimate" => Some(FileKind::PlainXml),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileKind::PlainXml => write!(f, "plain XML"),
+            FileKind::ZippedXml => write!(f, "zipped XML"),
+            FileKind::WorkingTree => write!(f, "coArchi working tree"),
+        }
+    }
+}
+
+/// How the entries of a rewritten archive are compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZipCompression {
+    /// Compress each entry like the entry it replaces, and new entries like
+    /// the model entry, so rewriting does not inflate the archive
+    #[default]
+    Keep,
+    Stored,
+    Deflate(Option<i64>),
+}
+
+impl ZipCompression {
+    /// The method for an entry first compressed with `original`. Kept
+    /// methods other than stored and deflate, which cannot be written, fall
+    /// back to deflate.
+    fn method(&self, original: Option<CompressionMethod>) -> CompressionMethod {
+        match self {
+            ZipCompression::Keep => match original {
+                Some(CompressionMethod::Stored) | None => CompressionMethod::Stored,
+                Some(_) => CompressionMethod::Deflated,
+            },
+            ZipCompression::Stored => CompressionMethod::Stored,
+            ZipCompression::Deflate(_) => CompressionMethod::Deflated,
+        }
+    }
+
+    fn level(&self) -> Option<i64> {
+        match self {
+            ZipCompression::Keep | ZipCompression::Stored => None,
+            ZipCompression::Deflate(level) => *level,
+        }
+    }
+}
+
+impl FromStr for ZipCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (method, level) = match s.split_once(':') {
+            Some((method, level)) => (method, Some(level)),
+            None => (s, None),
+        };
+        match (method.trim().to_lowercase().as_str(), level) {
+            ("keep", None) => Ok(ZipCompression::Keep),
+            ("stored", None) => Ok(ZipCompression::Stored),
+            ("deflate", None) => Ok(ZipCompression::Deflate(None)),
+            ("deflate", Some(level)) => match level.trim().parse::<i64>() {
+                Ok(level) if (0..=9).contains(&level) => Ok(ZipCompression::Deflate(Some(level))),
+                _ => Err(format!("Invalid deflate level: {} (expected 0-9)", level)),
+            },
+            _ => Err(format!(
+                "Invalid compression: {} (expected keep, stored or deflate[:level])",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ZipCompression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZipCompression::Keep => write!(f, "keep"),
+            ZipCompression::Stored => write!(f, "stored"),
+            ZipCompression::Deflate(None) => write!(f, "deflate"),
+            ZipCompression::Deflate(Some(level)) => write!(f, "deflate:{}", level),
+        }
+    }
 }
 
 impl FileDescriptor {
-    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, ImporterError> {
         let path = path.as_ref().to_path_buf();
 
-        // Try as plain XML
+        if path.is_dir() {
+            return match coarchi::find_repository_root(&path) {
+                Some(path) => Ok(FileDescriptor::WorkingTree { path }),
+                None => Err(ImporterError::Validation(format!(
+                    "{} is a directory but not a coArchi working tree",
+                    path.display()
+                ))),
+            };
+        }
+
+        // The extension is only a hint: a wrong guess falls through to
+        // sniffing the content
+        match FileKind::from_extension(&path) {
+            Some(FileKind::PlainXml) if Self::starts_like_xml(&path)? => {
+                return Ok(FileDescriptor::PlainXml { path });
+            }
+            Some(FileKind::ZippedXml) => {
+                if let Some(descriptor) = Self::find_zipped_xml(&path) {
+                    return Ok(descriptor);
+                }
+            }
+            _ => {}
+        }
+
+        // Try as plain XML, skipping zip archives whose stored entries would
+        // otherwise look like XML
         if let Ok(bytes) = fs::read(&path) {
             let (decoded, _had_errors) = UTF_8.decode_without_bom_handling(&bytes);
-            if decoded.contains("<?xml") {
+            if !bytes.starts_with(ZIP_SIGNATURE) && decoded.contains("<?xml") {
                 return Ok(FileDescriptor::PlainXml { path });
             }
         }
@@ -32,23 +194,120 @@ impl FileDescriptor {
         // Try as zip file containing an XML
         if let Ok(file) = fs::File::open(&path) {
             let mut archive = ZipArchive::new(file)?;
-            for i in 0..archive.len() {
-                let file = archive.by_index(i)?;
-                let name = file.name();
-
-                if name.eq("model.xml") {
-                    return Ok(FileDescriptor::ZippedXml {
-                        zip_path: path,
-                        xml_filename: name.to_string(),
-                    });
-                }
+            if let Some(xml_filename) = find_model_entry(&mut archive) {
+                return Ok(FileDescriptor::ZippedXml {
+                    zip_path: path,
+                    xml_filename,
+                    password: None,
+                });
             }
         }
 
-        Err("Could not determine file type or locate XML".into())
+        Err(ImporterError::Validation(
+            "Could not determine file type or locate XML".to_string(),
+        ))
+    }
+
+    /// Describes a model fetched into memory as `name`, modified at
+    /// `modified`. Zipped content is told apart by its signature, and its
+    /// model entry is found as in `from_path`.
+    pub fn fetched<P: Into<PathBuf>>(
+        name: P,
+        content: Vec<u8>,
+        modified: SystemTime,
+    ) -> Result<Self, ImporterError> {
+        let xml_filename = if content.starts_with(ZIP_SIGNATURE) {
+            let mut archive = ZipArchive::new(Cursor::new(content.as_slice()))?;
+            Some(find_model_entry(&mut archive).ok_or_else(|| {
+                ImporterError::Validation("Could not locate the model in the archive".to_string())
+            })?)
+        } else {
+            None
+        };
+        Ok(FileDescriptor::Fetched {
+            name: name.into(),
+            content,
+            xml_filename,
+            password: None,
+            modified,
+        })
+    }
+
+    /// Describes a file to write, which need not exist yet. The kind comes
+    /// from the extension, then from the existing content, and defaults to
+    /// plain XML.
+    pub fn for_output<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let kind = FileKind::from_extension(&path).or_else(|| {
+            FileDescriptor::from_path(&path)
+                .ok()
+                .map(|descriptor| descriptor.kind())
+        });
+        match kind {
+            Some(FileKind::ZippedXml) => FileDescriptor::ZippedXml {
+                zip_path: path,
+                xml_filename: "model.xml".to_string(),
+                password: None,
+            },
+            Some(FileKind::WorkingTree) => FileDescriptor::WorkingTree { path },
+            _ => FileDescriptor::PlainXml { path },
+        }
+    }
+
+    /// Checks only the start of the file, so large plain models are not
+    /// decoded twice.
+    fn starts_like_xml(path: &Path) -> Result<bool, ImporterError> {
+        let mut head = Vec::with_capacity(512);
+        fs::File::open(path)?.take(512).read_to_end(&mut head)?;
+        if head.starts_with(ZIP_SIGNATURE) {
+            return Ok(false);
+        }
+        let (decoded, _) = UTF_8.decode_with_bom_removal(&head);
+        Ok(decoded.trim_start().starts_with('<'))
+    }
+
+    fn find_zipped_xml(path: &Path) -> Option<Self> {
+        let file = fs::File::open(path).ok()?;
+        let mut archive = ZipArchive::new(file).ok()?;
+        let xml_filename = find_model_entry(&mut archive)?;
+        Some(FileDescriptor::ZippedXml {
+            zip_path: path.to_path_buf(),
+            xml_filename,
+            password: None,
+        })
     }
 
-    pub fn read_xml(&self) -> Result<String, Box<dyn std::error::Error>> {
+    /// Sets the password the entries of a zipped file are read with, and
+    /// encrypted with (as AES-256) when written. Other files ignore it.
+    pub fn with_password(self, password: Option<String>) -> Self {
+        match self {
+            FileDescriptor::ZippedXml {
+                zip_path,
+                xml_filename,
+                ..
+            } => FileDescriptor::ZippedXml {
+                zip_path,
+                xml_filename,
+                password,
+            },
+            FileDescriptor::Fetched {
+                name,
+                content,
+                xml_filename,
+                modified,
+                ..
+            } => FileDescriptor::Fetched {
+                name,
+                content,
+                xml_filename,
+                password,
+                modified,
+            },
+            other => other,
+        }
+    }
+
+    pub fn read_xml(&self) -> Result<String, ImporterError> {
         match self {
             FileDescriptor::PlainXml { path, .. } => {
                 let bytes = fs::read(path)?;
@@ -58,61 +317,472 @@ impl FileDescriptor {
             FileDescriptor::ZippedXml {
                 zip_path,
                 xml_filename,
-                ..
+                password,
             } => {
                 let file = fs::File::open(zip_path)?;
                 let mut archive = ZipArchive::new(file)?;
 
-                let mut xml_file = archive.by_name(xml_filename)?;
+                let mut xml_file = open_entry(&mut archive, xml_filename, password.as_deref())
+                    .map_err(|e| entry_error(zip_path, e))?;
                 let mut buffer = Vec::new();
                 xml_file.read_to_end(&mut buffer)?;
 
                 let (decoded, _, _) = UTF_8.decode(&buffer);
                 Ok(decoded.into())
             }
+            FileDescriptor::WorkingTree { path } => Ok(coarchi::read_working_tree(path)?),
+            FileDescriptor::Fetched {
+                content, password, ..
+            } => read_xml_from(Cursor::new(content.as_slice()), password.as_deref()),
         }
     }
 
-    pub fn write_xml(&self, new_xml: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn path(&self) -> &Path {
         match self {
+            FileDescriptor::PlainXml { path } => path,
+            FileDescriptor::ZippedXml { zip_path, .. } => zip_path,
+            FileDescriptor::WorkingTree { path } => path,
+            FileDescriptor::Fetched { name, .. } => name,
+        }
+    }
+
+    /// When the model was last modified, for `--on-conflict newer`.
+    pub fn modified(&self) -> std::io::Result<SystemTime> {
+        match self {
+            FileDescriptor::Fetched { modified, .. } => Ok(*modified),
+            other => fs::metadata(other.path())?.modified(),
+        }
+    }
+
+    pub fn is_zipped(&self) -> bool {
+        matches!(
+            self,
+            FileDescriptor::ZippedXml { .. }
+                | FileDescriptor::Fetched {
+                    xml_filename: Some(_),
+                    ..
+                }
+        )
+    }
+
+    /// Whether the file can hold images next to the model: archives hold
+    /// them as entries and coArchi repositories as files.
+    pub fn holds_images(&self) -> bool {
+        self.is_zipped() || matches!(self, FileDescriptor::WorkingTree { .. })
+    }
+
+    pub fn kind(&self) -> FileKind {
+        match self {
+            FileDescriptor::PlainXml { .. } => FileKind::PlainXml,
+            FileDescriptor::ZippedXml { .. } => FileKind::ZippedXml,
+            FileDescriptor::WorkingTree { .. } => FileKind::WorkingTree,
+            FileDescriptor::Fetched { .. } if self.is_zipped() => FileKind::ZippedXml,
+            FileDescriptor::Fetched { .. } => FileKind::PlainXml,
+        }
+    }
+
+    /// Reads an entry other than the model XML from a zipped file, or the
+    /// file at that path in a coArchi repository. Returns `None` when the
+    /// entry does not exist or the file is plain XML.
+    pub fn read_entry(&self, entry_name: &str) -> Result<Option<Vec<u8>>, ImporterError> {
+        match self {
+            FileDescriptor::PlainXml { .. } => Ok(None),
+            FileDescriptor::ZippedXml {
+                zip_path, password, ..
+            } => {
+                let file = fs::File::open(zip_path)?;
+                let mut archive = ZipArchive::new(file)?;
+                read_archive_entry(&mut archive, zip_path, entry_name, password.as_deref())
+            }
+            FileDescriptor::Fetched {
+                name,
+                content,
+                xml_filename: Some(_),
+                password,
+                ..
+            } => {
+                let mut archive = ZipArchive::new(Cursor::new(content.as_slice()))?;
+                read_archive_entry(&mut archive, name, entry_name, password.as_deref())
+            }
+            FileDescriptor::Fetched { .. } => Ok(None),
+            FileDescriptor::WorkingTree { path } => {
+                let entry_path = path.join(entry_name);
+                if !entry_path.is_file() {
+                    return Ok(None);
+                }
+                Ok(Some(fs::read(entry_path)?))
+            }
+        }
+    }
+
+    /// Lists the entries of a zipped file other than the model XML, or the
+    /// images of a coArchi repository. Plain XML files have none.
+    pub fn entry_names(&self) -> Result<Vec<String>, ImporterError> {
+        match self {
+            FileDescriptor::PlainXml { .. } => Ok(Vec::new()),
+            FileDescriptor::ZippedXml {
+                zip_path,
+                xml_filename,
+                ..
+            } => {
+                let file = fs::File::open(zip_path)?;
+                Ok(other_entry_names(&ZipArchive::new(file)?, xml_filename))
+            }
+            FileDescriptor::Fetched {
+                content,
+                xml_filename: Some(xml_filename),
+                ..
+            } => {
+                let archive = ZipArchive::new(Cursor::new(content.as_slice()))?;
+                Ok(other_entry_names(&archive, xml_filename))
+            }
+            FileDescriptor::Fetched { .. } => Ok(Vec::new()),
+            FileDescriptor::WorkingTree { path } => {
+                let Ok(images) = fs::read_dir(path.join("images")) else {
+                    return Ok(Vec::new());
+                };
+                let mut names = Vec::new();
+                for image in images {
+                    let image = image?;
+                    if image.path().is_file() {
+                        names.push(format!("images/{}", image.file_name().to_string_lossy()));
+                    }
+                }
+                names.sort();
+                Ok(names)
+            }
+        }
+    }
+
+    /// Writes the model XML together with extra archive entries, replacing
+    /// entries with the same name and appending new ones. Extra entries are
+    /// only supported for zipped files.
+    pub fn write_xml(
+        &self,
+        new_xml: &str,
+        entries: &[(&str, &[u8])],
+        compression: ZipCompression,
+    ) -> Result<(), ImporterError> {
+        self.write_xml_to(self, new_xml, entries, compression)
+    }
+
+    /// Like `write_xml`, but writes to `destination` in its own format. When
+    /// both are zipped, the other entries of this file are carried over,
+    /// encrypted with the destination's password when it has one. A working
+    /// tree gets only the fragment files that change, and entries as files
+    /// below its root.
+    pub fn write_xml_to(
+        &self,
+        destination: &FileDescriptor,
+        new_xml: &str,
+        entries: &[(&str, &[u8])],
+        compression: ZipCompression,
+    ) -> Result<(), ImporterError> {
+        match destination {
             FileDescriptor::PlainXml { path, .. } => {
+                if !entries.is_empty() {
+                    return Err(ImporterError::Other(
+                        "Plain XML files cannot hold extra entries".to_string(),
+                    ));
+                }
                 fs::write(path, new_xml.as_bytes())?;
             }
             FileDescriptor::ZippedXml {
                 zip_path,
                 xml_filename,
-                ..
+                password,
             } => {
-                let zip_data = fs::read(zip_path)?;
-                let reader = Cursor::new(zip_data);
-                let mut archive = ZipArchive::new(reader)?;
+                let archive = write_archive(
+                    Some(self),
+                    xml_filename,
+                    password.as_deref(),
+                    new_xml,
+                    entries,
+                    compression,
+                )?;
+                fs::write(zip_path, archive)?;
+            }
+            FileDescriptor::WorkingTree { path } => {
+                coarchi::write_working_tree(path, new_xml)?;
+                for (entry_name, content) in entries {
+                    let entry_path = path.join(entry_name);
+                    if let Some(parent) = entry_path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::write(entry_path, content)?;
+                }
+            }
+            FileDescriptor::Fetched { name, .. } => {
+                return Err(ImporterError::Other(format!(
+                    "{} was fetched into memory and cannot be written",
+                    name.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
 
-                let mut buffer = Cursor::new(Vec::new());
-                let mut zip_writer = ZipWriter::new(&mut buffer);
+/// Reads `entry_name` from an archive, or `None` when it has no such entry.
+fn read_archive_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    path: &Path,
+    entry_name: &str,
+    password: Option<&str>,
+) -> Result<Option<Vec<u8>>, ImporterError> {
+    let mut entry = match open_entry(archive, entry_name, password) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(entry_error(path, e)),
+    };
+    let mut buffer = Vec::new();
+    entry.read_to_end(&mut buffer)?;
+    Ok(Some(buffer))
+}
 
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i)?;
-                    let name = file.name().to_string();
+/// The entries of an archive other than its model entry.
+fn other_entry_names<R: Read + Seek>(archive: &ZipArchive<R>, xml_filename: &str) -> Vec<String> {
+    archive
+        .file_names()
+        .filter(|name| *name != xml_filename)
+        .map(|name| name.to_string())
+        .collect()
+}
 
-                    let options: FileOptions<()> =
-                        FileOptions::default().compression_method(CompressionMethod::Stored);
+/// Reads the model XML from `reader`, which holds either plain XML or an
+/// archive, told apart by the zip signature as files are. The model entry of
+/// an archive is found as in `from_path`, and decrypted with `password`.
+pub fn read_xml_from<R: Read + Seek>(
+    mut reader: R,
+    password: Option<&str>,
+) -> Result<String, ImporterError> {
+    let mut head = Vec::with_capacity(ZIP_SIGNATURE.len());
+    (&mut reader)
+        .take(ZIP_SIGNATURE.len() as u64)
+        .read_to_end(&mut head)?;
+    reader.rewind()?;
 
-                    zip_writer.start_file(name.clone(), options)?;
+    let mut buffer = Vec::new();
+    if head == ZIP_SIGNATURE {
+        let mut archive = ZipArchive::new(reader)?;
+        let xml_filename = find_model_entry(&mut archive).ok_or_else(|| {
+            ImporterError::Validation("Could not locate the model in the archive".to_string())
+        })?;
+        open_entry(&mut archive, &xml_filename, password)
+            .map_err(|e| entry_error(Path::new("the archive"), e))?
+            .read_to_end(&mut buffer)?;
+    } else {
+        reader.read_to_end(&mut buffer)?;
+    }
+    let (decoded, _, _) = UTF_8.decode(&buffer);
+    Ok(decoded.into())
+}
 
-                    if name == *xml_filename {
-                        zip_writer.write_all(new_xml.as_bytes())?;
-                    } else {
-                        let mut content = Vec::new();
-                        file.read_to_end(&mut content)?;
-                        zip_writer.write_all(&content)?;
-                    }
-                }
+/// Writes the model XML to `writer` as a file of `kind` would hold it, so
+/// it can go to a buffer, a socket or an upload rather than a path. A zipped
+/// model holds the XML as `model.xml`, or under the name it has in `source`,
+/// whose other entries are carried over when it is zipped too. A working
+/// tree is a directory, so it cannot be written this way.
+pub fn write_xml_into<W: Write>(
+    mut writer: W,
+    kind: FileKind,
+    source: Option<&FileDescriptor>,
+    new_xml: &str,
+    entries: &[(&str, &[u8])],
+    compression: ZipCompression,
+) -> Result<(), ImporterError> {
+    match kind {
+        FileKind::PlainXml => {
+            if !entries.is_empty() {
+                return Err(ImporterError::Other(
+                    "Plain XML files cannot hold extra entries".to_string(),
+                ));
+            }
+            writer.write_all(new_xml.as_bytes())?;
+        }
+        FileKind::ZippedXml => {
+            // The entries stay encrypted with the password they were read with
+            let (xml_filename, password) = match source {
+                Some(FileDescriptor::ZippedXml {
+                    xml_filename,
+                    password,
+                    ..
+                })
+                | Some(FileDescriptor::Fetched {
+                    xml_filename: Some(xml_filename),
+                    password,
+                    ..
+                }) => (xml_filename.as_str(), password.as_deref()),
+                _ => ("model.xml", None),
+            };
+            let archive = write_archive(
+                source,
+                xml_filename,
+                password,
+                new_xml,
+                entries,
+                compression,
+            )?;
+            writer.write_all(&archive)?;
+        }
+        FileKind::WorkingTree => {
+            return Err(ImporterError::Other(
+                "A coArchi working tree cannot be written to a stream".to_string(),
+            ));
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Builds a zipped model holding `new_xml` as `xml_filename` and `entries`.
+/// When `source` is zipped, its other entries are carried over.
+fn write_archive(
+    source: Option<&FileDescriptor>,
+    xml_filename: &str,
+    password: Option<&str>,
+    new_xml: &str,
+    entries: &[(&str, &[u8])],
+    compression: ZipCompression,
+) -> Result<Vec<u8>, ImporterError> {
+    let mut buffer = Cursor::new(Vec::new());
+    let mut zip_writer = ZipWriter::new(&mut buffer);
+    let options = |original: Option<CompressionMethod>| {
+        let options: FileOptions<()> = FileOptions::default()
+            .compression_method(compression.method(original))
+            .compression_level(compression.level());
+        // The zip crate only writes AES, so archives protected
+        // with ZipCrypto are rewritten with AES-256
+        match password {
+            Some(password) => options.with_aes_encryption(AesMode::Aes256, password),
+            None => options,
+        }
+    };
+    let mut written = Vec::new();
+    let mut model_method = None;
+
+    let source_archive = match source {
+        Some(FileDescriptor::ZippedXml {
+            zip_path,
+            xml_filename,
+            password,
+        }) => Some((zip_path, fs::read(zip_path)?, xml_filename, password)),
+        Some(FileDescriptor::Fetched {
+            name,
+            content,
+            xml_filename: Some(xml_filename),
+            password,
+            ..
+        }) => Some((name, content.clone(), xml_filename, password)),
+        _ => None,
+    };
+    if let Some((source_path, zip_data, source_xml_filename, source_password)) = source_archive {
+        let reader = Cursor::new(zip_data);
+        let mut archive = ZipArchive::new(reader)?;
+
+        for i in 0..archive.len() {
+            let mut file = match source_password {
+                Some(password) => archive.by_index_decrypt(i, password.as_bytes()),
+                None => archive.by_index(i),
+            }
+            .map_err(|e| entry_error(source_path, e))?;
+            let name = file.name().to_string();
+            let method = file.compression();
 
-                zip_writer.finish()?;
-                fs::write(zip_path, buffer.into_inner())?;
+            if name == *source_xml_filename {
+                model_method = Some(method);
+                zip_writer.start_file(xml_filename, options(Some(method)))?;
+                zip_writer.write_all(new_xml.as_bytes())?;
+                written.push(xml_filename.to_string());
+                continue;
             }
+            zip_writer.start_file(name.clone(), options(Some(method)))?;
+            if let Some((_, content)) = entries.iter().find(|(entry_name, _)| *entry_name == name) {
+                zip_writer.write_all(content)?;
+            } else {
+                let mut content = Vec::new();
+                file.read_to_end(&mut content)?;
+                zip_writer.write_all(&content)?;
+            }
+            written.push(name);
         }
-        Ok(())
+    }
+
+    if !written.iter().any(|name| name == xml_filename) {
+        zip_writer.start_file(xml_filename, options(model_method))?;
+        zip_writer.write_all(new_xml.as_bytes())?;
+    }
+    for (entry_name, content) in entries {
+        if !written.iter().any(|name| name == entry_name) {
+            zip_writer.start_file(*entry_name, options(model_method))?;
+            zip_writer.write_all(content)?;
+        }
+    }
+
+    zip_writer.finish()?;
+    Ok(buffer.into_inner())
+}
+
+/// Names the entry of an archive holding the model: `model.xml` as Archi
+/// saves it, or else the first entry, by name, whose document element is an
+/// Archi `model`, as other tools may name or nest it differently. Encrypted
+/// entries cannot be looked into, so only `model.xml` is found among them.
+fn find_model_entry<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<String> {
+    if archive.file_names().any(|name| name == "model.xml") {
+        return Some("model.xml".to_string());
+    }
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .map(|name| name.to_string())
+        .collect();
+    names.sort();
+    names.into_iter().find(|name| {
+        let Ok(entry) = archive.by_name(name) else {
+            return false;
+        };
+        // The document element is near the start
+        let mut head = Vec::new();
+        entry.take(4096).read_to_end(&mut head).is_ok()
+            && is_archi_model(&String::from_utf8_lossy(&head))
+    })
+}
+
+/// Whether the document element of `xml` is a `model` in the Archi
+/// namespace, under any prefix.
+fn is_archi_model(xml: &str) -> bool {
+    document_element_tag(xml).is_some_and(|tag| {
+        let name = tag[1..].split_whitespace().next().unwrap_or_default();
+        let local_name = name.rsplit(':').next().unwrap_or_default();
+        local_name == "model" && tag.contains(ARCHIMATE_NAMESPACE)
+    })
+}
+
+/// Opens an entry of an archive, decrypting it with `password` when it is
+/// encrypted.
+fn open_entry<'a, R: Read + std::io::Seek>(
+    archive: &'a mut ZipArchive<R>,
+    name: &str,
+    password: Option<&str>,
+) -> ZipResult<ZipFile<'a, R>> {
+    match password {
+        Some(password) => archive.by_name_decrypt(name, password.as_bytes()),
+        None => archive.by_name(name),
+    }
+}
+
+/// Explains the errors of reading an encrypted entry without the right
+/// password.
+fn entry_error(zip_path: &Path, error: ZipError) -> ImporterError {
+    match error {
+        ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => ImporterError::Other(format!(
+            "{} is password-protected; a password is needed to read it",
+            zip_path.display()
+        )),
+        ZipError::InvalidPassword => {
+            ImporterError::Other(format!("wrong password for {}", zip_path.display()))
+        }
+        error => error.into(),
     }
 }
 
@@ -156,6 +826,7 @@ mod tests {
             FileDescriptor::ZippedXml {
                 zip_path: path,
                 xml_filename,
+                ..
             } => {
                 assert_eq!(path, zip_path);
                 assert_eq!(xml_filename, "model.xml");
@@ -176,7 +847,7 @@ mod tests {
         assert_eq!(descriptor.read_xml()?, initial_content);
 
         let new_content = "<?xml version=\"1.0\"?><root><child/></root>";
-        descriptor.write_xml(new_content)?;
+        descriptor.write_xml(new_content, &[], ZipCompression::default())?;
         assert_eq!(descriptor.read_xml()?, new_content);
 
         Ok(())
@@ -200,12 +871,226 @@ mod tests {
         assert_eq!(descriptor.read_xml()?, initial_content);
 
         let new_content = "<?xml version=\"1.0\"?><root><child/></root>";
-        descriptor.write_xml(new_content)?;
+        descriptor.write_xml(new_content, &[], ZipCompression::default())?;
         assert_eq!(descriptor.read_xml()?, new_content);
 
         Ok(())
     }
 
+    #[test]
+    fn test_write_zipped_xml_deflate() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("test.zip");
+        let content = format!(
+            "<?xml version=\"1.0\"?><root>{}</root>",
+            "<child/>".repeat(500)
+        );
+
+        {
+            let file = fs::File::create(&zip_path)?;
+            let mut zip = ZipWriter::new(file);
+            zip.start_file::<_, ()>("model.xml", FileOptions::default())?;
+            zip.write_all(b"<?xml version=\"1.0\"?><root></root>")?;
+            zip.finish()?;
+        }
+
+        let descriptor = FileDescriptor::from_path(&zip_path)?;
+        descriptor.write_xml(&content, &[], ZipCompression::Stored)?;
+        let stored_size = fs::metadata(&zip_path)?.len();
+        descriptor.write_xml(&content, &[], "deflate:9".parse()?)?;
+        let deflated_size = fs::metadata(&zip_path)?.len();
+
+        assert!(deflated_size < stored_size);
+        assert_eq!(descriptor.read_xml()?, content);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_path_other_model_entry() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("export.zip");
+        let content = "<?xml version=\"1.0\"?>\n<archimate:model xmlns:archimate=\"http://www.archimatetool.com/archimate\" name=\"Export\"/>";
+
+        {
+            let file = fs::File::create(&zip_path)?;
+            let mut zip = ZipWriter::new(file);
+            zip.start_file::<_, ()>("docs/readme.xml", FileOptions::default())?;
+            zip.write_all(b"<?xml version=\"1.0\"?><readme/>")?;
+            zip.start_file::<_, ()>("export/Enterprise.archimate", FileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+            zip.finish()?;
+        }
+
+        let descriptor = FileDescriptor::from_path(&zip_path)?;
+        match &descriptor {
+            FileDescriptor::ZippedXml { xml_filename, .. } => {
+                assert_eq!(xml_filename, "export/Enterprise.archimate")
+            }
+            _ => return Err("Expected ZippedXml variant".into()),
+        }
+        assert_eq!(descriptor.read_xml()?, content);
+
+        // The model is rewritten in its own entry
+        let new_content = content.replace("Export", "Changed");
+        descriptor.write_xml(&new_content, &[], ZipCompression::default())?;
+        assert_eq!(descriptor.read_xml()?, new_content);
+        assert_eq!(
+            descriptor.entry_names()?,
+            vec!["docs/readme.xml".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_write_encrypted_zip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("test.zip");
+        let content = "<?xml version=\"1.0\"?><root></root>";
+
+        {
+            let file = fs::File::create(&zip_path)?;
+            let mut zip = ZipWriter::new(file);
+            let options: FileOptions<()> =
+                FileOptions::default().with_aes_encryption(AesMode::Aes128, "secret");
+            zip.start_file("model.xml", options)?;
+            zip.write_all(content.as_bytes())?;
+            zip.start_file("extra.txt", options)?;
+            zip.write_all(b"extra")?;
+            zip.finish()?;
+        }
+
+        let descriptor = FileDescriptor::from_path(&zip_path)?;
+        let error = descriptor.read_xml().unwrap_err().to_string();
+        assert!(error.contains("is password-protected"));
+        let descriptor = descriptor.with_password(Some("wrong".to_string()));
+        assert!(descriptor
+            .read_xml()
+            .unwrap_err()
+            .to_string()
+            .starts_with("wrong password"));
+
+        let descriptor = descriptor.with_password(Some("secret".to_string()));
+        assert_eq!(descriptor.read_xml()?, content);
+        let new_content = "<?xml version=\"1.0\"?><root><child/></root>";
+        descriptor.write_xml(new_content, &[], ZipCompression::default())?;
+        assert_eq!(descriptor.read_xml()?, new_content);
+        assert_eq!(descriptor.read_entry("extra.txt")?, Some(b"extra".to_vec()));
+
+        // Every entry stays encrypted
+        let mut archive = ZipArchive::new(fs::File::open(&zip_path)?)?;
+        for i in 0..archive.len() {
+            assert!(archive.by_index_raw(i)?.encrypted());
+        }
+
+        // Also when written to a stream
+        let mut buffer = Vec::new();
+        write_xml_into(
+            &mut buffer,
+            FileKind::ZippedXml,
+            Some(&descriptor),
+            content,
+            &[],
+            ZipCompression::default(),
+        )?;
+        let mut archive = ZipArchive::new(Cursor::new(buffer))?;
+        for i in 0..archive.len() {
+            assert!(archive.by_index_raw(i)?.encrypted());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_zipped_xml_keeps_compression() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("test.zip");
+        let content = "<?xml version=\"1.0\"?><root></root>";
+
+        {
+            let file = fs::File::create(&zip_path)?;
+            let mut zip = ZipWriter::new(file);
+            let deflated: FileOptions<()> =
+                FileOptions::default().compression_method(CompressionMethod::Deflated);
+            zip.start_file("model.xml", deflated)?;
+            zip.write_all(content.as_bytes())?;
+            let stored: FileOptions<()> =
+                FileOptions::default().compression_method(CompressionMethod::Stored);
+            zip.start_file("stored.txt", stored)?;
+            zip.write_all(b"stored")?;
+            zip.finish()?;
+        }
+
+        let descriptor = FileDescriptor::from_path(&zip_path)?;
+        descriptor.write_xml(content, &[("new.txt", b"new")], ZipCompression::Keep)?;
+        let mut archive = ZipArchive::new(fs::File::open(&zip_path)?)?;
+        let method = |archive: &mut ZipArchive<fs::File>, name: &str| {
+            archive.by_name(name).map(|entry| entry.compression())
+        };
+        assert_eq!(
+            method(&mut archive, "model.xml")?,
+            CompressionMethod::Deflated
+        );
+        assert_eq!(
+            method(&mut archive, "stored.txt")?,
+            CompressionMethod::Stored
+        );
+        // New entries follow the model
+        assert_eq!(
+            method(&mut archive, "new.txt")?,
+            CompressionMethod::Deflated
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_zip_compression() {
+        assert_eq!("keep".parse(), Ok(ZipCompression::Keep));
+        assert_eq!("stored".parse(), Ok(ZipCompression::Stored));
+        assert_eq!("deflate".parse(), Ok(ZipCompression::Deflate(None)));
+        assert_eq!("deflate:6".parse(), Ok(ZipCompression::Deflate(Some(6))));
+        assert!("deflate:10".parse::<ZipCompression>().is_err());
+        assert!("stored:1".parse::<ZipCompression>().is_err());
+        assert!("bzip2".parse::<ZipCompression>().is_err());
+    }
+
+    #[test]
+    fn test_add_and_update_entries() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("test.zip");
+        let content = "<?xml version=\"1.0\"?><root></root>";
+
+        {
+            let file = fs::File::create(&zip_path)?;
+            let mut zip = ZipWriter::new(file);
+            zip.start_file::<_, ()>("model.xml", FileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+            zip.finish()?;
+        }
+
+        let descriptor = FileDescriptor::from_path(&zip_path)?;
+        assert_eq!(descriptor.read_entry("extra.txt")?, None);
+        assert!(descriptor.entry_names()?.is_empty());
+
+        descriptor.write_xml(
+            content,
+            &[("extra.txt", b"first")],
+            ZipCompression::default(),
+        )?;
+        assert_eq!(descriptor.read_entry("extra.txt")?, Some(b"first".to_vec()));
+        assert_eq!(descriptor.entry_names()?, vec!["extra.txt".to_string()]);
+
+        descriptor.write_xml(
+            content,
+            &[("extra.txt", b"second")],
+            ZipCompression::default(),
+        )?;
+        assert_eq!(
+            descriptor.read_entry("extra.txt")?,
+            Some(b"second".to_vec())
+        );
+        assert_eq!(descriptor.read_xml()?, content);
+        Ok(())
+    }
+
     #[test]
     fn test_invalid_file() {
         let dir = tempdir().unwrap();
@@ -214,4 +1099,185 @@ mod tests {
 
         assert!(FileDescriptor::from_path(&file_path).is_err());
     }
+
+    #[test]
+    fn test_extension_hint_falls_back_to_sniffing() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let content = "<?xml version=\"1.0\"?><root></root>";
+
+        // A zipped model saved with a plain extension is still detected
+        let zip_path = dir.path().join("model.archimate");
+        {
+            let file = fs::File::create(&zip_path)?;
+            let mut zip = ZipWriter::new(file);
+            zip.start_file::<_, ()>("model.xml", FileOptions::default())?;
+            zip.write_all(content.as_bytes())?;
+            zip.finish()?;
+        }
+        assert_eq!(
+            FileDescriptor::from_path(&zip_path)?.kind(),
+            FileKind::ZippedXml
+        );
+
+        let xml_path = dir.path().join("model.archimate.zip");
+        fs::write(&xml_path, content)?;
+        assert_eq!(
+            FileDescriptor::from_path(&xml_path)?.kind(),
+            FileKind::PlainXml
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_xml_into() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("model.zip");
+        let content = "<?xml version=\"1.0\"?><root></root>";
+        let mut zip_writer = ZipWriter::new(fs::File::create(&zip_path)?);
+        zip_writer.start_file::<_, ()>("model.xml", FileOptions::default())?;
+        zip_writer.write_all(content.as_bytes())?;
+        zip_writer.start_file::<_, ()>("images/a.png", FileOptions::default())?;
+        zip_writer.write_all(b"png")?;
+        zip_writer.finish()?;
+        let descriptor = FileDescriptor::from_path(&zip_path)?;
+
+        let new_xml = "<?xml version=\"1.0\"?><root>new</root>";
+        let mut plain = Vec::new();
+        write_xml_into(
+            &mut plain,
+            FileKind::PlainXml,
+            Some(&descriptor),
+            new_xml,
+            &[],
+            ZipCompression::default(),
+        )?;
+        assert_eq!(plain, new_xml.as_bytes());
+
+        let mut zipped = Cursor::new(Vec::new());
+        write_xml_into(
+            &mut zipped,
+            FileKind::ZippedXml,
+            Some(&descriptor),
+            new_xml,
+            &[],
+            ZipCompression::default(),
+        )?;
+        let mut archive = ZipArchive::new(zipped)?;
+        let mut xml = String::new();
+        archive.by_name("model.xml")?.read_to_string(&mut xml)?;
+        assert_eq!(xml, new_xml);
+        assert!(archive.by_name("images/a.png").is_ok());
+
+        let mut fresh = Cursor::new(Vec::new());
+        write_xml_into(
+            &mut fresh,
+            FileKind::ZippedXml,
+            None,
+            new_xml,
+            &[],
+            ZipCompression::default(),
+        )?;
+        assert_eq!(
+            ZipArchive::new(fresh)?.file_names().collect::<Vec<_>>(),
+            ["model.xml"]
+        );
+
+        assert!(write_xml_into(
+            Vec::new(),
+            FileKind::WorkingTree,
+            None,
+            new_xml,
+            &[],
+            ZipCompression::default(),
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_xml_from() -> Result<(), Box<dyn std::error::Error>> {
+        let content = fs::read_to_string("tests/test1.archimate")?;
+        assert_eq!(
+            read_xml_from(Cursor::new(content.as_bytes()), None)?,
+            content
+        );
+
+        let mut zipped = Cursor::new(Vec::new());
+        let mut zip_writer = ZipWriter::new(&mut zipped);
+        let options: FileOptions<()> =
+            FileOptions::default().with_aes_encryption(AesMode::Aes256, "secret");
+        zip_writer.start_file("model.xml", options)?;
+        zip_writer.write_all(content.as_bytes())?;
+        zip_writer.finish()?;
+        let bytes = zipped.into_inner();
+        assert_eq!(read_xml_from(Cursor::new(&bytes), Some("secret"))?, content);
+        let error = read_xml_from(Cursor::new(&bytes), None).unwrap_err();
+        assert!(error.to_string().contains("password-protected"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetched() -> Result<(), Box<dyn std::error::Error>> {
+        let content = fs::read_to_string("tests/test1.archimate")?;
+        let modified = SystemTime::UNIX_EPOCH;
+        let plain = FileDescriptor::fetched("model.archimate", content.clone().into(), modified)?;
+        assert_eq!(plain.kind(), FileKind::PlainXml);
+        assert_eq!(plain.read_xml()?, content);
+        assert_eq!(plain.modified()?, modified);
+        assert!(plain.entry_names()?.is_empty());
+
+        let mut zipped = Cursor::new(Vec::new());
+        let mut zip_writer = ZipWriter::new(&mut zipped);
+        zip_writer.start_file("model.xml", FileOptions::<()>::default())?;
+        zip_writer.write_all(content.as_bytes())?;
+        zip_writer.start_file("images/logo.png", FileOptions::<()>::default())?;
+        zip_writer.write_all(b"png")?;
+        zip_writer.finish()?;
+        let zipped = FileDescriptor::fetched("model.zip", zipped.into_inner(), modified)?;
+        assert!(zipped.holds_images());
+        assert_eq!(zipped.read_xml()?, content);
+        assert_eq!(zipped.entry_names()?, ["images/logo.png"]);
+        assert_eq!(zipped.read_entry("images/logo.png")?, Some(b"png".to_vec()));
+
+        let mut rewritten = Vec::new();
+        write_xml_into(
+            &mut rewritten,
+            FileKind::ZippedXml,
+            Some(&zipped),
+            "<model/>",
+            &[],
+            ZipCompression::Keep,
+        )?;
+        let rewritten = FileDescriptor::fetched("model.zip", rewritten, modified)?;
+        assert_eq!(rewritten.read_xml()?, "<model/>");
+        assert_eq!(rewritten.entry_names()?, ["images/logo.png"]);
+        assert!(zipped
+            .write_xml("<model/>", &[], ZipCompression::Keep)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_xml_to_other_format() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let xml_path = dir.path().join("model.xml");
+        let content = "<?xml version=\"1.0\"?><root></root>";
+        fs::write(&xml_path, content)?;
+
+        let descriptor = FileDescriptor::from_path(&xml_path)?;
+        let output = FileDescriptor::for_output(dir.path().join("copy.archimate.zip"));
+        assert_eq!(output.kind(), FileKind::ZippedXml);
+        descriptor.write_xml_to(
+            &output,
+            content,
+            &[("extra.txt", b"extra")],
+            ZipCompression::default(),
+        )?;
+
+        let written = FileDescriptor::from_path(output.path())?;
+        assert_eq!(written.read_xml()?, content);
+        assert_eq!(written.read_entry("extra.txt")?, Some(b"extra".to_vec()));
+        assert_eq!(fs::read_to_string(&xml_path)?, content);
+        Ok(())
+    }
 }
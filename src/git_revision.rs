@@ -0,0 +1,91 @@
+use crate::file_descriptor::FileDescriptor;
+use std::error::Error;
+
+/// Splits a `--source-ref` such as `origin/main:models/enterprise.archimate`
+/// into the revision and the path of the model in it.
+pub fn split_reference(reference: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    match reference.split_once(':') {
+        Some((revision, path)) if !revision.is_empty() && !path.is_empty() => Ok((revision, path)),
+        _ => Err(format!(
+            "Invalid source reference {}: expected <revision>:<path>, e.g. origin/main:models/enterprise.archimate",
+            reference
+        )
+        .into()),
+    }
+}
+
+/// Reads the model a `<revision>:<path>` reference names from the Git
+/// repository around the working directory, without checking it out. The
+/// path is relative to the repository root, or to the working directory
+/// when it starts with `./`. The blob is read into memory, and counts as
+/// modified when the revision was committed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract(reference: &str) -> Result<FileDescriptor, Box<dyn Error>> {
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let (revision, path) = split_reference(reference)?;
+    let repository =
+        gix::discover(".").map_err(|e| format!("Cannot open the Git repository: {}", e))?;
+    let path = match path.strip_prefix("./") {
+        Some(relative) => match repository.prefix()? {
+            Some(prefix) => prefix.join(relative).to_string_lossy().replace('\\', "/"),
+            None => relative.to_string(),
+        },
+        None => path.to_string(),
+    };
+    let blob = repository
+        .rev_parse_single(format!("{}:{}", revision, path).as_str())
+        .map_err(|e| format!("Cannot read {} from Git: {}", reference, e))?
+        .object()?
+        .try_into_blob()
+        .map_err(|e| format!("Cannot read {} from Git: {}", reference, e))?;
+
+    let file_name = Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "model.archimate".to_string());
+    // When the commit the revision points at was made
+    let committed = repository
+        .rev_parse_single(revision)
+        .ok()
+        .and_then(|id| id.object().ok()?.peel_to_commit().ok()?.time().ok())
+        .and_then(|time| u64::try_from(time.seconds).ok())
+        .map_or_else(SystemTime::now, |seconds| {
+            UNIX_EPOCH + Duration::from_secs(seconds)
+        });
+    Ok(FileDescriptor::fetched(
+        file_name,
+        blob.detach().data,
+        committed,
+    )?)
+}
+
+/// Git repositories cannot be opened in the browser.
+#[cfg(target_arch = "wasm32")]
+pub fn extract(reference: &str) -> Result<FileDescriptor, Box<dyn Error>> {
+    Err(format!("Cannot read {} from Git in this build", reference).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reference() {
+        assert_eq!(
+            split_reference("origin/main:models/enterprise.archimate").unwrap(),
+            ("origin/main", "models/enterprise.archimate")
+        );
+        assert_eq!(
+            split_reference("HEAD~2:./model.archimate").unwrap(),
+            ("HEAD~2", "./model.archimate")
+        );
+        for reference in ["origin/main", ":model.archimate", "origin/main:"] {
+            assert!(split_reference(reference)
+                .unwrap_err()
+                .to_string()
+                .contains("expected <revision>:<path>"));
+        }
+    }
+}
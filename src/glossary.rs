@@ -0,0 +1,128 @@
+/// One-line descriptions of ArchiMate 3.2 concepts, keyed by the Archi
+/// `xsi:type` name without the `archimate:` prefix.
+const GLOSSARY: &[(&str, &str)] = &[
+    // Strategy
+    ("Resource", "An asset owned or controlled by an individual or organization"),
+    ("Capability", "An ability that an active structure element possesses"),
+    ("ValueStream", "A sequence of activities that create an overall result for a customer or stakeholder"),
+    ("CourseOfAction", "An approach or plan for configuring capabilities and resources to achieve a goal"),
+    // Business
+    ("BusinessActor", "A business entity capable of performing behavior"),
+    ("BusinessRole", "The responsibility for performing specific behavior, to which an actor can be assigned"),
+    ("BusinessCollaboration", "An aggregate of business internal active structure elements that work together"),
+    ("BusinessInterface", "A point of access where a business service is made available to the environment"),
+    ("BusinessProcess", "A sequence of business behaviors that achieves a specific result"),
+    ("BusinessFunction", "A collection of business behavior based on chosen criteria such as skills or resources"),
+    ("BusinessInteraction", "A unit of collective business behavior performed by two or more business actors, roles or collaborations"),
+    ("BusinessEvent", "A business state change"),
+    ("BusinessService", "Explicitly defined behavior that a business exposes to its environment"),
+    ("BusinessObject", "A concept used within a particular business domain"),
+    ("Contract", "A formal or informal specification of an agreement between a provider and a consumer"),
+    ("Representation", "A perceptible form of the information carried by a business object"),
+    ("Product", "A coherent collection of services and/or passive structure elements offered as a whole"),
+    // Application
+    ("ApplicationComponent", "An encapsulation of application functionality aligned to implementation structure"),
+    ("ApplicationCollaboration", "An aggregate of application internal active structure elements that work together"),
+    ("ApplicationInterface", "A point of access where application services are made available"),
+    ("ApplicationFunction", "Automated behavior that can be performed by an application component"),
+    ("ApplicationInteraction", "A unit of collective application behavior performed by two or more application components"),
+    ("ApplicationProcess", "A sequence of application behaviors that achieves a specific result"),
+    ("ApplicationEvent", "An application state change"),
+    ("ApplicationService", "Explicitly defined behavior that an application component exposes to its environment"),
+    ("DataObject", "Data structured for automated processing"),
+    // Technology
+    ("Node", "A computational or physical resource that hosts, manipulates or interacts with other resources"),
+    ("Device", "A physical IT resource upon which system software and artifacts may be stored or deployed"),
+    ("SystemSoftware", "Software that provides or contributes to an environment for storing, executing and using software or data"),
+    ("TechnologyCollaboration", "An aggregate of technology internal active structure elements that work together"),
+    ("TechnologyInterface", "A point of access where technology services offered by a node can be accessed"),
+    ("Path", "A link between two or more nodes, through which these nodes can exchange data, energy or material"),
+    ("CommunicationNetwork", "A set of structures that connects nodes for transmission, routing and reception of data"),
+    ("TechnologyFunction", "A collection of technology behavior that can be performed by a node"),
+    ("TechnologyProcess", "A sequence of technology behaviors that achieves a specific result"),
+    ("TechnologyInteraction", "A unit of collective technology behavior performed by two or more nodes"),
+    ("TechnologyEvent", "A technology state change"),
+    ("TechnologyService", "Explicitly defined behavior that a node exposes to its environment"),
+    ("Artifact", "A piece of data used or produced in a software development process or by deployment and operation of a system"),
+    // Physical
+    ("Equipment", "One or more physical machines, tools or instruments that can create, use, store, move or transform materials"),
+    ("Facility", "A physical structure or environment"),
+    ("DistributionNetwork", "A physical network used to transport materials or energy"),
+    ("Material", "Tangible physical matter or energy"),
+    // Motivation
+    ("Stakeholder", "The role of an individual, team or organization that represents their interests in the effects of the architecture"),
+    ("Driver", "An external or internal condition that motivates an organization to define its goals and implement changes"),
+    ("Assessment", "The result of an analysis of the state of affairs of the enterprise with respect to some driver"),
+    ("Goal", "A high-level statement of intent, direction or desired end state"),
+    ("Outcome", "An end result, effect or consequence of a certain state of affairs"),
+    ("Principle", "A statement of intent defining a general property that applies to any system in a certain context"),
+    ("Requirement", "A statement of need defining a property that applies to a specific system"),
+    ("Constraint", "A limitation on aspects of the architecture, its implementation process or its realization"),
+    ("Meaning", "The knowledge or expertise present in, or the interpretation given to, a concept in a particular context"),
+    ("Value", "The relative worth, utility or importance of a concept"),
+    // Implementation & Migration
+    ("WorkPackage", "A series of actions identified and designed to achieve specific results within specified time and resource constraints"),
+    ("Deliverable", "A precisely-defined result of a work package"),
+    ("ImplementationEvent", "A state change related to implementation or migration"),
+    ("Plateau", "A relatively stable state of the architecture that exists during a limited period of time"),
+    ("Gap", "A statement of difference between two plateaus"),
+    // Other
+    ("Location", "A conceptual or physical place or position where concepts are located or performed"),
+    ("Grouping", "An aggregation of concepts that belong together based on some common characteristic"),
+    ("Junction", "A connector used to connect relationships of the same type"),
+    // Relationships
+    ("CompositionRelationship", "Indicates that an element consists of one or more other concepts"),
+    ("AggregationRelationship", "Indicates that an element groups a number of other concepts"),
+    ("AssignmentRelationship", "Expresses the allocation of responsibility, performance of behavior, storage or execution"),
+    ("RealizationRelationship", "Indicates that an entity plays a critical role in the creation, achievement or operation of a more abstract entity"),
+    ("ServingRelationship", "Represents that an element provides its functionality to another element"),
+    ("AccessRelationship", "Represents the ability of behavior and active structure elements to observe or act upon passive structure elements"),
+    ("InfluenceRelationship", "Represents that an element affects the implementation or achievement of some motivation element"),
+    ("TriggeringRelationship", "Represents a temporal or causal relationship between elements"),
+    ("FlowRelationship", "Represents transfer from one element to another"),
+    ("SpecializationRelationship", "Indicates that an element is a particular kind of another element"),
+    ("AssociationRelationship", "Models an unspecified relationship, or one that is not represented by another ArchiMate relationship"),
+];
+
+/// Returns the one-line description of an ArchiMate concept type, accepting
+/// the type with or without the `archimate:` prefix.
+pub fn describe(xsi_type: &str) -> Option<&'static str> {
+    let type_name = xsi_type.rsplit(':').next().unwrap_or(xsi_type);
+    GLOSSARY
+        .iter()
+        .find(|(name, _)| *name == type_name)
+        .map(|(_, description)| *description)
+}
+
+/// Formats an ArchiMate type for display, e.g. `archimate:BusinessActor`
+/// becomes `Business Actor`.
+pub fn display_type(xsi_type: &str) -> String {
+    let type_name = xsi_type.rsplit(':').next().unwrap_or(xsi_type);
+    let mut display = String::new();
+    for (i, c) in type_name.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            display.push(' ');
+        }
+        display.push(c);
+    }
+    display
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe("archimate:Capability"),
+            Some("An ability that an active structure element possesses")
+        );
+        assert_eq!(describe("BusinessEvent"), Some("A business state change"));
+        assert_eq!(describe("archimate:DiagramModelReference"), None);
+        assert_eq!(
+            display_type("archimate:ApplicationComponent"),
+            "Application Component"
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::concept_type::ConceptType;
+use crate::model::{element_layer, ArchiModel};
+use crate::namespaces::{attribute, children, xsi_type};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use xot::{output, Node, Xot};
+
+const GRAPHML_NAMESPACE: &str = "http://graphml.graphdrawing.org/xmlns";
+
+/// Writes the concepts of a model as a GraphML graph for tools such as yEd
+/// and Gephi: elements become nodes and relations edges, with their name,
+/// type, layer, documentation and properties as data. With `concept_ids`,
+/// only those concepts are written. Relations on relations, and relations
+/// whose endpoints are not written, have no edge form and are left out.
+pub fn model_to_graphml(
+    model: &ArchiModel,
+    concept_ids: Option<&HashSet<String>>,
+) -> Result<String, Box<dyn Error>> {
+    let source = &model.xot;
+    let root = source.document_element(model.doc)?;
+    let mut elements = Vec::new();
+    let mut relations = Vec::new();
+    for node in source.descendants(root) {
+        if !source.is_element(node)
+            || source.local_name_str(source.get_element_name(node)) != "element"
+        {
+            continue;
+        }
+        let (Some(id), Some(concept_type)) = (
+            attribute(source, node, "id"),
+            xsi_type(source, node).and_then(|xsi_type| xsi_type.strip_prefix("archimate:")),
+        ) else {
+            continue;
+        };
+        if concept_ids.is_some_and(|ids| !ids.contains(id)) {
+            continue;
+        }
+        if ConceptType::from_xsi_type(concept_type).is_some_and(ConceptType::is_relationship) {
+            relations.push(node);
+        } else if element_layer(concept_type).is_some() {
+            elements.push(node);
+        }
+    }
+
+    // One key per property, numbered in the order the keys are first used
+    let mut property_keys: Vec<&str> = Vec::new();
+    for &node in elements.iter().chain(&relations) {
+        for (key, _) in properties(source, node) {
+            if !property_keys.contains(&key) {
+                property_keys.push(key);
+            }
+        }
+    }
+    let property_ids: HashMap<&str, String> = property_keys
+        .iter()
+        .enumerate()
+        .map(|(index, &key)| (key, format!("property-{}", index + 1)))
+        .collect();
+
+    let mut xot = Xot::new();
+    let doc = xot.parse(&format!(r#"<graphml xmlns="{}"/>"#, GRAPHML_NAMESPACE))?;
+    let graphml = xot.document_element(doc)?;
+    for (id, domain, name) in [
+        ("label", "all", "label"),
+        ("type", "all", "type"),
+        ("layer", "node", "layer"),
+        ("documentation", "all", "documentation"),
+    ] {
+        append_key(&mut xot, graphml, id, domain, name)?;
+    }
+    for key in &property_keys {
+        append_key(&mut xot, graphml, &property_ids[key], "all", key)?;
+    }
+
+    let graph = new_graphml_element(&mut xot, "graph");
+    set(
+        &mut xot,
+        graph,
+        "id",
+        attribute(source, root, "id").unwrap_or("model"),
+    );
+    set(&mut xot, graph, "edgedefault", "directed");
+    xot.append(graphml, graph)?;
+
+    let mut node_ids = HashSet::new();
+    for &element in &elements {
+        let id = attribute(source, element, "id").unwrap_or_default();
+        let node = new_graphml_element(&mut xot, "node");
+        set(&mut xot, node, "id", id);
+        append_concept_data(source, &mut xot, element, node, &property_ids)?;
+        let layer = xsi_type(source, element)
+            .and_then(|xsi_type| xsi_type.strip_prefix("archimate:"))
+            .and_then(element_layer)
+            .unwrap_or_default();
+        append_data(&mut xot, node, "layer", layer)?;
+        xot.append(graph, node)?;
+        node_ids.insert(id);
+    }
+    for &relation in &relations {
+        let (Some(id), Some(source_id), Some(target_id)) = (
+            attribute(source, relation, "id"),
+            attribute(source, relation, "source"),
+            attribute(source, relation, "target"),
+        ) else {
+            continue;
+        };
+        if !node_ids.contains(source_id) || !node_ids.contains(target_id) {
+            continue;
+        }
+        let edge = new_graphml_element(&mut xot, "edge");
+        set(&mut xot, edge, "id", id);
+        set(&mut xot, edge, "source", source_id);
+        set(&mut xot, edge, "target", target_id);
+        append_concept_data(source, &mut xot, relation, edge, &property_ids)?;
+        xot.append(graph, edge)?;
+    }
+
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            indentation: Some(Default::default()),
+            ..Default::default()
+        },
+        doc,
+    )?)
+}
+
+/// The type of a concept as Archi's CSV export names it, telling the two
+/// kinds of junction apart.
+fn concept_type(xot: &Xot, node: Node) -> &str {
+    let concept_type = xsi_type(xot, node)
+        .and_then(|xsi_type| xsi_type.strip_prefix("archimate:"))
+        .unwrap_or_default();
+    match concept_type {
+        "Junction" if attribute(xot, node, "type") == Some("or") => "OrJunction",
+        "Junction" => "AndJunction",
+        other => other,
+    }
+}
+
+fn append_concept_data(
+    source: &Xot,
+    xot: &mut Xot,
+    concept: Node,
+    node: Node,
+    property_ids: &HashMap<&str, String>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(name) = attribute(source, concept, "name") {
+        append_data(xot, node, "label", name)?;
+    }
+    append_data(xot, node, "type", concept_type(source, concept))?;
+    let documentation = children(source, concept, "documentation")
+        .first()
+        .and_then(|&child| source.text_content_str(child));
+    if let Some(documentation) = documentation {
+        append_data(xot, node, "documentation", documentation)?;
+    }
+    // GraphML allows one value per key; the first property with a key wins
+    let mut written = HashSet::new();
+    for (key, value) in properties(source, concept) {
+        if written.insert(key) {
+            append_data(xot, node, &property_ids[key], value)?;
+        }
+    }
+    Ok(())
+}
+
+fn append_key(
+    xot: &mut Xot,
+    graphml: Node,
+    id: &str,
+    domain: &str,
+    name: &str,
+) -> Result<(), Box<dyn Error>> {
+    let key = new_graphml_element(xot, "key");
+    set(xot, key, "id", id);
+    set(xot, key, "for", domain);
+    set(xot, key, "attr.name", name);
+    set(xot, key, "attr.type", "string");
+    xot.append(graphml, key)?;
+    Ok(())
+}
+
+fn append_data(xot: &mut Xot, node: Node, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    let data = new_graphml_element(xot, "data");
+    set(xot, data, "key", key);
+    let text = xot.new_text(value);
+    xot.append(data, text)?;
+    xot.append(node, data)?;
+    Ok(())
+}
+
+fn properties(xot: &Xot, node: Node) -> Vec<(&str, &str)> {
+    children(xot, node, "property")
+        .into_iter()
+        .filter_map(|property| {
+            Some((
+                attribute(xot, property, "key")?,
+                attribute(xot, property, "value").unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+fn new_graphml_element(xot: &mut Xot, local_name: &str) -> Node {
+    let namespace = xot.add_namespace(GRAPHML_NAMESPACE);
+    let name = xot.add_name_ns(local_name, namespace);
+    xot.new_element(name)
+}
+
+fn set(xot: &mut Xot, node: Node, name: &str, value: &str) {
+    let name = xot.add_name(name);
+    xot.set_attribute(node, name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_model_to_graphml() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal &amp; API" id="element-1">
+      <documentation>Customer facing</documentation>
+      <property key="status" value="live"/>
+    </element>
+    <element xsi:type="archimate:ApplicationService" name="Orders" id="element-2"/>
+  </folder>
+  <folder name="Technology" id="folder-2" type="technology">
+    <element xsi:type="archimate:Equipment" name="Scanner" id="element-3"/>
+  </folder>
+  <folder name="Relations" id="folder-3" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-2">
+      <property key="status" value="planned"/>
+    </element>
+    <element xsi:type="archimate:AssociationRelationship" id="relation-2" source="element-3" target="relation-1"/>
+  </folder>
+  <folder name="Views" id="folder-4" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1"/>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+
+        let graphml = model_to_graphml(&model, None)?;
+        assert!(graphml
+            .contains(r#"<key id="property-1" for="all" attr.name="status" attr.type="string"/>"#));
+        assert!(graphml.contains(r#"<graph id="model-1" edgedefault="directed">"#));
+        assert!(graphml.contains(r#"<data key="label">Portal &amp; API</data>"#));
+        assert!(graphml.contains(r#"<data key="layer">Physical</data>"#));
+        assert!(graphml.contains(r#"<edge id="relation-1" source="element-1" target="element-2">"#));
+        assert!(graphml.contains(r#"<data key="property-1">planned</data>"#));
+        assert!(!graphml.contains("relation-2"));
+        assert!(!graphml.contains("view-1"));
+
+        let subset = HashSet::from(["element-2".to_string(), "element-3".to_string()]);
+        let graphml = model_to_graphml(&model, Some(&subset))?;
+        assert!(graphml.contains(r#"<node id="element-2">"#));
+        assert!(!graphml.contains("element-1"));
+        assert!(!graphml.contains("<edge"));
+        Ok(())
+    }
+}
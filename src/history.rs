@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+pub const HISTORY_ENTRY: &str = "history/imports.json";
+pub const MAX_HISTORY_RECORDS: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportRecord {
+    pub timestamp: String,
+    pub tool_version: String,
+    pub source: String,
+    pub views: Vec<ImportedView>,
+    pub elements: usize,
+    pub relations: usize,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportedView {
+    pub id: String,
+    pub name: String,
+}
+
+impl ImportRecord {
+    pub fn new(
+        source: &str,
+        views: Vec<ImportedView>,
+        elements: usize,
+        relations: usize,
+        seed: u64,
+    ) -> Self {
+        ImportRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            source: source.to_string(),
+            views,
+            elements,
+            relations,
+            seed: Some(seed),
+        }
+    }
+}
+
+/// Appends `record` to the serialized history, dropping the oldest records
+/// once the history holds more than `MAX_HISTORY_RECORDS`. Unreadable
+/// history is replaced instead of failing the import.
+pub fn append_record(
+    existing: Option<&[u8]>,
+    record: ImportRecord,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut records: Vec<ImportRecord> = match existing {
+        Some(bytes) => serde_json::from_slice(bytes).unwrap_or_else(|e| {
+            eprintln!("Warning: discarding unreadable {}: {}", HISTORY_ENTRY, e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    records.push(record);
+    if records.len() > MAX_HISTORY_RECORDS {
+        records.drain(..records.len() - MAX_HISTORY_RECORDS);
+    }
+    Ok(serde_json::to_vec_pretty(&records)?)
+}
+
+/// Drops the newest record of the serialized history, as when its import
+/// is undone.
+pub fn remove_last_record(existing: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut records: Vec<ImportRecord> = serde_json::from_slice(existing)?;
+    records.pop();
+    Ok(serde_json::to_vec_pretty(&records)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_record_rotates() -> Result<(), Box<dyn std::error::Error>> {
+        let mut history = None;
+        for i in 0..MAX_HISTORY_RECORDS + 5 {
+            let record = ImportRecord::new(&format!("source-{}.archimate", i), vec![], i, 0, 0);
+            history = Some(append_record(history.as_deref(), record)?);
+        }
+
+        let records: Vec<ImportRecord> = serde_json::from_slice(&history.unwrap())?;
+        assert_eq!(records.len(), MAX_HISTORY_RECORDS);
+        assert_eq!(records[0].source, "source-5.archimate");
+        assert_eq!(
+            records.last().unwrap().source,
+            format!("source-{}.archimate", MAX_HISTORY_RECORDS + 4)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_record_replaces_unreadable_history() -> Result<(), Box<dyn std::error::Error>> {
+        let record = ImportRecord::new("source.archimate", vec![], 0, 0, 0);
+        let history = append_record(Some(b"not json"), record.clone())?;
+
+        let records: Vec<ImportRecord> = serde_json::from_slice(&history)?;
+        assert_eq!(records, vec![record]);
+        Ok(())
+    }
+}
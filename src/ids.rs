@@ -0,0 +1,119 @@
+use uuid::Uuid;
+
+/// Generates IDs for newly created nodes from a seeded SplitMix64 stream, so
+/// a run can be reproduced by passing the same seed.
+#[derive(Debug, Clone)]
+pub struct IdGenerator {
+    seed: u64,
+    state: u64,
+    prefix: String,
+}
+
+impl IdGenerator {
+    pub fn new(seed: u64) -> Self {
+        IdGenerator {
+            seed,
+            state: seed,
+            prefix: String::new(),
+        }
+    }
+
+    /// Prepends `prefix` to every generated ID.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn from_entropy() -> Self {
+        let bytes = Uuid::new_v4().into_bytes();
+        let mut seed_bytes = [0u8; 8];
+        seed_bytes.copy_from_slice(&bytes[..8]);
+        IdGenerator::new(u64::from_le_bytes(seed_bytes))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_id(&mut self) -> String {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+        bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+        format!(
+            "{}id-{}",
+            self.prefix,
+            uuid::Builder::from_random_bytes(bytes).into_uuid()
+        )
+    }
+}
+
+/// The 64-bit FNV-1a hash of `bytes`. Unlike the standard library's
+/// hasher, it is fixed by its definition, so it can be written to files and
+/// compared with what a later release computes.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Accepts prefixes that keep generated IDs valid XML IDs: a letter or
+/// underscore followed by letters, digits, `-`, `_` or `.`.
+pub fn parse_id_prefix(input: &str) -> Result<String, String> {
+    let mut chars = input.chars();
+    let valid_start = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_');
+    if valid_start && chars.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        Ok(input.to_string())
+    } else {
+        Err(format!(
+            "Invalid ID prefix: {} (must start with a letter or underscore)",
+            input
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash() {
+        // Reference values of 64-bit FNV-1a
+        assert_eq!(stable_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(stable_hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_same_seed_same_ids() {
+        let mut first = IdGenerator::new(42);
+        let mut second = IdGenerator::new(42);
+        let mut other = IdGenerator::new(43);
+
+        let id = first.next_id();
+        assert_eq!(id, second.next_id());
+        assert_ne!(id, other.next_id());
+        assert_ne!(id, first.next_id());
+        assert!(id.starts_with("id-"));
+    }
+
+    #[test]
+    fn test_id_prefix() {
+        let mut prefixed = IdGenerator::new(42).with_prefix("teamA-");
+        let mut plain = IdGenerator::new(42);
+        assert_eq!(prefixed.next_id(), format!("teamA-{}", plain.next_id()));
+
+        assert!(parse_id_prefix("teamA-").is_ok());
+        assert!(parse_id_prefix("_x.").is_ok());
+        assert!(parse_id_prefix("").is_err());
+        assert!(parse_id_prefix("1team").is_err());
+        assert!(parse_id_prefix("team A").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::copy::remap_references;
+use crate::file_descriptor::FileDescriptor;
+use crate::model::ArchiModel;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// An archive entry's name and content.
+pub type Entry = (String, Vec<u8>);
+
+/// Attribute of diagram objects and profiles naming an image entry.
+const IMAGE_PATH_ATTRIBUTE: &str = "imagePath";
+
+/// Returns the image entries the model refers to.
+pub fn find_image_paths(model: &ArchiModel) -> BTreeSet<String> {
+    let Some(image_path_name) = model.xot.name(IMAGE_PATH_ATTRIBUTE) else {
+        return BTreeSet::new();
+    };
+    model
+        .xot
+        .descendants(model.root)
+        .filter_map(|node| model.xot.get_attribute(node, image_path_name))
+        .map(|path| path.to_string())
+        .collect()
+}
+
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Finds the images the target refers to but does not hold and returns
+/// their content from the source archive as entries to add to the target.
+/// An image with the same content as one the target already holds is not
+/// added; references to it are pointed at the target's copy instead.
+pub fn import_images(
+    source_descriptor: &FileDescriptor,
+    target_descriptor: &FileDescriptor,
+    target: &mut ArchiModel,
+) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let image_paths = find_image_paths(target);
+    let target_entries: BTreeSet<String> = target_descriptor.entry_names()?.into_iter().collect();
+    let missing: Vec<&String> = image_paths
+        .iter()
+        .filter(|path| !target_entries.contains(*path))
+        .collect();
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut by_hash: HashMap<u64, Vec<Entry>> = HashMap::new();
+    for entry_name in target_entries
+        .iter()
+        .filter(|name| name.starts_with("images/"))
+    {
+        if let Some(content) = target_descriptor.read_entry(entry_name)? {
+            by_hash
+                .entry(content_hash(&content))
+                .or_default()
+                .push((entry_name.clone(), content));
+        }
+    }
+
+    let mut new_entries = Vec::new();
+    let mut remapped = HashMap::new();
+    for path in missing {
+        let Some(content) = source_descriptor.read_entry(path)? else {
+            println!("Warning: image {} found in neither model", path);
+            continue;
+        };
+        let same_content = by_hash.entry(content_hash(&content)).or_default();
+        if let Some((existing, _)) = same_content
+            .iter()
+            .find(|(_, existing_content)| *existing_content == content)
+        {
+            remapped.insert(path.clone(), existing.clone());
+            continue;
+        }
+        same_content.push((path.clone(), content.clone()));
+        new_entries.push((path.clone(), content));
+    }
+
+    if !remapped.is_empty() {
+        let root = target.root;
+        remap_references(&mut target.xot, root, &[IMAGE_PATH_ATTRIBUTE], &remapped);
+    }
+    Ok(new_entries)
+}
@@ -0,0 +1,684 @@
+use crate::concept_type::ConceptType;
+use crate::model::is_view_type;
+use crate::namespaces::{archi_type, attribute, child_text, children};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Write as _;
+use xot::{Node, Xot};
+
+/// The folders, concepts and views of a model by ID.
+struct Index {
+    folders: HashMap<String, Node>,
+    concepts: HashMap<String, Node>,
+    views: HashMap<String, Node>,
+    /// Everything in document order, so folders come before their content.
+    order: Vec<Node>,
+}
+
+/// Writes a jArchi script that makes the changes turning the model
+/// `original` into `modified` when run in Archi on the original model:
+/// folders, elements, relations and views are created, updated, moved or
+/// deleted, and views get their diagram objects and connections. Archi
+/// hands out new IDs to what the script creates.
+pub fn change_script(original: &str, modified: &str) -> Result<String, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let original_doc = xot.parse(original)?;
+    let modified_doc = xot.parse(modified)?;
+    let original_root = xot.document_element(original_doc)?;
+    let modified_root = xot.document_element(modified_doc)?;
+    let before = index(&xot, original_root);
+    let after = index(&xot, modified_root);
+
+    let mut script = Script {
+        xot: &xot,
+        text: String::new(),
+        variables: HashMap::new(),
+        counter: 0,
+        modified_root,
+        before: &before,
+    };
+    let model_name = attribute(&xot, original_root, "name").unwrap_or_default();
+    writeln!(
+        script.text,
+        "// Generated by {} {}; run it in Archi with the model {} selected.",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        model_name
+    )?;
+    writeln!(
+        script.text,
+        "if (!model.isSet() || model.id != {}) {{\n    throw new Error({});\n}}",
+        string(attribute(&xot, original_root, "id").unwrap_or_default()),
+        string(&format!("Select the model {} first", model_name))
+    )?;
+    writeln!(
+        script.text,
+        "function byId(id) {{\n    return $(\"#\" + id).first();\n}}"
+    )?;
+    writeln!(
+        script.text,
+        "function topFolder(name) {{\n    return $(model).children(\"folder\").filter(function (folder) {{\n        return folder.name == name;\n    }}).first();\n}}"
+    )?;
+
+    // Folders first, as concepts and views go into them
+    for &folder in &after.order {
+        let Some(id) = attribute(&xot, folder, "id") else {
+            continue;
+        };
+        if !after.folders.contains_key(id) || xot.parent(folder) == Some(modified_root) {
+            continue;
+        }
+        match before.folders.get(id) {
+            Some(&old) => script.update(old, folder)?,
+            None => {
+                let parent = script.folder_reference(xot.parent(folder))?;
+                let variable = script.declare(
+                    id,
+                    &format!(
+                        "{}.createFolder({})",
+                        parent.as_deref().unwrap_or("model"),
+                        string(attribute(&xot, folder, "name").unwrap_or_default())
+                    ),
+                )?;
+                script.set_annotations(&variable, None, folder)?;
+            }
+        }
+    }
+
+    // Elements before relations, and relations once their ends exist
+    let mut pending: Vec<Node> = after
+        .order
+        .iter()
+        .copied()
+        .filter(|&node| {
+            attribute(&xot, node, "id").is_some_and(|id| after.concepts.contains_key(id))
+        })
+        .collect();
+    pending.sort_by_key(|&node| is_relation(&xot, node));
+    while !pending.is_empty() {
+        let count = pending.len();
+        let mut waiting = Vec::new();
+        for concept in pending {
+            let id = attribute(&xot, concept, "id").unwrap_or_default();
+            if let Some(&old) = before.concepts.get(id) {
+                script.update(old, concept)?;
+                continue;
+            }
+            let ends = ["source", "target"].map(|end| attribute(&xot, concept, end));
+            let ready = ends.iter().flatten().all(|end| {
+                before.concepts.contains_key(*end) || script.variables.contains_key(*end)
+            });
+            if !ready {
+                waiting.push(concept);
+                continue;
+            }
+            script.create_concept(concept)?;
+        }
+        if waiting.len() == count {
+            // Relations whose ends are missing cannot be created
+            for concept in &waiting {
+                writeln!(
+                    script.text,
+                    "// Skipped relation {}: its ends are not in the model",
+                    attribute(&xot, *concept, "id").unwrap_or_default()
+                )?;
+            }
+            break;
+        }
+        pending = waiting;
+    }
+
+    // Views are created before their content, which may reference them
+    let mut filled = Vec::new();
+    for &view in &after.order {
+        let Some(id) = attribute(&xot, view, "id") else {
+            continue;
+        };
+        if !after.views.contains_key(id) {
+            continue;
+        }
+        match before.views.get(id) {
+            Some(&old) => {
+                script.update(old, view)?;
+                let old_content = xot.serialize_xml_string(Default::default(), old)?;
+                let new_content = xot.serialize_xml_string(Default::default(), view)?;
+                if old_content != new_content {
+                    let reference = script.reference(id);
+                    writeln!(script.text, "$({}).children().delete();", reference)?;
+                    filled.push(view);
+                }
+            }
+            None if archi_type(&xot, view) == Some("ArchimateDiagramModel") => {
+                let folder = script.content_folder(view)?;
+                let arguments = match folder {
+                    Some(folder) => format!(
+                        "{}, {}",
+                        string(attribute(&xot, view, "name").unwrap_or_default()),
+                        folder
+                    ),
+                    None => string(attribute(&xot, view, "name").unwrap_or_default()),
+                };
+                let variable =
+                    script.declare(id, &format!("model.createArchimateView({})", arguments))?;
+                script.set_annotations(&variable, None, view)?;
+                if let Some(viewpoint) = attribute(&xot, view, "viewpoint") {
+                    writeln!(
+                        script.text,
+                        "{}.viewpoint = {};",
+                        variable,
+                        string(viewpoint)
+                    )?;
+                }
+                filled.push(view);
+            }
+            None => writeln!(
+                script.text,
+                "// Skipped view {}: jArchi cannot create {} views",
+                attribute(&xot, view, "name").unwrap_or_default(),
+                archi_type(&xot, view).unwrap_or_default()
+            )?,
+        }
+    }
+    for view in filled {
+        script.fill_view(view)?;
+    }
+
+    // What the modified model no longer has, views and relations first
+    let mut deleted: Vec<(u8, &str)> = Vec::new();
+    for (id, &node) in &before.views {
+        if !after.views.contains_key(id) {
+            deleted.push((0, attribute(&xot, node, "id").unwrap_or_default()));
+        }
+    }
+    for (id, &node) in &before.concepts {
+        if !after.concepts.contains_key(id) {
+            deleted.push((
+                if is_relation(&xot, node) { 1 } else { 2 },
+                attribute(&xot, node, "id").unwrap_or_default(),
+            ));
+        }
+    }
+    for (id, &node) in &before.folders {
+        if !after.folders.contains_key(id) {
+            deleted.push((3, attribute(&xot, node, "id").unwrap_or_default()));
+        }
+    }
+    deleted.sort();
+    for (_, id) in deleted {
+        writeln!(script.text, "$(\"#\" + {}).delete();", string(id))?;
+    }
+    Ok(script.text)
+}
+
+struct Script<'a> {
+    xot: &'a Xot,
+    text: String,
+    /// The variables holding what the script creates, by model ID.
+    variables: HashMap<String, String>,
+    counter: usize,
+    modified_root: Node,
+    before: &'a Index,
+}
+
+impl Script<'_> {
+    /// Declares a variable for what `expression` creates, returning its name.
+    fn declare(&mut self, id: &str, expression: &str) -> Result<String, Box<dyn Error>> {
+        self.counter += 1;
+        let variable = format!("created{}", self.counter);
+        writeln!(self.text, "var {} = {};", variable, expression)?;
+        self.variables.insert(id.to_string(), variable.clone());
+        Ok(variable)
+    }
+
+    /// An expression for the folder, concept or view with this ID.
+    fn reference(&self, id: &str) -> String {
+        match self.variables.get(id) {
+            Some(variable) => variable.clone(),
+            None => format!("byId({})", string(id)),
+        }
+    }
+
+    /// An expression for the folder `folder`, or `None` for the model
+    /// itself and its top-level folders, which jArchi picks by type.
+    fn folder_reference(&self, folder: Option<Node>) -> Result<Option<String>, Box<dyn Error>> {
+        let Some(folder) = folder.filter(|&folder| folder != self.modified_root) else {
+            return Ok(None);
+        };
+        let id = attribute(self.xot, folder, "id").unwrap_or_default();
+        if self.xot.parent(folder) == Some(self.modified_root)
+            && !self.before.folders.contains_key(id)
+        {
+            let name = attribute(self.xot, folder, "name").unwrap_or_default();
+            return Ok(Some(format!("topFolder({})", string(name))));
+        }
+        Ok(Some(self.reference(id)))
+    }
+
+    /// An expression for the folder of a concept or view, or `None` when
+    /// jArchi puts it into the right folder anyway: the top-level folder of
+    /// its type.
+    fn content_folder(&self, node: Node) -> Result<Option<String>, Box<dyn Error>> {
+        match self.xot.parent(node) {
+            Some(parent) if self.xot.parent(parent) == Some(self.modified_root) => Ok(None),
+            parent => self.folder_reference(parent),
+        }
+    }
+
+    fn create_concept(&mut self, concept: Node) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let id = attribute(xot, concept, "id").unwrap_or_default();
+        let concept_type = archi_type(xot, concept).unwrap_or_default();
+        let name = string(attribute(xot, concept, "name").unwrap_or_default());
+        let folder = self.content_folder(concept)?;
+        let mut arguments = vec![string(&kebab_case(concept_type)), name];
+        let method = if is_relation(xot, concept) {
+            for end in ["source", "target"] {
+                arguments.push(self.reference(attribute(xot, concept, end).unwrap_or_default()));
+            }
+            "createRelationship"
+        } else {
+            "createElement"
+        };
+        arguments.extend(folder);
+        let variable = self.declare(id, &format!("model.{}({})", method, arguments.join(", ")))?;
+        self.set_annotations(&variable, None, concept)?;
+        match concept_type {
+            "Junction" if attribute(xot, concept, "type") == Some("or") => {
+                writeln!(self.text, "{}.junctionType = \"or\";", variable)?;
+            }
+            "AccessRelationship" => {
+                // Archi leaves out its default access type, write
+                let access_type = match attribute(xot, concept, "accessType") {
+                    Some("1") => "read",
+                    Some("2") => "access",
+                    Some("3") => "readwrite",
+                    _ => "write",
+                };
+                writeln!(
+                    self.text,
+                    "{}.accessType = {};",
+                    variable,
+                    string(access_type)
+                )?;
+            }
+            "InfluenceRelationship" => {
+                if let Some(strength) = attribute(xot, concept, "strength") {
+                    writeln!(
+                        self.text,
+                        "{}.influenceStrength = {};",
+                        variable,
+                        string(strength)
+                    )?;
+                }
+            }
+            "AssociationRelationship" if attribute(xot, concept, "directed") == Some("true") => {
+                writeln!(self.text, "{}.associationDirected = true;", variable)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Brings a folder, concept or view the original model has in line with
+    /// the modified one: its name, documentation, properties and folder.
+    fn update(&mut self, old: Node, new: Node) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let id = attribute(xot, new, "id").unwrap_or_default();
+        let reference = self.reference(id);
+        let old_name = attribute(xot, old, "name").unwrap_or_default();
+        let new_name = attribute(xot, new, "name").unwrap_or_default();
+        if old_name != new_name {
+            writeln!(self.text, "{}.name = {};", reference, string(new_name))?;
+        }
+        self.set_annotations(&reference, Some(old), new)?;
+
+        let old_parent = xot
+            .parent(old)
+            .and_then(|parent| attribute(xot, parent, "id"));
+        let new_parent = xot
+            .parent(new)
+            .filter(|&parent| parent != self.modified_root);
+        let moved = new_parent.is_some_and(|parent| attribute(xot, parent, "id") != old_parent);
+        if moved {
+            if let Some(folder) = self.folder_reference(new_parent)? {
+                writeln!(self.text, "{}.add({});", folder, reference)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the documentation and properties `new` has and `old`, if any,
+    /// has not.
+    fn set_annotations(
+        &mut self,
+        reference: &str,
+        old: Option<Node>,
+        new: Node,
+    ) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let old_documentation = old.and_then(|old| child_text(xot, old, "documentation"));
+        let new_documentation = child_text(xot, new, "documentation");
+        if old_documentation != new_documentation {
+            writeln!(
+                self.text,
+                "{}.documentation = {};",
+                reference,
+                string(new_documentation.unwrap_or_default())
+            )?;
+        }
+        let old_properties = old.map(|old| properties(xot, old)).unwrap_or_default();
+        let new_properties = properties(xot, new);
+        if old_properties != new_properties {
+            for (key, value) in &new_properties {
+                if !old_properties.contains(&(key, value)) {
+                    writeln!(
+                        self.text,
+                        "{}.prop({}, {});",
+                        reference,
+                        string(key),
+                        string(value)
+                    )?;
+                }
+            }
+            let new_keys: HashSet<&str> = new_properties.iter().map(|(key, _)| *key).collect();
+            for (key, _) in &old_properties {
+                if !new_keys.contains(key) {
+                    writeln!(self.text, "{}.removeProp({});", reference, string(key))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the diagram objects and connections of `view` to the view the
+    /// script holds for it.
+    fn fill_view(&mut self, view: Node) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let view_reference = self.reference(attribute(xot, view, "id").unwrap_or_default());
+        let mut objects = HashMap::new();
+        let mut connections = Vec::new();
+        for child in children(xot, view, "child") {
+            self.add_object(child, &view_reference, &mut objects, &mut connections)?;
+        }
+
+        // Connections may end on other connections
+        while !connections.is_empty() {
+            let count = connections.len();
+            let mut waiting = Vec::new();
+            for connection in connections {
+                let (Some(source), Some(target)) = (
+                    attribute(xot, connection, "source").and_then(|id| objects.get(id)),
+                    attribute(xot, connection, "target").and_then(|id| objects.get(id)),
+                ) else {
+                    waiting.push(connection);
+                    continue;
+                };
+                let expression = match attribute(xot, connection, "archimateRelationship") {
+                    Some(relation) => format!(
+                        "{}.add({}, {}, {})",
+                        view_reference,
+                        self.reference(relation),
+                        source,
+                        target
+                    ),
+                    None => format!(
+                        "{}.createConnection({}, {})",
+                        view_reference, source, target
+                    ),
+                };
+                self.counter += 1;
+                let variable = format!("connection{}", self.counter);
+                writeln!(self.text, "var {} = {};", variable, expression)?;
+                self.set_style(&variable, connection)?;
+                objects.insert(
+                    attribute(xot, connection, "id").unwrap_or_default(),
+                    variable,
+                );
+            }
+            if waiting.len() == count {
+                break;
+            }
+            connections = waiting;
+        }
+        Ok(())
+    }
+
+    fn add_object<'n>(
+        &mut self,
+        object: Node,
+        parent: &str,
+        objects: &mut HashMap<&'n str, String>,
+        connections: &mut Vec<Node>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        Self: 'n,
+    {
+        let xot = self.xot;
+        let Some(id) = attribute(xot, object, "id") else {
+            return Ok(());
+        };
+        let bounds = children(xot, object, "bounds").first().copied();
+        let bound = |name: &str, default: &str| {
+            bounds
+                .and_then(|bounds| attribute(xot, bounds, name))
+                .unwrap_or(default)
+                .to_string()
+        };
+        let bounds = format!(
+            "{}, {}, {}, {}",
+            bound("x", "0"),
+            bound("y", "0"),
+            bound("width", "-1"),
+            bound("height", "-1")
+        );
+        let expression = match archi_type(xot, object) {
+            Some("DiagramObject") => {
+                let Some(element) = attribute(xot, object, "archimateElement") else {
+                    return Ok(());
+                };
+                format!("{}.add({}, {})", parent, self.reference(element), bounds)
+            }
+            Some("DiagramModelGroup") => format!("{}.createObject(\"group\", {})", parent, bounds),
+            Some("DiagramModelNote") => format!("{}.createObject(\"note\", {})", parent, bounds),
+            Some("DiagramModelReference") => {
+                let Some(view) = attribute(xot, object, "model") else {
+                    return Ok(());
+                };
+                format!(
+                    "{}.createViewReference({}, {})",
+                    parent,
+                    self.reference(view),
+                    bounds
+                )
+            }
+            other => {
+                writeln!(
+                    self.text,
+                    "// Skipped diagram object {}: jArchi cannot create {}",
+                    id,
+                    other.unwrap_or_default()
+                )?;
+                return Ok(());
+            }
+        };
+        self.counter += 1;
+        let variable = format!("object{}", self.counter);
+        writeln!(self.text, "var {} = {};", variable, expression)?;
+        match archi_type(xot, object) {
+            Some("DiagramModelGroup") => {
+                if let Some(name) = attribute(xot, object, "name") {
+                    writeln!(self.text, "{}.name = {};", variable, string(name))?;
+                }
+            }
+            Some("DiagramModelNote") => {
+                if let Some(content) = child_text(xot, object, "content") {
+                    writeln!(self.text, "{}.text = {};", variable, string(content))?;
+                }
+            }
+            _ => {}
+        }
+        self.set_style(&variable, object)?;
+        objects.insert(id, variable.clone());
+        connections.extend(children(xot, object, "sourceConnection"));
+        for child in children(xot, object, "child") {
+            self.add_object(child, &variable, objects, connections)?;
+        }
+        Ok(())
+    }
+
+    fn set_style(&mut self, variable: &str, node: Node) -> Result<(), Box<dyn Error>> {
+        for color in ["fillColor", "lineColor", "fontColor"] {
+            if let Some(value) = attribute(self.xot, node, color) {
+                writeln!(self.text, "{}.{} = {};", variable, color, string(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn index(xot: &Xot, root: Node) -> Index {
+    let mut index = Index {
+        folders: HashMap::new(),
+        concepts: HashMap::new(),
+        views: HashMap::new(),
+        order: Vec::new(),
+    };
+    for folder in children(xot, root, "folder") {
+        index_folder(xot, folder, &mut index);
+    }
+    index
+}
+
+fn index_folder(xot: &Xot, folder: Node, index: &mut Index) {
+    if let Some(id) = attribute(xot, folder, "id") {
+        index.folders.insert(id.to_string(), folder);
+        index.order.push(folder);
+    }
+    for child in xot.children(folder).filter(|&child| xot.is_element(child)) {
+        match xot.local_name_str(xot.get_element_name(child)) {
+            "folder" => index_folder(xot, child, index),
+            "element" => {
+                let Some(id) = attribute(xot, child, "id") else {
+                    continue;
+                };
+                if is_view_type(archi_type(xot, child).unwrap_or_default()) {
+                    index.views.insert(id.to_string(), child);
+                } else {
+                    index.concepts.insert(id.to_string(), child);
+                }
+                index.order.push(child);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_relation(xot: &Xot, node: Node) -> bool {
+    archi_type(xot, node)
+        .and_then(ConceptType::from_xsi_type)
+        .is_some_and(ConceptType::is_relationship)
+}
+
+/// jArchi's name for a type, e.g. `business-actor` for `BusinessActor`.
+fn kebab_case(name: &str) -> String {
+    let mut result = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            result.push('-');
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+/// A JavaScript string literal.
+fn string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+fn properties(xot: &Xot, node: Node) -> Vec<(&str, &str)> {
+    children(xot, node, "property")
+        .into_iter()
+        .map(|property| {
+            (
+                attribute(xot, property, "key").unwrap_or_default(),
+                attribute(xot, property, "value").unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1"/>
+    <element xsi:type="archimate:DataObject" name="Order" id="element-2"/>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations"/>
+  <folder name="Views" id="folder-3" type="diagrams"/>
+</archimate:model>"#;
+
+    const MODIFIED: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1">
+      <documentation>Customer "facing"</documentation>
+      <property key="owner" value="Sales"/>
+    </element>
+    <folder name="Imported" id="folder-4">
+      <element xsi:type="archimate:ApplicationInterface" name="API" id="element-3"/>
+    </folder>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations">
+    <element xsi:type="archimate:AccessRelationship" id="relation-1" source="relation-2" target="element-1" accessType="1"/>
+    <element xsi:type="archimate:AssignmentRelationship" id="relation-2" source="element-1" target="element-3"/>
+  </folder>
+  <folder name="Views" id="folder-3" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramModelGroup" id="object-1" name="Front">
+        <bounds x="10" y="20" width="300" height="200"/>
+        <child xsi:type="archimate:DiagramObject" id="object-2" archimateElement="element-1" fillColor="#ffffff">
+          <bounds x="12" y="24" width="120" height="55"/>
+          <sourceConnection xsi:type="archimate:Connection" id="connection-1" source="object-2" target="object-3" archimateRelationship="relation-2"/>
+        </child>
+      </child>
+      <child xsi:type="archimate:DiagramObject" id="object-3" archimateElement="element-3">
+        <bounds x="400" y="20"/>
+      </child>
+    </element>
+  </folder>
+</archimate:model>"##;
+
+    #[test]
+    fn test_change_script() -> Result<(), Box<dyn Error>> {
+        let script = change_script(ORIGINAL, MODIFIED)?;
+        let body = script
+            .split_once("}).first();\n}\n")
+            .map(|(_, body)| body)
+            .unwrap_or_default();
+        assert!(script.contains("if (!model.isSet() || model.id != \"model-1\")"));
+        assert_eq!(
+            body,
+            "var created1 = byId(\"folder-1\").createFolder(\"Imported\");\n\
+             byId(\"element-1\").documentation = \"Customer \\\"facing\\\"\";\n\
+             byId(\"element-1\").prop(\"owner\", \"Sales\");\n\
+             var created2 = model.createElement(\"application-interface\", \"API\", created1);\n\
+             var created3 = model.createRelationship(\"assignment-relationship\", \"\", byId(\"element-1\"), created2);\n\
+             var created4 = model.createRelationship(\"access-relationship\", \"\", created3, byId(\"element-1\"));\n\
+             created4.accessType = \"read\";\n\
+             var created5 = model.createArchimateView(\"Overview\");\n\
+             var object6 = created5.createObject(\"group\", 10, 20, 300, 200);\n\
+             object6.name = \"Front\";\n\
+             var object7 = object6.add(byId(\"element-1\"), 12, 24, 120, 55);\n\
+             object7.fillColor = \"#ffffff\";\n\
+             var object8 = created5.add(created2, 400, 20, -1, -1);\n\
+             var connection9 = created5.add(created3, object7, object8);\n\
+             $(\"#\" + \"element-2\").delete();\n"
+        );
+        Ok(())
+    }
+}
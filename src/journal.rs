@@ -0,0 +1,259 @@
+use crate::error::ImporterError;
+use crate::ids::stable_hash;
+use crate::model::{find_node_by_id, load_model, serialize_model};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use xot::{Node, Xot};
+
+/// Appended to the name of a model to name its journal.
+pub const JOURNAL_SUFFIX: &str = ".undo.json";
+
+/// What the last import changed in a model, to undo it later: the nodes it
+/// added, and the nodes it changed, moved or removed as they were before.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Journal {
+    pub timestamp: String,
+    pub tool_version: String,
+    /// Hash of the model XML the import wrote, to tell whether the model
+    /// was changed since.
+    pub written_hash: String,
+    /// Whether the import appended a record to the model's history.
+    #[serde(default)]
+    pub history: bool,
+    /// IDs of the folders, concepts, views and profiles added, outermost
+    /// first.
+    pub added: Vec<String>,
+    /// Nodes changed, moved or removed, in document order.
+    pub replaced: Vec<ReplacedNode>,
+}
+
+/// A node as it was before the import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplacedNode {
+    pub id: String,
+    /// The ID of the folder, or model, the node was in.
+    pub parent: String,
+    /// The position of the node among the elements of its parent.
+    pub position: usize,
+    pub xml: String,
+}
+
+/// The journal of the model at `path`, as in `model.archimate.undo.json`.
+pub fn journal_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(JOURNAL_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// Hash of a model's XML, kept in the journal, so it must not change
+/// between releases.
+pub fn content_hash(xml: &str) -> String {
+    format!("{:016x}", stable_hash(xml.as_bytes()))
+}
+
+/// The folders, concepts, views and profiles of a model by ID, in document
+/// order: the elements with an ID that are in a folder or the model itself.
+fn tracked_nodes(xot: &Xot, root: Node) -> Vec<(&str, Node)> {
+    let Some(id_name) = xot.name("id") else {
+        return Vec::new();
+    };
+    let folder_name = xot.name("folder");
+    xot.descendants(root)
+        .filter(|&node| node != root && xot.is_element(node))
+        .filter(|&node| {
+            xot.parent(node).is_some_and(|parent| {
+                parent == root || Some(xot.get_element_name(parent)) == folder_name
+            })
+        })
+        .filter_map(|node| xot.get_attribute(node, id_name).map(|id| (id, node)))
+        .collect()
+}
+
+fn parent_id(xot: &Xot, node: Node) -> Option<&str> {
+    let parent = xot.parent(node)?;
+    xot.get_attribute(parent, xot.name("id")?)
+}
+
+fn is_folder(xot: &Xot, node: Node) -> bool {
+    xot.local_name_str(xot.get_element_name(node)) == "folder"
+}
+
+/// Reads the journal saved at `path` from its `content`.
+pub fn parse(content: &[u8], path: &Path) -> Result<Journal, ImporterError> {
+    serde_json::from_slice(content).map_err(|e| {
+        ImporterError::Validation(format!("Invalid journal {}: {}", path.display(), e))
+    })
+}
+
+/// Records the changes turning the model `original` into `modified`.
+/// Folders are compared by place only, as imports don't rename them.
+pub fn record(original: &str, modified: &str) -> Result<Journal, ImporterError> {
+    let mut xot = Xot::new();
+    let original_doc = xot.parse(original)?;
+    let modified_doc = xot.parse(modified)?;
+    let original_root = xot.document_element(original_doc)?;
+    let modified_root = xot.document_element(modified_doc)?;
+    let before = tracked_nodes(&xot, original_root);
+    let after = tracked_nodes(&xot, modified_root);
+    let before_ids: HashMap<&str, Node> = before.iter().copied().collect();
+    let after_ids: HashMap<&str, Node> = after.iter().copied().collect();
+
+    let mut added = Vec::new();
+    let mut new_ids = HashSet::new();
+    for &(id, node) in &after {
+        if before_ids.contains_key(id) {
+            continue;
+        }
+        // Nodes within an added folder go with it
+        if !parent_id(&xot, node).is_some_and(|parent| new_ids.contains(parent)) {
+            added.push(id.to_string());
+        }
+        new_ids.insert(id);
+    }
+
+    let mut replaced = Vec::new();
+    let mut gone = HashSet::new();
+    for &(id, node) in &before {
+        let parent = parent_id(&xot, node).unwrap_or_default();
+        let changed = match after_ids.get(id) {
+            None => {
+                gone.insert(id);
+                !gone.contains(parent)
+            }
+            Some(&now) => {
+                parent_id(&xot, now) != Some(parent)
+                    || (!is_folder(&xot, node)
+                        && xot.serialize_xml_string(Default::default(), node)?
+                            != xot.serialize_xml_string(Default::default(), now)?)
+            }
+        };
+        if changed {
+            let parent_node = xot
+                .parent(node)
+                .ok_or_else(|| ImporterError::Xml(format!("{} has no parent", id)))?;
+            let position = xot
+                .children(parent_node)
+                .filter(|&child| xot.is_element(child))
+                .position(|child| child == node)
+                .unwrap_or_default();
+            replaced.push(ReplacedNode {
+                id: id.to_string(),
+                parent: parent.to_string(),
+                position,
+                xml: xot.serialize_xml_string(Default::default(), node)?,
+            });
+        }
+    }
+
+    Ok(Journal {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        written_hash: content_hash(modified),
+        history: false,
+        added,
+        replaced,
+    })
+}
+
+/// Reverts the changes `journal` records in the model `current`, returning
+/// the model as it was before the import.
+pub fn undo(journal: &Journal, current: &str) -> Result<String, ImporterError> {
+    let mut model = load_model(current)?;
+    for id in &journal.added {
+        if let Some(node) = find_node_by_id(&model, id) {
+            model.xot.remove(node)?;
+        }
+    }
+    for replaced in &journal.replaced {
+        let fragment = model.xot.parse_fragment(&replaced.xml)?;
+        let restored = model
+            .xot
+            .first_child(fragment)
+            .filter(|&node| model.xot.is_element(node))
+            .ok_or_else(|| {
+                ImporterError::Validation(format!("Journal entry {} holds no element", replaced.id))
+            })?;
+        let existing = find_node_by_id(&model, &replaced.id);
+        if let Some(node) = existing {
+            if parent_id(&model.xot, node) == Some(replaced.parent.as_str()) {
+                model.xot.insert_before(node, restored)?;
+                model.xot.remove(node)?;
+                continue;
+            }
+            model.xot.remove(node)?;
+        }
+        let parent = find_node_by_id(&model, &replaced.parent).ok_or_else(|| {
+            ImporterError::Validation(format!(
+                "Cannot restore {}: its folder {} is gone",
+                replaced.id, replaced.parent
+            ))
+        })?;
+        let sibling = model
+            .xot
+            .children(parent)
+            .filter(|&child| model.xot.is_element(child))
+            .nth(replaced.position);
+        match sibling {
+            Some(sibling) => model.xot.insert_before(sibling, restored)?,
+            None => model.xot.append(parent, restored)?,
+        }
+    }
+    Ok(serialize_model(&model)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_record_and_undo() -> Result<(), Box<dyn Error>> {
+        let original = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-1"/>
+    <element xsi:type="archimate:ApplicationComponent" name="ERP" id="element-2"/>
+  </folder>
+  <folder name="Views" id="folder-2" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Old" id="view-1"/>
+  </folder>
+</archimate:model>"#;
+        let modified = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Customer Relations" id="element-1"/>
+    <folder name="Sales" id="folder-3">
+      <element xsi:type="archimate:ApplicationComponent" name="Shop" id="element-3"/>
+      <element xsi:type="archimate:ApplicationComponent" name="ERP" id="element-2"/>
+    </folder>
+  </folder>
+  <folder name="Views" id="folder-2" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="New" id="view-2"/>
+  </folder>
+</archimate:model>"#;
+        let journal = record(original, modified)?;
+        assert_eq!(journal.added, ["folder-3", "view-2"]);
+        let replaced: Vec<&str> = journal.replaced.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(replaced, ["element-1", "element-2", "view-1"]);
+        assert_eq!(journal.written_hash, content_hash(modified));
+
+        let restored = undo(&journal, modified)?;
+        assert!(record(original, &restored)?.replaced.is_empty());
+        assert!(record(original, &restored)?.added.is_empty());
+        let names: Vec<String> = load_model(&restored)?
+            .elements()
+            .map(|info| info.name.to_string())
+            .collect();
+        assert_eq!(names, ["CRM", "ERP"]);
+
+        let path = Path::new("model.archimate.undo.json");
+        let saved = serde_json::to_vec(&journal)?;
+        assert_eq!(parse(&saved, path)?, journal);
+        assert!(matches!(
+            parse(b"{}", path),
+            Err(ImporterError::Validation(_))
+        ));
+        Ok(())
+    }
+}
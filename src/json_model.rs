@@ -0,0 +1,421 @@
+use crate::concept_type::ConceptType;
+use crate::model::{is_view_type, ArchiModel};
+use crate::namespaces::{attribute, child_text, xsi_type, XSI_NAMESPACE};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use xot::{output, Node, Xot};
+
+const ARCHIMATE_NAMESPACE: &str = "http://www.archimatetool.com/archimate";
+
+/// A model as JSON: its folder tree with the elements, relations and views
+/// each folder holds. What has no field of its own, such as the diagram of
+/// a view or the profiles of the model, is kept as Archi XML in `content`,
+/// so a model survives the trip to JSON and back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonModel {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<JsonProperty>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    #[serde(default)]
+    pub folders: Vec<JsonFolder>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonFolder {
+    pub id: String,
+    pub name: String,
+    /// The type of a top-level folder, e.g. `business`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub folder_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<JsonProperty>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<JsonFolder>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<JsonConcept>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<JsonConcept>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<JsonConcept>,
+}
+
+/// An element, relation or view.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonConcept {
+    pub id: String,
+    /// The Archi type without its `archimate:` prefix, e.g. `BusinessRole`.
+    #[serde(rename = "type")]
+    pub concept_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<JsonProperty>,
+    /// The source of a relation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The target of a relation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Other attributes, e.g. the `accessType` of an access relation.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonProperty {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Writes a model as JSON.
+pub fn to_json(model: &ArchiModel) -> Result<String, Box<dyn Error>> {
+    let xot = &model.xot;
+    let root = xot.document_element(model.doc)?;
+    let mut json_model = JsonModel {
+        id: attribute(xot, root, "id").unwrap_or_default().to_string(),
+        name: attribute(xot, root, "name").unwrap_or_default().to_string(),
+        purpose: child_text(xot, root, "purpose").map(str::to_string),
+        properties: properties(xot, root),
+        attributes: other_attributes(xot, root, &["id", "name"]),
+        folders: Vec::new(),
+        content: None,
+    };
+    let mut content = String::new();
+    for child in xot.children(root).filter(|&child| xot.is_element(child)) {
+        match xot.local_name_str(xot.get_element_name(child)) {
+            "folder" => json_model.folders.push(read_folder(xot, child)?),
+            "purpose" | "property" => {}
+            _ => content.push_str(&xot.serialize_xml_string(Default::default(), child)?),
+        }
+    }
+    json_model.content = (!content.is_empty()).then_some(content);
+    Ok(serde_json::to_string_pretty(&json_model)?)
+}
+
+/// Reads a model written by [`to_json`], or by other tools in its form,
+/// returning it as Archi XML.
+pub fn from_json(json: &str) -> Result<String, Box<dyn Error>> {
+    let json_model: JsonModel = serde_json::from_str(json.trim_start_matches('\u{feff}'))
+        .map_err(|e| format!("Cannot read JSON model: {}", e))?;
+    let mut xot = Xot::new();
+    let doc = xot.parse(&format!(
+        r#"<archimate:model xmlns:xsi="{}" xmlns:archimate="{}"/>"#,
+        XSI_NAMESPACE, ARCHIMATE_NAMESPACE
+    ))?;
+    let root = xot.document_element(doc)?;
+    set(&mut xot, root, "name", &json_model.name);
+    set(&mut xot, root, "id", &json_model.id);
+    for (name, value) in &json_model.attributes {
+        set(&mut xot, root, name, value);
+    }
+    for folder in &json_model.folders {
+        write_folder(&mut xot, root, folder)?;
+    }
+    if let Some(purpose) = &json_model.purpose {
+        append_text(&mut xot, root, "purpose", purpose)?;
+    }
+    append_properties(&mut xot, root, &json_model.properties)?;
+    if let Some(content) = &json_model.content {
+        append_content(&mut xot, root, content)?;
+    }
+    xot.deduplicate_namespaces(root);
+    Ok(xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        doc,
+    )?)
+}
+
+fn read_folder(xot: &Xot, folder: Node) -> Result<JsonFolder, Box<dyn Error>> {
+    let mut json_folder = JsonFolder {
+        id: attribute(xot, folder, "id").unwrap_or_default().to_string(),
+        name: attribute(xot, folder, "name")
+            .unwrap_or_default()
+            .to_string(),
+        folder_type: attribute(xot, folder, "type").map(str::to_string),
+        documentation: child_text(xot, folder, "documentation").map(str::to_string),
+        properties: properties(xot, folder),
+        folders: Vec::new(),
+        elements: Vec::new(),
+        relations: Vec::new(),
+        views: Vec::new(),
+    };
+    for child in xot.children(folder).filter(|&child| xot.is_element(child)) {
+        match xot.local_name_str(xot.get_element_name(child)) {
+            "folder" => json_folder.folders.push(read_folder(xot, child)?),
+            "element" => {
+                let concept = read_concept(xot, child)?;
+                if ConceptType::from_xsi_type(&concept.concept_type)
+                    .is_some_and(ConceptType::is_relationship)
+                {
+                    json_folder.relations.push(concept);
+                } else if is_view_type(&concept.concept_type) {
+                    json_folder.views.push(concept);
+                } else {
+                    json_folder.elements.push(concept);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(json_folder)
+}
+
+fn read_concept(xot: &Xot, node: Node) -> Result<JsonConcept, Box<dyn Error>> {
+    let mut content = String::new();
+    for child in xot.children(node).filter(|&child| xot.is_element(child)) {
+        if !matches!(
+            xot.local_name_str(xot.get_element_name(child)),
+            "documentation" | "property"
+        ) {
+            content.push_str(&xot.serialize_xml_string(Default::default(), child)?);
+        }
+    }
+    Ok(JsonConcept {
+        id: attribute(xot, node, "id").unwrap_or_default().to_string(),
+        concept_type: xsi_type(xot, node)
+            .map(|xsi_type| xsi_type.strip_prefix("archimate:").unwrap_or(xsi_type))
+            .unwrap_or_default()
+            .to_string(),
+        name: attribute(xot, node, "name").unwrap_or_default().to_string(),
+        documentation: child_text(xot, node, "documentation").map(str::to_string),
+        properties: properties(xot, node),
+        source: attribute(xot, node, "source").map(str::to_string),
+        target: attribute(xot, node, "target").map(str::to_string),
+        attributes: other_attributes(xot, node, &["id", "name", "source", "target"]),
+        content: (!content.is_empty()).then_some(content),
+    })
+}
+
+fn write_folder(xot: &mut Xot, parent: Node, folder: &JsonFolder) -> Result<(), Box<dyn Error>> {
+    let node = new_element(xot, "folder");
+    set(xot, node, "name", &folder.name);
+    set(xot, node, "id", &folder.id);
+    if let Some(folder_type) = &folder.folder_type {
+        set(xot, node, "type", folder_type);
+    }
+    xot.append(parent, node)?;
+    for child in &folder.folders {
+        write_folder(xot, node, child)?;
+    }
+    for concept in folder
+        .elements
+        .iter()
+        .chain(&folder.relations)
+        .chain(&folder.views)
+    {
+        write_concept(xot, node, concept)?;
+    }
+    if let Some(documentation) = &folder.documentation {
+        append_text(xot, node, "documentation", documentation)?;
+    }
+    append_properties(xot, node, &folder.properties)
+}
+
+fn write_concept(xot: &mut Xot, parent: Node, concept: &JsonConcept) -> Result<(), Box<dyn Error>> {
+    let node = new_element(xot, "element");
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    xot.set_attribute(
+        node,
+        type_name,
+        format!("archimate:{}", concept.concept_type),
+    );
+    if !concept.name.is_empty() {
+        set(xot, node, "name", &concept.name);
+    }
+    set(xot, node, "id", &concept.id);
+    for (name, value) in [("source", &concept.source), ("target", &concept.target)] {
+        if let Some(value) = value {
+            set(xot, node, name, value);
+        }
+    }
+    for (name, value) in &concept.attributes {
+        set(xot, node, name, value);
+    }
+    xot.append(parent, node)?;
+    if let Some(documentation) = &concept.documentation {
+        append_text(xot, node, "documentation", documentation)?;
+    }
+    append_properties(xot, node, &concept.properties)?;
+    if let Some(content) = &concept.content {
+        append_content(xot, node, content)?;
+    }
+    Ok(())
+}
+
+/// Appends the elements of Archi XML `content` to `parent`.
+fn append_content(xot: &mut Xot, parent: Node, content: &str) -> Result<(), Box<dyn Error>> {
+    // The prefixes Archi uses are declared around the content, so it may
+    // leave them out
+    let wrapper = format!(
+        r#"<content xmlns:xsi="{}" xmlns:archimate="{}">{}</content>"#,
+        XSI_NAMESPACE, ARCHIMATE_NAMESPACE, content
+    );
+    let fragment = xot
+        .parse_fragment(&wrapper)
+        .map_err(|e| format!("Cannot read content: {}", e))?;
+    let wrapper = xot
+        .first_child(fragment)
+        .ok_or("Cannot read content: nothing parsed")?;
+    while let Some(child) = xot.first_child(wrapper) {
+        xot.detach(child)?;
+        xot.append(parent, child)?;
+    }
+    Ok(())
+}
+
+fn append_properties(
+    xot: &mut Xot,
+    parent: Node,
+    properties: &[JsonProperty],
+) -> Result<(), Box<dyn Error>> {
+    for property in properties {
+        let node = new_element(xot, "property");
+        set(xot, node, "key", &property.key);
+        set(xot, node, "value", &property.value);
+        xot.append(parent, node)?;
+    }
+    Ok(())
+}
+
+fn append_text(xot: &mut Xot, parent: Node, name: &str, text: &str) -> Result<(), Box<dyn Error>> {
+    let node = new_element(xot, name);
+    let text = xot.new_text(text);
+    xot.append(node, text)?;
+    xot.append(parent, node)?;
+    Ok(())
+}
+
+fn properties(xot: &Xot, node: Node) -> Vec<JsonProperty> {
+    xot.children(node)
+        .filter(|&child| {
+            xot.is_element(child) && xot.local_name_str(xot.get_element_name(child)) == "property"
+        })
+        .map(|property| JsonProperty {
+            key: attribute(xot, property, "key")
+                .unwrap_or_default()
+                .to_string(),
+            value: attribute(xot, property, "value")
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect()
+}
+
+/// The attributes without a namespace other than `known`.
+fn other_attributes(xot: &Xot, node: Node, known: &[&str]) -> BTreeMap<String, String> {
+    xot.attributes(node)
+        .iter()
+        .filter_map(|(attribute, value)| {
+            let (local_name, namespace) = xot.name_ns_str(attribute);
+            (namespace.is_empty() && !known.contains(&local_name))
+                .then(|| (local_name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn new_element(xot: &mut Xot, name: &str) -> Node {
+    let name = xot.add_name(name);
+    xot.new_element(name)
+}
+
+fn set(xot: &mut Xot, node: Node, name: &str, value: &str) {
+    let name = xot.add_name(name);
+    xot.set_attribute(node, name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{format_folder_path, load_model};
+
+    const MODEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1" version="5.0.0">
+  <folder name="Application" id="folder-1" type="application">
+    <folder name="Portals" id="folder-2">
+      <element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-1" profiles="profile-1">
+        <documentation>Customer facing</documentation>
+        <property key="status" value="live"/>
+      </element>
+    </folder>
+    <element xsi:type="archimate:DataObject" name="Order" id="element-2"/>
+  </folder>
+  <folder name="Relations" id="folder-3" type="relations">
+    <element xsi:type="archimate:AccessRelationship" id="relation-1" source="element-1" target="element-2" accessType="1"/>
+  </folder>
+  <folder name="Views" id="folder-4" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1">
+        <bounds x="10" y="20" width="120" height="55"/>
+      </child>
+    </element>
+  </folder>
+  <purpose>Reviews</purpose>
+  <property key="owner" value="EA team"/>
+  <profile name="Portal" id="profile-1" conceptType="ApplicationComponent"/>
+</archimate:model>"#;
+
+    #[test]
+    fn test_to_json() -> Result<(), Box<dyn Error>> {
+        let model = load_model(MODEL)?;
+
+        let json_model: JsonModel = serde_json::from_str(&to_json(&model)?)?;
+        assert_eq!(json_model.name, "Enterprise");
+        assert_eq!(json_model.purpose.as_deref(), Some("Reviews"));
+        assert_eq!(json_model.attributes["version"], "5.0.0");
+        assert!(json_model
+            .content
+            .unwrap()
+            .contains("conceptType=\"ApplicationComponent\""));
+        let application = &json_model.folders[0];
+        assert_eq!(application.folder_type.as_deref(), Some("application"));
+        let portal = &application.folders[0].elements[0];
+        assert_eq!(portal.concept_type, "ApplicationComponent");
+        assert_eq!(portal.documentation.as_deref(), Some("Customer facing"));
+        assert_eq!(portal.attributes["profiles"], "profile-1");
+        assert_eq!(portal.properties[0].value, "live");
+        let access = &json_model.folders[1].relations[0];
+        assert_eq!(access.source.as_deref(), Some("element-1"));
+        assert_eq!(access.attributes["accessType"], "1");
+        let view = &json_model.folders[2].views[0];
+        assert!(view.content.as_ref().unwrap().contains("<bounds x=\"10\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_json_round_trip() -> Result<(), Box<dyn Error>> {
+        let model = load_model(MODEL)?;
+        let json = to_json(&model)?;
+
+        let xml = from_json(&json)?;
+        let restored = load_model(&xml)?;
+        assert_eq!(to_json(&restored)?, json);
+        assert_eq!(
+            format_folder_path(&restored.element_map["element-1"].folder_path),
+            "Application[application] > Portals"
+        );
+        assert!(from_json("{\"id\": \"model-1\"}").is_err());
+        Ok(())
+    }
+}
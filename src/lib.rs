@@ -0,0 +1,60 @@
+//! Imports views, with the concepts they show, from one Archi model into
+//! another. The `archi-view-importer` command is built on this crate; other
+//! programs can run an import through [`import`] and [`ImportOptions`], and
+//! compare models through [`diff`].
+
+macro_rules! verbose_println {
+    ($verbose:expr, $($arg:tt)*) => {
+        if $verbose {
+            println!($($arg)*)
+        }
+    };
+}
+
+pub mod api;
+mod builder;
+mod bundle;
+pub mod cli;
+mod coarchi;
+pub mod commands;
+mod concept_type;
+mod conflict;
+mod copy;
+mod csv_export;
+mod csv_import;
+mod cypher;
+mod dot;
+mod download;
+mod error;
+mod exchange;
+mod file_descriptor;
+mod git_revision;
+mod glossary;
+mod graphml;
+mod history;
+mod ids;
+mod images;
+mod jarchi;
+mod journal;
+mod json_model;
+mod lock;
+mod merge;
+mod model;
+mod namespaces;
+mod plantuml;
+mod profiles;
+mod prune;
+mod query;
+mod report;
+mod search;
+mod selection;
+mod selection_file;
+mod shared_concepts;
+mod sqlite_export;
+mod stats;
+mod streaming;
+mod svg;
+mod validate;
+mod view_hash;
+
+pub use api::{diff, import, ImportOptions, ImportReport, ModelDiff};
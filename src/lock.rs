@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Appended to the name of a model to name its lock file.
+pub const LOCK_SUFFIX: &str = ".lock";
+
+/// An exclusive advisory lock on a model, held on a lock file next to it
+/// until dropped, so that runs writing the same model one after another
+/// cannot overwrite each other's changes. The lock file is left in place:
+/// removing it would let a run that opened it before the removal and a run
+/// creating a new one both hold a lock.
+pub struct ModelLock {
+    // Holds the lock while open
+    _file: File,
+}
+
+/// The lock file of the model at `path`, as in `model.archimate.lock`.
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(LOCK_SUFFIX);
+    path.with_file_name(name)
+}
+
+impl ModelLock {
+    /// Locks the model at `path`, failing at once when another run holds
+    /// the lock rather than waiting for it. The lock is released when the
+    /// value is dropped or the process ends, however it ends.
+    pub fn acquire(path: &Path) -> Result<ModelLock, Box<dyn Error>> {
+        let lock_path = lock_path(path);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let holder = fs::read_to_string(&lock_path).unwrap_or_default();
+                let holder = match holder.trim() {
+                    "" => String::new(),
+                    pid => format!(" (process {})", pid),
+                };
+                return Err(format!(
+                    "{} is being written by another run{}; try again when it is done",
+                    path.display(),
+                    holder
+                )
+                .into());
+            }
+            Err(TryLockError::Error(e)) if e.kind() == io::ErrorKind::Unsupported => {
+                eprintln!(
+                    "Warning: cannot lock {} on this file system; concurrent runs are not detected",
+                    path.display()
+                );
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+        file.set_len(0)?;
+        write!(file, "{}", process::id())?;
+        Ok(ModelLock { _file: file })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_lock() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let model = temp_dir.path().join("model.archimate");
+        let lock = ModelLock::acquire(&model)?;
+        assert_eq!(
+            fs::read_to_string(lock_path(&model))?,
+            process::id().to_string()
+        );
+        let error = ModelLock::acquire(&model)
+            .err()
+            .ok_or("locked twice")?
+            .to_string();
+        assert!(error.contains("is being written by another run"));
+
+        drop(lock);
+        ModelLock::acquire(&model)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use crate::conflict::{ConflictRecord, Resolution};
+use crate::model::{ArchiModel, ElementInfo, MissingElementInfo};
+use crate::view_hash::{concept_content_hash, view_content_hash};
+use std::error::Error;
+
+/// Which side changed a concept or view since the common ancestor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangedSide {
+    Neither,
+    Source,
+    Target,
+    Both,
+}
+
+/// Compares both versions with the ancestor using `hash`. Something missing
+/// from the ancestor was added on both sides, which counts as `Both`.
+pub fn find_changed_side(
+    base: Option<&ElementInfo>,
+    source: &ElementInfo,
+    target: &ElementInfo,
+    hash: fn(&str) -> Result<u64, Box<dyn Error>>,
+) -> Result<ChangedSide, Box<dyn Error>> {
+    let Some(base) = base else {
+        return Ok(ChangedSide::Both);
+    };
+    let base_hash = hash(&base.xml_string)?;
+    let source_changed = hash(&source.xml_string)? != base_hash;
+    let target_changed = hash(&target.xml_string)? != base_hash;
+    Ok(match (source_changed, target_changed) {
+        (false, false) => ChangedSide::Neither,
+        (true, false) => ChangedSide::Source,
+        (false, true) => ChangedSide::Target,
+        (true, true) => ChangedSide::Both,
+    })
+}
+
+/// Settles conflicts that only one side changed since the ancestor and
+/// returns them with their resolution; conflicts changed on both sides are
+/// returned unresolved.
+pub fn resolve_with_base(
+    base: &ArchiModel,
+    source: &ArchiModel,
+    target: &ArchiModel,
+    conflicts: Vec<ConflictRecord>,
+) -> Result<Vec<ConflictRecord>, Box<dyn Error>> {
+    let mut resolved = Vec::new();
+    for mut conflict in conflicts {
+        let (Some(source_info), Some(target_info)) = (
+            source.element_map.get(conflict.id.as_str()),
+            target.element_map.get(conflict.id.as_str()),
+        ) else {
+            continue;
+        };
+        conflict.resolution = match find_changed_side(
+            base.element_map.get(conflict.id.as_str()),
+            source_info,
+            target_info,
+            concept_content_hash,
+        )? {
+            ChangedSide::Source => Resolution::TakeSource,
+            ChangedSide::Target | ChangedSide::Neither => Resolution::KeepTarget,
+            ChangedSide::Both => Resolution::Unresolved,
+        };
+        resolved.push(conflict);
+    }
+    Ok(resolved)
+}
+
+/// Drops the views the target has deleted or kept unchanged relative to the
+/// ancestor while the source left them alone, printing why each was dropped.
+pub fn filter_views_with_base(
+    base: &ArchiModel,
+    source: &ArchiModel,
+    target: &ArchiModel,
+    views: Vec<MissingElementInfo>,
+) -> Result<Vec<MissingElementInfo>, Box<dyn Error>> {
+    let mut kept = Vec::new();
+    for view in views {
+        let base_info = base.view_map.get(&view.id);
+        let Some(source_info) = source.view_map.get(&view.id) else {
+            continue;
+        };
+        match &view.target_id {
+            // New to the target: only re-add it if the target never had it
+            None => {
+                if base_info.is_some() {
+                    println!(
+                        "Skipping view {}: deleted from target since base",
+                        view.name
+                    );
+                    continue;
+                }
+            }
+            Some(target_id) => {
+                let Some(target_info) = target.view_map.get(target_id) else {
+                    continue;
+                };
+                match find_changed_side(base_info, source_info, target_info, view_content_hash)? {
+                    ChangedSide::Source => {}
+                    ChangedSide::Both => println!(
+                        "Warning: view {} changed in both models since base",
+                        view.name
+                    ),
+                    ChangedSide::Target | ChangedSide::Neither => {
+                        println!(
+                            "Skipping view {}: only changed in target since base",
+                            view.name
+                        );
+                        continue;
+                    }
+                }
+            }
+        }
+        kept.push(view);
+    }
+    Ok(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{find_changed_views, find_missing_views, load_model, MatchBy};
+
+    fn model_xml(views: &str) -> String {
+        format!(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>{}</folder>
+            </archimate:model>"#,
+            views
+        )
+    }
+
+    #[test]
+    fn test_filter_views_with_base() -> Result<(), Box<dyn Error>> {
+        let base_xml = model_xml(
+            "<element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Deleted'/>
+             <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Upstream'/>
+             <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Local'/>",
+        );
+        let source_xml = model_xml(
+            "<element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Deleted'/>
+             <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Upstream'><child xsi:type='archimate:Note' id='note-1'/></element>
+             <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Local'/>
+             <element xsi:type='archimate:ArchimateDiagramModel' id='view-4' name='Added'/>",
+        );
+        let target_xml = model_xml(
+            "<element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Upstream'/>
+             <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Local'><child xsi:type='archimate:Note' id='note-2'/></element>",
+        );
+        let base = load_model(&base_xml)?;
+        let source = load_model(&source_xml)?;
+        let target = load_model(&target_xml)?;
+
+        let mut views = find_missing_views(&source, &target, MatchBy::Id);
+        views.extend(find_changed_views(&source, &target, MatchBy::Id));
+        let kept = filter_views_with_base(&base, &source, &target, views)?;
+        let mut ids: Vec<&str> = kept.iter().map(|view| &*view.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["view-2", "view-4"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,1356 @@
+use crate::concept_type::ConceptType;
+use crate::error::ImporterError;
+use crate::glossary::display_type;
+use crate::ids::{stable_hash, IdGenerator};
+use crate::namespaces::xsi_type;
+use crate::validate::{IssueKind, ValidationIssue};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use xot::{output, Node, Xot};
+
+#[derive(Clone)]
+pub struct ArchiModel {
+    pub xot: Xot,
+    pub doc: Node,
+    pub root: Node,
+    pub view_map: HashMap<Arc<str>, ElementInfo>,
+    pub element_map: HashMap<Arc<str>, ElementInfo>,
+    pub ids: IdGenerator,
+    pub folder_id_collision: FolderIdCollision,
+    /// Folders, concepts and views that had no ID when the model was
+    /// loaded and were given one.
+    pub repaired: Vec<ValidationIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementInfo {
+    pub id: Arc<str>,
+    pub name: Arc<str>,
+    #[serde(rename = "type")]
+    pub xsi_type: Arc<str>,
+    /// Left out of the serialized form, which describes the concept
+    /// rather than carrying it.
+    #[serde(skip)]
+    pub xml_string: String,
+    pub folder_path: Arc<[FolderInfo]>,
+    pub properties: Vec<(String, String)>,
+}
+
+impl ElementInfo {
+    /// The ArchiMate type of the concept, or `None` for a view or a type
+    /// this version of ArchiMate doesn't have.
+    pub fn concept_type(&self) -> Option<ConceptType> {
+        ConceptType::from_xsi_type(&self.xsi_type)
+    }
+
+    pub fn is_relationship(&self) -> bool {
+        self.concept_type()
+            .is_some_and(ConceptType::is_relationship)
+    }
+
+    pub fn is_junction(&self) -> bool {
+        self.concept_type().is_some_and(ConceptType::is_junction)
+    }
+}
+
+/// A folder of a model with the folders it is in, as listed by
+/// `ArchiModel::folders`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    /// The type of a top-level folder, as in `business` or `diagrams`.
+    #[serde(rename = "type")]
+    pub folder_type: Option<String>,
+    /// The folders this one is in, outermost first.
+    pub folder_path: Arc<[FolderInfo]>,
+    pub properties: Vec<(String, String)>,
+}
+
+impl Folder {
+    /// The path of the folder itself, as in `Views[diagrams] > Sales`.
+    pub fn path(&self) -> String {
+        let info = FolderInfo {
+            id: self.id.as_str().into(),
+            name: self.name.as_str().into(),
+            folder_type: self.folder_type.clone(),
+        };
+        let path: Vec<FolderInfo> = self.folder_path.iter().cloned().chain([info]).collect();
+        format_folder_path(&path)
+    }
+}
+
+impl ArchiModel {
+    /// The views of the model, ordered by folder path and name.
+    pub fn views(&self) -> impl Iterator<Item = &ElementInfo> {
+        sorted_concepts(self.view_map.values())
+    }
+
+    /// The elements of the model, ordered by folder path and name.
+    pub fn elements(&self) -> impl Iterator<Item = &ElementInfo> {
+        sorted_concepts(
+            self.element_map
+                .values()
+                .filter(|info| !info.is_relationship()),
+        )
+    }
+
+    /// The relations of the model, ordered by folder path and name.
+    pub fn relations(&self) -> impl Iterator<Item = &ElementInfo> {
+        sorted_concepts(
+            self.element_map
+                .values()
+                .filter(|info| info.is_relationship()),
+        )
+    }
+
+    /// Every folder of the model, empty ones included, each followed by
+    /// the folders inside it.
+    pub fn folders(&self) -> impl Iterator<Item = Folder> {
+        fn collect(
+            xot: &Xot,
+            node: Node,
+            folder_path: Arc<[FolderInfo]>,
+            folders: &mut Vec<Folder>,
+        ) {
+            for child in xot.children(node).filter(|&child| {
+                xot.is_element(child) && xot.local_name_str(xot.get_element_name(child)) == "folder"
+            }) {
+                let attribute = |name: &str| {
+                    xot.name(name)
+                        .and_then(|name| xot.get_attribute(child, name))
+                        .map(|value| value.to_string())
+                };
+                let folder = Folder {
+                    id: attribute("id").unwrap_or_default(),
+                    name: attribute("name").unwrap_or_default(),
+                    folder_type: attribute("type"),
+                    folder_path: folder_path.clone(),
+                    properties: extract_properties(xot, child),
+                };
+                let inner_path: Arc<[FolderInfo]> = folder_path
+                    .iter()
+                    .cloned()
+                    .chain([FolderInfo {
+                        id: folder.id.as_str().into(),
+                        name: folder.name.as_str().into(),
+                        folder_type: folder.folder_type.clone(),
+                    }])
+                    .collect();
+                folders.push(folder);
+                collect(xot, child, inner_path, folders);
+            }
+        }
+        let mut folders = Vec::new();
+        if let Ok(root) = self.xot.document_element(self.doc) {
+            collect(&self.xot, root, Arc::from([]), &mut folders);
+        }
+        folders.into_iter()
+    }
+}
+
+fn sorted_concepts<'m>(
+    concepts: impl Iterator<Item = &'m ElementInfo>,
+) -> impl Iterator<Item = &'m ElementInfo> {
+    let mut concepts: Vec<&ElementInfo> = concepts.collect();
+    concepts.sort_by(|a, b| {
+        compare_views(
+            (&a.folder_path, &a.name, &a.id),
+            (&b.folder_path, &b.name, &b.id),
+        )
+    });
+    concepts.into_iter()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingElementInfo {
+    pub id: Arc<str>,
+    pub name: Arc<str>,
+    pub folder_path: Arc<[FolderInfo]>,
+    /// The target view this one replaces in update mode.
+    pub target_id: Option<Arc<str>>,
+}
+
+/// How source views are paired with views already in the target.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MatchBy {
+    #[default]
+    Id,
+    /// Same name and folder path, for views recreated with new IDs.
+    Name,
+}
+
+impl FromStr for MatchBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "id" => Ok(MatchBy::Id),
+            "name" => Ok(MatchBy::Name),
+            _ => Err(format!("Invalid match mode: {} (expected id or name)", s)),
+        }
+    }
+}
+
+impl fmt::Display for MatchBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchBy::Id => write!(f, "id"),
+            MatchBy::Name => write!(f, "name"),
+        }
+    }
+}
+
+/// What to do when a folder created in the target would take an ID that a
+/// target node already has.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FolderIdCollision {
+    /// Give the new folder a freshly generated ID.
+    #[default]
+    NewId,
+    /// Use the node with that ID instead if it is a folder with the same
+    /// name in the same parent folder, otherwise generate a new ID.
+    ReuseByName,
+    /// Stop with an error.
+    Abort,
+}
+
+impl FromStr for FolderIdCollision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "new-id" => Ok(FolderIdCollision::NewId),
+            "reuse-by-name" => Ok(FolderIdCollision::ReuseByName),
+            "abort" => Ok(FolderIdCollision::Abort),
+            _ => Err(format!(
+                "Invalid folder ID collision policy: {} (expected new-id, reuse-by-name or abort)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FolderIdCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FolderIdCollision::NewId => write!(f, "new-id"),
+            FolderIdCollision::ReuseByName => write!(f, "reuse-by-name"),
+            FolderIdCollision::Abort => write!(f, "abort"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderInfo {
+    pub id: Arc<str>,
+    pub name: Arc<str>,
+    #[serde(rename = "type")]
+    pub folder_type: Option<String>,
+}
+
+impl fmt::Display for FolderInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.folder_type {
+            Some(folder_type) => write!(f, "{}[{}]", self.name, folder_type),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl Borrow<str> for FolderInfo {
+    fn borrow(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Borrow<str> for &FolderInfo {
+    fn borrow(&self) -> &str {
+        &self.name
+    }
+}
+
+pub fn format_folder_path(folder_path: &[FolderInfo]) -> String {
+    folder_path
+        .iter()
+        .map(|folder| folder.to_string())
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+/// Whether an element of this `xsi:type` is a view: an ArchiMate diagram,
+/// a sketch or a canvas.
+pub fn is_view_type(xsi_type: &str) -> bool {
+    xsi_type.ends_with("ArchimateDiagramModel") || view_kind(xsi_type).is_some()
+}
+
+/// The kind of a view that is not an ArchiMate diagram, for listings.
+pub fn view_kind(xsi_type: &str) -> Option<&'static str> {
+    if xsi_type.ends_with("SketchModel") {
+        Some("sketch")
+    } else if xsi_type.ends_with("CanvasModel") {
+        Some("canvas")
+    } else {
+        None
+    }
+}
+
+pub fn load_model(content: &str) -> Result<ArchiModel, ImporterError> {
+    let mut xot = Xot::new();
+    let doc = xot.parse(content)?;
+    let root = xot.root(doc);
+    let mut model = ArchiModel {
+        xot,
+        doc,
+        root,
+        view_map: HashMap::new(),
+        element_map: HashMap::new(),
+        ids: IdGenerator::from_entropy(),
+        folder_id_collision: FolderIdCollision::default(),
+        repaired: Vec::new(),
+    };
+
+    extract_elements(&mut model)?;
+    Ok(model)
+}
+
+pub fn extract_elements(model: &mut ArchiModel) -> Result<(), ImporterError> {
+    fill_missing_ids(model);
+    let Ok(root) = model.xot.document_element(model.doc) else {
+        return Ok(());
+    };
+
+    fn traverse_folders(
+        xot: &Xot,
+        node: Node,
+        current_path: Vec<FolderInfo>,
+        elements: &mut HashMap<Arc<str>, ElementInfo>,
+        views: &mut HashMap<Arc<str>, ElementInfo>,
+        strings: &mut HashSet<Arc<str>>,
+    ) {
+        // Shared by every concept in the folder instead of cloned into each
+        let current_path_info: Arc<[FolderInfo]> = current_path.into();
+        for child in xot.children(node).filter(|&n| xot.is_element(n)) {
+            if Some(xot.get_element_name(child)) == xot.name("element") {
+                let Some(xsi_type) = xsi_type(xot, child) else {
+                    continue;
+                };
+                let id: Arc<str> = attribute(xot, child, "id").into();
+                let name = intern(strings, &attribute(xot, child, "name"));
+                let Ok(xml_string) = xot.serialize_xml_string(Default::default(), child) else {
+                    continue;
+                };
+                let info = ElementInfo {
+                    id: id.clone(),
+                    name,
+                    xsi_type: intern(strings, xsi_type),
+                    xml_string,
+                    folder_path: current_path_info.clone(),
+                    properties: extract_properties(xot, child),
+                };
+                if is_view_type(xsi_type) {
+                    views.insert(id, info);
+                } else {
+                    elements.insert(id, info);
+                }
+            } else if Some(xot.get_element_name(child)) == xot.name("folder") {
+                let mut new_path = current_path_info.to_vec();
+                new_path.push(folder_info(xot, child, strings));
+                traverse_folders(xot, child, new_path, elements, views, strings);
+            }
+        }
+    }
+
+    // Start traversal from the root
+    let mut elements = HashMap::new();
+    let mut views = HashMap::new();
+    let mut strings = HashSet::new();
+    for child in model
+        .xot
+        .children(root)
+        .filter(|&n| model.xot.is_element(n))
+    {
+        if Some(model.xot.get_element_name(child)) == model.xot.name("folder") {
+            traverse_folders(
+                &model.xot,
+                child,
+                vec![folder_info(&model.xot, child, &mut strings)],
+                &mut elements,
+                &mut views,
+                &mut strings,
+            );
+        }
+    }
+    model.element_map = elements;
+    model.view_map = views;
+    Ok(())
+}
+
+/// Gives each folder, concept and view of the model without an `id` a
+/// generated one, as some exporters leave IDs out. The ID is derived from
+/// the node's folder path, type and name, so loading the same file again
+/// gives the same IDs. The nodes are recorded in `repaired` for the caller
+/// to report.
+fn fill_missing_ids(model: &mut ArchiModel) {
+    let Ok(root) = model.xot.document_element(model.doc) else {
+        return;
+    };
+    let xot = &model.xot;
+    let (Some(folder), Some(element)) = (xot.name("folder"), xot.name("element")) else {
+        return;
+    };
+    let in_folder_tree = |node: Node| {
+        let name = xot.get_element_name(node);
+        xot.parent(node)
+            .filter(|&parent| xot.is_element(parent))
+            .is_some_and(|parent| {
+                (parent == root && name == folder)
+                    || (xot.get_element_name(parent) == folder
+                        && (name == folder || name == element))
+            })
+    };
+    let missing: Vec<Node> = xot
+        .descendants(root)
+        .filter(|&node| xot.is_element(node) && in_folder_tree(node))
+        .filter(|&node| {
+            xot.name("id")
+                .and_then(|id| xot.get_attribute(node, id))
+                .is_none()
+        })
+        .collect();
+
+    let id_name = model.xot.add_name("id");
+    // Nodes alike in all of these are told apart by their order
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for node in missing {
+        let name = attribute(&model.xot, node, "name");
+        let (what, node_type) = if model.xot.get_element_name(node) == folder {
+            (
+                format!("Folder \"{}\"", name),
+                attribute(&model.xot, node, "type"),
+            )
+        } else {
+            let xsi_type = xsi_type(&model.xot, node).unwrap_or("element");
+            (
+                format!("{} \"{}\"", display_type(xsi_type), name),
+                xsi_type.to_string(),
+            )
+        };
+        let mut key: Vec<String> = model
+            .xot
+            .ancestors(node)
+            .skip(1)
+            .filter(|&ancestor| {
+                model.xot.is_element(ancestor) && model.xot.get_element_name(ancestor) == folder
+            })
+            .map(|ancestor| attribute(&model.xot, ancestor, "name"))
+            .collect();
+        key.reverse();
+        key.extend([node_type, name]);
+        let key = key.join("\0");
+        let occurrence = occurrences.entry(key.clone()).or_default();
+        *occurrence += 1;
+        let id = format!(
+            "id-{:016x}",
+            stable_hash(format!("{}\0{}", key, occurrence).as_bytes())
+        );
+        model.xot.set_attribute(node, id_name, id.as_str());
+        let message = format!("{} has no id attribute", what);
+        model.repaired.push(ValidationIssue {
+            kind: IssueKind::MissingAttribute,
+            id: Some(id),
+            message,
+        });
+    }
+}
+
+fn attribute(xot: &Xot, node: Node, attribute: &str) -> String {
+    xot.name(attribute)
+        .and_then(|name| xot.get_attribute(node, name))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The folder `node` as listed in folder paths. A folder without a name,
+/// as some exporters write, gets an empty one, which `validate` reports.
+fn folder_info(xot: &Xot, node: Node, strings: &mut HashSet<Arc<str>>) -> FolderInfo {
+    let id = attribute(xot, node, "id");
+    let name = xot
+        .name("name")
+        .and_then(|name| xot.get_attribute(node, name));
+    FolderInfo {
+        id: id.into(),
+        name: intern(strings, name.unwrap_or_default()),
+        folder_type: xot
+            .name("type")
+            .and_then(|name| xot.get_attribute(node, name))
+            .map(str::to_string),
+    }
+}
+
+/// Returns the shared copy of `value`, adding it to `strings` on first use.
+/// Concept types, names such as those of unnamed relationships and folder
+/// names repeat across thousands of concepts, so they are stored once per
+/// model.
+fn intern(strings: &mut HashSet<Arc<str>>, value: &str) -> Arc<str> {
+    if let Some(interned) = strings.get(value) {
+        return interned.clone();
+    }
+    let interned: Arc<str> = value.into();
+    strings.insert(interned.clone());
+    interned
+}
+
+pub fn extract_properties(xot: &Xot, node: Node) -> Vec<(String, String)> {
+    let property_name = xot.name("property");
+    let key_name = xot.name("key");
+    let value_name = xot.name("value");
+    xot.children(node)
+        .filter(|&child| {
+            xot.is_element(child) && Some(xot.get_element_name(child)) == property_name
+        })
+        .filter_map(|child| {
+            let key = xot.get_attribute(child, key_name?)?;
+            let value = value_name
+                .and_then(|name| xot.get_attribute(child, name))
+                .unwrap_or("");
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+pub fn find_missing_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    match_by: MatchBy,
+) -> Vec<MissingElementInfo> {
+    let mut missing = Vec::new();
+
+    for view_info in source.view_map.values() {
+        if find_target_view(view_info, target, match_by).is_none() {
+            missing.push(MissingElementInfo {
+                id: view_info.id.clone(),
+                name: view_info.name.clone(),
+                folder_path: view_info.folder_path.clone(),
+                target_id: None,
+            });
+        }
+    }
+
+    sort_views(&mut missing);
+    missing
+}
+
+/// Returns the views present in both models whose content differs, for
+/// replacing the target's copy in update mode.
+pub fn find_changed_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    match_by: MatchBy,
+) -> Vec<MissingElementInfo> {
+    let mut changed: Vec<MissingElementInfo> = source
+        .view_map
+        .values()
+        .filter_map(|view_info| {
+            let target_info = find_target_view(view_info, target, match_by)?;
+            (target_info.xml_string != view_info.xml_string).then(|| MissingElementInfo {
+                id: view_info.id.clone(),
+                name: view_info.name.clone(),
+                folder_path: view_info.folder_path.clone(),
+                target_id: Some(target_info.id.clone()),
+            })
+        })
+        .collect();
+
+    sort_views(&mut changed);
+    changed
+}
+
+/// Returns the source views to import: those missing from the target, and
+/// those that differ from the target view they match, which they replace.
+/// Without `update`, only views matched by name are replaced, as the source
+/// recreated them under a new ID rather than changing the target's view.
+pub fn find_views_to_import(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    match_by: MatchBy,
+    update: bool,
+) -> Vec<MissingElementInfo> {
+    let mut views = find_missing_views(source, target, match_by);
+    views.extend(
+        find_changed_views(source, target, match_by)
+            .into_iter()
+            .filter(|view| update || view.target_id.as_ref() != Some(&view.id)),
+    );
+    sort_views(&mut views);
+    views
+}
+
+/// Finds the target view a source view corresponds to. An ID match always
+/// wins; matching by name falls back to the first view, by ID, with the same
+/// name and folder path.
+pub fn find_target_view<'t>(
+    view_info: &ElementInfo,
+    target: &'t ArchiModel,
+    match_by: MatchBy,
+) -> Option<&'t ElementInfo> {
+    if let Some(target_info) = target.view_map.get(&view_info.id) {
+        return Some(target_info);
+    }
+    match match_by {
+        MatchBy::Id => None,
+        MatchBy::Name => target
+            .view_map
+            .values()
+            .filter(|target_info| {
+                target_info.name == view_info.name
+                    && target_info
+                        .folder_path
+                        .iter()
+                        .map(|folder| &*folder.name)
+                        .eq(view_info.folder_path.iter().map(|folder| &*folder.name))
+            })
+            .min_by(|a, b| a.id.cmp(&b.id)),
+    }
+}
+
+/// Finds the element carrying the given `id` attribute anywhere in the model.
+pub fn find_node_by_id(model: &ArchiModel, id: &str) -> Option<Node> {
+    let id_name = model.xot.name("id")?;
+    model.xot.descendants(model.root).find(|&node| {
+        model.xot.is_element(node) && model.xot.get_attribute(node, id_name) == Some(id)
+    })
+}
+
+/// Returns the model's own name, or an empty string if it has none.
+pub fn model_name(model: &ArchiModel) -> String {
+    model
+        .xot
+        .document_element(model.root)
+        .ok()
+        .and_then(|node| {
+            let name = model.xot.name("name")?;
+            model.xot.get_attribute(node, name)
+        })
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Orders views by folder path, then name, then ID so listings and
+/// index-based selections are stable between runs.
+pub fn sort_views(views: &mut [MissingElementInfo]) {
+    views.sort_by(|a, b| {
+        compare_views(
+            (&a.folder_path, &a.name, &a.id),
+            (&b.folder_path, &b.name, &b.id),
+        )
+    });
+}
+
+pub fn compare_views(a: (&[FolderInfo], &str, &str), b: (&[FolderInfo], &str, &str)) -> Ordering {
+    let a_path = a.0.iter().map(|folder| &*folder.name);
+    let b_path = b.0.iter().map(|folder| &*folder.name);
+    a_path
+        .cmp(b_path)
+        .then_with(|| a.1.cmp(b.1))
+        .then_with(|| a.2.cmp(b.2))
+}
+
+pub fn serialize_model(model: &ArchiModel) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(model.xot.serialize_xml_string(
+        output::xml::Parameters {
+            declaration: Some(output::xml::Declaration {
+                encoding: Some("UTF-8".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        model.doc,
+    )?)
+}
+
+/// Archi's top-level folder types, in the order Archi writes them.
+pub const FOLDER_TYPES: &[&str] = &[
+    "strategy",
+    "business",
+    "application",
+    "technology",
+    "motivation",
+    "implementation_migration",
+    "other",
+    "relations",
+    "diagrams",
+];
+
+/// The name Archi gives the top-level folder of a folder type.
+pub fn default_folder_name(folder_type: &str) -> &'static str {
+    match folder_type {
+        "business" => "Business",
+        "application" => "Application",
+        "technology" => "Technology & Physical",
+        "strategy" => "Strategy",
+        "motivation" => "Motivation",
+        "implementation_migration" => "Implementation & Migration",
+        "relations" => "Relations",
+        "diagrams" => "Views",
+        _ => "Other",
+    }
+}
+
+/// The top-level folder Archi keeps an element of this type in, or `None`
+/// when the type is not an element type.
+pub fn element_folder_type(archi_type: &str) -> Option<&'static str> {
+    Some(
+        ConceptType::from_xsi_type(archi_type)?
+            .layer()?
+            .folder_type(),
+    )
+}
+
+/// The ArchiMate layer of an element type, e.g. `Physical` for `Equipment`,
+/// or `None` when the type is not an element type. Locations, groupings
+/// and junctions belong to `Other`.
+pub fn element_layer(archi_type: &str) -> Option<&'static str> {
+    Some(ConceptType::from_xsi_type(archi_type)?.layer()?.name())
+}
+
+pub fn find_or_create_folder(
+    model: &mut ArchiModel,
+    folder_type: &str,
+) -> Result<Node, Box<dyn std::error::Error>> {
+    let root = model.xot.document_element(model.root)?;
+
+    for child in model
+        .xot
+        .children(root)
+        .filter(|&n| model.xot.is_element(n))
+    {
+        let element = model.xot.element(child).unwrap();
+        if element.name() == model.xot.name("folder").unwrap()
+            && model
+                .xot
+                .get_attribute(child, model.xot.name("type").unwrap())
+                == Some(folder_type)
+        {
+            return Ok(child);
+        }
+    }
+
+    let folder_node = model.xot.new_element(model.xot.name("folder").unwrap());
+    model
+        .xot
+        .set_attribute(folder_node, model.xot.name("type").unwrap(), folder_type);
+    let folder_id = model.ids.next_id();
+    model
+        .xot
+        .set_attribute(folder_node, model.xot.name("id").unwrap(), folder_id);
+
+    let name = default_folder_name(folder_type);
+    model
+        .xot
+        .set_attribute(folder_node, model.xot.name("name").unwrap(), name);
+
+    model.xot.append(root, folder_node)?;
+
+    Ok(folder_node)
+}
+
+/// Rebuilds a source view's folder path under `Imports/<label>` in the views
+/// folder. Folders below the views folder get fresh IDs so they never clash
+/// with the target folders they mirror.
+pub fn snapshot_folder_path(
+    model: &mut ArchiModel,
+    folder_path: &[FolderInfo],
+    label: &str,
+) -> Vec<FolderInfo> {
+    let (views_folder, subfolders) = match folder_path.split_first() {
+        Some((views_folder, subfolders)) => (views_folder.clone(), subfolders),
+        None => (
+            FolderInfo {
+                id: model.ids.next_id().into(),
+                name: "Views".into(),
+                folder_type: Some("diagrams".to_string()),
+            },
+            folder_path,
+        ),
+    };
+
+    let mut snapshot_path = vec![views_folder];
+    for name in ["Imports", label]
+        .into_iter()
+        .chain(subfolders.iter().map(|folder| &*folder.name))
+    {
+        snapshot_path.push(FolderInfo {
+            id: model.ids.next_id().into(),
+            name: name.into(),
+            folder_type: None,
+        });
+    }
+    snapshot_path
+}
+
+/// Resolves a folder path such as `Views > Imported > 2024-Q3` below the
+/// views folder; naming the views folder itself first is optional. Folders
+/// the target lacks get fresh IDs.
+pub fn into_folder_path(
+    model: &mut ArchiModel,
+    path: &str,
+) -> Result<Vec<FolderInfo>, Box<dyn std::error::Error>> {
+    folder_path_below(model, "diagrams", path)
+}
+
+/// Resolves a folder path below the top-level folder of `folder_type`, as
+/// `into_folder_path` does below the views folder.
+pub fn folder_path_below(
+    model: &mut ArchiModel,
+    folder_type: &str,
+    path: &str,
+) -> Result<Vec<FolderInfo>, Box<dyn std::error::Error>> {
+    let top_folder = find_or_create_folder(model, folder_type)?;
+    let id_name = model.xot.add_name("id");
+    let name_name = model.xot.add_name("name");
+    let top_info = FolderInfo {
+        id: model
+            .xot
+            .get_attribute(top_folder, id_name)
+            .unwrap_or("")
+            .into(),
+        name: model
+            .xot
+            .get_attribute(top_folder, name_name)
+            .unwrap_or("")
+            .into(),
+        folder_type: Some(folder_type.to_string()),
+    };
+
+    let mut names: Vec<&str> = path
+        .split('>')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    if names.first() == Some(&&*top_info.name) {
+        names.remove(0);
+    }
+    let mut folder_path = vec![top_info];
+    for name in names {
+        folder_path.push(FolderInfo {
+            id: model.ids.next_id().into(),
+            name: name.into(),
+            folder_type: None,
+        });
+    }
+    Ok(folder_path)
+}
+
+pub fn recursive_find_or_create_folder_path(
+    model: &mut ArchiModel,
+    folder_path: &[FolderInfo],
+) -> Result<Node, Box<dyn std::error::Error>> {
+    if folder_path.is_empty() {
+        return find_or_create_folder(model, "diagrams");
+    }
+
+    let mut current = model.xot.document_element(model.root)?;
+    for folder_info in folder_path {
+        let mut found = false;
+        let mut next_folder = None;
+        let folder_name: &str = &folder_info.name;
+
+        for child in model
+            .xot
+            .children(current)
+            .filter(|&n| model.xot.is_element(n))
+        {
+            let element = model.xot.element(child).unwrap();
+            if element.name() == model.xot.name("folder").unwrap()
+                && model
+                    .xot
+                    .get_attribute(child, model.xot.name("name").unwrap())
+                    == Some(folder_name)
+            {
+                found = true;
+                next_folder = Some(child);
+                break;
+            }
+        }
+
+        if found {
+            current = next_folder.unwrap();
+            continue;
+        }
+
+        // Source folder IDs may already be taken by unrelated target nodes
+        let mut id = folder_info.id.clone();
+        if let Some(existing) = find_node_by_id(model, &id) {
+            let name_name = model.xot.name("name").unwrap();
+            // Elsewhere in the tree, the folder would not follow the path
+            let is_same_folder = model.xot.get_element_name(existing)
+                == model.xot.name("folder").unwrap()
+                && model.xot.get_attribute(existing, name_name) == Some(folder_name)
+                && model.xot.parent(existing) == Some(current);
+            match model.folder_id_collision {
+                FolderIdCollision::Abort => {
+                    return Err(format!(
+                        "Folder {} cannot be created: ID {} is already used in target",
+                        folder_name, id
+                    )
+                    .into());
+                }
+                FolderIdCollision::ReuseByName if is_same_folder => {
+                    current = existing;
+                    continue;
+                }
+                _ => id = model.ids.next_id().into(),
+            }
+        }
+        let new_folder = model.xot.new_element(model.xot.name("folder").unwrap());
+        model
+            .xot
+            .set_attribute(new_folder, model.xot.name("name").unwrap(), folder_name);
+        model
+            .xot
+            .set_attribute(new_folder, model.xot.name("id").unwrap(), &*id);
+        if let Some(folder_type) = &folder_info.folder_type {
+            model.xot.set_attribute(
+                new_folder,
+                model.xot.name("type").unwrap(),
+                folder_type.as_str(),
+            );
+        }
+        model.xot.append(current, new_folder)?;
+        current = new_folder;
+    }
+
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    #[allow(clippy::needless_borrow)]
+    fn test_folder_info_borrow() {
+        let folder = FolderInfo {
+            id: "id-1".into(),
+            name: "Test Folder".into(),
+            folder_type: None,
+        };
+        let borrowed: &str = folder.borrow();
+        assert_eq!(borrowed, "Test Folder");
+        let borrowed2: &str = (&folder).borrow();
+        assert_eq!(borrowed2, "Test Folder");
+    }
+
+    #[test]
+    fn test_format_folder_path() {
+        let folder_path = vec![
+            FolderInfo {
+                id: "folder-1".into(),
+                name: "Views".into(),
+                folder_type: Some("diagrams".to_string()),
+            },
+            FolderInfo {
+                id: "folder-2".into(),
+                name: "Domain A".into(),
+                folder_type: None,
+            },
+        ];
+        assert_eq!(
+            format_folder_path(&folder_path),
+            "Views[diagrams] > Domain A"
+        );
+    }
+
+    #[test]
+    fn test_load_model() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate'>
+                <folder type='diagrams' name='Views' id='folder-1'/>
+            </archimate:model>"#;
+
+        let model = load_model(xml)?;
+
+        assert!(model.view_map.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_iterators() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <folder name="Sales" id="folder-2">
+      <element xsi:type="archimate:ApplicationComponent" name="Shop" id="element-2"/>
+      <property key="owner" value="Sales"/>
+    </folder>
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-1"/>
+  </folder>
+  <folder name="Relations" id="folder-3" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-2"/>
+  </folder>
+  <folder name="Views" id="folder-4" type="diagrams">
+    <folder name="Empty" id="folder-5"/>
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1"/>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+
+        let names = |concepts: Vec<&ElementInfo>| -> Vec<String> {
+            concepts.iter().map(|info| info.name.to_string()).collect()
+        };
+        assert_eq!(names(model.views().collect()), vec!["Overview"]);
+        // Ordered by folder path, then name
+        assert_eq!(names(model.elements().collect()), vec!["CRM", "Shop"]);
+        let relations: Vec<_> = model.relations().collect();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(&*relations[0].id, "relation-1");
+
+        let folders: Vec<Folder> = model.folders().collect();
+        let paths: Vec<String> = folders.iter().map(Folder::path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "Application[application]",
+                "Application[application] > Sales",
+                "Relations[relations]",
+                "Views[diagrams]",
+                "Views[diagrams] > Empty",
+            ]
+        );
+        assert_eq!(
+            folders[1].properties,
+            vec![("owner".to_string(), "Sales".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_element_info() -> Result<(), Box<dyn Error>> {
+        let info = ElementInfo {
+            id: "element-1".into(),
+            name: "Shop".into(),
+            xsi_type: Arc::from("archimate:ApplicationComponent"),
+            xml_string: "<element/>".to_string(),
+            folder_path: Arc::from([FolderInfo {
+                id: "folder-1".into(),
+                name: "Application".into(),
+                folder_type: Some("application".to_string()),
+            }]),
+            properties: vec![("owner".to_string(), "Sales".to_string())],
+        };
+        let json = serde_json::to_value(&info)?;
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": "element-1",
+                "name": "Shop",
+                "type": "archimate:ApplicationComponent",
+                "folder_path": [{ "id": "folder-1", "name": "Application", "type": "application" }],
+                "properties": [["owner", "Sales"]],
+            })
+        );
+        let read: ElementInfo = serde_json::from_value(json)?;
+        assert_eq!(read.name, info.name);
+        assert_eq!(
+            read.folder_path[0].folder_type,
+            info.folder_path[0].folder_type
+        );
+        assert!(read.xml_string.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_model_shares_types_and_folder_paths() -> Result<(), Box<dyn Error>> {
+        let model = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Backend'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+
+        let first = model.element_map.get("element-1").unwrap();
+        let second = model.element_map.get("element-2").unwrap();
+        assert!(Arc::ptr_eq(&first.xsi_type, &second.xsi_type));
+        assert!(Arc::ptr_eq(&first.folder_path, &second.folder_path));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_model_fills_missing_ids() -> Result<(), Box<dyn Error>> {
+        // No node has a name, and neither the folder nor the relation an ID
+        let content = r#"<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate">
+            <folder type="relations">
+                <element xsi:type="archimate:AssociationRelationship" source="a" target="b"/>
+            </folder>
+        </archimate:model>"#;
+        let model = load_model(content)?;
+        let relation = model.relations().next().ok_or("relation not loaded")?;
+        assert!(relation.name.is_empty());
+        assert_eq!(&*relation.folder_path[0].name, "");
+        assert_eq!(
+            relation.folder_path[0].id,
+            model.repaired[0].id.clone().unwrap().into()
+        );
+        assert!(find_node_by_id(&model, &relation.id).is_some());
+        let messages: Vec<&str> = model
+            .repaired
+            .iter()
+            .map(|issue| issue.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                r#"Folder "" has no id attribute"#,
+                r#"Association Relationship "" has no id attribute"#
+            ]
+        );
+
+        // Loading the file again gives the same IDs
+        let again = load_model(content)?;
+        assert!(again.element_map.contains_key(&relation.id));
+        assert_eq!(again.repaired[0].id, model.repaired[0].id);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_sketch_and_canvas_views() -> Result<(), Box<dyn Error>> {
+        let model = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:canvas='http://www.archimatetool.com/canvas' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                </folder>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Diagram'/>
+                    <element xsi:type='archimate:SketchModel' id='view-2' name='Sketch'>
+                        <child xsi:type='archimate:SketchModelSticky' id='sticky-1' name='Idea'/>
+                    </element>
+                    <element xsi:type='canvas:CanvasModel' id='view-3' name='Canvas'>
+                        <child xsi:type='canvas:CanvasModelBlock' id='block-1'/>
+                    </element>
+                </folder>
+            </archimate:model>"#,
+        )?;
+
+        assert_eq!(model.view_map.len(), 3);
+        assert_eq!(model.element_map.len(), 1);
+        let kinds: Vec<_> = ["view-1", "view-2", "view-3"]
+            .iter()
+            .map(|id| view_kind(&model.view_map.get(*id).unwrap().xsi_type))
+            .collect();
+        assert_eq!(kinds, vec![None, Some("sketch"), Some("canvas")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_missing_views() -> Result<(), Box<dyn Error>> {
+        // Create source model with one view
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' 
+                            id='view-1' name='Test View'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+
+        // Create target model with no views
+        let target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate'>
+                <folder type='diagrams' name='Views' id='folder-1'/>
+            </archimate:model>"#,
+        )?;
+
+        let missing = find_missing_views(&source, &target, MatchBy::Id);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(&*missing[0].id, "view-1");
+        assert_eq!(&*missing[0].name, "Test View");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_missing_views_sorted() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <folder name='B' id='folder-3'>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Alpha'/>
+                    </folder>
+                    <folder name='A' id='folder-2'>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-4' name='Beta'/>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Beta'/>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Gamma'/>
+                    </folder>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-5' name='Zeta'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate'>
+                <folder type='diagrams' name='Views' id='folder-1'/>
+            </archimate:model>"#,
+        )?;
+
+        let missing = find_missing_views(&source, &target, MatchBy::Id);
+        let ids: Vec<&str> = missing.iter().map(|view| &*view.id).collect();
+        assert_eq!(ids, vec!["view-5", "view-3", "view-4", "view-2", "view-1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_target_view_by_name() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Landscape'/>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Context'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-9' name='Landscape'/>
+                    <folder name='Archive' id='folder-3'>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-8' name='Context'/>
+                    </folder>
+                </folder>
+            </archimate:model>"#,
+        )?;
+
+        assert_eq!(find_missing_views(&source, &target, MatchBy::Id).len(), 2);
+        let missing = find_missing_views(&source, &target, MatchBy::Name);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(&*missing[0].id, "view-2");
+        let changed = find_changed_views(&source, &target, MatchBy::Name);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].target_id.as_deref(), Some("view-9"));
+        let views = find_views_to_import(&source, &target, MatchBy::Name, false);
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[1].target_id.as_deref(), Some("view-9"));
+        assert_eq!("name".parse::<MatchBy>()?, MatchBy::Name);
+        assert!("title".parse::<MatchBy>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_find_or_create_folder_path() -> Result<(), Box<dyn Error>> {
+        // The comment comes before the model element
+        let mut model = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <!-- Exported for review -->
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' name='Model'>
+                <folder type='diagrams' name='Views' id='folder-1'/>
+            </archimate:model>"#,
+        )?;
+        assert_eq!(model_name(&model), "Model");
+        let views = find_or_create_folder(&mut model, "diagrams")?;
+        assert_eq!(
+            model
+                .xot
+                .get_attribute(views, model.xot.name("id").unwrap()),
+            Some("folder-1")
+        );
+
+        let folder_path = vec![
+            FolderInfo {
+                id: "folder-1".into(),
+                name: "Level 1".into(),
+                folder_type: None,
+            },
+            FolderInfo {
+                id: "folder-2".into(),
+                name: "Level 2".into(),
+                folder_type: None,
+            },
+        ];
+
+        let folder = recursive_find_or_create_folder_path(&mut model, &folder_path)?;
+        let folder_name = model
+            .xot
+            .get_attribute(folder, model.xot.name("name").unwrap());
+        assert_eq!(folder_name, Some("Level 2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_folder_id_collision() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <folder name='Team' id='folder-2'/>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'/>
+                </folder>
+            </archimate:model>"#;
+        let folder_path = vec![
+            FolderInfo {
+                id: "folder-1".into(),
+                name: "Views".into(),
+                folder_type: Some("diagrams".to_string()),
+            },
+            FolderInfo {
+                id: "folder-3".into(),
+                name: "Domain".into(),
+                folder_type: None,
+            },
+            FolderInfo {
+                id: "folder-2".into(),
+                name: "Team".into(),
+                folder_type: None,
+            },
+        ];
+        let folder_id = |model: &ArchiModel, folder: Node| {
+            model
+                .xot
+                .get_attribute(folder, model.xot.name("id").unwrap())
+                .map(|id| id.to_string())
+        };
+
+        let mut model = load_model(xml)?;
+        let folder = recursive_find_or_create_folder_path(&mut model, &folder_path)?;
+        let new_id = folder_id(&model, folder).unwrap();
+        assert_ne!(new_id, "folder-2");
+        assert!(new_id.starts_with("id-"));
+
+        let mut model = load_model(xml)?;
+        model.folder_id_collision = FolderIdCollision::ReuseByName;
+        let folder = recursive_find_or_create_folder_path(&mut model, &folder_path)?;
+        // Views > Team is not on the path Views > Domain > Team
+        assert_ne!(folder_id(&model, folder).as_deref(), Some("folder-2"));
+        let parent = model.xot.parent(folder).unwrap();
+        assert_eq!(folder_id(&model, parent).as_deref(), Some("folder-3"));
+        assert_eq!(
+            model
+                .xot
+                .get_attribute(folder, model.xot.name("name").unwrap()),
+            Some("Team")
+        );
+
+        let mut model = load_model(xml)?;
+        model.folder_id_collision = FolderIdCollision::Abort;
+        let result = recursive_find_or_create_folder_path(&mut model, &folder_path);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("ID folder-2 is already used"));
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use xot::{Node, Xot};
+
+pub const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// Brings the prefixes of `node`, a source fragment just placed in the
+/// target, in line with the target document: `xsi:type` values and
+/// namespace declarations use the prefixes the target already declares,
+/// and namespaces the target lacks are declared on its model element.
+/// Returns whether anything changed.
+pub fn normalize_namespaces(xot: &mut Xot, node: Node) -> bool {
+    let Some(parent) = xot.parent(node) else {
+        return false;
+    };
+    let Ok(model) = xot.document_element(xot.root(node)) else {
+        return false;
+    };
+    let xsi = xot.add_namespace(XSI_NAMESPACE);
+    let type_name = xot.add_name_ns("type", xsi);
+    let elements: Vec<Node> = xot
+        .descendants(node)
+        .filter(|&element| xot.is_element(element))
+        .collect();
+    let mut changed = false;
+
+    // Type values are QNames, which no declaration change reaches
+    for &element in &elements {
+        let Some((prefix, local_name)) = xot
+            .get_attribute(element, type_name)
+            .and_then(|value| value.split_once(':'))
+        else {
+            continue;
+        };
+        let Some(target_prefix) = xot
+            .prefix(prefix)
+            .and_then(|prefix| xot.namespace_for_prefix(element, prefix))
+            .and_then(|namespace| xot.prefix_for_namespace(parent, namespace))
+        else {
+            continue;
+        };
+        if xot.prefix_str(target_prefix) != prefix {
+            let value = format!("{}:{}", xot.prefix_str(target_prefix), local_name);
+            xot.set_attribute(element, type_name, value);
+            changed = true;
+        }
+    }
+
+    for &element in &elements {
+        for (prefix, namespace) in xot.namespace_declarations(element) {
+            if xot.prefix_for_namespace(parent, namespace).is_some() {
+                xot.remove_namespace(element, prefix);
+            } else if prefix != xot.empty_prefix()
+                && xot.namespace_for_prefix(model, prefix).is_none()
+            {
+                xot.remove_namespace(element, prefix);
+                xot.set_namespace(model, prefix, namespace);
+            } else {
+                // The prefix means something else in the target
+                continue;
+            }
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// The `xsi:type` of an element, as in `archimate:BusinessActor`.
+pub fn xsi_type(xot: &Xot, node: Node) -> Option<&str> {
+    let type_name = xot.name_ns("type", xot.namespace(XSI_NAMESPACE)?)?;
+    xot.get_attribute(node, type_name)
+}
+
+/// The Archi type of an element without its prefix, as in
+/// `BusinessActor`.
+pub fn archi_type(xot: &Xot, node: Node) -> Option<&str> {
+    let xsi_type = xsi_type(xot, node)?;
+    Some(xsi_type.rsplit(':').next().unwrap_or(xsi_type))
+}
+
+/// The value of an attribute without a namespace, as Archi writes all but
+/// `xsi:type`.
+pub fn attribute<'a>(xot: &'a Xot, node: Node, name: &str) -> Option<&'a str> {
+    xot.attributes(node).iter().find_map(|(attribute, value)| {
+        let (local_name, namespace) = xot.name_ns_str(attribute);
+        (local_name == name && namespace.is_empty()).then_some(value.as_str())
+    })
+}
+
+/// The child elements of `node` named `name`.
+pub fn children(xot: &Xot, node: Node, name: &str) -> Vec<Node> {
+    xot.children(node)
+        .filter(|&child| {
+            xot.is_element(child) && xot.local_name_str(xot.get_element_name(child)) == name
+        })
+        .collect()
+}
+
+/// The text of the first child element named `name`, such as a concept's
+/// `documentation`, or `None` when there is no such child.
+pub fn child_text<'a>(xot: &'a Xot, node: Node, name: &str) -> Option<&'a str> {
+    children(xot, node, name)
+        .first()
+        .map(|&child| xot.text_content_str(child).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_namespaces() -> Result<(), Box<dyn std::error::Error>> {
+        let mut xot = Xot::new();
+        let doc = xot.parse(
+            r#"<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate"><folder/></archimate:model>"#,
+        )?;
+        let folder = xot
+            .first_child(xot.document_element(doc)?)
+            .ok_or("no folder")?;
+        let fragment = xot.parse(
+            r#"<element xmlns:x="http://www.w3.org/2001/XMLSchema-instance" xmlns:am="http://www.archimatetool.com/archimate" xmlns:ext="urn:extension" x:type="am:ArchimateDiagramModel" ext:note="kept"><child x:type="am:DiagramObject"/></element>"#,
+        )?;
+        let element = xot.document_element(fragment)?;
+        xot.append(folder, element)?;
+
+        assert!(normalize_namespaces(&mut xot, element));
+        assert_eq!(
+            xot.to_string(doc)?,
+            r#"<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" xmlns:ext="urn:extension"><folder><element xsi:type="archimate:ArchimateDiagramModel" ext:note="kept"><child xsi:type="archimate:DiagramObject"/></element></folder></archimate:model>"#
+        );
+        assert!(!normalize_namespaces(&mut xot, element));
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_helpers() -> Result<(), Box<dyn std::error::Error>> {
+        let mut xot = Xot::new();
+        let doc = xot.parse(
+            r#"<model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:ext="urn:extension"><element xsi:type="archimate:BusinessActor" name="Clerk" ext:name="other"><documentation>First</documentation><documentation>Second</documentation></element></model>"#,
+        )?;
+        let model = xot.document_element(doc)?;
+        let element = children(&xot, model, "element")[0];
+        assert_eq!(xsi_type(&xot, element), Some("archimate:BusinessActor"));
+        assert_eq!(archi_type(&xot, element), Some("BusinessActor"));
+        assert_eq!(attribute(&xot, element, "name"), Some("Clerk"));
+        assert_eq!(child_text(&xot, element, "documentation"), Some("First"));
+        assert_eq!(child_text(&xot, element, "purpose"), None);
+        assert_eq!(archi_type(&xot, model), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use crate::model::{element_layer, find_node_by_id, ArchiModel};
+use crate::namespaces::{attribute, children, xsi_type};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use xot::Node;
+
+/// Writes each of `view_ids` to `directory` as a PlantUML diagram using the
+/// ArchiMate sprites of PlantUML's standard library, one `.puml` file per
+/// view named after it. Sketches and canvases are skipped. Returns the files
+/// written.
+pub fn write_views(
+    model: &ArchiModel,
+    view_ids: &[String],
+    directory: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+    let mut file_names = HashSet::new();
+    let mut written = Vec::new();
+    for view_id in view_ids {
+        let Some(view) = find_node_by_id(model, view_id) else {
+            continue;
+        };
+        if xsi_type(&model.xot, view) != Some("archimate:ArchimateDiagramModel") {
+            println!(
+                "Warning: {} is not an ArchiMate view, not exported to PlantUML",
+                attribute(&model.xot, view, "name").unwrap_or(view_id)
+            );
+            continue;
+        }
+        let name = attribute(&model.xot, view, "name").unwrap_or(view_id);
+        let base = file_stem(name);
+        let mut file_name = format!("{}.puml", base);
+        let mut counter = 2;
+        while !file_names.insert(file_name.to_lowercase()) {
+            file_name = format!("{} ({}).puml", base, counter);
+            counter += 1;
+        }
+        let path = directory.join(file_name);
+        fs::write(&path, view_to_plantuml(model, view)?)
+            .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Renders an ArchiMate view as PlantUML. Nested diagram objects become
+/// nested elements and visual groups become rectangles; notes, view
+/// references and connections without a relationship are left out.
+pub fn view_to_plantuml(model: &ArchiModel, view: Node) -> Result<String, Box<dyn Error>> {
+    let xot = &model.xot;
+    let mut text = String::new();
+    let name = attribute(xot, view, "name").unwrap_or_default();
+    writeln!(text, "@startuml")?;
+    writeln!(text, "!include <archimate/Archimate>")?;
+    writeln!(text)?;
+    writeln!(text, "title {}", name.replace('\n', " "))?;
+    writeln!(text)?;
+
+    let mut drawn = HashSet::new();
+    for child in children(xot, view, "child") {
+        write_object(model, child, 0, &mut drawn, &mut text)?;
+    }
+
+    let mut connections = Vec::new();
+    for node in xot.descendants(view) {
+        if xot.is_element(node)
+            && xot.local_name_str(xot.get_element_name(node)) == "sourceConnection"
+        {
+            connections.push(node);
+        }
+    }
+    if !connections.is_empty() {
+        writeln!(text)?;
+    }
+    for connection in connections {
+        let (Some(source), Some(target), Some(relation_id)) = (
+            attribute(xot, connection, "source"),
+            attribute(xot, connection, "target"),
+            attribute(xot, connection, "archimateRelationship"),
+        ) else {
+            continue;
+        };
+        if !drawn.contains(source) || !drawn.contains(target) {
+            continue;
+        }
+        let Some(relation) = find_node_by_id(model, relation_id) else {
+            continue;
+        };
+        let Some(relation_type) = xsi_type(xot, relation)
+            .and_then(|xsi_type| xsi_type.strip_prefix("archimate:"))
+            .and_then(|xsi_type| xsi_type.strip_suffix("Relationship"))
+        else {
+            continue;
+        };
+        let relation_macro = match relation_type {
+            // Archi leaves out the default, write access
+            "Access" => match attribute(xot, relation, "accessType").unwrap_or("0") {
+                "1" => "Rel_Access_r".to_string(),
+                "2" => "Rel_Access".to_string(),
+                "3" => "Rel_Access_rw".to_string(),
+                _ => "Rel_Access_w".to_string(),
+            },
+            other => format!("Rel_{}", other),
+        };
+        writeln!(
+            text,
+            "{}({}, {}, {})",
+            relation_macro,
+            alias(source),
+            alias(target),
+            label(attribute(xot, relation, "name").unwrap_or_default())
+        )?;
+    }
+
+    writeln!(text)?;
+    writeln!(text, "@enduml")?;
+    Ok(text)
+}
+
+fn write_object(
+    model: &ArchiModel,
+    object: Node,
+    depth: usize,
+    drawn: &mut HashSet<String>,
+    text: &mut String,
+) -> Result<(), Box<dyn Error>> {
+    let xot = &model.xot;
+    let Some(id) = attribute(xot, object, "id") else {
+        return Ok(());
+    };
+    let declaration = match xsi_type(xot, object) {
+        Some("archimate:DiagramObject") => {
+            let Some(element) = attribute(xot, object, "archimateElement")
+                .and_then(|element_id| find_node_by_id(model, element_id))
+            else {
+                return Ok(());
+            };
+            let Some(element_type) =
+                xsi_type(xot, element).and_then(|xsi_type| xsi_type.strip_prefix("archimate:"))
+            else {
+                return Ok(());
+            };
+            let Some(element_macro) = element_macro(element_type, attribute(xot, element, "type"))
+            else {
+                return Ok(());
+            };
+            format!(
+                "{}({}, {})",
+                element_macro,
+                alias(id),
+                label(attribute(xot, element, "name").unwrap_or_default())
+            )
+        }
+        Some("archimate:DiagramModelGroup") => format!(
+            "rectangle {} as {}",
+            label(attribute(xot, object, "name").unwrap_or_default()),
+            alias(id)
+        ),
+        _ => return Ok(()),
+    };
+    drawn.insert(id.to_string());
+
+    let indent = "  ".repeat(depth);
+    let nested = children(xot, object, "child");
+    if nested.is_empty() {
+        writeln!(text, "{}{}", indent, declaration)?;
+        return Ok(());
+    }
+    writeln!(text, "{}{} {{", indent, declaration)?;
+    for child in nested {
+        write_object(model, child, depth + 1, drawn, text)?;
+    }
+    writeln!(text, "{}}}", indent)?;
+    Ok(())
+}
+
+/// The standard library macro drawing an element of this ArchiMate type,
+/// e.g. `Business_Role` for `BusinessRole`. `junction_type` is the `type`
+/// attribute of a junction.
+fn element_macro(element_type: &str, junction_type: Option<&str>) -> Option<String> {
+    let layer = match element_type {
+        "Junction" if junction_type == Some("or") => return Some("Junction_Or".to_string()),
+        "Junction" => return Some("Junction_And".to_string()),
+        "Grouping" => return Some("Grouping".to_string()),
+        "Location" => return Some("Other_Location".to_string()),
+        _ => element_layer(element_type)?,
+    };
+    let name = element_type.strip_prefix(layer).unwrap_or(element_type);
+    Some(format!("{}_{}", layer, name))
+}
+
+/// A PlantUML identifier for a diagram object.
+fn alias(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// A quoted PlantUML label; quotes cannot be escaped, so they become
+/// apostrophes.
+fn label(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('"', "'")
+            .replace("\r\n", "\\n")
+            .replace('\n', "\\n")
+    )
+}
+
+/// A file name for a view, without characters file systems reject.
+fn file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let stem = stem.trim().trim_matches('.');
+    if stem.is_empty() {
+        "view".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_view_to_plantuml() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Business" id="folder-1" type="business">
+    <element xsi:type="archimate:BusinessRole" name="Clerk &quot;A&quot;" id="element-1"/>
+  </folder>
+  <folder name="Application" id="folder-2" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal" id="element-2"/>
+    <element xsi:type="archimate:DataObject" name="Order" id="element-3"/>
+  </folder>
+  <folder name="Relations" id="folder-3" type="relations">
+    <element xsi:type="archimate:ServingRelationship" name="serves" id="relation-1" source="element-2" target="element-1"/>
+    <element xsi:type="archimate:AccessRelationship" id="relation-2" source="element-2" target="element-3" accessType="1"/>
+  </folder>
+  <folder name="Views" id="folder-4" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramModelGroup" name="Front office" id="group-1">
+        <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1"/>
+      </child>
+      <child xsi:type="archimate:DiagramObject" id="object-2" archimateElement="element-2">
+        <sourceConnection xsi:type="archimate:Connection" id="connection-1" source="object-2" target="object-1" archimateRelationship="relation-1"/>
+        <sourceConnection xsi:type="archimate:Connection" id="connection-2" source="object-2" target="object-3" archimateRelationship="relation-2"/>
+      </child>
+      <child xsi:type="archimate:DiagramObject" id="object-3" archimateElement="element-3"/>
+      <child xsi:type="archimate:Note" id="note-1"/>
+    </element>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+        let view = find_node_by_id(&model, "view-1").unwrap();
+
+        assert_eq!(
+            view_to_plantuml(&model, view)?,
+            "@startuml\n\
+             !include <archimate/Archimate>\n\
+             \n\
+             title Overview\n\
+             \n\
+             rectangle \"Front office\" as group_1 {\n\
+             \x20 Business_Role(object_1, \"Clerk 'A'\")\n\
+             }\n\
+             Application_Component(object_2, \"Portal\")\n\
+             Application_DataObject(object_3, \"Order\")\n\
+             \n\
+             Rel_Serving(object_2, object_1, \"serves\")\n\
+             Rel_Access_r(object_2, object_3, \"\")\n\
+             \n\
+             @enduml\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use crate::model::{find_node_by_id, ArchiModel};
+use crate::namespaces::normalize_namespaces;
+use std::error::Error;
+use xot::{Node, Xot};
+
+/// A specialization defined by a `<profile>` child of the model.
+struct Profile {
+    node: Node,
+    id: String,
+    name: String,
+    concept_type: String,
+}
+
+fn find_profiles(xot: &Xot, model_node: Node) -> Vec<Profile> {
+    let (Some(profile_name), Some(id_name), Some(name_name), Some(concept_type_name)) = (
+        xot.name("profile"),
+        xot.name("id"),
+        xot.name("name"),
+        xot.name("conceptType"),
+    ) else {
+        return Vec::new();
+    };
+    xot.children(model_node)
+        .filter(|&node| xot.is_element(node) && xot.get_element_name(node) == profile_name)
+        .map(|node| {
+            let attribute = |name| xot.get_attribute(node, name).unwrap_or("").to_string();
+            Profile {
+                node,
+                id: attribute(id_name),
+                name: attribute(name_name),
+                concept_type: attribute(concept_type_name),
+            }
+        })
+        .collect()
+}
+
+/// Points the `profiles` attribute of a concept copied into the target at
+/// target profiles, reusing a profile with the same name and concept type
+/// or else copying the source profile. Returns whether the attribute changed.
+pub fn import_profiles(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    element: Node,
+) -> Result<bool, Box<dyn Error>> {
+    let profiles_name = target.xot.add_name("profiles");
+    let Some(value) = target.xot.get_attribute(element, profiles_name) else {
+        return Ok(false);
+    };
+    let profile_ids: Vec<String> = value.split_whitespace().map(str::to_string).collect();
+
+    let mut target_ids = Vec::new();
+    for profile_id in &profile_ids {
+        target_ids.push(import_profile(source, target, profile_id)?);
+    }
+    if target_ids == profile_ids {
+        return Ok(false);
+    }
+    target
+        .xot
+        .set_attribute(element, profiles_name, target_ids.join(" "));
+    Ok(true)
+}
+
+/// Returns the ID of the target profile matching a source profile, copying
+/// the source profile into the target when there is none.
+fn import_profile(
+    source: &ArchiModel,
+    target: &mut ArchiModel,
+    profile_id: &str,
+) -> Result<String, Box<dyn Error>> {
+    let source_model = source.xot.document_element(source.root)?;
+    let Some(profile) = find_profiles(&source.xot, source_model)
+        .into_iter()
+        .find(|profile| profile.id == profile_id)
+    else {
+        return Ok(profile_id.to_string());
+    };
+
+    let target_model = target.xot.document_element(target.root)?;
+    let target_profiles = find_profiles(&target.xot, target_model);
+    if let Some(existing) = target_profiles.iter().find(|existing| {
+        existing.name == profile.name && existing.concept_type == profile.concept_type
+    }) {
+        return Ok(existing.id.clone());
+    }
+
+    let xml_string = source
+        .xot
+        .serialize_xml_string(Default::default(), profile.node)?;
+    let cloned_node = target.xot.parse(&xml_string)?;
+    let cloned_profile = target.xot.document_element(cloned_node)?;
+    let mut id = profile.id.clone();
+    if find_node_by_id(target, &id).is_some() {
+        id = target.ids.next_id();
+        let id_name = target.xot.add_name("id");
+        target
+            .xot
+            .set_attribute(cloned_profile, id_name, id.as_str());
+    }
+    // Profiles are kept together after the folders
+    match target_profiles.last() {
+        Some(last) => target.xot.insert_after(last.node, cloned_profile)?,
+        None => target.xot.append(target_model, cloned_profile)?,
+    }
+    normalize_namespaces(&mut target.xot, cloned_profile);
+    println!(
+        "Copied specialization {} [{}]",
+        profile.name, profile.concept_type
+    );
+    Ok(id)
+}
+
+/// Returns the ID of the model's specialization named `name` for concepts
+/// of `concept_type`, as in `ApplicationComponent`, creating it when the
+/// model has none.
+pub fn find_or_create_profile(
+    model: &mut ArchiModel,
+    name: &str,
+    concept_type: &str,
+) -> Result<String, Box<dyn Error>> {
+    let model_node = model.xot.document_element(model.root)?;
+    let profiles = find_profiles(&model.xot, model_node);
+    if let Some(existing) = profiles
+        .iter()
+        .find(|existing| existing.name == name && existing.concept_type == concept_type)
+    {
+        return Ok(existing.id.clone());
+    }
+
+    let profile_name = model.xot.add_name("profile");
+    let profile = model.xot.new_element(profile_name);
+    let id = model.ids.next_id();
+    for (attribute, value) in [("name", name), ("id", &id), ("conceptType", concept_type)] {
+        let attribute = model.xot.add_name(attribute);
+        model.xot.set_attribute(profile, attribute, value);
+    }
+    // Profiles are kept together after the folders
+    match profiles.last() {
+        Some(last) => model.xot.insert_after(last.node, profile)?,
+        None => model.xot.append(model_node, profile)?,
+    }
+    println!("Created specialization {} [{}]", name, concept_type);
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copy::{copy_view, CopyOptions};
+    use crate::model::{load_model, serialize_model, MissingElementInfo};
+
+    #[test]
+    fn test_copy_view_imports_profiles() -> Result<(), Box<dyn Error>> {
+        let mut source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal' profiles='profile-1'/>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Billing' profiles='profile-2'/>
+                </folder>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                        <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                        <child xsi:type='archimate:DiagramObject' id='object-2' archimateElement='element-2'/>
+                    </element>
+                </folder>
+                <profile name='Cloud Service' id='profile-1' conceptType='ApplicationComponent'/>
+                <profile name='Legacy' id='profile-2' conceptType='ApplicationComponent'/>
+            </archimate:model>"#,
+        )?;
+        let mut target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='profile-2' name='Unlucky ID'/>
+                </folder>
+                <profile name='Cloud Service' id='profile-9' conceptType='ApplicationComponent'/>
+            </archimate:model>"#,
+        )?;
+        let view = MissingElementInfo {
+            id: "view-1".into(),
+            name: "View".into(),
+            folder_path: Vec::new().into(),
+            target_id: None,
+        };
+
+        copy_view(&mut source, &mut target, &view, &CopyOptions::default())?;
+
+        assert!(target.element_map["element-1"]
+            .xml_string
+            .contains(r#"profiles="profile-9""#));
+        let xml = serialize_model(&target)?;
+        assert_eq!(xml.matches("<profile ").count(), 2);
+        assert_eq!(xml.matches(r#"name="Cloud Service""#).count(), 1);
+        let target_model = target.xot.document_element(target.root)?;
+        let legacy = find_profiles(&target.xot, target_model)
+            .into_iter()
+            .find(|profile| profile.name == "Legacy")
+            .unwrap();
+        assert_ne!(legacy.id, "profile-2");
+        assert!(target.element_map["element-2"]
+            .xml_string
+            .contains(&format!(r#"profiles="{}""#, legacy.id)));
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::copy::find_diagram_references;
+use crate::model::{find_missing_views, find_node_by_id, ArchiModel, MatchBy, MissingElementInfo};
+use crate::selection::is_in_folder;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Returns the target views under `folder` that have no counterpart in the
+/// source. Views still linked from target views that stay are kept, so no
+/// view reference is left dangling.
+pub fn find_prunable_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    match_by: MatchBy,
+    folder: &str,
+) -> Result<Vec<MissingElementInfo>, Box<dyn std::error::Error>> {
+    let mut prunable = find_missing_views(target, source, match_by);
+    prunable.retain(|view| is_in_folder(&view.folder_path, folder));
+    keep_linked_views(target, &mut prunable)?;
+    Ok(prunable)
+}
+
+/// Takes the views still linked from views of `model` that stay out of
+/// `removed`, so no view reference is left dangling.
+pub fn keep_linked_views(
+    model: &ArchiModel,
+    removed: &mut Vec<MissingElementInfo>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut views: Vec<_> = model.view_map.values().collect();
+    views.sort_by(|a, b| a.id.cmp(&b.id));
+    // Keeping a view may keep the views it links to in turn
+    loop {
+        let removed_ids: HashSet<Arc<str>> = removed.iter().map(|view| view.id.clone()).collect();
+        let mut kept = HashSet::new();
+        for view_info in views.iter().filter(|info| !removed_ids.contains(&*info.id)) {
+            for reference in find_diagram_references(&view_info.xml_string)? {
+                if removed_ids.contains(reference.as_str()) && kept.insert(reference.clone()) {
+                    println!(
+                        "Keeping view {}: linked from view {}",
+                        model.view_map[reference.as_str()].name,
+                        view_info.name
+                    );
+                }
+            }
+        }
+        if kept.is_empty() {
+            return Ok(());
+        }
+        removed.retain(|view| !kept.contains(&*view.id));
+    }
+}
+
+/// Deletes views from the target. The concepts they show stay in the model.
+pub fn remove_views(
+    target: &mut ArchiModel,
+    views: &[MissingElementInfo],
+) -> Result<(), Box<dyn std::error::Error>> {
+    for view in views {
+        let node = find_node_by_id(target, &view.id)
+            .ok_or_else(|| format!("View {} not found in target", view.id))?;
+        target.xot.remove(node)?;
+        target.view_map.remove(&view.id);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{load_model, serialize_model};
+
+    #[test]
+    fn test_prune_views() -> Result<(), Box<dyn std::error::Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                </folder>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <folder name='Catalog' id='folder-3'>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Kept'/>
+                    </folder>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let mut target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal'/>
+                </folder>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <folder name='Catalog' id='folder-3'>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Kept'/>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Retired'/>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Linked'>
+                            <child xsi:type='archimate:DiagramModelReference' id='ref-2' model='view-4'/>
+                        </element>
+                        <element xsi:type='archimate:ArchimateDiagramModel' id='view-4' name='Linked Again'/>
+                    </folder>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-5' name='Local'>
+                        <child xsi:type='archimate:DiagramModelReference' id='ref-1' model='view-3'/>
+                    </element>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-6' name='Local Too'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let prunable = find_prunable_views(&source, &target, MatchBy::Id, "Views > Catalog")?;
+        let ids: Vec<&str> = prunable.iter().map(|view| &*view.id).collect();
+        assert_eq!(ids, vec!["view-2"]);
+
+        remove_views(&mut target, &prunable)?;
+        assert!(!target.view_map.contains_key("view-2"));
+        let xml = serialize_model(&target)?;
+        assert!(!xml.contains("view-2"));
+        assert!(xml.contains("element-1"));
+        Ok(())
+    }
+}
@@ -0,0 +1,377 @@
+//! A small subset of XPath for selecting the nodes of a loaded model.
+//!
+//! A query is a path of steps separated by `/` (children) or `//`
+//! (descendants), each a name or `*` followed by any number of predicates:
+//! a position as in `[1]`, or attribute tests joined by `and`, as in
+//! `[@name]`, `[@name='Shop']`, `[@name!='Shop']`, `[contains(@name,'Sh')]`
+//! and `[starts-with(@name,'Sh')]`. Prefixes, as in `xsi:type`, are those
+//! declared on the model's root element.
+
+use crate::error::ImporterError;
+use crate::model::ArchiModel;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use xot::{NameId, Node, Xot};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    descendants: bool,
+    /// `None` for `*`.
+    name: Option<String>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Predicate {
+    Position(usize),
+    All(Vec<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Has(String),
+    Equals(String, String),
+    NotEquals(String, String),
+    Contains(String, String),
+    StartsWith(String, String),
+}
+
+impl Condition {
+    fn attribute(&self) -> &str {
+        match self {
+            Condition::Has(name)
+            | Condition::Equals(name, _)
+            | Condition::NotEquals(name, _)
+            | Condition::Contains(name, _)
+            | Condition::StartsWith(name, _) => name,
+        }
+    }
+
+    fn holds(&self, value: Option<&str>) -> bool {
+        match (self, value) {
+            (Condition::Has(_), value) => value.is_some(),
+            (Condition::Equals(_, wanted), Some(value)) => value == wanted,
+            (Condition::NotEquals(_, wanted), Some(value)) => value != wanted,
+            (Condition::Contains(_, wanted), Some(value)) => value.contains(wanted.as_str()),
+            (Condition::StartsWith(_, wanted), Some(value)) => value.starts_with(wanted.as_str()),
+            (_, None) => false,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_spaces();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", token)))
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!(
+            "invalid query '{}': {} at position {}",
+            self.input,
+            message,
+            self.position + 1
+        )
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        self.skip_spaces();
+        let rest = self.rest();
+        let length = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_alphanumeric() || c == '_' || (i > 0 && matches!(c, '-' | '.' | ':')))
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.position += length;
+        Ok(rest[..length].to_string())
+    }
+
+    fn literal(&mut self) -> Result<String, String> {
+        self.skip_spaces();
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('\'' | '"')) => quote,
+            _ => return Err(self.error("expected a quoted value")),
+        };
+        let rest = &self.rest()[1..];
+        let end = rest
+            .find(quote)
+            .ok_or_else(|| self.error("unterminated value"))?;
+        self.position += end + 2;
+        Ok(rest[..end].to_string())
+    }
+
+    fn step(&mut self, descendants: bool) -> Result<Step, String> {
+        let name = if self.eat("*") {
+            None
+        } else {
+            Some(self.name()?)
+        };
+        let mut predicates = Vec::new();
+        while self.eat("[") {
+            predicates.push(self.predicate()?);
+            self.expect("]")?;
+        }
+        Ok(Step {
+            descendants,
+            name,
+            predicates,
+        })
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, String> {
+        self.skip_spaces();
+        let digits = self.rest().len()
+            - self
+                .rest()
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if digits > 0 {
+            let position = self.rest()[..digits]
+                .parse::<usize>()
+                .ok()
+                .filter(|&position| position > 0)
+                .ok_or_else(|| self.error("positions start at 1"))?;
+            self.position += digits;
+            return Ok(Predicate::Position(position));
+        }
+        let mut conditions = vec![self.condition()?];
+        while self.eat("and ") {
+            conditions.push(self.condition()?);
+        }
+        Ok(Predicate::All(conditions))
+    }
+
+    fn condition(&mut self) -> Result<Condition, String> {
+        if self.eat("@") {
+            let attribute = self.name()?;
+            return if self.eat("!=") {
+                Ok(Condition::NotEquals(attribute, self.literal()?))
+            } else if self.eat("=") {
+                Ok(Condition::Equals(attribute, self.literal()?))
+            } else {
+                Ok(Condition::Has(attribute))
+            };
+        }
+        let function = self.name()?;
+        self.expect("(")?;
+        self.expect("@")?;
+        let attribute = self.name()?;
+        self.expect(",")?;
+        let value = self.literal()?;
+        self.expect(")")?;
+        match function.as_str() {
+            "contains" => Ok(Condition::Contains(attribute, value)),
+            "starts-with" => Ok(Condition::StartsWith(attribute, value)),
+            _ => Err(self.error(&format!("unknown function '{}'", function))),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input: s,
+            position: 0,
+        };
+        let mut steps = Vec::new();
+        let mut descendants = parser.eat("//");
+        if !descendants {
+            parser.eat("/");
+        }
+        loop {
+            steps.push(parser.step(descendants)?);
+            if parser.eat("//") {
+                descendants = true;
+            } else if parser.eat("/") {
+                descendants = false;
+            } else {
+                break;
+            }
+        }
+        parser.skip_spaces();
+        if !parser.rest().is_empty() {
+            return Err(parser.error("unexpected text"));
+        }
+        Ok(Query { steps })
+    }
+}
+
+impl Query {
+    /// The elements of `doc` the query selects, in document order.
+    pub fn select(&self, xot: &Xot, doc: Node) -> Result<Vec<Node>, String> {
+        let scope = xot.document_element(doc).unwrap_or(doc);
+        let mut names = HashMap::new();
+        for step in &self.steps {
+            let attributes = step
+                .predicates
+                .iter()
+                .flat_map(|predicate| match predicate {
+                    Predicate::Position(_) => Vec::new(),
+                    Predicate::All(conditions) => {
+                        conditions.iter().map(|c| c.attribute()).collect()
+                    }
+                });
+            for name in step.name.as_deref().into_iter().chain(attributes) {
+                if !names.contains_key(name) {
+                    names.insert(name, resolve(xot, scope, name)?);
+                }
+            }
+        }
+
+        let mut context = vec![doc];
+        for step in &self.steps {
+            let mut selected = Vec::new();
+            let mut seen = HashSet::new();
+            for &node in &context {
+                let parents: Vec<Node> = if step.descendants {
+                    xot.descendants(node).collect()
+                } else {
+                    vec![node]
+                };
+                for parent in parents {
+                    let mut candidates: Vec<Node> = xot
+                        .children(parent)
+                        .filter(|&child| {
+                            xot.is_element(child)
+                                && step.name.as_deref().is_none_or(|name| {
+                                    names[name] == Some(xot.get_element_name(child))
+                                })
+                        })
+                        .collect();
+                    for predicate in &step.predicates {
+                        candidates = match predicate {
+                            Predicate::Position(position) => {
+                                candidates.into_iter().skip(position - 1).take(1).collect()
+                            }
+                            Predicate::All(conditions) => candidates
+                                .into_iter()
+                                .filter(|&candidate| {
+                                    conditions.iter().all(|condition| {
+                                        let value = names[condition.attribute()]
+                                            .and_then(|name| xot.get_attribute(candidate, name));
+                                        condition.holds(value)
+                                    })
+                                })
+                                .collect(),
+                        };
+                    }
+                    selected.extend(candidates.into_iter().filter(|&node| seen.insert(node)));
+                }
+            }
+            context = selected;
+        }
+        Ok(context)
+    }
+}
+
+/// The name `qualified` stands for, or `None` when the document has no
+/// such name at all.
+fn resolve(xot: &Xot, scope: Node, qualified: &str) -> Result<Option<NameId>, String> {
+    match qualified.split_once(':') {
+        Some((prefix, local)) => {
+            let namespace = xot
+                .prefix(prefix)
+                .and_then(|prefix| xot.namespace_for_prefix(scope, prefix))
+                .ok_or_else(|| format!("unknown prefix '{}' in query", prefix))?;
+            Ok(xot.name_ns(local, namespace))
+        }
+        None => Ok(xot.name(qualified)),
+    }
+}
+
+impl ArchiModel {
+    /// The elements of the model the query selects, as in
+    /// `//element[@xsi:type='archimate:ApplicationComponent']`; see the
+    /// module documentation for what a query can contain.
+    pub fn query(&self, query: &str) -> Result<Vec<Node>, ImporterError> {
+        let query: Query = query.parse().map_err(ImporterError::Validation)?;
+        query
+            .select(&self.xot, self.doc)
+            .map_err(ImporterError::Validation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+    use std::error::Error;
+
+    #[test]
+    fn test_query() -> Result<(), Box<dyn Error>> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-1"/>
+    <folder name="Shops" id="folder-2">
+      <element xsi:type="archimate:ApplicationComponent" name="Shop" id="element-2"/>
+      <element xsi:type="archimate:ApplicationService" name="Shopping" id="element-3"/>
+    </folder>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(content)?;
+        let ids = |query: &str| -> Result<Vec<String>, Box<dyn Error>> {
+            let id = model.xot.name("id").unwrap();
+            Ok(model
+                .query(query)?
+                .into_iter()
+                .map(|node| model.xot.get_attribute(node, id).unwrap().to_string())
+                .collect())
+        };
+
+        assert_eq!(
+            ids("//element[@xsi:type='archimate:ApplicationComponent']")?,
+            ["element-1", "element-2"]
+        );
+        assert_eq!(ids("/archimate:model/folder/*")?, ["element-1", "folder-2"]);
+        assert_eq!(
+            ids("//folder[@name='Shops']/element[starts-with(@name, 'Shop') and @xsi:type!='archimate:ApplicationComponent']")?,
+            ["element-3"]
+        );
+        assert_eq!(ids("//folder//element[2]")?, ["element-3"]);
+        assert_eq!(ids("//element[contains(@name,'R')]")?, ["element-1"]);
+        assert!(ids("//element[@documentation]")?.is_empty());
+        assert!(model.query("//element[@dc:title]").is_err());
+        assert!("//element[@name=Shop]".parse::<Query>().is_err());
+        assert!("//element[0]".parse::<Query>().is_err());
+        Ok(())
+    }
+}
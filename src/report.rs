@@ -0,0 +1,368 @@
+use crate::conflict::ConflictRecord;
+use crate::copy::ViewOutcome;
+use crate::glossary::display_type;
+use crate::model::{format_folder_path, ArchiModel, ElementInfo};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Write as _;
+
+const STYLE: &str = "body { font-family: Helvetica, Arial, sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #eee; }
+.failed { color: #b00; }
+code { font-size: 0.9em; }
+.diagram svg { max-width: 100%; height: auto; border: 1px solid #ccc; }";
+
+/// What an import did, for its HTML report.
+pub struct ImportReport<'a> {
+    pub source: &'a str,
+    pub target: &'a str,
+    pub seed: u64,
+    pub views: &'a [ViewOutcome],
+    /// SVG drawings of the imported views, by view name.
+    pub diagrams: Vec<(String, String)>,
+    /// The elements and relations the import added to the target.
+    pub new_concepts: Vec<&'a ElementInfo>,
+    /// The paths of the folders the import created.
+    pub new_folders: Vec<String>,
+    /// The concepts that differed between the models, with how each was
+    /// settled.
+    pub conflicts: Vec<(&'a ConflictRecord, String)>,
+    pub warnings: &'a [String],
+}
+
+/// Writes the report as a self-contained HTML page: the imported views,
+/// the new elements and relations grouped by type and folder, the
+/// conflicts and the warnings, ready to attach to a change ticket.
+pub fn report_html(report: &ImportReport) -> Result<String, Box<dyn Error>> {
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+    )?;
+    writeln!(
+        html,
+        "<title>Import of {} into {}</title>",
+        escape(report.source),
+        escape(report.target)
+    )?;
+    writeln!(html, "<style>\n{}\n</style>\n</head>\n<body>", STYLE)?;
+    writeln!(html, "<h1>Import of {}</h1>", escape(report.source))?;
+    writeln!(
+        html,
+        "<p>Into {} on {} with seed <code>{}</code>.</p>",
+        escape(report.target),
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        report.seed
+    )?;
+
+    writeln!(html, "<h2>Views ({})</h2>", report.views.len())?;
+    writeln!(html, "<table>\n<tr><th>View</th><th>Result</th></tr>")?;
+    for view in report.views {
+        match &view.error {
+            None => writeln!(
+                html,
+                "<tr><td>{}</td><td>imported</td></tr>",
+                escape(&view.name)
+            )?,
+            Some(error) => writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"failed\">failed: {}</td></tr>",
+                escape(&view.name),
+                escape(error)
+            )?,
+        }
+    }
+    writeln!(html, "</table>")?;
+    for (name, svg) in &report.diagrams {
+        writeln!(
+            html,
+            "<h3>{}</h3>\n<div class=\"diagram\">\n{}</div>",
+            escape(name),
+            svg
+        )?;
+    }
+
+    let (relations, elements): (Vec<&ElementInfo>, Vec<&ElementInfo>) = report
+        .new_concepts
+        .iter()
+        .partition(|concept| concept.is_relationship());
+    writeln!(html, "<h2>New folders ({})</h2>", report.new_folders.len())?;
+    if !report.new_folders.is_empty() {
+        writeln!(html, "<ul>")?;
+        for folder in &report.new_folders {
+            writeln!(html, "<li>{}</li>", escape(folder))?;
+        }
+        writeln!(html, "</ul>")?;
+    }
+    write_concepts(&mut html, "New elements", &elements)?;
+    write_concepts(&mut html, "New relations", &relations)?;
+
+    writeln!(html, "<h2>Conflicts ({})</h2>", report.conflicts.len())?;
+    if !report.conflicts.is_empty() {
+        writeln!(
+            html,
+            "<table>\n<tr><th>Concept</th><th>Type</th><th>Views</th><th>Settled by</th></tr>"
+        )?;
+        for (conflict, resolution) in &report.conflicts {
+            writeln!(
+                html,
+                "<tr><td>{} <code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&conflict.name),
+                escape(&conflict.id),
+                escape(&conflict.xsi_type),
+                escape(&conflict.views.join(", ")),
+                escape(resolution)
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+
+    writeln!(html, "<h2>Warnings ({})</h2>", report.warnings.len())?;
+    if !report.warnings.is_empty() {
+        writeln!(html, "<ul>")?;
+        for warning in report.warnings {
+            writeln!(html, "<li>{}</li>", escape(warning))?;
+        }
+        writeln!(html, "</ul>")?;
+    }
+    writeln!(html, "</body>\n</html>")?;
+    Ok(html)
+}
+
+/// A section of concepts with a table per type, sorted by folder and name.
+fn write_concepts(
+    html: &mut String,
+    title: &str,
+    concepts: &[&ElementInfo],
+) -> Result<(), Box<dyn Error>> {
+    writeln!(html, "<h2>{} ({})</h2>", title, concepts.len())?;
+    let rows = concept_rows(concepts);
+    for rows in rows.chunk_by(|(a, _, _), (b, _, _)| a == b) {
+        writeln!(html, "<h3>{} ({})</h3>", escape(&rows[0].0), rows.len())?;
+        writeln!(
+            html,
+            "<table>\n<tr><th>Folder</th><th>Name</th><th>ID</th></tr>"
+        )?;
+        for (_, folder, concept) in rows {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td><code>{}</code></td></tr>",
+                escape(folder),
+                escape(&concept.name),
+                escape(&concept.id)
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+    Ok(())
+}
+
+/// Writes the report as Markdown tables, to paste into a pull request
+/// description or a Confluence page.
+pub fn report_markdown(report: &ImportReport) -> Result<String, Box<dyn Error>> {
+    let mut markdown = String::new();
+    writeln!(
+        markdown,
+        "# Import of {}\n\nInto {} on {} with seed `{}`.",
+        cell(report.source),
+        cell(report.target),
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        report.seed
+    )?;
+
+    writeln!(markdown, "\n## Views ({})\n", report.views.len())?;
+    writeln!(markdown, "| View | Result |\n| --- | --- |")?;
+    for view in report.views {
+        let result = match &view.error {
+            None => "imported".to_string(),
+            Some(error) => format!("failed: {}", cell(error)),
+        };
+        writeln!(markdown, "| {} | {} |", cell(&view.name), result)?;
+    }
+
+    writeln!(
+        markdown,
+        "\n## Folders created ({})",
+        report.new_folders.len()
+    )?;
+    if !report.new_folders.is_empty() {
+        writeln!(markdown)?;
+        for folder in &report.new_folders {
+            writeln!(markdown, "- {}", cell(folder))?;
+        }
+    }
+
+    let (relations, elements): (Vec<&ElementInfo>, Vec<&ElementInfo>) = report
+        .new_concepts
+        .iter()
+        .partition(|concept| concept.is_relationship());
+    for (title, concepts) in [
+        ("Elements imported", elements),
+        ("Relations imported", relations),
+    ] {
+        writeln!(markdown, "\n## {} ({})", title, concepts.len())?;
+        if concepts.is_empty() {
+            continue;
+        }
+        writeln!(
+            markdown,
+            "\n| Type | Folder | Name | ID |\n| --- | --- | --- | --- |"
+        )?;
+        for (concept_type, folder, concept) in concept_rows(&concepts) {
+            writeln!(
+                markdown,
+                "| {} | {} | {} | `{}` |",
+                concept_type,
+                cell(&folder),
+                cell(&concept.name),
+                concept.id
+            )?;
+        }
+    }
+
+    writeln!(markdown, "\n## Conflicts ({})", report.conflicts.len())?;
+    if !report.conflicts.is_empty() {
+        writeln!(
+            markdown,
+            "\n| Concept | Type | Views | Settled by |\n| --- | --- | --- | --- |"
+        )?;
+        for (conflict, resolution) in &report.conflicts {
+            writeln!(
+                markdown,
+                "| {} `{}` | {} | {} | {} |",
+                cell(&conflict.name),
+                conflict.id,
+                conflict.xsi_type,
+                cell(&conflict.views.join(", ")),
+                resolution
+            )?;
+        }
+    }
+
+    writeln!(markdown, "\n## Warnings ({})", report.warnings.len())?;
+    if !report.warnings.is_empty() {
+        writeln!(markdown)?;
+        for warning in report.warnings {
+            writeln!(markdown, "- {}", cell(warning))?;
+        }
+    }
+    Ok(markdown)
+}
+
+/// The type, folder path and concept of each concept, sorted in that order
+/// and then by name.
+fn concept_rows<'a>(concepts: &[&'a ElementInfo]) -> Vec<(String, String, &'a ElementInfo)> {
+    let mut rows: Vec<_> = concepts
+        .iter()
+        .map(|concept| {
+            (
+                display_type(&concept.xsi_type),
+                format_folder_path(&concept.folder_path),
+                *concept,
+            )
+        })
+        .collect();
+    rows.sort_by(|(a_type, a_folder, a), (b_type, b_folder, b)| {
+        (a_type, a_folder, &a.name).cmp(&(b_type, b_folder, &b.name))
+    });
+    rows
+}
+
+/// The path of every folder of a model by folder ID, to tell which folders
+/// an import created.
+pub fn folder_paths(model: &ArchiModel) -> BTreeMap<String, String> {
+    model
+        .folders()
+        .map(|folder| {
+            let path = folder.path();
+            (folder.id, path)
+        })
+        .collect()
+}
+
+/// A Markdown table cell: pipes escaped and on one line.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace('\n', " ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_report_html() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal &lt;web&gt;" id="element-1"/>
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-2"/>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-2"/>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+        let views = [
+            ViewOutcome {
+                name: "Overview".to_string(),
+                error: None,
+            },
+            ViewOutcome {
+                name: "Detail".to_string(),
+                error: Some("missing element".to_string()),
+            },
+        ];
+        let warnings = ["images are not copied into plain XML files".to_string()];
+        let report = ImportReport {
+            source: "source.archimate",
+            target: "target.archimate",
+            seed: 7,
+            views: &views,
+            diagrams: vec![("Overview".to_string(), "<svg></svg>\n".to_string())],
+            new_concepts: ["element-1", "element-2", "relation-1"]
+                .iter()
+                .map(|id| &model.element_map[*id])
+                .collect(),
+            new_folders: vec!["Application[application] > CRM".to_string()],
+            conflicts: Vec::new(),
+            warnings: &warnings,
+        };
+
+        let html = report_html(&report)?;
+        assert!(html.contains("<td class=\"failed\">failed: missing element</td>"));
+        assert!(html.contains("<h3>Overview</h3>\n<div class=\"diagram\">\n<svg></svg>\n</div>"));
+        assert!(html.contains("<h2>New elements (2)</h2>\n<h3>Application Component (2)</h3>"));
+        // Sorted by name within the folder
+        let crm = html.find("<td>CRM</td>").unwrap();
+        let portal = html.find("<td>Portal &lt;web&gt;</td>").unwrap();
+        assert!(crm < portal);
+        assert!(html.contains("<h2>New relations (1)</h2>\n<h3>Serving Relationship (1)</h3>"));
+        assert!(html.contains("<h2>Conflicts (0)</h2>"));
+        assert!(html.contains("<li>images are not copied into plain XML files</li>"));
+
+        let markdown = report_markdown(&report)?;
+        assert!(markdown.contains(
+            "| View | Result |\n| --- | --- |\n| Overview | imported |\n| Detail | failed: missing element |\n"
+        ));
+        assert!(markdown.contains("## Folders created (1)\n\n- Application[application] > CRM\n"));
+        assert!(markdown.contains(
+            "| Application Component | Application[application] | CRM | `element-2` |\n\
+             | Application Component | Application[application] | Portal <web> | `element-1` |\n"
+        ));
+        assert!(markdown.contains("## Relations imported (1)"));
+        assert!(markdown.contains("## Conflicts (0)\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use crate::copy::find_view_references;
+use crate::error::ImporterError;
+use crate::model::{ArchiModel, ElementInfo};
+use crate::selection::has_properties;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// What `ArchiModel::find` looks for; a concept must match every field that
+/// is set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Search {
+    /// Text the name contains, ignoring case.
+    pub name_contains: Option<String>,
+    /// The type, with or without the `archimate:` prefix.
+    pub xsi_type: Option<String>,
+    /// A property key and the value it must have.
+    pub property: Option<(String, String)>,
+}
+
+impl Search {
+    fn matches(&self, info: &ElementInfo) -> bool {
+        let type_name = |xsi_type: &str| -> String {
+            xsi_type
+                .strip_prefix("archimate:")
+                .unwrap_or(xsi_type)
+                .to_string()
+        };
+        if let Some(text) = &self.name_contains {
+            if !info.name.to_lowercase().contains(&text.to_lowercase()) {
+                return false;
+            }
+        }
+        if let Some(xsi_type) = &self.xsi_type {
+            if type_name(xsi_type) != type_name(&info.xsi_type) {
+                return false;
+            }
+        }
+        match &self.property {
+            Some(property) => has_properties(&info.properties, std::slice::from_ref(property)),
+            None => true,
+        }
+    }
+}
+
+/// A concept `ArchiModel::find` found, with the views that show it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch<'a> {
+    pub concept: &'a ElementInfo,
+    /// The folder path of the concept, as in `Application > Sales`.
+    pub folder: String,
+    /// The views showing the concept, ordered by folder path and name.
+    pub views: Vec<&'a ElementInfo>,
+}
+
+impl ArchiModel {
+    /// The elements and relations matching `search`, elements first, each
+    /// ordered by folder path and name.
+    pub fn find(&self, search: &Search) -> Result<Vec<SearchMatch<'_>>, ImporterError> {
+        let concepts: Vec<&ElementInfo> = self
+            .elements()
+            .chain(self.relations())
+            .filter(|info| search.matches(info))
+            .collect();
+        if concepts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut shown_on: HashMap<String, Vec<&ElementInfo>> = HashMap::new();
+        for view in self.views() {
+            let (elements, relations) = find_view_references(&view.xml_string)?;
+            for id in elements.into_iter().chain(relations) {
+                shown_on.entry(id).or_default().push(view);
+            }
+        }
+        Ok(concepts
+            .into_iter()
+            .map(|concept| SearchMatch {
+                concept,
+                folder: concept
+                    .folder_path
+                    .iter()
+                    .map(|folder| &*folder.name)
+                    .collect::<Vec<_>>()
+                    .join(" > "),
+                views: shown_on.remove(&*concept.id).unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_find() -> Result<(), Box<dyn std::error::Error>> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <folder name="Sales" id="folder-2">
+      <element xsi:type="archimate:ApplicationComponent" name="Web Shop" id="element-1">
+        <property key="owner" value="Sales"/>
+      </element>
+    </folder>
+    <element xsi:type="archimate:ApplicationService" name="Shopping" id="element-2"/>
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-3"/>
+  </folder>
+  <folder name="Views" id="folder-3" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1"/>
+    </element>
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Detail" id="view-2">
+      <child xsi:type="archimate:DiagramObject" id="object-2" archimateElement="element-1"/>
+      <child xsi:type="archimate:DiagramObject" id="object-3" archimateElement="element-2"/>
+    </element>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(content)?;
+        let names = |search: Search| -> Result<Vec<String>, ImporterError> {
+            Ok(model
+                .find(&search)?
+                .into_iter()
+                .map(|found| found.concept.name.to_string())
+                .collect())
+        };
+
+        assert_eq!(
+            names(Search {
+                name_contains: Some("shop".to_string()),
+                ..Default::default()
+            })?,
+            ["Shopping", "Web Shop"]
+        );
+        assert_eq!(
+            names(Search {
+                name_contains: Some("shop".to_string()),
+                xsi_type: Some("ApplicationComponent".to_string()),
+                ..Default::default()
+            })?,
+            ["Web Shop"]
+        );
+        assert!(names(Search {
+            property: Some(("owner".to_string(), "Marketing".to_string())),
+            ..Default::default()
+        })?
+        .is_empty());
+
+        let found = model.find(&Search {
+            property: Some(("owner".to_string(), "Sales".to_string())),
+            ..Default::default()
+        })?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].folder, "Application > Sales");
+        let views: Vec<&str> = found[0].views.iter().map(|view| &*view.name).collect();
+        assert_eq!(views, ["Detail", "Overview"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,454 @@
+use crate::copy::{find_diagram_references, find_view_references};
+use crate::error::ImporterError;
+use crate::model::{ArchiModel, FolderInfo, MissingElementInfo};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+pub fn is_in_folder(folder_path: &[FolderInfo], folder: &str) -> bool {
+    let wanted: Vec<&str> = folder.split('>').map(|part| part.trim()).collect();
+    wanted.len() <= folder_path.len()
+        && wanted
+            .iter()
+            .zip(folder_path)
+            .all(|(name, info)| **name == *info.name)
+}
+
+pub fn is_excluded(view_name: &str, excludes: &[String], exclude_patterns: &[String]) -> bool {
+    excludes.iter().any(|name| name == view_name)
+        || exclude_patterns
+            .iter()
+            .any(|pattern| matches_pattern(pattern, view_name))
+}
+
+/// Matches `text` against a glob-like `pattern` where `*` matches any run of
+/// characters and `?` matches a single character.
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+pub fn get_input(prompt: &str) -> Result<String, io::Error> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+pub fn parse_property_filter(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!(
+            "Invalid property filter: {} (expected key=value)",
+            input
+        )),
+    }
+}
+
+pub fn has_properties(properties: &[(String, String)], filters: &[(String, String)]) -> bool {
+    filters.iter().all(|(key, value)| {
+        properties
+            .iter()
+            .any(|(property_key, property_value)| property_key == key && property_value == value)
+    })
+}
+
+pub fn parse_selection(input: &str, max_count: usize) -> Result<Vec<usize>, ImporterError> {
+    let mut selected = HashSet::new();
+    let mut deselected = HashSet::new();
+
+    let input = input.trim();
+    let lowercase = input.to_lowercase();
+    if let Some(rest) = lowercase.strip_prefix("all") {
+        let rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok((1..=max_count).collect());
+        }
+        // "all - 3,7" selects everything except the listed views
+        let exclusions = rest
+            .strip_prefix('-')
+            .ok_or_else(|| ImporterError::Selection(format!("Invalid selection: {}", input)))?;
+        selected.extend(1..=max_count);
+        parse_selection_parts(exclusions, max_count, &mut deselected)?;
+        return Ok(subtract_selection(selected, &deselected));
+    }
+
+    for part in input.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+
+        // "!2-4" deselects views; on its own it applies to all views
+        if let Some(negated) = part.strip_prefix('!') {
+            parse_selection_parts(negated, max_count, &mut deselected)?;
+        } else {
+            parse_selection_parts(part, max_count, &mut selected)?;
+        }
+    }
+
+    if selected.is_empty() && !deselected.is_empty() {
+        selected.extend(1..=max_count);
+    }
+
+    Ok(subtract_selection(selected, &deselected))
+}
+
+pub fn parse_selection_parts(
+    input: &str,
+    max_count: usize,
+    selected: &mut HashSet<usize>,
+) -> Result<(), ImporterError> {
+    let number = |text: &str| {
+        text.trim()
+            .parse::<usize>()
+            .map_err(|_| ImporterError::Selection(format!("Invalid view number: {}", text.trim())))
+    };
+    for part in input.split(',') {
+        let part = part.trim();
+        if part.is_empty() {
+            continue;
+        }
+
+        if part.contains('-') {
+            // Range selection
+            let range: Vec<&str> = part.split('-').collect();
+            if range.len() == 2 {
+                let start = number(range[0])?;
+                let end = number(range[1])?;
+
+                if start > end || start == 0 || end > max_count {
+                    return Err(ImporterError::Selection(format!(
+                        "Invalid range: {}-{}",
+                        start, end
+                    )));
+                }
+
+                for i in start..=end {
+                    selected.insert(i);
+                }
+            }
+        } else {
+            // Single number
+            let num = number(part)?;
+            if num == 0 || num > max_count {
+                return Err(ImporterError::Selection(format!(
+                    "Invalid view number: {}",
+                    num
+                )));
+            }
+            selected.insert(num);
+        }
+    }
+    Ok(())
+}
+
+pub fn subtract_selection(selected: HashSet<usize>, deselected: &HashSet<usize>) -> Vec<usize> {
+    // Convert to sorted vector
+    let mut result: Vec<usize> = selected
+        .into_iter()
+        .filter(|i| !deselected.contains(i))
+        .collect();
+    result.sort();
+    result
+}
+
+/// Returns the 1-based indices of the missing views whose diagram objects
+/// reference an element matching `element` by ID or name.
+pub fn find_views_containing_element(
+    source: &ArchiModel,
+    missing_views: &[MissingElementInfo],
+    element: &str,
+) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    let element_ids: HashSet<&str> = source
+        .element_map
+        .values()
+        .filter(|info| &*info.id == element || &*info.name == element)
+        .map(|info| &*info.id)
+        .collect();
+    if element_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut indices = Vec::new();
+    for (i, view) in missing_views.iter().enumerate() {
+        let view_info = source.view_map.get(&view.id).unwrap();
+        let (referenced_elements, _) = find_view_references(&view_info.xml_string)?;
+        if referenced_elements
+            .iter()
+            .any(|id| element_ids.contains(id.as_str()))
+        {
+            indices.push(i + 1);
+        }
+    }
+    Ok(indices)
+}
+
+/// Returns the 1-based indices of the missing views that the selected views
+/// link to, directly or through other linked views, and are not selected
+/// yet. Links to views found in neither the missing views nor `target` are
+/// reported.
+pub fn find_referenced_views(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    missing_views: &[MissingElementInfo],
+    selected_indices: &[usize],
+) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    let index_by_id: HashMap<&str, usize> = missing_views
+        .iter()
+        .enumerate()
+        .map(|(i, view)| (&*view.id, i + 1))
+        .collect();
+    // Views already visited, so reference cycles end
+    let mut visited: HashSet<usize> = selected_indices.iter().copied().collect();
+    let mut queue: Vec<usize> = selected_indices.to_vec();
+    let mut referenced = Vec::new();
+    while let Some(idx) = queue.pop() {
+        let view = &missing_views[idx - 1];
+        let Some(view_info) = source.view_map.get(&view.id) else {
+            continue;
+        };
+        let mut references: Vec<String> = find_diagram_references(&view_info.xml_string)?
+            .into_iter()
+            .collect();
+        references.sort();
+        for reference in references {
+            match index_by_id.get(reference.as_str()) {
+                Some(&referenced_idx) if visited.insert(referenced_idx) => {
+                    queue.push(referenced_idx);
+                    referenced.push(referenced_idx);
+                }
+                Some(_) => {}
+                None if !target.view_map.contains_key(reference.as_str())
+                    && !target.element_map.contains_key(reference.as_str()) =>
+                {
+                    println!(
+                        "Warning: view {} links to view {}, which is not imported",
+                        view.name, reference
+                    );
+                }
+                None => {}
+            }
+        }
+    }
+    referenced.sort();
+    Ok(referenced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{find_missing_views, load_model, MatchBy};
+    use std::error::Error;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("WIP*", "WIP Landscape"));
+        assert!(matches_pattern("*Draft*", "Old Draft View"));
+        assert!(matches_pattern("View ?", "View 1"));
+        assert!(!matches_pattern("WIP*", "Landscape WIP"));
+        assert!(!matches_pattern("View ?", "View 10"));
+    }
+
+    #[test]
+    fn test_is_in_folder() {
+        let folder_path = vec![
+            FolderInfo {
+                id: "folder-1".into(),
+                name: "Views".into(),
+                folder_type: Some("diagrams".to_string()),
+            },
+            FolderInfo {
+                id: "folder-2".into(),
+                name: "Domain A".into(),
+                folder_type: None,
+            },
+        ];
+        assert!(is_in_folder(&folder_path, "Views"));
+        assert!(is_in_folder(&folder_path, "Views > Domain A"));
+        assert!(!is_in_folder(&folder_path, "Views > Domain B"));
+        assert!(!is_in_folder(&folder_path, "Domain A"));
+    }
+
+    #[test]
+    fn test_parse_selection_single() -> Result<(), Box<dyn Error>> {
+        let result = parse_selection("1", 5)?;
+        assert_eq!(result, vec![1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selection_multiple() -> Result<(), Box<dyn Error>> {
+        let result = parse_selection("1,3,5", 5)?;
+        assert_eq!(result, vec![1, 3, 5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selection_range() -> Result<(), Box<dyn Error>> {
+        let result = parse_selection("1-3", 5)?;
+        assert_eq!(result, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selection_all() -> Result<(), Box<dyn Error>> {
+        let result = parse_selection("all", 3)?;
+        assert_eq!(result, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selection_all_except() -> Result<(), Box<dyn Error>> {
+        assert_eq!(parse_selection("all - 3,7", 8)?, vec![1, 2, 4, 5, 6, 8]);
+        assert_eq!(parse_selection("ALL -2-4", 5)?, vec![1, 5]);
+        assert!(parse_selection("all 3", 5).is_err());
+        assert!(parse_selection("all - 9", 5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selection_negated() -> Result<(), Box<dyn Error>> {
+        assert_eq!(parse_selection("!2-4", 6)?, vec![1, 5, 6]);
+        assert_eq!(parse_selection("1-5,!3", 6)?, vec![1, 2, 4, 5]);
+        assert!(parse_selection("!7", 6).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_selection_invalid() {
+        assert!(parse_selection("0", 5).is_err());
+        assert!(parse_selection("6", 5).is_err());
+        assert!(parse_selection("1,6", 5).is_err());
+        assert!(parse_selection("invalid", 5).is_err());
+    }
+
+    #[test]
+    fn test_view_property_filters() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Approved View'>
+                        <property key='status' value='approved'/>
+                        <property key='owner' value='Team A'/>
+                    </element>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let properties = &source.view_map.get("view-1").unwrap().properties;
+
+        let filters = vec![parse_property_filter("status=approved")?];
+        assert!(has_properties(properties, &filters));
+        let filters = vec![
+            parse_property_filter("status=approved")?,
+            parse_property_filter("owner=Team B")?,
+        ];
+        assert!(!has_properties(properties, &filters));
+        assert!(parse_property_filter("status").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_views_containing_element() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-1'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Customer Portal'/>
+                </folder>
+                <folder type='diagrams' name='Views' id='folder-2'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='With Portal'>
+                        <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                    </element>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Without Portal'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let missing_views = vec![
+            MissingElementInfo {
+                id: "view-1".into(),
+                name: "With Portal".into(),
+                folder_path: Vec::new().into(),
+                target_id: None,
+            },
+            MissingElementInfo {
+                id: "view-2".into(),
+                name: "Without Portal".into(),
+                folder_path: Vec::new().into(),
+                target_id: None,
+            },
+        ];
+
+        let by_name = find_views_containing_element(&source, &missing_views, "Customer Portal")?;
+        assert_eq!(by_name, vec![1]);
+        let by_id = find_views_containing_element(&source, &missing_views, "element-1")?;
+        assert_eq!(by_id, vec![1]);
+        let unknown = find_views_containing_element(&source, &missing_views, "Unknown")?;
+        assert!(unknown.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_referenced_views() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Overview'>
+                        <child xsi:type='archimate:DiagramModelReference' id='ref-1' model='view-2'/>
+                    </element>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Detail'>
+                        <child xsi:type='archimate:DiagramModelReference' id='ref-2' model='view-1'/>
+                        <child xsi:type='archimate:DiagramModelReference' id='ref-3' model='view-3'/>
+                        <child xsi:type='archimate:DiagramModelReference' id='ref-4' model='view-5'/>
+                    </element>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Existing'/>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-4' name='Unrelated'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-3' name='Existing'/>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let missing_views = find_missing_views(&source, &target, MatchBy::Id);
+        let index = |id: &str| {
+            missing_views
+                .iter()
+                .position(|view| &*view.id == id)
+                .unwrap()
+                + 1
+        };
+
+        let referenced =
+            find_referenced_views(&source, &target, &missing_views, &[index("view-1")])?;
+        assert_eq!(referenced, vec![index("view-2")]);
+        Ok(())
+    }
+}
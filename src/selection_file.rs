@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::Path;
+
+/// Reads view IDs from a selection file: one ID per line, with blank lines
+/// and anything after `#` ignored.
+pub fn read_selection_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+        .collect())
+}
+
+/// Writes `(id, name)` pairs as a selection file, keeping view names as
+/// comments so the file stays readable.
+pub fn write_selection_file<P: AsRef<Path>>(
+    path: P,
+    views: &[(&str, &str)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut content = String::from("# archi-view-importer selection: one view ID per line\n");
+    for (id, name) in views {
+        content.push_str(&format!("{} # {}\n", id, name));
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_selection_file_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("selection.txt");
+
+        write_selection_file(
+            &path,
+            &[("view-1", "First View"), ("view-2", "Second # View")],
+        )?;
+        assert_eq!(read_selection_file(&path)?, vec!["view-1", "view-2"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use crate::copy::find_view_references;
+use crate::model::ArchiModel;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// A concept that is drawn on views from both models after an import, and so
+/// needs a decision on which model owns it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SharedConcept {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub xsi_type: String,
+    pub imported_views: Vec<String>,
+    pub target_views: Vec<String>,
+}
+
+/// Maps each concept ID to the names of the views in `model` that reference it.
+pub fn find_concept_views(
+    model: &ArchiModel,
+) -> Result<HashMap<String, BTreeSet<String>>, Box<dyn std::error::Error>> {
+    let mut concept_views: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for view in model.view_map.values() {
+        let (elements, relations) = find_view_references(&view.xml_string)?;
+        for id in elements.into_iter().chain(relations) {
+            concept_views
+                .entry(id)
+                .or_default()
+                .insert(view.name.to_string());
+        }
+    }
+    Ok(concept_views)
+}
+
+/// Returns the concepts referenced by the imported views that were already
+/// drawn on a target view before the import, sorted by name and ID.
+pub fn find_shared_concepts(
+    source: &ArchiModel,
+    target: &ArchiModel,
+    target_concept_views: &HashMap<String, BTreeSet<String>>,
+    imported_view_ids: &[String],
+) -> Result<Vec<SharedConcept>, Box<dyn std::error::Error>> {
+    let mut imported_concept_views: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for view_id in imported_view_ids {
+        let Some(view) = source.view_map.get(view_id.as_str()) else {
+            continue;
+        };
+        let (elements, relations) = find_view_references(&view.xml_string)?;
+        for id in elements.into_iter().chain(relations) {
+            imported_concept_views
+                .entry(id)
+                .or_default()
+                .insert(view.name.to_string());
+        }
+    }
+
+    let mut shared: Vec<SharedConcept> = imported_concept_views
+        .into_iter()
+        .filter_map(|(id, imported_views)| {
+            let target_views = target_concept_views.get(&id)?;
+            let info = target.element_map.get(id.as_str())?;
+            Some(SharedConcept {
+                id,
+                name: info.name.to_string(),
+                xsi_type: info
+                    .xsi_type
+                    .rsplit(':')
+                    .next()
+                    .unwrap_or(&info.xsi_type)
+                    .to_string(),
+                imported_views: imported_views.into_iter().collect(),
+                target_views: target_views.iter().cloned().collect(),
+            })
+        })
+        .collect();
+    shared.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    Ok(shared)
+}
+
+/// Writes the shared concepts as JSON when `path` ends in `.json`, otherwise
+/// as CSV with view names separated by `;`.
+pub fn write_shared_concepts<P: AsRef<Path>>(
+    path: P,
+    concepts: &[SharedConcept],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    if is_json {
+        fs::write(path, serde_json::to_vec_pretty(concepts)?)?;
+        return Ok(());
+    }
+
+    let mut content = String::from("id,name,type,imported_views,target_views\n");
+    for concept in concepts {
+        let fields = [
+            concept.id.clone(),
+            concept.name.clone(),
+            concept.xsi_type.clone(),
+            concept.imported_views.join(";"),
+            concept.target_views.join(";"),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        content.push_str(&row.join(","));
+        content.push('\n');
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+    use std::error::Error;
+
+    #[test]
+    fn test_find_shared_concepts() -> Result<(), Box<dyn Error>> {
+        let source = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='diagrams' name='Views' id='folder-1'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-2' name='Imported'>
+                        <child xsi:type='archimate:DiagramObject' id='object-2' archimateElement='element-1'/>
+                        <child xsi:type='archimate:DiagramObject' id='object-3' archimateElement='element-2'/>
+                    </element>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let target = load_model(
+            r#"<?xml version='1.0' encoding='UTF-8'?>
+            <archimate:model xmlns:archimate='http://www.archimatetool.com/archimate' xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance'>
+                <folder type='application' name='Application' id='folder-2'>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-1' name='Portal, Web'/>
+                    <element xsi:type='archimate:ApplicationComponent' id='element-2' name='Backend'/>
+                </folder>
+                <folder type='diagrams' name='Views' id='folder-3'>
+                    <element xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='Existing'>
+                        <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1'/>
+                    </element>
+                </folder>
+            </archimate:model>"#,
+        )?;
+        let target_concept_views = find_concept_views(&target)?;
+
+        let shared = find_shared_concepts(
+            &source,
+            &target,
+            &target_concept_views,
+            &["view-2".to_string()],
+        )?;
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].id, "element-1");
+        assert_eq!(shared[0].imported_views, vec!["Imported"]);
+        assert_eq!(shared[0].target_views, vec!["Existing"]);
+
+        let dir = tempfile::tempdir()?;
+        let csv_path = dir.path().join("shared.csv");
+        write_shared_concepts(&csv_path, &shared)?;
+        assert_eq!(
+            fs::read_to_string(&csv_path)?,
+            "id,name,type,imported_views,target_views\n\
+             element-1,\"Portal, Web\",ApplicationComponent,Imported,Existing\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,397 @@
+use crate::concept_type::ConceptType;
+use crate::model::{is_view_type, ArchiModel};
+use crate::namespaces::{archi_type, attribute, child_text, children};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use xot::{Node, Xot};
+
+/// The tables, created when missing so that several models can be written
+/// to one database.
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS models (id TEXT PRIMARY KEY, name TEXT, purpose TEXT, file TEXT);
+CREATE TABLE IF NOT EXISTS folders (model_id TEXT, id TEXT, name TEXT, type TEXT, parent_id TEXT, path TEXT, PRIMARY KEY (model_id, id));
+CREATE TABLE IF NOT EXISTS elements (model_id TEXT, id TEXT, type TEXT, name TEXT, documentation TEXT, folder_id TEXT, PRIMARY KEY (model_id, id));
+CREATE TABLE IF NOT EXISTS relations (model_id TEXT, id TEXT, type TEXT, name TEXT, documentation TEXT, source_id TEXT, target_id TEXT, folder_id TEXT, PRIMARY KEY (model_id, id));
+CREATE TABLE IF NOT EXISTS views (model_id TEXT, id TEXT, type TEXT, name TEXT, documentation TEXT, viewpoint TEXT, folder_id TEXT, PRIMARY KEY (model_id, id));
+CREATE TABLE IF NOT EXISTS view_objects (model_id TEXT, id TEXT, view_id TEXT, parent_id TEXT, type TEXT, concept_id TEXT, name TEXT, x INTEGER, y INTEGER, width INTEGER, height INTEGER, PRIMARY KEY (model_id, id));
+CREATE TABLE IF NOT EXISTS properties (model_id TEXT, owner_id TEXT, key TEXT, value TEXT);
+";
+
+const TABLES: &[&str] = &[
+    "folders",
+    "elements",
+    "relations",
+    "views",
+    "view_objects",
+    "properties",
+];
+
+/// Checks that a database can be written to `path` before any work is done:
+/// a path ending in `.sql` needs nothing, a database needs the `sqlite3`
+/// command line shell on the `PATH`.
+pub fn check_sqlite(path: &Path) -> Result<(), Box<dyn Error>> {
+    if is_script(path) {
+        return Ok(());
+    }
+    match Command::new("sqlite3")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+    {
+        Ok(_) => Ok(()),
+        Err(e) => Err(missing_shell(path, e)),
+    }
+}
+
+/// Writes a model to the SQLite database at `path` for SQL queries such as
+/// which views show an element, replacing what the database held for the
+/// same model. Needs the `sqlite3` command line shell; a path ending in
+/// `.sql` gets the SQL script instead, to load with any SQLite client.
+/// `file` is recorded as where the model came from.
+pub fn write_sqlite(model: &ArchiModel, path: &Path, file: &str) -> Result<(), Box<dyn Error>> {
+    let script = model_to_sql(model, file)?;
+    if is_script(path) {
+        fs::write(path, script).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        return Ok(());
+    }
+
+    let mut child = Command::new("sqlite3")
+        .arg("-bail")
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| missing_shell(path, e))?;
+    child
+        .stdin
+        .take()
+        .ok_or("Cannot write to sqlite3")?
+        .write_all(script.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "Cannot write {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn is_script(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("sql"))
+}
+
+fn missing_shell(path: &Path, e: std::io::Error) -> Box<dyn Error> {
+    format!(
+        "Cannot write {} as a SQLite database: the sqlite3 shell could not be run ({}); install it, or give a file ending in .sql to get the SQL script",
+        path.display(),
+        e
+    )
+    .into()
+}
+
+/// The SQL script creating the tables and inserting the model's folders,
+/// elements, relations, views, view objects and properties in one
+/// transaction.
+pub fn model_to_sql(model: &ArchiModel, file: &str) -> Result<String, Box<dyn Error>> {
+    let xot = &model.xot;
+    let root = xot.document_element(model.doc)?;
+    let model_id = attribute(xot, root, "id").unwrap_or_default();
+    let mut sql = String::new();
+    sql.push_str(SCHEMA);
+    writeln!(sql, "BEGIN;")?;
+    writeln!(
+        sql,
+        "DELETE FROM models WHERE id = {};",
+        text(Some(model_id))
+    )?;
+    for table in TABLES {
+        writeln!(
+            sql,
+            "DELETE FROM {} WHERE model_id = {};",
+            table,
+            text(Some(model_id))
+        )?;
+    }
+    insert(
+        &mut sql,
+        "models",
+        &[
+            text(Some(model_id)),
+            text(attribute(xot, root, "name")),
+            text(child_text(xot, root, "purpose")),
+            text(Some(file)),
+        ],
+    )?;
+    let writer = Writer { xot, model_id };
+    writer.properties(&mut sql, root, model_id)?;
+    for folder in children(xot, root, "folder") {
+        writer.folder(&mut sql, folder, None, "")?;
+    }
+    writeln!(sql, "COMMIT;")?;
+    Ok(sql)
+}
+
+struct Writer<'a> {
+    xot: &'a Xot,
+    model_id: &'a str,
+}
+
+impl Writer<'_> {
+    fn folder(
+        &self,
+        sql: &mut String,
+        folder: Node,
+        parent_id: Option<&str>,
+        parent_path: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let id = attribute(xot, folder, "id").unwrap_or_default();
+        let name = attribute(xot, folder, "name").unwrap_or_default();
+        let path = if parent_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} > {}", parent_path, name)
+        };
+        insert(
+            sql,
+            "folders",
+            &[
+                text(Some(self.model_id)),
+                text(Some(id)),
+                text(Some(name)),
+                text(attribute(xot, folder, "type")),
+                text(parent_id),
+                text(Some(&path)),
+            ],
+        )?;
+        self.properties(sql, folder, id)?;
+        for child in children(xot, folder, "folder") {
+            self.folder(sql, child, Some(id), &path)?;
+        }
+        for concept in children(xot, folder, "element") {
+            self.concept(sql, concept, id)?;
+        }
+        Ok(())
+    }
+
+    fn concept(
+        &self,
+        sql: &mut String,
+        concept: Node,
+        folder_id: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let Some(id) = attribute(xot, concept, "id") else {
+            return Ok(());
+        };
+        let concept_type = archi_type(xot, concept).unwrap_or_default();
+        let name = text(attribute(xot, concept, "name"));
+        let documentation = text(child_text(xot, concept, "documentation"));
+        if ConceptType::from_xsi_type(concept_type).is_some_and(ConceptType::is_relationship) {
+            insert(
+                sql,
+                "relations",
+                &[
+                    text(Some(self.model_id)),
+                    text(Some(id)),
+                    text(Some(concept_type)),
+                    name,
+                    documentation,
+                    text(attribute(xot, concept, "source")),
+                    text(attribute(xot, concept, "target")),
+                    text(Some(folder_id)),
+                ],
+            )?;
+        } else if is_view_type(concept_type) {
+            insert(
+                sql,
+                "views",
+                &[
+                    text(Some(self.model_id)),
+                    text(Some(id)),
+                    text(Some(concept_type)),
+                    name,
+                    documentation,
+                    text(attribute(xot, concept, "viewpoint")),
+                    text(Some(folder_id)),
+                ],
+            )?;
+            for object in children(xot, concept, "child") {
+                self.view_object(sql, object, id, None)?;
+            }
+        } else {
+            insert(
+                sql,
+                "elements",
+                &[
+                    text(Some(self.model_id)),
+                    text(Some(id)),
+                    text(Some(concept_type)),
+                    name,
+                    documentation,
+                    text(Some(folder_id)),
+                ],
+            )?;
+        }
+        self.properties(sql, concept, id)
+    }
+
+    /// Writes a diagram object with its connections and nested objects.
+    fn view_object(
+        &self,
+        sql: &mut String,
+        object: Node,
+        view_id: &str,
+        parent_id: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let xot = self.xot;
+        let Some(id) = attribute(xot, object, "id") else {
+            return Ok(());
+        };
+        let bounds = children(xot, object, "bounds").first().copied();
+        let bound = |name: &str| {
+            bounds
+                .and_then(|bounds| attribute(xot, bounds, name))
+                .and_then(|value| value.parse::<i64>().ok())
+                .map_or("NULL".to_string(), |value| value.to_string())
+        };
+        insert(
+            sql,
+            "view_objects",
+            &[
+                text(Some(self.model_id)),
+                text(Some(id)),
+                text(Some(view_id)),
+                text(parent_id),
+                text(archi_type(xot, object)),
+                text(
+                    attribute(xot, object, "archimateElement")
+                        .or_else(|| attribute(xot, object, "model")),
+                ),
+                text(attribute(xot, object, "name")),
+                bound("x"),
+                bound("y"),
+                bound("width"),
+                bound("height"),
+            ],
+        )?;
+        for connection in children(xot, object, "sourceConnection") {
+            let Some(connection_id) = attribute(xot, connection, "id") else {
+                continue;
+            };
+            insert(
+                sql,
+                "view_objects",
+                &[
+                    text(Some(self.model_id)),
+                    text(Some(connection_id)),
+                    text(Some(view_id)),
+                    text(Some(id)),
+                    text(archi_type(xot, connection)),
+                    text(attribute(xot, connection, "archimateRelationship")),
+                    text(attribute(xot, connection, "name")),
+                    "NULL".to_string(),
+                    "NULL".to_string(),
+                    "NULL".to_string(),
+                    "NULL".to_string(),
+                ],
+            )?;
+        }
+        for child in children(xot, object, "child") {
+            self.view_object(sql, child, view_id, Some(id))?;
+        }
+        Ok(())
+    }
+
+    fn properties(
+        &self,
+        sql: &mut String,
+        node: Node,
+        owner_id: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        for property in children(self.xot, node, "property") {
+            insert(
+                sql,
+                "properties",
+                &[
+                    text(Some(self.model_id)),
+                    text(Some(owner_id)),
+                    text(attribute(self.xot, property, "key")),
+                    text(attribute(self.xot, property, "value")),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn insert(sql: &mut String, table: &str, values: &[String]) -> Result<(), Box<dyn Error>> {
+    writeln!(sql, "INSERT INTO {} VALUES ({});", table, values.join(", "))?;
+    Ok(())
+}
+
+/// A SQL string literal, or `NULL`.
+fn text(value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("'{}'", value.replace('\'', "''")),
+        None => "NULL".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_model_to_sql() -> Result<(), Box<dyn Error>> {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <folder name="Portals" id="folder-2">
+      <element xsi:type="archimate:ApplicationComponent" name="Bob's Portal" id="element-1">
+        <property key="status" value="live"/>
+      </element>
+    </folder>
+  </folder>
+  <folder name="Relations" id="folder-3" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-1"/>
+  </folder>
+  <folder name="Views" id="folder-4" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1">
+        <bounds x="10" y="20" width="120" height="55"/>
+        <sourceConnection xsi:type="archimate:Connection" id="connection-1" source="object-1" target="object-1" archimateRelationship="relation-1"/>
+      </child>
+    </element>
+  </folder>
+</archimate:model>"#;
+        let model = load_model(xml)?;
+
+        let sql = model_to_sql(&model, "enterprise.archimate")?;
+        assert!(sql.starts_with(SCHEMA));
+        assert!(sql.contains("DELETE FROM view_objects WHERE model_id = 'model-1';"));
+        assert!(sql.contains(
+            "INSERT INTO models VALUES ('model-1', 'Enterprise', NULL, 'enterprise.archimate');"
+        ));
+        assert!(sql.contains("INSERT INTO folders VALUES ('model-1', 'folder-2', 'Portals', NULL, 'folder-1', 'Application > Portals');"));
+        assert!(sql.contains("INSERT INTO elements VALUES ('model-1', 'element-1', 'ApplicationComponent', 'Bob''s Portal', NULL, 'folder-2');"));
+        assert!(sql.contains("INSERT INTO relations VALUES ('model-1', 'relation-1', 'ServingRelationship', NULL, NULL, 'element-1', 'element-1', 'folder-3');"));
+        assert!(sql.contains("INSERT INTO views VALUES ('model-1', 'view-1', 'ArchimateDiagramModel', 'Overview', NULL, NULL, 'folder-4');"));
+        assert!(sql.contains("INSERT INTO view_objects VALUES ('model-1', 'object-1', 'view-1', NULL, 'DiagramObject', 'element-1', NULL, 10, 20, 120, 55);"));
+        assert!(sql.contains("INSERT INTO view_objects VALUES ('model-1', 'connection-1', 'view-1', 'object-1', 'Connection', 'relation-1', NULL, NULL, NULL, NULL, NULL);"));
+        assert!(sql
+            .contains("INSERT INTO properties VALUES ('model-1', 'element-1', 'status', 'live');"));
+        assert!(sql.ends_with("COMMIT;\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::model::ArchiModel;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+/// Counts of what a model holds, for tracking its growth.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelStats {
+    pub elements: usize,
+    pub relationships: usize,
+    pub views: usize,
+    /// Relationships per element, or 0 without elements.
+    pub relationship_density: f64,
+    /// Concepts by type, as in `ApplicationComponent`.
+    pub by_type: BTreeMap<String, usize>,
+    /// Elements by layer, as in `Application`; elements of types ArchiMate
+    /// doesn't have are left out.
+    pub by_layer: BTreeMap<String, usize>,
+    /// Concepts and views by the folder path they are in, as in
+    /// `Views > Sales`.
+    pub by_folder: BTreeMap<String, usize>,
+}
+
+impl ArchiModel {
+    pub fn stats(&self) -> ModelStats {
+        let mut stats = ModelStats {
+            views: self.view_map.len(),
+            ..Default::default()
+        };
+        for info in self.element_map.values() {
+            if info.is_relationship() {
+                stats.relationships += 1;
+            } else {
+                stats.elements += 1;
+            }
+            let type_name = info
+                .xsi_type
+                .strip_prefix("archimate:")
+                .unwrap_or(&info.xsi_type);
+            *stats.by_type.entry(type_name.to_string()).or_default() += 1;
+            if let Some(layer) = info.concept_type().and_then(|t| t.layer()) {
+                *stats.by_layer.entry(layer.to_string()).or_default() += 1;
+            }
+        }
+        for info in self.element_map.values().chain(self.view_map.values()) {
+            let folder = info
+                .folder_path
+                .iter()
+                .map(|folder| &*folder.name)
+                .collect::<Vec<_>>()
+                .join(" > ");
+            *stats.by_folder.entry(folder).or_default() += 1;
+        }
+        if stats.elements > 0 {
+            stats.relationship_density = stats.relationships as f64 / stats.elements as f64;
+        }
+        stats
+    }
+}
+
+/// What the index of a model holds in memory, as reported by
+/// `import --memory-stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexStats {
+    /// Concepts and views indexed.
+    pub entries: usize,
+    /// IDs, names, types and folder names the entries refer to.
+    pub strings: usize,
+    /// Of those, the ones stored separately rather than shared.
+    pub distinct_strings: usize,
+    /// Bytes the distinct strings take.
+    pub string_bytes: usize,
+    /// Bytes the strings would take if each entry had its own copy.
+    pub unshared_bytes: usize,
+}
+
+impl ArchiModel {
+    pub fn index_stats(&self) -> IndexStats {
+        let mut stats = IndexStats::default();
+        let mut seen = HashSet::new();
+        let mut count = |string: &Arc<str>| {
+            stats.strings += 1;
+            stats.unshared_bytes += string.len();
+            if seen.insert(Arc::as_ptr(string) as *const u8) {
+                stats.distinct_strings += 1;
+                stats.string_bytes += string.len();
+            }
+        };
+        for info in self.element_map.values().chain(self.view_map.values()) {
+            for string in [&info.id, &info.name, &info.xsi_type] {
+                count(string);
+            }
+            for folder in info.folder_path.iter() {
+                count(&folder.id);
+                count(&folder.name);
+            }
+        }
+        stats.entries = self.element_map.len() + self.view_map.len();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::load_model;
+
+    #[test]
+    fn test_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let model = load_model(&std::fs::read_to_string("tests/test1.archimate")?)?;
+        let stats = model.stats();
+        assert_eq!(
+            (stats.elements, stats.relationships, stats.views),
+            (3, 1, 2)
+        );
+        assert!((stats.relationship_density - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.by_type["ApplicationComponent"], 1);
+        assert_eq!(stats.by_type["RealizationRelationship"], 1);
+        assert_eq!(stats.by_layer["Application"], 2);
+        assert_eq!(stats.by_layer["Strategy"], 1);
+        assert_eq!(stats.by_folder["Views"], 2);
+        assert_eq!(stats.by_folder["Relations"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let model = load_model(&std::fs::read_to_string("tests/test1.archimate")?)?;
+        let stats = model.index_stats();
+        assert_eq!(stats.entries, 6);
+        assert!(stats.distinct_strings < stats.strings);
+        assert!(stats.string_bytes < stats.unshared_bytes);
+        Ok(())
+    }
+}
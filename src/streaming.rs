@@ -0,0 +1,293 @@
+use crate::concept_type::ConceptType;
+use crate::model::FolderInfo;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::Arc;
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+/// A concept or view found by `index_model`, with where its XML is in the
+/// model instead of the XML itself.
+#[derive(Debug, Clone)]
+pub struct IndexedConcept {
+    pub id: String,
+    pub name: String,
+    pub xsi_type: String,
+    pub folder_path: Arc<[FolderInfo]>,
+    span: Range<usize>,
+    /// The concepts a relation connects, or the concepts and views a view
+    /// shows or links to.
+    references: Vec<String>,
+}
+
+/// The concepts and views of a model, read in a single pass over its XML
+/// without building a document, so that only the part of a huge model an
+/// import needs is ever parsed into one.
+#[derive(Debug, Default)]
+pub struct ModelIndex {
+    concepts: Vec<IndexedConcept>,
+    by_id: HashMap<String, usize>,
+}
+
+enum Frame {
+    Folder(FolderInfo),
+    Concept(usize),
+    Other,
+}
+
+pub fn index_model(content: &str) -> Result<ModelIndex, Box<dyn std::error::Error>> {
+    let mut index = ModelIndex::default();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut folder_path: Vec<FolderInfo> = Vec::new();
+    let mut current_path: Arc<[FolderInfo]> = Arc::from([]);
+    // The concept the tokens are inside of, if any
+    let mut concept: Option<usize> = None;
+
+    for token in Tokenizer::from(content) {
+        match token? {
+            Token::ElementStart { local, span, .. } => {
+                let in_folder_tree =
+                    stack.len() == 1 || matches!(stack.last(), Some(Frame::Folder(_)));
+                let frame = match local.as_str() {
+                    "folder" if in_folder_tree => Frame::Folder(FolderInfo {
+                        id: String::new().into(),
+                        name: String::new().into(),
+                        folder_type: None,
+                    }),
+                    "element" if matches!(stack.last(), Some(Frame::Folder(_))) => {
+                        index.concepts.push(IndexedConcept {
+                            id: String::new(),
+                            name: String::new(),
+                            xsi_type: String::new(),
+                            folder_path: current_path.clone(),
+                            span: span.start()..span.end(),
+                            references: Vec::new(),
+                        });
+                        concept = Some(index.concepts.len() - 1);
+                        Frame::Concept(index.concepts.len() - 1)
+                    }
+                    _ => Frame::Other,
+                };
+                stack.push(frame);
+            }
+            Token::Attribute {
+                prefix,
+                local,
+                value,
+                ..
+            } => {
+                let value = unescape(value.as_str());
+                match stack.last_mut() {
+                    Some(Frame::Folder(folder)) => match local.as_str() {
+                        "id" => folder.id = value.into(),
+                        "name" => folder.name = value.into(),
+                        "type" => folder.folder_type = Some(value),
+                        _ => {}
+                    },
+                    Some(Frame::Concept(i)) => {
+                        let indexed = &mut index.concepts[*i];
+                        match (prefix.as_str(), local.as_str()) {
+                            ("", "id") => indexed.id = value,
+                            ("", "name") => indexed.name = value,
+                            ("xsi", "type") => indexed.xsi_type = value,
+                            ("", "source" | "target") => indexed.references.push(value),
+                            _ => {}
+                        }
+                    }
+                    _ => {
+                        if let Some(i) = concept {
+                            if matches!(
+                                local.as_str(),
+                                "archimateElement" | "archimateRelationship" | "model"
+                            ) {
+                                index.concepts[i].references.push(value);
+                            }
+                        }
+                    }
+                }
+            }
+            Token::ElementEnd { end, span } => {
+                let closed = match end {
+                    ElementEnd::Open => {
+                        if let Some(Frame::Folder(folder)) = stack.last() {
+                            folder_path.push(folder.clone());
+                            current_path = folder_path.clone().into();
+                        }
+                        None
+                    }
+                    ElementEnd::Empty => stack.pop(),
+                    ElementEnd::Close(..) => {
+                        let frame = stack.pop();
+                        if let Some(Frame::Folder(_)) = frame {
+                            folder_path.pop();
+                            current_path = folder_path.clone().into();
+                        }
+                        frame
+                    }
+                };
+                if let Some(Frame::Concept(i)) = closed {
+                    index.concepts[i].span.end = span.end();
+                    index.by_id.insert(index.concepts[i].id.clone(), i);
+                    concept = None;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(index)
+}
+
+impl ModelIndex {
+    pub fn views(&self) -> impl Iterator<Item = &IndexedConcept> {
+        self.concepts
+            .iter()
+            .filter(|concept| crate::model::is_view_type(&concept.xsi_type))
+    }
+
+    pub fn concept_count(&self) -> usize {
+        self.concepts.len()
+    }
+
+    /// The ids of `views` with every concept and view they need: what they
+    /// show, the ends of the relations they show, the views they link to,
+    /// and the relations between all of those.
+    pub fn needed_by(&self, views: &HashSet<String>) -> HashSet<String> {
+        let mut needed = HashSet::new();
+        let mut pending: Vec<&str> = views.iter().map(|id| id.as_str()).collect();
+        loop {
+            while let Some(id) = pending.pop() {
+                if let Some(&i) = self.by_id.get(id) {
+                    if needed.insert(id.to_string()) {
+                        pending.extend(self.concepts[i].references.iter().map(|r| r.as_str()));
+                    }
+                }
+            }
+            // Relations between kept concepts, for --include-model-relations
+            pending.extend(
+                self.concepts
+                    .iter()
+                    .filter(|concept| {
+                        ConceptType::from_xsi_type(&concept.xsi_type)
+                            .is_some_and(ConceptType::is_relationship)
+                            && !needed.contains(&concept.id)
+                            && concept.references.iter().all(|r| needed.contains(r))
+                    })
+                    .map(|concept| concept.id.as_str()),
+            );
+            if pending.is_empty() {
+                return needed;
+            }
+        }
+    }
+
+    /// `content` without the concepts and views that are not `needed`.
+    pub fn reduce(&self, content: &str, needed: &HashSet<String>) -> String {
+        let mut reduced = String::with_capacity(content.len());
+        let mut position = 0;
+        for concept in self
+            .concepts
+            .iter()
+            .filter(|concept| !needed.contains(&concept.id))
+        {
+            reduced.push_str(&content[position..concept.span.start]);
+            position = concept.span.end;
+        }
+        reduced.push_str(&content[position..]);
+        reduced
+    }
+}
+
+fn unescape(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let character = match &rest[1..end] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match character {
+            Some(character) => {
+                unescaped.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_index_and_reduce() -> Result<(), Box<dyn std::error::Error>> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-1"/>
+    <element xsi:type="archimate:ApplicationComponent" name="Shop" id="element-2"/>
+    <element xsi:type="archimate:ApplicationComponent" name="Unused" id="element-3"/>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-2"/>
+    <element xsi:type="archimate:ServingRelationship" id="relation-2" source="element-1" target="element-3"/>
+  </folder>
+  <folder name="Views" id="folder-3" type="diagrams">
+    <folder name="Sales &amp; Service" id="folder-4">
+      <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+        <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1"/>
+        <child xsi:type="archimate:DiagramModelReference" id="object-2" model="view-2"/>
+      </element>
+    </folder>
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Detail" id="view-2">
+      <child xsi:type="archimate:DiagramObject" id="object-3" archimateElement="element-2"/>
+    </element>
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Other" id="view-3"/>
+  </folder>
+</archimate:model>"#;
+        let index = index_model(content)?;
+        assert_eq!(index.concept_count(), 8);
+        let overview = index.views().find(|view| view.id == "view-1").unwrap();
+        assert_eq!(overview.name, "Overview");
+        assert_eq!(overview.folder_path.len(), 2);
+        assert_eq!(&*overview.folder_path[1].name, "Sales & Service");
+
+        let needed = index.needed_by(&HashSet::from(["view-1".to_string()]));
+        let mut ids: Vec<&str> = needed.iter().map(|id| id.as_str()).collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            ["element-1", "element-2", "relation-1", "view-1", "view-2"]
+        );
+
+        let reduced = index.reduce(content, &needed);
+        let model = load_model(&reduced)?;
+        assert_eq!(model.view_map.len(), 2);
+        assert_eq!(model.element_map.len(), 3);
+        assert!(!reduced.contains("Unused"));
+        assert!(reduced.contains(r#"<folder name="Sales &amp; Service" id="folder-4">"#));
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use crate::model::{element_layer, ArchiModel, ElementInfo};
+use crate::namespaces::{attribute, children, xsi_type};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write as _;
+use xot::{Node, Xot};
+
+/// Archi's size for a diagram object whose bounds leave it to the default.
+const DEFAULT_SIZE: (i64, i64) = (120, 55);
+
+const MARGIN: i64 = 10;
+
+/// A diagram object or connection end with its absolute bounds.
+struct Shape {
+    x: i64,
+    y: i64,
+    width: i64,
+    height: i64,
+}
+
+impl Shape {
+    fn center(&self) -> (i64, i64) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+}
+
+/// Draws a view as a simple SVG image: boxes for its diagram objects at
+/// their bounds, filled in their layer's color and labelled with what they
+/// show, and lines for its connections. It looks nothing like Archi's
+/// rendering, but shows the layout well enough to recognize the diagram.
+pub fn view_to_svg(model: &ArchiModel, view: &ElementInfo) -> Result<String, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let doc = xot.parse(&view.xml_string)?;
+    let root = xot.document_element(doc)?;
+
+    let mut shapes = HashMap::new();
+    let mut boxes = String::new();
+    let mut connections = Vec::new();
+    for child in children(&xot, root, "child") {
+        draw_object(
+            &xot,
+            model,
+            child,
+            (0, 0),
+            &mut shapes,
+            &mut boxes,
+            &mut connections,
+        )?;
+    }
+
+    let mut lines = String::new();
+    for connection in connections {
+        let (Some(source), Some(target)) = (
+            attribute(&xot, connection, "source").and_then(|id| shapes.get(id)),
+            attribute(&xot, connection, "target").and_then(|id| shapes.get(id)),
+        ) else {
+            continue;
+        };
+        // Archi keeps bendpoints as offsets from the center of the source
+        let (source_x, source_y) = source.center();
+        let mut points = vec![(source_x, source_y)];
+        for bendpoint in children(&xot, connection, "bendpoint") {
+            let offset = |name| {
+                attribute(&xot, bendpoint, name)
+                    .and_then(|value| value.parse::<i64>().ok())
+                    .unwrap_or(0)
+            };
+            points.push((source_x + offset("startX"), source_y + offset("startY")));
+        }
+        points.push(target.center());
+        let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+        writeln!(
+            lines,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\"/>",
+            points.join(" "),
+            attribute(&xot, connection, "lineColor").unwrap_or("#000000")
+        )?;
+    }
+
+    let (min_x, min_y, max_x, max_y) = shapes.values().fold(
+        (i64::MAX, i64::MAX, i64::MIN, i64::MIN),
+        |(min_x, min_y, max_x, max_y), shape| {
+            (
+                min_x.min(shape.x),
+                min_y.min(shape.y),
+                max_x.max(shape.x + shape.width),
+                max_y.max(shape.y + shape.height),
+            )
+        },
+    );
+    let (min_x, min_y, width, height) = if shapes.is_empty() {
+        (0, 0, 0, 0)
+    } else {
+        (min_x, min_y, max_x - min_x, max_y - min_y)
+    };
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\" font-family=\"Helvetica, Arial, sans-serif\" font-size=\"11\">",
+        min_x - MARGIN,
+        min_y - MARGIN,
+        width + 2 * MARGIN,
+        height + 2 * MARGIN,
+        width + 2 * MARGIN,
+        height + 2 * MARGIN
+    )?;
+    writeln!(svg, "<title>{}</title>", escape(&view.name))?;
+    svg.push_str(&boxes);
+    svg.push_str(&lines);
+    writeln!(svg, "</svg>")?;
+    Ok(svg)
+}
+
+/// Draws a diagram object and the objects nested in it, whose bounds are
+/// relative to it, collecting their connections to draw over the boxes.
+fn draw_object(
+    xot: &Xot,
+    model: &ArchiModel,
+    object: Node,
+    origin: (i64, i64),
+    shapes: &mut HashMap<String, Shape>,
+    svg: &mut String,
+    connections: &mut Vec<Node>,
+) -> Result<(), Box<dyn Error>> {
+    let bounds = children(xot, object, "bounds").first().copied();
+    let bound = |name: &str| {
+        bounds
+            .and_then(|bounds| attribute(xot, bounds, name))
+            .and_then(|value| value.parse::<i64>().ok())
+    };
+    let shape = Shape {
+        x: origin.0 + bound("x").unwrap_or(0),
+        y: origin.1 + bound("y").unwrap_or(0),
+        width: bound("width")
+            .filter(|&width| width > 0)
+            .unwrap_or(DEFAULT_SIZE.0),
+        height: bound("height")
+            .filter(|&height| height > 0)
+            .unwrap_or(DEFAULT_SIZE.1),
+    };
+
+    let element =
+        attribute(xot, object, "archimateElement").and_then(|id| model.element_map.get(id));
+    let (label, default_fill) = match xsi_type(xot, object) {
+        Some("archimate:DiagramModelGroup") => (
+            attribute(xot, object, "name")
+                .unwrap_or_default()
+                .to_string(),
+            "#d2d7d7",
+        ),
+        Some("archimate:DiagramModelNote") => (
+            children(xot, object, "content")
+                .first()
+                .and_then(|&content| xot.text_content_str(content))
+                .unwrap_or_default()
+                .to_string(),
+            "#ffffff",
+        ),
+        Some("archimate:DiagramModelReference") => (
+            attribute(xot, object, "model")
+                .and_then(|id| model.view_map.get(id))
+                .map(|view| view.name.to_string())
+                .unwrap_or_default(),
+            "#dcebeb",
+        ),
+        _ => match element {
+            Some(element) => (element.name.to_string(), layer_color(&element.xsi_type)),
+            None => (
+                attribute(xot, object, "name")
+                    .unwrap_or_default()
+                    .to_string(),
+                "#ffffff",
+            ),
+        },
+    };
+    writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\"/>",
+        shape.x,
+        shape.y,
+        shape.width,
+        shape.height,
+        attribute(xot, object, "fillColor").unwrap_or(default_fill),
+        attribute(xot, object, "lineColor").unwrap_or("#5c5c5c")
+    )?;
+    // Groups are labelled at the top, as their content fills them
+    let nested = children(xot, object, "child");
+    let label_y = if nested.is_empty() {
+        shape.y + shape.height / 2
+    } else {
+        shape.y + 14
+    };
+    let first_line = label.lines().next().unwrap_or_default();
+    if !first_line.is_empty() {
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\" fill=\"{}\">{}</text>",
+            shape.x + shape.width / 2,
+            label_y,
+            attribute(xot, object, "fontColor").unwrap_or("#000000"),
+            escape(first_line)
+        )?;
+    }
+
+    connections.extend(children(xot, object, "sourceConnection"));
+    let position = (shape.x, shape.y);
+    if let Some(id) = attribute(xot, object, "id") {
+        shapes.insert(id.to_string(), shape);
+    }
+    for child in nested {
+        draw_object(xot, model, child, position, shapes, svg, connections)?;
+    }
+    Ok(())
+}
+
+/// Archi's default fill color for elements of the layer of `xsi_type`.
+fn layer_color(xsi_type: &str) -> &'static str {
+    let archi_type = xsi_type.strip_prefix("archimate:").unwrap_or(xsi_type);
+    match element_layer(archi_type) {
+        Some("Strategy") => "#f5deaa",
+        Some("Business") => "#ffffb5",
+        Some("Application") => "#b5ffff",
+        Some("Technology") | Some("Physical") => "#c9e7b7",
+        Some("Motivation") => "#ccccff",
+        Some("Implementation") => "#ffe0e0",
+        _ => "#ffffff",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_view_to_svg() -> Result<(), Box<dyn Error>> {
+        let xml = r##"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Enterprise" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Portal &amp; API" id="element-1"/>
+    <element xsi:type="archimate:DataObject" name="Order" id="element-2"/>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations">
+    <element xsi:type="archimate:AccessRelationship" id="relation-1" source="element-1" target="element-2"/>
+  </folder>
+  <folder name="Views" id="folder-3" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramModelGroup" id="object-1" name="Front">
+        <bounds x="10" y="20" width="300" height="200"/>
+        <child xsi:type="archimate:DiagramObject" id="object-2" archimateElement="element-1" fillColor="#ffffff">
+          <bounds x="20" y="30" width="120" height="60"/>
+          <sourceConnection xsi:type="archimate:Connection" id="connection-1" source="object-2" target="object-3" archimateRelationship="relation-1">
+            <bendpoint startX="0" startY="150"/>
+          </sourceConnection>
+        </child>
+      </child>
+      <child xsi:type="archimate:DiagramObject" id="object-3" archimateElement="element-2">
+        <bounds x="400" y="200" width="-1" height="-1"/>
+      </child>
+    </element>
+  </folder>
+</archimate:model>"##;
+        let model = load_model(xml)?;
+
+        let svg = view_to_svg(&model, &model.view_map["view-1"])?;
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 10 530 255\"")
+        );
+        assert!(
+            svg.contains("<rect x=\"10\" y=\"20\" width=\"300\" height=\"200\" fill=\"#d2d7d7\"")
+        );
+        // Nested bounds are relative to the group
+        assert!(
+            svg.contains("<rect x=\"30\" y=\"50\" width=\"120\" height=\"60\" fill=\"#ffffff\"")
+        );
+        assert!(svg.contains(">Portal &amp; API</text>"));
+        // Default sizes and layer colors
+        assert!(
+            svg.contains("<rect x=\"400\" y=\"200\" width=\"120\" height=\"55\" fill=\"#b5ffff\"")
+        );
+        assert!(svg.contains("<polyline points=\"90,80 90,230 460,227\""));
+        Ok(())
+    }
+}
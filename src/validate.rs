@@ -0,0 +1,267 @@
+use crate::concept_type::ConceptType;
+use crate::model::{is_view_type, ArchiModel, FOLDER_TYPES};
+use crate::namespaces::XSI_NAMESPACE;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use xot::{Node, Xot};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A reference to an ID that nothing in the model has.
+    DanglingReference,
+    /// An ID that more than one node has.
+    DuplicateId,
+    /// A folder, concept, view or diagram node without an attribute Archi
+    /// needs.
+    MissingAttribute,
+    /// A concept whose `xsi:type` is not an ArchiMate type or a view type,
+    /// or a folder whose `type` is not one of Archi's folder types.
+    UnknownType,
+}
+
+/// Something wrong with a model that Archi would refuse or that an import
+/// would carry into the target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub kind: IssueKind,
+    /// The ID of the node the issue is on, when it has one.
+    pub id: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+struct Validator<'a> {
+    xot: &'a Xot,
+    ids: HashSet<&'a str>,
+    issues: Vec<ValidationIssue>,
+}
+
+impl<'a> Validator<'a> {
+    fn attribute(&self, node: Node, name: &str) -> Option<&'a str> {
+        self.xot
+            .name(name)
+            .and_then(|name| self.xot.get_attribute(node, name))
+    }
+
+    fn xsi_type(&self, node: Node) -> Option<&'a str> {
+        let xsi = self.xot.namespace(XSI_NAMESPACE)?;
+        let name = self.xot.name_ns("type", xsi)?;
+        self.xot.get_attribute(node, name)
+    }
+
+    fn issue(&mut self, kind: IssueKind, node: Node, message: String) {
+        let id = self.attribute(node, "id").map(str::to_string);
+        self.issues.push(ValidationIssue { kind, id, message });
+    }
+
+    /// Checks that `node`, described as `what`, has the attributes Archi
+    /// needs.
+    fn require(&mut self, node: Node, what: &str, attributes: &[&str]) {
+        for &attribute in attributes {
+            let present = if attribute == "xsi:type" {
+                self.xsi_type(node).is_some()
+            } else {
+                self.attribute(node, attribute).is_some()
+            };
+            if !present {
+                let described = match self.attribute(node, "id") {
+                    Some(id) => format!("{} {}", what, id),
+                    None => what.to_string(),
+                };
+                self.issue(
+                    IssueKind::MissingAttribute,
+                    node,
+                    format!("{} has no {} attribute", described, attribute),
+                );
+            }
+        }
+    }
+
+    /// Checks that each of the space-separated IDs of the `attribute` of
+    /// `node` is in the model.
+    fn references(&mut self, node: Node, what: &str, attribute: &str) {
+        let Some(value) = self.attribute(node, attribute) else {
+            return;
+        };
+        for reference in value.split_whitespace() {
+            if !self.ids.contains(reference) {
+                let described = match self.attribute(node, "id") {
+                    Some(id) => format!("{} {}", what, id),
+                    None => what.to_string(),
+                };
+                self.issue(
+                    IssueKind::DanglingReference,
+                    node,
+                    format!(
+                        "{} refers to {} in {}, which is not in the model",
+                        described, reference, attribute
+                    ),
+                );
+            }
+        }
+    }
+}
+
+impl ArchiModel {
+    /// Checks the model for dangling references, duplicate IDs, missing
+    /// required attributes and unknown concept types, returning the issues
+    /// in document order after the IDs missing when the model was loaded.
+    /// A model without issues can be merged safely.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let xot = &self.xot;
+        let Ok(root) = xot.document_element(self.doc) else {
+            return Vec::new();
+        };
+        let mut validator = Validator {
+            xot,
+            ids: HashSet::new(),
+            // The IDs generated on loading are not in the file
+            issues: self.repaired.clone(),
+        };
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut order = Vec::new();
+        for node in xot.descendants(root).filter(|&node| xot.is_element(node)) {
+            if let Some(id) = validator.attribute(node, "id") {
+                let count = counts.entry(id).or_default();
+                *count += 1;
+                if *count == 2 {
+                    order.push((id, node));
+                }
+                validator.ids.insert(id);
+            }
+        }
+        for (id, node) in order {
+            validator.issue(
+                IssueKind::DuplicateId,
+                node,
+                format!("ID {} is used by {} nodes", id, counts[id]),
+            );
+        }
+
+        let folder_name = xot.name("folder");
+        for node in xot.descendants(root).filter(|&node| xot.is_element(node)) {
+            let in_folder = xot
+                .parent(node)
+                .filter(|&parent| xot.is_element(parent))
+                .is_some_and(|parent| Some(xot.get_element_name(parent)) == folder_name);
+            match xot.local_name_str(xot.get_element_name(node)) {
+                "folder" => {
+                    validator.require(node, "Folder", &["id", "name"]);
+                    match validator.attribute(node, "type") {
+                        Some(folder_type) if !FOLDER_TYPES.contains(&folder_type) => validator
+                            .issue(
+                                IssueKind::UnknownType,
+                                node,
+                                format!("Folder type {} is not an Archi folder type", folder_type),
+                            ),
+                        Some(_) => {}
+                        // Archi tells the top-level folders apart by type
+                        None if xot.parent(node) == Some(root) => {
+                            validator.require(node, "Top-level folder", &["type"])
+                        }
+                        None => {}
+                    }
+                }
+                "element" if in_folder => {
+                    validator.require(node, "Concept", &["id", "xsi:type"]);
+                    let Some(xsi_type) = validator.xsi_type(node) else {
+                        continue;
+                    };
+                    match ConceptType::from_xsi_type(xsi_type) {
+                        Some(concept_type) if concept_type.is_relationship() => {
+                            validator.require(node, "Relationship", &["source", "target"]);
+                            validator.references(node, "Relationship", "source");
+                            validator.references(node, "Relationship", "target");
+                        }
+                        Some(_) => {}
+                        None if is_view_type(xsi_type) => {}
+                        None => validator.issue(
+                            IssueKind::UnknownType,
+                            node,
+                            format!("Concept type {} is not an ArchiMate type", xsi_type),
+                        ),
+                    }
+                }
+                "child" => {
+                    validator.require(node, "Diagram object", &["id"]);
+                    validator.references(node, "Diagram object", "archimateElement");
+                    validator.references(node, "Diagram object", "model");
+                    validator.references(node, "Diagram object", "targetConnections");
+                }
+                "sourceConnection" => {
+                    validator.require(node, "Connection", &["id", "source", "target"]);
+                    validator.references(node, "Connection", "source");
+                    validator.references(node, "Connection", "target");
+                    validator.references(node, "Connection", "archimateRelationship");
+                }
+                _ => {}
+            }
+        }
+        validator.issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::load_model;
+
+    #[test]
+    fn test_validate() -> Result<(), Box<dyn std::error::Error>> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="CRM" id="element-1"/>
+    <element xsi:type="archimate:ApplicationWidget" name="Widget" id="element-2"/>
+    <element xsi:type="archimate:ApplicationComponent" name="Copy" id="element-1"/>
+  </folder>
+  <folder name="Relations" id="folder-2" type="relations">
+    <element xsi:type="archimate:ServingRelationship" id="relation-1" source="element-1" target="element-9"/>
+    <element xsi:type="archimate:FlowRelationship" id="relation-2" source="element-1"/>
+  </folder>
+  <folder name="Views" id="folder-3" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="Overview" id="view-1">
+      <child xsi:type="archimate:DiagramObject" id="object-1" archimateElement="element-1" targetConnections="connection-9">
+        <bounds x="0" y="0" width="120" height="55"/>
+      </child>
+    </element>
+  </folder>
+  <folder name="Sketches" id="folder-4" type="sketches"/>
+  <folder name="Misc" id="folder-5"/>
+</archimate:model>"#;
+        let model = load_model(content)?;
+        let issues = model.validate();
+        let kinds: Vec<(IssueKind, Option<&str>)> = issues
+            .iter()
+            .map(|issue| (issue.kind, issue.id.as_deref()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (IssueKind::DuplicateId, Some("element-1")),
+                (IssueKind::UnknownType, Some("element-2")),
+                (IssueKind::DanglingReference, Some("relation-1")),
+                (IssueKind::MissingAttribute, Some("relation-2")),
+                (IssueKind::DanglingReference, Some("object-1")),
+                (IssueKind::UnknownType, Some("folder-4")),
+                (IssueKind::MissingAttribute, Some("folder-5")),
+            ]
+        );
+        assert_eq!(
+            issues[2].to_string(),
+            "Relationship relation-1 refers to element-9 in target, which is not in the model"
+        );
+
+        let valid = load_model(&std::fs::read_to_string("tests/test1.archimate")?)?;
+        assert!(valid.validate().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use crate::model::{ArchiModel, ElementInfo, MissingElementInfo};
+use crate::namespaces::{attribute, xsi_type};
+use std::collections::HashMap;
+use std::error::Error;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use xot::{Node, Xot};
+
+/// Attributes that only identify diagram parts, and so differ between
+/// copies of the same view.
+const IGNORED_ATTRIBUTES: &[&str] = &["id"];
+
+/// Attributes pointing at other diagram parts, compared by the concepts
+/// those parts show rather than by their IDs.
+const REFERENCE_ATTRIBUTES: &[&str] = &["targetConnections", "source", "target"];
+
+/// Attributes linking a diagram part to the concept it shows.
+const CONCEPT_ATTRIBUTES: &[&str] = &["archimateElement", "archimateRelationship"];
+
+/// Geometry attributes, compared after snapping to `GRID_SIZE`.
+const GEOMETRY_ATTRIBUTES: &[&str] = &[
+    "x", "y", "width", "height", "startX", "startY", "endX", "endY",
+];
+
+/// Archi's default grid size in pixels.
+const GRID_SIZE: f64 = 12.0;
+
+/// Hashes a serialized view with its IDs left out, its connections compared
+/// by the concepts at their ends and its geometry snapped to the grid, so
+/// copies of a view that differ only by ID hash the same.
+pub fn view_content_hash(xml_string: &str) -> Result<u64, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let node = xot.parse_fragment(xml_string)?;
+    let references = diagram_references(&xot, node);
+    Ok(canonical_hash(&xot, node, IGNORED_ATTRIBUTES, &references))
+}
+
+/// Maps the ID of each diagram part in a view to the concept it shows, or
+/// to its type for parts such as notes and groups that show none.
+fn diagram_references(xot: &Xot, node: Node) -> HashMap<String, String> {
+    xot.descendants(node)
+        .filter(|&part| xot.is_element(part))
+        .filter_map(|part| {
+            let id = attribute(xot, part, "id")?;
+            let concept = CONCEPT_ATTRIBUTES
+                .iter()
+                .find_map(|name| attribute(xot, part, name))
+                .or_else(|| xsi_type(xot, part))
+                .unwrap_or_default();
+            Some((id.to_string(), concept.to_string()))
+        })
+        .collect()
+}
+
+/// Hashes a serialized concept, ignoring only formatting, so two copies of
+/// a concept hash the same unless their names, properties, documentation or
+/// endpoints differ.
+pub fn concept_content_hash(xml_string: &str) -> Result<u64, Box<dyn Error>> {
+    content_hash(xml_string, &[])
+}
+
+/// Hashes a serialized concept like `concept_content_hash`, leaving out the
+/// child elements named in `skipped`, such as `property`.
+pub fn concept_hash_without(xml_string: &str, skipped: &[&str]) -> Result<u64, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let node = xot.parse_fragment(xml_string)?;
+    let skipped_nodes: Vec<Node> = xot
+        .descendants(node)
+        .filter(|&child| {
+            xot.is_element(child) && {
+                let (name, _) = xot.name_ns_str(xot.get_element_name(child));
+                skipped.contains(&name)
+            }
+        })
+        .collect();
+    for skipped_node in skipped_nodes {
+        xot.remove(skipped_node)?;
+    }
+    Ok(canonical_hash(&xot, node, &[], &HashMap::new()))
+}
+
+fn content_hash(xml_string: &str, ignored: &[&str]) -> Result<u64, Box<dyn Error>> {
+    let mut xot = Xot::new();
+    let node = xot.parse_fragment(xml_string)?;
+    Ok(canonical_hash(&xot, node, ignored, &HashMap::new()))
+}
+
+fn canonical_hash(
+    xot: &Xot,
+    node: Node,
+    ignored: &[&str],
+    references: &HashMap<String, String>,
+) -> u64 {
+    let mut canonical = String::new();
+    write_canonical(xot, node, ignored, references, &mut canonical);
+
+    let mut hasher = DefaultHasher::new();
+    canonical.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn write_canonical(
+    xot: &Xot,
+    node: Node,
+    ignored: &[&str],
+    references: &HashMap<String, String>,
+    canonical: &mut String,
+) {
+    if let Some(text) = xot.text_str(node) {
+        let text = text.trim();
+        if !text.is_empty() {
+            canonical.push_str(&format!("{:?}", text));
+        }
+        return;
+    }
+    let is_element = xot.is_element(node);
+    if is_element {
+        let (name, namespace) = xot.name_ns_str(xot.get_element_name(node));
+        let mut attributes: Vec<String> = xot
+            .attributes(node)
+            .iter()
+            .filter_map(|(name, value)| {
+                let (local_name, namespace) = xot.name_ns_str(name);
+                if ignored.contains(&local_name) {
+                    return None;
+                }
+                let value = if GEOMETRY_ATTRIBUTES.contains(&local_name) {
+                    snap_to_grid(value)
+                } else if !references.is_empty() && REFERENCE_ATTRIBUTES.contains(&local_name) {
+                    resolve_references(value, references)
+                } else {
+                    value.clone()
+                };
+                Some(format!("{{{}}}{}={:?}", namespace, local_name, value))
+            })
+            .collect();
+        attributes.sort();
+        canonical.push_str(&format!(
+            "<{{{}}}{} {}",
+            namespace,
+            name,
+            attributes.join(" ")
+        ));
+    }
+    for child in xot.children(node) {
+        write_canonical(xot, child, ignored, references, canonical);
+    }
+    if is_element {
+        canonical.push('>');
+    }
+}
+
+/// Replaces the diagram part IDs in a space-separated reference list with
+/// the concepts they show, sorted so their order does not matter.
+fn resolve_references(value: &str, references: &HashMap<String, String>) -> String {
+    let mut resolved: Vec<&str> = value
+        .split_whitespace()
+        .map(|id| references.get(id).map_or("", String::as_str))
+        .collect();
+    resolved.sort_unstable();
+    resolved.join(" ")
+}
+
+fn snap_to_grid(value: &str) -> String {
+    match value.trim().parse::<f64>() {
+        Ok(number) => ((number / GRID_SIZE).round() as i64).to_string(),
+        Err(_) => value.to_string(),
+    }
+}
+
+/// Maps each new source view to a target view with the same content hash.
+/// Views already paired with a target view are not checked.
+pub fn find_identical_views<'t>(
+    source: &ArchiModel,
+    target: &'t ArchiModel,
+    views: &[MissingElementInfo],
+) -> Result<HashMap<Arc<str>, &'t ElementInfo>, Box<dyn Error>> {
+    let mut target_views: Vec<&ElementInfo> = target.view_map.values().collect();
+    target_views.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut target_hashes: HashMap<u64, &ElementInfo> = HashMap::new();
+    for view_info in target_views {
+        target_hashes
+            .entry(view_content_hash(&view_info.xml_string)?)
+            .or_insert(view_info);
+    }
+
+    let mut identical = HashMap::new();
+    for view in views.iter().filter(|view| view.target_id.is_none()) {
+        let Some(view_info) = source.view_map.get(&view.id) else {
+            continue;
+        };
+        if let Some(target_info) = target_hashes.get(&view_content_hash(&view_info.xml_string)?) {
+            identical.insert(view.id.clone(), *target_info);
+        }
+    }
+    Ok(identical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_content_hash_ignores_ids_and_geometry_noise() -> Result<(), Box<dyn Error>> {
+        let original = r#"<element xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance' xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1' type='1'>
+                    <bounds x='24' y='120' width='144' height='60'/>
+                    <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-2' archimateRelationship='relation-1'/>
+                </child>
+                <child xsi:type='archimate:DiagramObject' id='object-2' targetConnections='connection-1' archimateElement='element-2'/>
+            </element>"#;
+        let recreated = original
+            .replace("view-1", "view-9")
+            .replace("object-", "copy-")
+            .replace("connection-1", "link-1")
+            .replace("x='24'", "x='25'");
+        let changed = original.replace("element-2", "element-3");
+
+        let hash = view_content_hash(original)?;
+        assert_eq!(hash, view_content_hash(&recreated)?);
+        assert_ne!(hash, view_content_hash(&changed)?);
+        assert_ne!(hash, view_content_hash(&original.replace("type='1'", ""))?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_view_content_hash_compares_connection_ends() -> Result<(), Box<dyn Error>> {
+        let view = r#"<element xmlns:xsi='http://www.w3.org/2001/XMLSchema-instance' xsi:type='archimate:ArchimateDiagramModel' id='view-1' name='View'>
+                <child xsi:type='archimate:DiagramObject' id='object-1' archimateElement='element-1' targetConnections='connection-1'>
+                    <sourceConnection xsi:type='archimate:Connection' id='connection-1' source='object-1' target='object-1' archimateRelationship='relation-1'/>
+                </child>
+                <child xsi:type='archimate:DiagramObject' id='object-2' archimateElement='element-2'/>
+            </element>"#;
+        let reversed = view
+            .replace("source='object-1'", "source='object-2'")
+            .replace(
+                "id='object-2'",
+                "id='object-2' targetConnections='connection-1'",
+            )
+            .replace("targetConnections='connection-1'>", ">");
+        let redrawn = view.replace("target='object-1'", "target='object-2'");
+
+        let hash = view_content_hash(view)?;
+        assert_ne!(hash, view_content_hash(&reversed)?);
+        assert_ne!(hash, view_content_hash(&redrawn)?);
+        Ok(())
+    }
+}
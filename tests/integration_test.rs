@@ -1,6 +1,9 @@
 use std::fs;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
 
 #[test]
 fn test_interactive_view_selection() -> Result<(), Box<dyn std::error::Error>> {
@@ -26,6 +29,9 @@ fn test_interactive_view_selection() -> Result<(), Box<dyn std::error::Error>> {
     assert!(output_str.contains("Views in source that don't exist in target"));
     assert!(output_str.contains("Default View"));
     assert!(output_str.contains("Default_View"));
+    assert!(output_str.contains("(in folder: Views[diagrams])"));
+    assert!(output_str.contains("[1] Default View"));
+    assert!(output_str.contains("[2] Default_View"));
     assert!(output_str.contains("No views selected for copying."));
 
     Ok(())
@@ -62,6 +68,7 @@ fn test_cli_view_selection_verbose() -> Result<(), Box<dyn std::error::Error>> {
     assert!(output_str.contains("Successfully imported views and elements into target file"));
     assert!(output_str.contains("Successfully copied:"));
     assert!(output_str.contains("- 1 view"));
+    assert!(output_str.contains("- [ok] Default View"));
     assert!(output_str.contains(".found element:"));
     assert!(output_str.contains(".found relation:"));
     assert!(output_str.contains(".new elements"));
@@ -101,3 +108,2984 @@ fn test_cli_view_selection_non_verbose() -> Result<(), Box<dyn std::error::Error
 
     Ok(())
 }
+
+#[test]
+fn test_cli_all_with_exclude_pattern() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let test2_path = PathBuf::from("tests/test2.archimate");
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy(&test2_path, &temp_file)?;
+
+    let test1_path = PathBuf::from("tests/test1.archimate");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg(test1_path)
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--exclude-pattern")
+        .arg("Default_*")
+        .output()?;
+
+    let output_str = String::from_utf8(output.stdout)?;
+    let error_str = String::from_utf8(output.stderr)?;
+    println!(
+        "=== STDOUT ===\n{}\n=== STDERR ===\n{}",
+        output_str, error_str
+    );
+
+    assert!(output_str.contains("Creating view Default View"));
+    assert!(!output_str.contains("Creating view Default_View"));
+    assert!(output_str.contains("- 1 view"));
+
+    Ok(())
+}
+
+#[test]
+fn test_zipped_target_records_history() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let temp_file = temp_dir.path().join("temp.archimate");
+    {
+        let file = fs::File::create(&temp_file)?;
+        let mut zip = ZipWriter::new(file);
+        zip.start_file("model.xml", SimpleFileOptions::default())?;
+        zip.write_all(&fs::read("tests/test2.archimate")?)?;
+        zip.finish()?;
+    }
+
+    let test1_path = PathBuf::from("tests/test1.archimate");
+    for view in ["Default View", "Default_View"] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg(&test1_path)
+            .arg(&temp_file)
+            .arg("--view")
+            .arg(view)
+            .output()?;
+        assert!(output.status.success());
+    }
+
+    let mut archive = ZipArchive::new(fs::File::open(&temp_file)?)?;
+    let mut history = String::new();
+    archive
+        .by_name("history/imports.json")?
+        .read_to_string(&mut history)?;
+    let records: serde_json::Value = serde_json::from_str(&history)?;
+    let records = records.as_array().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["source"], "test1.archimate");
+    assert_eq!(records[0]["views"][0]["name"], "Default View");
+    assert_eq!(records[1]["views"][0]["name"], "Default_View");
+
+    Ok(())
+}
+
+#[test]
+fn test_undo() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::load_model;
+
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    {
+        let file = fs::File::create(&temp_file)?;
+        let mut zip = ZipWriter::new(file);
+        zip.start_file("model.xml", SimpleFileOptions::default())?;
+        zip.write_all(&fs::read("tests/test2.archimate")?)?;
+        zip.finish()?;
+    }
+    let read_model = || -> Result<String, Box<dyn std::error::Error>> {
+        let mut archive = ZipArchive::new(fs::File::open(&temp_file)?)?;
+        let mut xml = String::new();
+        archive.by_name("model.xml")?.read_to_string(&mut xml)?;
+        Ok(xml)
+    };
+    let concept_ids = |xml: &str| -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let model = load_model(xml)?;
+        let mut ids: Vec<String> = model
+            .element_map
+            .keys()
+            .chain(model.view_map.keys())
+            .map(|id| id.to_string())
+            .collect();
+        ids.sort();
+        Ok(ids)
+    };
+    let undo = || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("undo")
+            .arg(&temp_file)
+            .output()
+    };
+
+    let mut after_first = String::new();
+    for view in ["Default View", "Default_View"] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&temp_file)
+            .arg("--view")
+            .arg(view)
+            .output()?;
+        assert!(output.status.success());
+        if after_first.is_empty() {
+            after_first = read_model()?;
+        }
+    }
+    let journal = temp_dir.path().join("temp.archimate.undo.json");
+    assert!(journal.exists());
+
+    let output = undo()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Undid the import"));
+    assert!(!journal.exists());
+    assert_eq!(concept_ids(&read_model()?)?, concept_ids(&after_first)?);
+    let mut archive = ZipArchive::new(fs::File::open(&temp_file)?)?;
+    let mut history = String::new();
+    archive
+        .by_name("history/imports.json")?
+        .read_to_string(&mut history)?;
+    let records: serde_json::Value = serde_json::from_str(&history)?;
+    assert_eq!(records.as_array().unwrap().len(), 1);
+
+    // Only the last import can be undone
+    let output = undo()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("No import to undo"));
+    Ok(())
+}
+
+#[test]
+fn test_import_verifies_merged_model() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target = temp_dir.path().join("target.archimate");
+    // A copy of Default_View under another ID repeats its diagram object IDs
+    let content = fs::read_to_string("tests/test1.archimate")?
+        .replace("id-ccbd94c7a8ac4eba9c32e5193b3a6ffe", "id-other-view");
+    fs::write(&target, &content)?;
+    let import = |extra: Option<&str>| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&target)
+            .arg("--view")
+            .arg("Default_View")
+            .arg("--keep-duplicates")
+            .args(extra)
+            .output()
+    };
+
+    let output = import(None)?;
+    assert_eq!(output.status.code(), Some(8));
+    assert!(String::from_utf8(output.stdout)?
+        .contains("The merged model has issues the target did not have"));
+    assert!(String::from_utf8(output.stderr)?.contains("target file left unchanged"));
+    assert_eq!(fs::read_to_string(&target)?, content);
+
+    let output = import(Some("--no-verify"))?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Warning: writing the merged model despite"));
+    assert_ne!(fs::read_to_string(&target)?, content);
+    Ok(())
+}
+
+#[test]
+fn test_import_memory_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target)
+        .arg("--all")
+        .arg("--memory-stats")
+        .output()?;
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("Memory stats: source loaded in"));
+    assert!(stderr.contains("its index of 6 concepts and views refers to 30 strings"));
+    assert!(stderr.contains("Memory stats: target loaded in"));
+    assert!(!String::from_utf8(output.stdout)?.contains("Memory stats"));
+    Ok(())
+}
+
+#[test]
+fn test_strict_import() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source = temp_dir.path().join("source.archimate");
+    fs::write(
+        &source,
+        fs::read_to_string("tests/test1.archimate")?.replace(
+            r#"<element xsi:type="archimate:ApplicationComponent" name="Application Component" id="id-a0d52c37910b48b3a80c8b8be102d877"/>"#,
+            "",
+        ),
+    )?;
+    let target = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target)?;
+    let original = fs::read_to_string(&target)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source)
+        .arg(&target)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--strict")
+        .output()?;
+    assert_eq!(output.status.code(), Some(8));
+    let error_str = String::from_utf8(output.stderr)?;
+    assert!(error_str.contains(
+        "View Default View shows concepts missing from the source: id-a0d52c37910b48b3a80c8b8be102d877"
+    ));
+    assert!(error_str.contains("target file left unchanged"));
+    assert_eq!(fs::read_to_string(&target)?, original);
+    Ok(())
+}
+
+#[test]
+fn test_import_remaps_namespace_prefixes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source = temp_dir.path().join("source.archimate");
+    fs::write(
+        &source,
+        fs::read_to_string("tests/test1.archimate")?
+            .replace("xmlns:archimate=", "xmlns:am=")
+            .replace("archimate:", "am:"),
+    )?;
+    let target = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source)
+        .arg(&target)
+        .arg("--view")
+        .arg("Default_View")
+        .output()?;
+    assert!(output.status.success());
+    let merged = fs::read_to_string(&target)?;
+    assert!(merged.contains(r#"xsi:type="archimate:ApplicationComponent""#));
+    assert!(merged.contains(r#"xsi:type="archimate:ArchimateDiagramModel""#));
+    assert!(!merged.contains("am:"));
+    assert_eq!(merged.matches("xmlns:").count(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_import_refuses_same_file() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test1.archimate", &target)?;
+    let original = fs::read_to_string(&target)?;
+    let mut aliases = vec![temp_dir.path().join(".").join("target.archimate")];
+    #[cfg(unix)]
+    {
+        let link = temp_dir.path().join("link.archimate");
+        std::os::unix::fs::symlink(&target, &link)?;
+        aliases.push(link);
+    }
+
+    for source in aliases {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg(&source)
+            .arg(&target)
+            .arg("--all")
+            .output()?;
+        assert_eq!(output.status.code(), Some(8));
+        assert!(String::from_utf8(output.stderr)?.contains("are the same file; nothing to import"));
+        assert_eq!(fs::read_to_string(&target)?, original);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_import_fails_while_target_locked() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target)?;
+    let original = fs::read_to_string(&target)?;
+    let lock_path = temp_dir.path().join("target.archimate.lock");
+
+    let import = || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&target)
+            .arg("--view")
+            .arg("Default_View")
+            .output()
+    };
+    // Another run holding the lock
+    let lock = fs::File::create(&lock_path)?;
+    lock.lock()?;
+    let output = import()?;
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)?.contains("is being written by another run"));
+    assert_eq!(fs::read_to_string(&target)?, original);
+
+    lock.unlock()?;
+    assert!(import()?.status.success());
+    // Left for the next run, which locks the same file
+    assert!(lock_path.exists());
+    assert!(import()?.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_save_and_replay_selection() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let selection_path = temp_dir.path().join("selection.txt");
+
+    let test1_path = PathBuf::from("tests/test1.archimate");
+    let first_target = temp_dir.path().join("first.archimate");
+    fs::copy("tests/test2.archimate", &first_target)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg(&test1_path)
+        .arg(&first_target)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--save-selection")
+        .arg(&selection_path)
+        .output()?;
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&selection_path)?.contains("# Default_View"));
+
+    let second_target = temp_dir.path().join("second.archimate");
+    fs::copy("tests/test2.archimate", &second_target)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg(&test1_path)
+        .arg(&second_target)
+        .arg("--selection-file")
+        .arg(&selection_path)
+        .output()?;
+
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Creating view Default_View"));
+    assert!(!output_str.contains("Creating view Default View"));
+
+    Ok(())
+}
+
+#[test]
+fn test_import_subcommand() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+
+    let output_str = String::from_utf8(output.stdout)?;
+    let error_str = String::from_utf8(output.stderr)?;
+    assert!(output_str.contains("Creating view Default View"));
+    assert!(!error_str.contains("deprecated"));
+
+    Ok(())
+}
+
+#[test]
+fn test_legacy_invocation_warns() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+
+    let error_str = String::from_utf8(output.stderr)?;
+    assert!(output.status.success());
+    assert!(error_str.contains("invoking without a subcommand is deprecated"));
+
+    Ok(())
+}
+
+#[test]
+fn test_list_and_diff_subcommands() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg("tests/test2.archimate")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Views in tests/test2.archimate:"));
+
+    let temp_dir = TempDir::new()?;
+    let dot_file = temp_dir.path().join("views.dot");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg("tests/test1.archimate")
+        .arg("--export-dot")
+        .arg(&dot_file)
+        .output()?;
+    assert!(output.status.success());
+    let graph = fs::read_to_string(&dot_file)?;
+    assert!(graph.starts_with("digraph views {"));
+    assert!(graph.contains(
+        "\"id-ea1025087eeb4c608d070157218992bf\" -> \"id-a0d52c37910b48b3a80c8b8be102d877\";"
+    ));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("diff")
+        .arg("tests/test1.archimate")
+        .arg("tests/test2.archimate")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(
+        "[1] Default View (in folder: Views[diagrams]) - 2 elements (0 in target), 1 relation (0 in target)"
+    ));
+    assert!(output_str.contains("2 views missing from target"));
+
+    Ok(())
+}
+
+#[test]
+fn test_list_as_json() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::ElementInfo;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg("tests/test1.archimate")
+        .arg("--json")
+        .output()?;
+    assert!(output.status.success());
+    let views: Vec<ElementInfo> = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(views.len(), 2);
+    assert_eq!(&*views[0].name, "Default View");
+    assert_eq!(&*views[0].folder_path[0].name, "Views");
+    Ok(())
+}
+
+#[test]
+fn test_error_exit_codes() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::load_model;
+
+    let temp_dir = TempDir::new()?;
+    let malformed = temp_dir.path().join("malformed.archimate");
+    fs::write(&malformed, "<archimate:model><folder>")?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&malformed)
+        .output()?;
+    assert_eq!(output.status.code(), Some(5));
+    assert!(
+        String::from_utf8(output.stderr)?.starts_with("Error: the model is not well-formed XML")
+    );
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(temp_dir.path().join("missing.archimate"))
+        .output()?;
+    assert_eq!(output.status.code(), Some(3));
+
+    let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<archimate:model xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:archimate="http://www.archimatetool.com/archimate" name="Model" id="model-1">
+  <folder name="Application" id="folder-1" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="CRM"/>
+  </folder>
+</archimate:model>"#;
+    // A concept without an ID is given one instead of failing the load
+    let model = load_model(content)?;
+    let crm = model.elements().next().ok_or("element not loaded")?;
+    assert_eq!(&*crm.name, "CRM");
+    assert!(crm.id.starts_with("id-"));
+    assert_eq!(model.repaired.len(), 1);
+
+    let unnamed = temp_dir.path().join("unnamed.archimate");
+    fs::write(&unnamed, content.replace(r#"name="Application" "#, ""))?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("validate")
+        .arg(&unnamed)
+        .output()?;
+    assert_eq!(output.status.code(), Some(8));
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains(r#"- Application Component "CRM" has no id attribute"#));
+    assert!(stdout.contains("- Folder folder-1 has no name attribute"));
+
+    // Other commands warn about the generated IDs, which stay the same
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&unnamed)
+        .output()?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    let repaired = load_model(&fs::read_to_string(&unnamed)?)?;
+    assert!(stderr.contains(&format!(
+        r#"Warning: model file: Application Component "CRM" has no id attribute; gave it the ID {}"#,
+        repaired.repaired[0].id.as_deref().unwrap_or_default()
+    )));
+    Ok(())
+}
+
+#[test]
+fn test_library_concept_types() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::{load_model, Category, ConceptType, Layer};
+
+    let model = load_model(&fs::read_to_string("tests/test1.archimate")?)?;
+    let application: Vec<&str> = model
+        .elements()
+        .filter(|info| info.concept_type().and_then(ConceptType::layer) == Some(Layer::Application))
+        .map(|info| &*info.name)
+        .collect();
+    assert_eq!(application.len(), 2);
+    let relation = model.relations().next().unwrap();
+    assert_eq!(
+        relation.concept_type(),
+        Some(ConceptType::RealizationRelationship)
+    );
+    assert_eq!(
+        relation.concept_type().map(ConceptType::category),
+        Some(Category::Relationship)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_library_model_builder() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::{load_model, ConceptType, ModelBuilder};
+
+    let mut builder = ModelBuilder::with_seed("Generated", 1);
+    let crm = builder.element(ConceptType::ApplicationComponent, "CRM", None)?;
+    let database = builder.element(ConceptType::SystemSoftware, "Database", None)?;
+    let serving = builder.relationship(ConceptType::ServingRelationship, &database, &crm, None)?;
+    let view = builder.view("Generated View", None)?;
+    builder.show(&view, &crm, (20, 20, 120, 55))?;
+    builder.show(&view, &database, (20, 120, 120, 55))?;
+    builder.connect(&view, &serving)?;
+
+    let temp_dir = TempDir::new()?;
+    let source = temp_dir.path().join("generated.archimate");
+    fs::write(&source, builder.build()?.to_xml()?)?;
+    let target = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg(&source)
+        .arg(&target)
+        .arg("--view")
+        .arg("Generated View")
+        .output()?;
+    assert!(output.status.success());
+
+    let model = load_model(&fs::read_to_string(&target)?)?;
+    assert!(model.views().any(|view| &*view.name == "Generated View"));
+    assert!(model.element_map.contains_key(serving.as_str()));
+    assert_eq!(
+        model.element_map[database.as_str()].folder_path[0]
+            .folder_type
+            .as_deref(),
+        Some("technology")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_delete_views() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let model_file = temp_dir.path().join("model.archimate");
+    fs::copy("tests/test1.archimate", &model_file)?;
+    let original = fs::read_to_string(&model_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("delete")
+        .arg(&model_file)
+        .output()?;
+    assert_eq!(output.status.code(), Some(7));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("delete")
+        .arg(&model_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--dry-run")
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("[1] Default View"));
+    assert_eq!(fs::read_to_string(&model_file)?, original);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("delete")
+        .arg(&model_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--yes")
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Deleted 1 view from"));
+    let content = fs::read_to_string(&model_file)?;
+    assert!(!content.contains(r#"name="Default View""#));
+    assert!(content.contains(r#"name="Default_View""#));
+    // The concepts the view showed stay
+    assert!(content.contains(r#"id="id-a0d52c37910b48b3a80c8b8be102d877""#));
+    Ok(())
+}
+
+#[test]
+fn test_doctor() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("doctor")
+        .arg("tests/test1.archimate")
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Tools:"));
+    assert!(stdout.contains(" +- 3 element(s), 1 relationship(s), 2 view(s)"));
+    assert!(stdout.contains("No issues found"));
+
+    // Missing tools are reported without failing
+    let temp_dir = TempDir::new()?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("doctor")
+        .env("PATH", temp_dir.path())
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?
+        .contains(" +- curl: not found on the PATH; needed for importing from a URL"));
+
+    let broken = temp_dir.path().join("broken.archimate");
+    let content = fs::read_to_string("tests/test1.archimate")?.replace(
+        r#"target="id-c6691ea6aa8a4bc8b1e500260ca236c8"/>"#,
+        r#"target="id-missing"/>"#,
+    );
+    fs::write(&broken, content)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("doctor")
+        .arg(&broken)
+        .output()?;
+    assert_eq!(output.status.code(), Some(8));
+    assert!(String::from_utf8(output.stdout)?.contains(" +- 1 issue(s) found:"));
+    Ok(())
+}
+
+#[test]
+fn test_validate() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("validate")
+        .arg("tests/test1.archimate")
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("No issues found"));
+
+    let temp_dir = TempDir::new()?;
+    let broken = temp_dir.path().join("broken.archimate");
+    let content = fs::read_to_string("tests/test1.archimate")?.replace(
+        r#"target="id-c6691ea6aa8a4bc8b1e500260ca236c8"/>"#,
+        r#"target="id-missing"/>"#,
+    );
+    fs::write(&broken, content)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("validate")
+        .arg(&broken)
+        .arg("--json")
+        .output()?;
+    assert_eq!(output.status.code(), Some(8));
+    let issues: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(issues[0]["kind"], "dangling_reference");
+    assert_eq!(issues[0]["id"], "id-d92ce6e3a2ee45ce8269fa7a0399a882");
+    assert!(String::from_utf8(output.stderr)?.contains("1 issue found"));
+
+    let content =
+        fs::read_to_string("tests/test1.archimate")?.replace(r#"type="other""#, r#"type="misc""#);
+    fs::write(&broken, content)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("validate")
+        .arg(&broken)
+        .output()?;
+    assert_eq!(output.status.code(), Some(8));
+    assert!(
+        String::from_utf8(output.stdout)?.contains("Folder type misc is not an Archi folder type")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_list_stats() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg("tests/test1.archimate")
+        .arg("--stats")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("3 elements, 1 relationship, 2 views"));
+    assert!(output_str.contains("- Application Component: 1"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg("tests/test1.archimate")
+        .arg("--stats")
+        .arg("--json")
+        .output()?;
+    assert!(output.status.success());
+    let stats: archi_view_importer::api::ModelStats = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(stats.by_layer["Application"], 2);
+    assert_eq!(stats.by_folder["Views"], 2);
+    Ok(())
+}
+
+#[test]
+fn test_stats_command() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("stats")
+        .arg("tests/test1.archimate")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Statistics of tests/test1.archimate:"));
+    assert!(output_str.contains("3 elements, 1 relationship, 2 views"));
+    assert!(!output_str.contains("Default View"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("stats")
+        .arg("tests/test1.archimate")
+        .arg("--json")
+        .output()?;
+    assert!(output.status.success());
+    let stats: archi_view_importer::api::ModelStats = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(stats.elements, 3);
+    assert_eq!(stats.by_folder["Views"], 2);
+    Ok(())
+}
+
+#[test]
+fn test_library_find() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::{load_model, Search};
+
+    let model = load_model(&fs::read_to_string("tests/test1.archimate")?)?;
+    let found = model.find(&Search {
+        name_contains: Some("application".to_string()),
+        xsi_type: Some("archimate:ApplicationComponent".to_string()),
+        ..Default::default()
+    })?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(&*found[0].concept.name, "Application Component");
+    assert_eq!(found[0].folder, "Application");
+    let views: Vec<&str> = found[0].views.iter().map(|view| &*view.name).collect();
+    assert_eq!(views, ["Default View", "Default_View"]);
+    Ok(())
+}
+
+#[test]
+fn test_diff_as_json() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("diff")
+        .arg("tests/test1.archimate")
+        .arg("tests/test2.archimate")
+        .arg("--json")
+        .output()?;
+    assert!(output.status.success());
+    let diff: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(diff["missing_views"].as_array().map(Vec::len), Some(2));
+    assert_eq!(diff["missing_elements"][0]["type"], "Application Component");
+    assert_eq!(diff["missing_relations"].as_array().map(Vec::len), Some(1));
+    assert!(diff["conflicts"].as_array().is_some_and(Vec::is_empty));
+    Ok(())
+}
+
+#[test]
+fn test_seed_reproduces_output() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let mut results = Vec::new();
+    for name in ["first.archimate", "second.archimate"] {
+        let temp_file = temp_dir.path().join(name);
+        fs::copy("tests/test2.archimate", &temp_file)?;
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&temp_file)
+            .arg("--all")
+            .arg("--seed")
+            .arg("1234")
+            .output()?;
+        let output_str = String::from_utf8(output.stdout)?;
+        assert!(output_str.contains("Seed: 1234"));
+        results.push(fs::read(&temp_file)?);
+    }
+    assert_eq!(results[0], results[1]);
+
+    Ok(())
+}
+
+#[test]
+fn test_import_low_memory() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let mut results = Vec::new();
+    // A view is picked by name or by ID, as without --low-memory
+    for (name, low_memory, view) in [
+        ("full.archimate", false, "Default View"),
+        ("low.archimate", true, "Default View"),
+        (
+            "low-by-id.archimate",
+            true,
+            "id-ea1025087eeb4c608d070157218992bf",
+        ),
+    ] {
+        let temp_file = temp_dir.path().join(name);
+        fs::copy("tests/test2.archimate", &temp_file)?;
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"));
+        command
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&temp_file)
+            .arg("--view")
+            .arg(view)
+            .arg("--include-model-relations")
+            .arg("--seed")
+            .arg("1234");
+        if low_memory {
+            command.arg("--low-memory");
+        }
+        assert!(command.output()?.status.success());
+        results.push(fs::read_to_string(&temp_file)?);
+    }
+    assert_ne!(results[0], fs::read_to_string("tests/test2.archimate")?);
+    assert_eq!(results[0], results[1]);
+    assert_eq!(results[0], results[2]);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(temp_dir.path().join("low.archimate"))
+        .arg("--low-memory")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("--low-memory needs the views chosen"));
+    Ok(())
+}
+
+#[test]
+fn test_serve_mcp() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+    let call = |id: u32, name: &str, arguments: serde_json::Value| {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": arguments },
+        })
+        .to_string()
+    };
+    let messages = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#.to_string(),
+        call(
+            2,
+            "diff_models",
+            serde_json::json!({ "source": "tests/test1.archimate", "target": temp_file }),
+        ),
+        call(
+            3,
+            "import_views",
+            serde_json::json!({
+                "source": "tests/test1.archimate",
+                "target": temp_file,
+                "views": ["Default View"],
+            }),
+        ),
+        call(4, "list_views", serde_json::json!({ "model": temp_file })),
+    ];
+
+    let mut server = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("serve-mcp")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    let mut stdin = server.stdin.take().unwrap();
+    for message in &messages {
+        writeln!(stdin, "{}", message)?;
+    }
+    drop(stdin);
+    let output = server.wait_with_output()?;
+    assert!(output.status.success());
+
+    let responses: Vec<serde_json::Value> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2025-06-18");
+    let diff: serde_json::Value = serde_json::from_str(
+        responses[1]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap(),
+    )?;
+    assert_eq!(diff["missing_views"].as_array().map(Vec::len), Some(2));
+    assert_eq!(responses[2]["result"]["isError"], false);
+    let views = responses[3]["result"]["content"][0]["text"]
+        .as_str()
+        .unwrap();
+    assert!(views.contains("Default View"));
+    Ok(())
+}
+
+#[test]
+fn test_explain_imported_concepts() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--explain")
+        .output()?;
+
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(
+        "  + Application Component [Application Component]: An encapsulation of application functionality"
+    ));
+    assert!(output_str.contains("  + (unnamed) [Realization Relationship]:"));
+
+    Ok(())
+}
+
+#[test]
+fn test_paged_interactive_listing() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--page-size")
+        .arg("1")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(b"q\n2\n")?;
+    let output = child.wait_with_output()?;
+
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("[1] Default View"));
+    assert!(output_str.contains("-- 1 of 2 listed, Enter for more, q to stop listing --"));
+    assert!(!output_str.contains("[2] Default_View"));
+    assert!(output_str.contains("Creating view Default_View"));
+
+    Ok(())
+}
+
+#[test]
+fn test_match_views_by_name() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("diff")
+        .arg("tests/test1.archimate")
+        .arg("tests/test2.archimate")
+        .arg("--match-by")
+        .arg("name")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(!output_str.contains("] Default View"));
+    assert!(output_str.contains("1 view missing from target"));
+
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--match-by")
+        .arg("name")
+        .arg("--update")
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Default View [update]"));
+    assert!(output_str.contains("Updating view Default View"));
+
+    // The target view keeps its own ID but takes the source content
+    let content = fs::read_to_string(&temp_file)?;
+    assert_eq!(content.matches("name=\"Default View\"").count(), 1);
+    assert!(content.contains("id=\"id-b3832661dc8d4fc4974d3381416286bd\""));
+    assert!(!content.contains("id=\"id-ea1025087eeb4c608d070157218992bf\""));
+    assert!(content.contains("id-23aca3da0ede46fcbb3124e68fcad485"));
+
+    // A view matched by name replaces its target view without --update too
+    fs::copy("tests/test2.archimate", &temp_file)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--match-by")
+        .arg("name")
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    assert!(String::from_utf8(output.stdout)?.contains("Updating view Default View"));
+    assert_eq!(fs::read_to_string(&temp_file)?, content);
+
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_folder() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--snapshot-folder")
+        .output()?;
+    assert!(output.status.success());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&temp_file)
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    let snapshot_line = output_str
+        .lines()
+        .find(|line| line.contains("Default_View"))
+        .unwrap();
+    assert!(snapshot_line.contains("Views[diagrams] > Imports > 20"));
+
+    Ok(())
+}
+
+#[test]
+fn test_skip_identical_views() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    let recreated = fs::read_to_string("tests/test1.archimate")?
+        .replace("id-ea1025087eeb4c608d070157218992bf", "id-recreated");
+    fs::write(&temp_file, &recreated)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(
+        "Skipping view Default View: identical to target view Default View (id-recreated)"
+    ));
+    assert!(output_str.contains("No new views to copy from source to target."));
+    assert_eq!(fs::read_to_string(&temp_file)?, recreated);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--keep-duplicates")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Creating view Default View"));
+
+    Ok(())
+}
+
+#[test]
+fn test_output_format_from_extension() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let output_file = temp_dir.path().join("merged.archimate.zip");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg("tests/test2.archimate")
+        .arg("--all")
+        .arg("--output")
+        .arg(&output_file)
+        .arg("--verbose")
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(" +- Target format: plain XML"));
+    assert!(output_str.contains(" +- Output format: zipped XML"));
+    assert!(output_str.contains("Written as zipped XML"));
+
+    // The target is left alone and the output is a zipped model
+    let target = fs::read_to_string("tests/test2.archimate")?;
+    assert!(!target.contains("Default_View"));
+    let mut archive = ZipArchive::new(fs::File::open(&output_file)?)?;
+    let mut model = String::new();
+    archive.by_name("model.xml")?.read_to_string(&mut model)?;
+    assert!(model.contains("Default_View"));
+    assert!(archive.by_name("history/imports.json").is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_conflicts_file_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    let conflicts_file = temp_dir.path().join("conflicts.yaml");
+    let target = fs::read_to_string("tests/test2.archimate")?.replace(
+        r#"<folder name="Application" id="id-8520096ad9134e6cb5d5b65f969ebac2" type="application"/>"#,
+        r#"<folder name="Application" id="id-8520096ad9134e6cb5d5b65f969ebac2" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Renamed Component" id="id-a0d52c37910b48b3a80c8b8be102d877"/>
+  </folder>"#,
+    );
+    fs::write(&temp_file, &target)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--write-conflicts")
+        .arg(&conflicts_file)
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Wrote 1 conflict(s)"));
+    assert_eq!(fs::read_to_string(&temp_file)?, target);
+
+    let conflicts = fs::read_to_string(&conflicts_file)?;
+    assert!(conflicts.contains("id: id-a0d52c37910b48b3a80c8b8be102d877"));
+    fs::write(
+        &conflicts_file,
+        conflicts.replace("resolution: unresolved", "resolution: take-source"),
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--resolutions")
+        .arg(&conflicts_file)
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains(r#"name="Application Component""#));
+    assert!(!content.contains("Renamed Component"));
+
+    Ok(())
+}
+
+#[test]
+fn test_three_way_merge_with_base() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    let base_file = temp_dir.path().join("base.archimate");
+    let conflicts_file = temp_dir.path().join("conflicts.yaml");
+    let with_component = |name: &str| -> Result<String, Box<dyn std::error::Error>> {
+        Ok(fs::read_to_string("tests/test2.archimate")?.replace(
+            r#"<folder name="Application" id="id-8520096ad9134e6cb5d5b65f969ebac2" type="application"/>"#,
+            &format!(
+                r#"<folder name="Application" id="id-8520096ad9134e6cb5d5b65f969ebac2" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="{}" id="id-a0d52c37910b48b3a80c8b8be102d877"/>
+  </folder>"#,
+                name
+            ),
+        ))
+    };
+    fs::write(&base_file, with_component("Application Component")?)?;
+    fs::write(&temp_file, with_component("Renamed Component")?)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--base")
+        .arg(&base_file)
+        .arg("--on-conflict")
+        .arg("theirs")
+        .arg("--write-conflicts")
+        .arg(&conflicts_file)
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Merge: Application Component only changed in target since base"));
+    assert!(output_str.contains("No conflicts found."));
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains("Renamed Component"));
+    assert!(content.contains("id-ea1025087eeb4c608d070157218992bf"));
+
+    Ok(())
+}
+
+#[test]
+fn test_view_conflicts() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    // The target's copy of Default_View was renamed since
+    let content = fs::read_to_string("tests/test1.archimate")?
+        .replace(r#"name="Default_View""#, r#"name="Old View""#);
+    let import = |extra: &[&str], input: &[u8]| -> Result<_, Box<dyn std::error::Error>> {
+        fs::write(&temp_file, &content)?;
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&temp_file)
+            .arg("--all")
+            .args(extra)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()?;
+        child.stdin.take().unwrap().write_all(input)?;
+        let output = child.wait_with_output()?;
+        Ok((
+            String::from_utf8(output.stdout)?,
+            fs::read_to_string(&temp_file)?,
+        ))
+    };
+
+    let (output, written) = import(&[], b"")?;
+    assert!(output.contains("Keeping the target version of 1 view(s) that differ from the source"));
+    assert!(written.contains("Old View"));
+
+    let (output, written) = import(&["--on-conflict", "theirs"], b"")?;
+    assert!(output.contains("Updating view Default_View"));
+    assert!(!written.contains("Old View"));
+
+    let (output, written) = import(&["--on-conflict", "prompt"], b"t\n")?;
+    assert!(output
+        .contains("Conflict: view Default_View differs between models, keeping target version"));
+    assert!(written.contains("Old View"));
+
+    let (output, written) = import(&["--on-conflict", "prompt"], b"s\n")?;
+    assert!(output.contains("Updating view Default_View"));
+    assert!(!written.contains("Old View"));
+    Ok(())
+}
+
+#[test]
+fn test_id_prefix() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--snapshot-folder")
+        .arg("--fresh-ids")
+        .arg("--id-prefix")
+        .arg("teamA-")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains(r#"<folder name="Imports" id="teamA-id-"#));
+    assert!(content.contains(r#"name="Default_View" id="teamA-id-"#));
+    assert!(!content.contains("id-ccbd94c7"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--id-prefix")
+        .arg("team A")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("Invalid ID prefix"));
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_views() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--keep-duplicates")
+        .arg("--rename")
+        .arg("Default View=Team View")
+        .arg("--rename-template")
+        .arg("{name} (imported)")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Renaming view Default View to Team View"));
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains(r#"name="Team View" id="id-ea1025087eeb4c608d070157218992bf""#));
+    assert!(content.contains(r#"name="Default_View (imported)""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_on_name_collision() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--keep-duplicates")
+        .arg("--on-name-collision")
+        .arg("model-name")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(
+        "View name Default View is taken in target, importing as Default View (testmodel)"
+    ));
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains(r#"name="Default View (testmodel)""#));
+    assert!(content.contains(r#"name="Default_View" id="#));
+
+    Ok(())
+}
+
+#[test]
+fn test_into_folder() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--all")
+        .arg("--keep-duplicates")
+        .arg("--into-folder")
+        .arg("Views > Imported > 2024-Q3")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&temp_file)?;
+    assert_eq!(content.matches(r#"<folder name="Imported""#).count(), 1);
+    assert_eq!(content.matches(r#"<folder name="2024-Q3""#).count(), 1);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&temp_file)
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    for view_name in ["Default_View", "Default View"] {
+        assert!(output_str.lines().any(|line| line.contains(view_name)
+            && line.contains("Views[diagrams] > Imported > 2024-Q3")));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_visual_only_import() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    let target = fs::read_to_string("tests/test2.archimate")?.replace(
+        r#"<folder name="Application" id="id-8520096ad9134e6cb5d5b65f969ebac2" type="application"/>"#,
+        r#"<folder name="Application" id="id-8520096ad9134e6cb5d5b65f969ebac2" type="application">
+    <element xsi:type="archimate:ApplicationComponent" name="Application Component" id="component-1"/>
+    <element xsi:type="archimate:ApplicationInterface" name="Application Interface" id="interface-1"/>
+  </folder>"#,
+    );
+    fs::write(&temp_file, &target)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--visual-only")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str
+        .contains("Skipping relation id-d92ce6e3a2ee45ce8269fa7a0399a882: no matching relation"));
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains(r#"name="Default_View""#));
+    assert!(content.contains(r#"archimateElement="component-1""#));
+    assert!(content.contains(r#"archimateElement="interface-1""#));
+    assert!(!content.contains("id-a0d52c37910b48b3a80c8b8be102d877"));
+    assert!(!content.contains("id-d92ce6e3a2ee45ce8269fa7a0399a882"));
+
+    Ok(())
+}
+
+fn write_archive(
+    path: &std::path::Path,
+    model: &str,
+    entries: &[(&str, &[u8])],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut zip = ZipWriter::new(fs::File::create(path)?);
+    zip.start_file("model.xml", SimpleFileOptions::default())?;
+    zip.write_all(model.as_bytes())?;
+    for (name, content) in entries {
+        zip.start_file(*name, SimpleFileOptions::default())?;
+        zip.write_all(content)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[test]
+fn test_import_password_protected_archives() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source_file = temp_dir.path().join("source.archimate.zip");
+    let target_file = temp_dir.path().join("target.archimate.zip");
+    let options = SimpleFileOptions::default().with_aes_encryption(zip::AesMode::Aes256, "s3cret");
+    for (path, model) in [
+        (&source_file, "tests/test1.archimate"),
+        (&target_file, "tests/test2.archimate"),
+    ] {
+        let mut zip = ZipWriter::new(fs::File::create(path)?);
+        zip.start_file("model.xml", options)?;
+        zip.write_all(&fs::read(model)?)?;
+        zip.finish()?;
+    }
+
+    // Without the password the source cannot be read
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--all")
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("is password-protected"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--all")
+        .arg("--source-password")
+        .arg("s3cret")
+        .env("ARCHI_TARGET_PASSWORD", "s3cret")
+        .output()?;
+    assert!(output.status.success());
+
+    // The target is rewritten encrypted
+    let mut archive = ZipArchive::new(fs::File::open(&target_file)?)?;
+    assert!(archive.by_name("model.xml").is_err());
+    let mut model = String::new();
+    archive
+        .by_name_decrypt("model.xml", b"s3cret")?
+        .read_to_string(&mut model)?;
+    assert!(model.contains("Default_View"));
+
+    // Commands that only read take the passwords too
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&source_file)
+        .env("ARCHI_SOURCE_PASSWORD", "s3cret")
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Default View"));
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("diff")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--source-password")
+        .arg("s3cret")
+        .arg("--target-password")
+        .arg("s3cret")
+        .output()?;
+    assert!(output.status.success());
+
+    // So does a common ancestor, which falls back to the source's password
+    let base_file = temp_dir.path().join("base.archimate.zip");
+    let mut zip = ZipWriter::new(fs::File::create(&base_file)?);
+    zip.start_file(
+        "model.xml",
+        SimpleFileOptions::default().with_aes_encryption(zip::AesMode::Aes256, "ancestor"),
+    )?;
+    zip.write_all(&fs::read("tests/test2.archimate")?)?;
+    zip.finish()?;
+    for base_password in [None, Some("ancestor")] {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"));
+        command
+            .arg("import")
+            .arg(&source_file)
+            .arg(&target_file)
+            .arg("--all")
+            .arg("--base")
+            .arg(&base_file)
+            .arg("--output")
+            .arg(temp_dir.path().join("merged.archimate"))
+            .env("ARCHI_SOURCE_PASSWORD", "s3cret")
+            .env("ARCHI_TARGET_PASSWORD", "s3cret");
+        if let Some(base_password) = base_password {
+            command.arg("--base-password").arg(base_password);
+        }
+        let output = command.output()?;
+        let stderr = String::from_utf8(output.stderr)?;
+        match base_password {
+            None => assert!(stderr.contains("wrong password"), "{}", stderr),
+            Some(_) => assert!(output.status.success(), "{}", stderr),
+        }
+    }
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("validate")
+        .arg(&target_file)
+        .output()?;
+    assert!(String::from_utf8(output.stderr)?.contains("is password-protected"));
+    Ok(())
+}
+
+#[test]
+fn test_import_into_archive_with_other_model_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.zip");
+    let mut zip = ZipWriter::new(fs::File::create(&target_file)?);
+    zip.start_file("Enterprise/Enterprise.xml", SimpleFileOptions::default())?;
+    zip.write_all(&fs::read("tests/test2.archimate")?)?;
+    zip.finish()?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--all")
+        .arg("--no-history")
+        .output()?;
+    assert!(output.status.success());
+
+    // The model is rewritten in the entry it was read from
+    let mut archive = ZipArchive::new(fs::File::open(&target_file)?)?;
+    assert_eq!(
+        archive.file_names().collect::<Vec<_>>(),
+        vec!["Enterprise/Enterprise.xml"]
+    );
+    let mut model = String::new();
+    archive
+        .by_name("Enterprise/Enterprise.xml")?
+        .read_to_string(&mut model)?;
+    assert!(model.contains("Default_View"));
+    Ok(())
+}
+
+#[test]
+fn test_import_keeps_archive_compression() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate.zip");
+    write_archive(
+        &target_file,
+        &fs::read_to_string("tests/test2.archimate")?,
+        &[],
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--all")
+        .output()?;
+    assert!(output.status.success());
+
+    let mut archive = ZipArchive::new(fs::File::open(&target_file)?)?;
+    assert_eq!(
+        archive.by_name("model.xml")?.compression(),
+        zip::CompressionMethod::Deflated
+    );
+    assert_eq!(
+        archive.by_name("history/imports.json")?.compression(),
+        zip::CompressionMethod::Deflated
+    );
+    Ok(())
+}
+
+#[test]
+fn test_library_import() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::ConflictPolicy;
+    use archi_view_importer::{import, ImportOptions};
+
+    let options = ImportOptions::new()
+        .views(["Default View"])
+        .include_model_relations(true)
+        .on_conflict(ConflictPolicy::Theirs);
+    let report = import(
+        &fs::read_to_string("tests/test1.archimate")?,
+        &fs::read_to_string("tests/test2.archimate")?,
+        &options,
+    )?;
+    assert_eq!(report.imported(), 1);
+    assert!(report.model.contains("name=\"Default View\""));
+    Ok(())
+}
+
+#[test]
+fn test_library_plan() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::{apply, load_model, plan};
+    use archi_view_importer::ImportOptions;
+
+    let source = fs::read_to_string("tests/test1.archimate")?;
+    let target = fs::read_to_string("tests/test2.archimate")?;
+    let mut import_plan = plan(&source, &target, &ImportOptions::new())?;
+    assert!(import_plan
+        .views
+        .iter()
+        .any(|view| &*view.name == "Default View"));
+
+    // Vetoing every view leaves the target as it was
+    import_plan.views.clear();
+    assert!(import_plan.is_empty());
+    let report = apply(import_plan)?;
+    assert_eq!(report.imported(), 0);
+    assert_eq!(
+        load_model(&report.model)?.views().count(),
+        load_model(&target)?.views().count()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_library_write_to() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::{import, ImportOptions};
+
+    let report = import(
+        &fs::read_to_string("tests/test1.archimate")?,
+        &fs::read_to_string("tests/test2.archimate")?,
+        &ImportOptions::new().views(["Default View"]),
+    )?;
+    let mut plain = Vec::new();
+    report.write_to(&mut plain, false)?;
+    assert_eq!(String::from_utf8(plain)?, report.model);
+
+    let mut zipped = Vec::new();
+    report.write_to(&mut zipped, true)?;
+    let temp_dir = TempDir::new()?;
+    let merged = temp_dir.path().join("merged.zip");
+    fs::write(&merged, zipped)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&merged)
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Default View"));
+    Ok(())
+}
+
+#[test]
+fn test_library_read_model() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::{read_model, read_model_xml, ModelBuilder};
+    use archi_view_importer::{import, ImportOptions};
+    use std::io::Cursor;
+
+    let mut zipped = Vec::new();
+    ModelBuilder::with_seed("Empty", 1)
+        .build()?
+        .write_to(&mut zipped, true)?;
+    assert_eq!(read_model(Cursor::new(zipped))?.views().count(), 0);
+
+    let source = read_model_xml(fs::File::open("tests/test1.archimate")?, None)?;
+    let target = read_model_xml(fs::File::open("tests/test2.archimate")?, None)?;
+    let report = import(
+        &source,
+        &target,
+        &ImportOptions::new().views(["Default View"]),
+    )?;
+    assert_eq!(report.imported(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_library_model_iterators() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::load_model;
+
+    let content = fs::read_to_string("tests/test1.archimate")?;
+    let model = load_model(&content)?;
+    assert!(model.views().any(|view| &*view.name == "Default View"));
+    assert!(model
+        .elements()
+        .all(|element| !element.xsi_type.ends_with("Relationship")));
+    assert!(model
+        .relations()
+        .all(|relation| relation.xsi_type.ends_with("Relationship")));
+    assert!(model
+        .folders()
+        .any(|folder| folder.path() == "Views[diagrams]"));
+    Ok(())
+}
+
+#[test]
+fn test_library_model_query() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::load_model;
+
+    let content = fs::read_to_string("tests/test1.archimate")?;
+    let model = load_model(&content)?;
+    let views = model.query("//element[@xsi:type='archimate:ArchimateDiagramModel']")?;
+    assert_eq!(
+        views.len(),
+        model
+            .views()
+            .filter(|view| &*view.xsi_type == "archimate:ArchimateDiagramModel")
+            .count()
+    );
+    assert!(model.query("//element[@xsi:type=").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_library_models_across_threads() -> Result<(), Box<dyn std::error::Error>> {
+    use archi_view_importer::api::{diff_models, load_model, MatchBy};
+
+    let source = load_model(&fs::read_to_string("tests/test1.archimate")?)?;
+    let missing_views = std::thread::scope(|scope| {
+        let handles: Vec<_> = ["tests/test1.archimate", "tests/test2.archimate"]
+            .into_iter()
+            .map(|path| {
+                let source = &source;
+                scope.spawn(move || -> Result<usize, String> {
+                    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+                    let target = load_model(&content).map_err(|e| e.to_string())?;
+                    let diff =
+                        diff_models(source, &target, MatchBy::Id).map_err(|e| e.to_string())?;
+                    Ok(diff.missing_views.len())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    assert_eq!(missing_views, [0, 2]);
+
+    // A loaded model can move to another thread
+    let count = std::thread::spawn(move || source.views().count())
+        .join()
+        .unwrap();
+    assert!(count > 0);
+    Ok(())
+}
+
+#[test]
+fn test_copy_images_between_archives() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source_file = temp_dir.path().join("source.archimate");
+    let target_file = temp_dir.path().join("target.archimate");
+    let source = fs::read_to_string("tests/test1.archimate")?
+        .replace(
+            r#"id="id-23aca3da0ede46fcbb3124e68fcad485""#,
+            r#"id="id-23aca3da0ede46fcbb3124e68fcad485" imagePath="images/a.png""#,
+        )
+        .replace(
+            r#"id="id-ac2971115dd7403093902c77b663e5ce""#,
+            r#"id="id-ac2971115dd7403093902c77b663e5ce" imagePath="images/c.png""#,
+        );
+    write_archive(
+        &source_file,
+        &source,
+        &[
+            ("images/a.png", b"first image"),
+            ("images/c.png", b"second image"),
+        ],
+    )?;
+    write_archive(
+        &target_file,
+        &fs::read_to_string("tests/test2.archimate")?,
+        &[("images/b.png", b"first image")],
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--all")
+        .arg("--keep-duplicates")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Copied 1 image(s)"));
+
+    let mut archive = ZipArchive::new(fs::File::open(&target_file)?)?;
+    let mut content = Vec::new();
+    archive.by_name("images/c.png")?.read_to_end(&mut content)?;
+    assert_eq!(content, b"second image");
+    assert!(archive.by_name("images/a.png").is_err());
+    let mut model = String::new();
+    archive.by_name("model.xml")?.read_to_string(&mut model)?;
+    assert!(model.contains(r#"imagePath="images/b.png""#));
+    assert!(model.contains(r#"imagePath="images/c.png""#));
+    assert!(!model.contains("images/a.png"));
+
+    Ok(())
+}
+
+#[test]
+fn test_import_sketch_and_canvas_views() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source_file = temp_dir.path().join("source.archimate");
+    let target_file = temp_dir.path().join("target.archimate");
+    let source = fs::read_to_string("tests/test1.archimate")?
+        .replace(
+            r#"xmlns:archimate="http://www.archimatetool.com/archimate""#,
+            r#"xmlns:archimate="http://www.archimatetool.com/archimate" xmlns:canvas="http://www.archimatetool.com/canvas""#,
+        )
+        .replace(
+            r#"<folder name="Views" id="id-7903a3496ea044d8b829cb8f8fd2b400" type="diagrams">"#,
+            r#"<folder name="Views" id="id-7903a3496ea044d8b829cb8f8fd2b400" type="diagrams">
+    <element xsi:type="archimate:SketchModel" name="Whiteboard" id="sketch-1">
+      <child xsi:type="archimate:SketchModelSticky" name="Idea" id="sticky-1"/>
+      <child xsi:type="archimate:SketchModelActor" name="User" id="actor-1">
+        <sourceConnection xsi:type="archimate:DiagramModelConnection" id="connection-1" source="actor-1" target="sticky-1"/>
+      </child>
+    </element>
+    <element xsi:type="canvas:CanvasModel" name="Canvas" id="canvas-1">
+      <child xsi:type="canvas:CanvasModelBlock" id="block-1"/>
+    </element>"#,
+        );
+    fs::write(&source_file, &source)?;
+    fs::copy("tests/test2.archimate", &target_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("diff")
+        .arg(&source_file)
+        .arg(&target_file)
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Whiteboard [sketch]"));
+    assert!(output_str.contains("Canvas [canvas]"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Whiteboard")
+        .arg("--view")
+        .arg("Canvas")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(r#"id="sticky-1""#));
+    assert!(content.contains(r#"id="connection-1""#));
+    assert!(content.contains(r#"id="block-1""#));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&target_file)
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Whiteboard [sketch]"));
+    assert!(output_str.contains("Canvas [canvas]"));
+
+    Ok(())
+}
+
+#[test]
+fn test_provenance() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--provenance")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&temp_file)?;
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let property = format!(
+        r#"<property key="imported-from" value="test1.archimate @ {}"/>"#,
+        today
+    );
+    // The view, both elements and the relation between them
+    assert_eq!(content.matches(&property).count(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_prune_views() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let temp_file = temp_dir.path().join("temp.archimate");
+    fs::copy("tests/test2.archimate", &temp_file)?;
+
+    // Without confirmation nothing is pruned
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--prune")
+        .arg("Views")
+        .stdin(std::process::Stdio::null())
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Views in target under Views that don't exist in source:"));
+    assert!(output_str.contains("[1] Default View (in folder: Views[diagrams])"));
+    assert!(output_str.contains("Views not pruned."));
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(content.contains("id-b3832661dc8d4fc4974d3381416286bd"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&temp_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--prune")
+        .arg("Views")
+        .arg("--yes")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Pruned 1 view(s)"));
+    assert!(output_str.contains("No views selected for copying."));
+    assert!(output_str.contains("Removed 1 view from target file."));
+    let content = fs::read_to_string(&temp_file)?;
+    assert!(!content.contains("id-b3832661dc8d4fc4974d3381416286bd"));
+    assert!(content.contains(r#"name="Default_View""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_sync_models() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let left_file = temp_dir.path().join("left.archimate");
+    let right_file = temp_dir.path().join("right.archimate");
+    let with_view = |model: &str, id: &str, name: &str| {
+        model.replace(
+            r#"<folder name="Views" id="id-7903a3496ea044d8b829cb8f8fd2b400" type="diagrams">"#,
+            &format!(
+                r#"<folder name="Views" id="id-7903a3496ea044d8b829cb8f8fd2b400" type="diagrams">
+    <element xsi:type="archimate:ArchimateDiagramModel" name="{}" id="{}">
+      <child xsi:type="archimate:Note" id="{}-note"/>
+    </element>"#,
+                name, id, id
+            ),
+        )
+    };
+    let model = fs::read_to_string("tests/test1.archimate")?;
+    fs::write(&left_file, with_view(&model, "team-a-view", "Team A View"))?;
+    // The right model recreated Default View under another ID
+    let recreated = model.replace("id-ea1025087eeb4c608d070157218992bf", "id-recreated");
+    fs::write(
+        &right_file,
+        with_view(&recreated, "team-b-view", "Team B View"),
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("sync")
+        .arg(&left_file)
+        .arg(&right_file)
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str
+        .contains("Skipping view Default View: identical to view Default View (id-recreated)"));
+    assert!(output_str.contains("[1] Team A View"));
+    assert!(output_str.contains("[1] Team B View"));
+
+    let left = fs::read_to_string(&left_file)?;
+    assert!(left.contains(r#"name="Team B View""#));
+    assert!(!left.contains("id-recreated"));
+    let right = fs::read_to_string(&right_file)?;
+    assert!(right.contains(r#"name="Team A View""#));
+    assert!(!right.contains("id-ea1025087eeb4c608d070157218992bf"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("sync")
+        .arg(&left_file)
+        .arg(&right_file)
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("No views to sync; both models have the same views."));
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_properties() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source_file = temp_dir.path().join("source.archimate");
+    let target_file = temp_dir.path().join("target.archimate");
+    let component = r#"<element xsi:type="archimate:ApplicationComponent" name="Application Component" id="id-a0d52c37910b48b3a80c8b8be102d877"/>"#;
+    let with_properties =
+        |properties: &str| component.replace("/>", &format!(">{}</element>", properties));
+    let model = fs::read_to_string("tests/test1.archimate")?;
+    assert!(model.contains(component));
+    fs::write(
+        &source_file,
+        model.replace(
+            component,
+            &with_properties(
+                r#"<property key="status" value="approved"/><property key="tier" value="1"/>"#,
+            ),
+        ),
+    )?;
+    // The target has the component with other properties
+    fs::write(
+        &target_file,
+        model
+            .replace(
+                component,
+                &with_properties(r#"<property key="status" value="draft"/><property key="owner" value="Team B"/>"#),
+            )
+            .replace("id-ccbd94c7a8ac4eba9c32e5193b3a6ffe", "id-removed"),
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default_View")
+        // The target's copy of Default_View has another ID but the same content
+        .arg("--keep-duplicates")
+        // and so the same diagram object IDs, which the copy repeats
+        .arg("--no-verify")
+        .arg("--merge-properties")
+        .arg("prefer-source")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(
+        "Merged properties of Application Component [Application Component] (prefer-source)"
+    ));
+    assert!(!output_str.contains("Conflict:"));
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(
+        r#"<property key="status" value="approved"/><property key="owner" value="Team B"/><property key="tier" value="1"/>"#
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_documentation() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source_file = temp_dir.path().join("source.archimate");
+    let target_file = temp_dir.path().join("target.archimate");
+    let component = r#"<element xsi:type="archimate:ApplicationComponent" name="Application Component" id="id-a0d52c37910b48b3a80c8b8be102d877"/>"#;
+    let with_documentation = |text: &str| {
+        component.replace(
+            "/>",
+            &format!("><documentation>{}</documentation></element>", text),
+        )
+    };
+    let model = fs::read_to_string("tests/test1.archimate")?;
+    fs::write(
+        &source_file,
+        model.replace(component, &with_documentation("Upstream notes")),
+    )?;
+    fs::write(
+        &target_file,
+        model
+            .replace(component, &with_documentation("Local notes"))
+            .replace("id-ccbd94c7a8ac4eba9c32e5193b3a6ffe", "id-removed"),
+    )?;
+
+    let import = |policy: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg(&source_file)
+            .arg(&target_file)
+            .arg("--view")
+            .arg("Default_View")
+            // The target's copy of Default_View has another ID but the same content
+            .arg("--keep-duplicates")
+            // and so the same diagram object IDs, which the copy repeats
+            .arg("--no-verify")
+            .arg("--merge-documentation")
+            .arg(policy)
+            .arg("--output")
+            .arg(temp_dir.path().join(format!("{}.archimate", policy)))
+            .output()
+    };
+
+    let output = import("keep")?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains(
+        "Warning: documentation of Application Component [Application Component] differs between models, keeping target text"
+    ));
+    assert!(!output_str.contains("Conflict:"));
+
+    let output = import("append")?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(temp_dir.path().join("append.archimate"))?;
+    assert!(content.contains(
+        "<documentation>Local notes\n\n--- Imported from testmodel ---\nUpstream notes</documentation>"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_import_from_exchange_format() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg("tests/exchange.xml")
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Exchange View"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/exchange.xml")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Exchange View")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(
+        r#"xsi:type="archimate:ApplicationComponent" name="Portal" id="id-exchange-portal""#
+    ));
+    assert!(content.contains(r#"xsi:type="archimate:ServingRelationship""#));
+    assert!(content.contains(r#"<folder name="Exchange""#));
+    assert!(content.contains(r#"archimateRelationship="id-exchange-serving""#));
+    assert!(content.contains(r#"targetConnections="id-exchange-connection""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_export_exchange_format() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let export_file = temp_dir.path().join("export.xml");
+    let target_file = temp_dir.path().join("target.archimate");
+    let merged_file = temp_dir.path().join("merged.xml");
+    fs::copy("tests/test2.archimate", &target_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("export")
+        .arg("tests/test1.archimate")
+        .arg(&export_file)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&export_file)?;
+    assert!(content.contains(r#"xmlns="http://www.opengroup.org/xsd/archimate/3.0/""#));
+    assert!(content.contains(r#"xsi:type="Realization""#));
+    assert!(content.contains("<label>Views</label>"));
+    assert!(!content.contains("Default_View"));
+    assert!(!content.contains(r#"xsi:type="Capability""#));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default_View")
+        .arg("--export-exchange")
+        .arg(&merged_file)
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&merged_file)?;
+    assert!(content.contains("<name>Default_View</name>"));
+    assert!(content.contains("<name>Default View</name>"));
+
+    Ok(())
+}
+
+#[test]
+fn test_import_from_coarchi_working_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/coarchi")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Team View")
+        .arg("--verbose")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Source format: coArchi working tree"));
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(r#"<folder name="Team" id="id-coarchi-team">"#));
+    assert!(content.contains(r#"archimateElement="id-coarchi-portal""#));
+    assert!(content.contains(r#"source="id-coarchi-orders" target="id-coarchi-portal""#));
+    assert!(content.contains("<documentation>Customer facing</documentation>"));
+    assert!(content.contains("<content>Reviewed</content>"));
+
+    Ok(())
+}
+
+fn copy_directory(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_directory(&path, &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_import_into_coarchi_working_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repository = temp_dir.path().join("repository");
+    copy_directory(std::path::Path::new("tests/coarchi"), &repository)?;
+    let portal_file = "model/application/ApplicationComponent_id-coarchi-portal.xml";
+    let view_file = "model/diagrams/id-coarchi-team/ArchimateDiagramModel_id-coarchi-view.xml";
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&repository)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Written as coArchi working tree"));
+
+    // Fragments of concepts the import did not touch are left as they were
+    for file in [portal_file, view_file, "model/folder.xml"] {
+        assert_eq!(
+            fs::read_to_string(repository.join(file))?,
+            fs::read_to_string(std::path::Path::new("tests/coarchi").join(file))?
+        );
+    }
+    let view = fs::read_to_string(
+        repository
+            .join("model/diagrams/ArchimateDiagramModel_id-ea1025087eeb4c608d070157218992bf.xml"),
+    )?;
+    assert!(view.contains(r#"name="Default View""#));
+    assert!(view.contains(
+        r#"<archimateElement xsi:type="archimate:ApplicationComponent" href="../application/ApplicationComponent_id-a0d52c37910b48b3a80c8b8be102d877.xml#id-a0d52c37910b48b3a80c8b8be102d877"/>"#
+    ));
+    assert!(repository
+        .join("model/relations/RealizationRelationship_id-d92ce6e3a2ee45ce8269fa7a0399a882.xml")
+        .is_file());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("list")
+        .arg(&repository)
+        .output()?;
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Default View"));
+    assert!(output_str.contains("Team View"));
+
+    Ok(())
+}
+
+#[test]
+fn test_import_source_from_git_revision() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let repository = temp_dir.path();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(repository)
+            .output()
+    };
+    git(&["init", "-q"])?;
+    fs::create_dir(repository.join("models"))?;
+    fs::copy(
+        "tests/test1.archimate",
+        repository.join("models/enterprise.archimate"),
+    )?;
+    git(&["add", "models"])?;
+    git(&["commit", "-q", "-m", "Release model"])?;
+    git(&["tag", "release"])?;
+    // The checkout moves on; the import still reads the tagged version
+    fs::remove_file(repository.join("models/enterprise.archimate"))?;
+    let target_file = repository.join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&target_file)
+        .arg("--source-ref")
+        .arg("release:models/enterprise.archimate")
+        .arg("--view")
+        .arg("Default View")
+        .current_dir(repository)
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Source: models/enterprise.archimate at release"));
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(r#"name="Default View" id="id-ea1025087eeb4c608d070157218992bf""#));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&target_file)
+        .arg("--source-ref")
+        .arg("release:models/missing.archimate")
+        .current_dir(repository)
+        .output()?;
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)?.contains("Cannot read release:models/missing.archimate")
+    );
+
+    // A path starting with ./ is relative to the working directory, and the
+    // repository is read without the git command
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&target_file)
+        .arg("--source-ref")
+        .arg("release:./enterprise.archimate")
+        .arg("--view")
+        .arg("Default View")
+        .env("PATH", "")
+        .current_dir(repository.join("models"))
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(r#"name="Default View" id="id-ea1025087eeb4c608d070157218992bf""#));
+
+    // The reference names the source, which is not given again
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("models/enterprise.archimate")
+        .arg(&target_file)
+        .arg("--source-ref")
+        .arg("release:models/enterprise.archimate")
+        .current_dir(repository)
+        .output()?;
+    assert!(!output.status.success());
+
+    // The blob is read into memory, so an import ending early leaves nothing
+    // behind in the temporary directory
+    let temp_files = TempDir::new()?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("missing-target.archimate")
+        .arg("--source-ref")
+        .arg("release:models/enterprise.archimate")
+        .env("TMPDIR", temp_files.path())
+        .current_dir(repository)
+        .output()?;
+    assert!(!output.status.success());
+    assert_eq!(fs::read_dir(temp_files.path())?.count(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_import_source_from_url() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    let model = fs::read("tests/test1.archimate")?;
+    let server = std::thread::spawn(move || -> std::io::Result<String> {
+        let (mut stream, _) = listener.accept()?;
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            let read = stream.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            model.len()
+        )?;
+        stream.write_all(&model)?;
+        Ok(String::from_utf8_lossy(&request).to_string())
+    });
+
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let url = format!("http://{}/models/enterprise.archimate", address);
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&url)
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .env("ARCHI_SOURCE_TOKEN", "secret-token")
+        .output()?;
+    assert!(output.status.success());
+    let request = server.join().unwrap()?;
+    assert!(request.starts_with("GET /models/enterprise.archimate "));
+    assert!(request.contains("Authorization: Bearer secret-token"));
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(r#"name="Default View" id="id-ea1025087eeb4c608d070157218992bf""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_extract_view_bundle() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let source_file = temp_dir.path().join("source.archimate");
+    let bundle_file = temp_dir.path().join("default.viewpkg");
+    let source = fs::read_to_string("tests/test1.archimate")?
+        .replace(
+            r#"id="id-23aca3da0ede46fcbb3124e68fcad485""#,
+            r#"id="id-23aca3da0ede46fcbb3124e68fcad485" imagePath="images/a.png""#,
+        )
+        .replace(
+            r#"id="id-ac2971115dd7403093902c77b663e5ce""#,
+            r#"id="id-ac2971115dd7403093902c77b663e5ce" imagePath="images/c.png""#,
+        );
+    write_archive(
+        &source_file,
+        &source,
+        &[
+            ("images/a.png", b"first image"),
+            ("images/c.png", b"second image"),
+        ],
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("extract")
+        .arg(&source_file)
+        .arg(&bundle_file)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Extracted 1 view, 2 elements and 1 relation to"));
+
+    let mut archive = ZipArchive::new(fs::File::open(&bundle_file)?)?;
+    let mut content = Vec::new();
+    archive.by_name("images/a.png")?.read_to_end(&mut content)?;
+    assert_eq!(content, b"first image");
+    assert!(archive.by_name("images/c.png").is_err());
+    let mut manifest = String::new();
+    archive
+        .by_name("bundle.json")?
+        .read_to_string(&mut manifest)?;
+    assert!(manifest.contains(r#""name": "Default View""#));
+    assert!(manifest.contains(r#""folder_path": "Views[diagrams]""#));
+    let mut model = String::new();
+    archive.by_name("model.xml")?.read_to_string(&mut model)?;
+    assert!(model.contains(r#"id="id-a0d52c37910b48b3a80c8b8be102d877""#));
+    assert!(!model.contains("Default_View"));
+
+    // A bundle is a source like any other model
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&bundle_file)
+        .arg(&target_file)
+        .arg("--all")
+        .arg("--output")
+        .arg(temp_dir.path().join("output.zip"))
+        .output()?;
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("Copied 1 image(s)"));
+
+    Ok(())
+}
+
+#[test]
+fn test_import_from_view_bundle() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let bundle_file = temp_dir.path().join("default.viewpkg");
+    let target_file = temp_dir.path().join("target.archimate");
+    write_archive(
+        &target_file,
+        &fs::read_to_string("tests/test2.archimate")?,
+        &[],
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("extract")
+        .arg("tests/test1.archimate")
+        .arg(&bundle_file)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    assert!(output.status.success());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&bundle_file)
+        .arg(&target_file)
+        .arg("--all")
+        .output()?;
+    assert!(output.status.success());
+    let output_str = String::from_utf8(output.stdout)?;
+    assert!(output_str.contains("Bundle of 1 view extracted from test1.archimate"));
+    assert!(output_str.contains("[ok] Default View"));
+
+    let mut archive = ZipArchive::new(fs::File::open(&target_file)?)?;
+    let mut history = String::new();
+    archive
+        .by_name("history/imports.json")?
+        .read_to_string(&mut history)?;
+    let records: serde_json::Value = serde_json::from_str(&history)?;
+    assert_eq!(
+        records[0]["source"],
+        "default.viewpkg (bundle from test1.archimate)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_export_csv() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let model_csv = temp_dir.path().join("model-csv");
+    let imported_csv = temp_dir.path().join("imported-csv");
+
+    for (directory, scope) in [(&model_csv, "model"), (&imported_csv, "imported")] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("import")
+            .arg("tests/test1.archimate")
+            .arg(&target_file)
+            .arg("--view")
+            .arg("Default View")
+            .arg("--output")
+            .arg(temp_dir.path().join("output.archimate"))
+            .arg("--export-csv")
+            .arg(directory)
+            .arg("--csv-scope")
+            .arg(scope)
+            .output()?;
+        assert!(output.status.success());
+    }
+
+    let elements = fs::read_to_string(model_csv.join("elements.csv"))?;
+    assert!(
+        elements.starts_with("\"ID\",\"Type\",\"Name\",\"Documentation\",\"Specialization\"\r\n")
+    );
+    assert!(elements.contains("\"ArchimateModel\""));
+    assert!(elements.contains("\"id-a0d52c37910b48b3a80c8b8be102d877\",\"ApplicationComponent\",\"Application Component\",\"\",\"\""));
+    assert!(elements.contains("\"Business Role\""));
+
+    let elements = fs::read_to_string(imported_csv.join("elements.csv"))?;
+    assert!(elements.contains("\"Application Component\""));
+    assert!(elements.contains("\"Application Interface\""));
+    assert!(!elements.contains("\"Business Role\""));
+    let relations = fs::read_to_string(imported_csv.join("relations.csv"))?;
+    assert!(relations.contains("\"id-d92ce6e3a2ee45ce8269fa7a0399a882\",\"RealizationRelationship\",\"\",\"\",\"id-a0d52c37910b48b3a80c8b8be102d877\",\"id-c6691ea6aa8a4bc8b1e500260ca236c8\",\"\""));
+    assert!(imported_csv.join("properties.csv").is_file());
+
+    Ok(())
+}
+
+#[test]
+fn test_import_csv_elements() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let csv_file = temp_dir.path().join("elements.csv");
+    fs::write(
+        &csv_file,
+        "Type,Name,Documentation,Folder,owner\r\n\
+         BusinessRole,Business Role,Handles requests,,Sales\r\n\
+         DataObject,Customer,Master data,Reference Data,Data office\r\n",
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--csv-elements")
+        .arg(&csv_file)
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Created 1 and updated 1 element(s) from"));
+
+    let content = fs::read_to_string(&target_file)?;
+    assert_eq!(content.matches("name=\"Business Role\"").count(), 1);
+    assert!(content.contains("<documentation>Handles requests</documentation>"));
+    assert!(content.contains("<property key=\"owner\" value=\"Sales\"/>"));
+    assert!(content.contains("name=\"Reference Data\""));
+    assert!(content.contains("xsi:type=\"archimate:DataObject\" name=\"Customer\""));
+    assert!(content.contains("name=\"Default View\""));
+    Ok(())
+}
+
+#[test]
+fn test_export_plantuml() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let imported = temp_dir.path().join("imported");
+    let exported = temp_dir.path().join("exported");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--export-plantuml")
+        .arg(&imported)
+        .output()?;
+    assert!(output.status.success());
+    let diagram = fs::read_to_string(imported.join("Default View.puml"))?;
+    assert!(diagram.starts_with("@startuml\n!include <archimate/Archimate>\n"));
+    assert!(diagram.contains(
+        "Application_Component(id_23aca3da0ede46fcbb3124e68fcad485, \"Application Component\")"
+    ));
+    assert!(diagram.contains("Rel_Realization(id_23aca3da0ede46fcbb3124e68fcad485, id_4afa0022910f44ada8998626bfcaf6ee, \"\")"));
+    assert_eq!(fs::read_dir(&imported)?.count(), 1);
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("export")
+        .arg("tests/test1.archimate")
+        .arg(&exported)
+        .arg("--format")
+        .arg("plantuml")
+        .output()?;
+    assert!(output.status.success());
+    assert!(exported.join("Default View.puml").is_file());
+    assert!(exported.join("Default_View.puml").is_file());
+    Ok(())
+}
+
+#[test]
+fn test_export_graphml() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let imported = temp_dir.path().join("imported.graphml");
+    let exported = temp_dir.path().join("exported.graphml");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--export-graphml")
+        .arg(&imported)
+        .arg("--graphml-scope")
+        .arg("imported")
+        .output()?;
+    assert!(output.status.success());
+    let graph = fs::read_to_string(&imported)?;
+    assert!(graph.contains("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"));
+    assert!(graph.contains("<node id=\"id-a0d52c37910b48b3a80c8b8be102d877\">"));
+    assert!(graph.contains("<data key=\"layer\">Application</data>"));
+    assert!(graph.contains("<edge id=\"id-d92ce6e3a2ee45ce8269fa7a0399a882\" source=\"id-a0d52c37910b48b3a80c8b8be102d877\" target=\"id-c6691ea6aa8a4bc8b1e500260ca236c8\">"));
+    assert!(!graph.contains("Business Role"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("export")
+        .arg(&target_file)
+        .arg(&exported)
+        .arg("--format")
+        .arg("graphml")
+        .output()?;
+    assert!(output.status.success());
+    let graph = fs::read_to_string(&exported)?;
+    assert!(graph.contains("<data key=\"label\">Business Role</data>"));
+    assert!(graph.contains("<data key=\"layer\">Business</data>"));
+    Ok(())
+}
+
+#[test]
+fn test_export_cypher() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let imported = temp_dir.path().join("imported.cypher");
+    let exported = temp_dir.path().join("exported.cypher");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--export-cypher")
+        .arg(&imported)
+        .arg("--cypher-scope")
+        .arg("imported")
+        .output()?;
+    assert!(output.status.success());
+    let cypher = fs::read_to_string(&imported)?;
+    assert!(cypher.contains(
+        "MERGE (n:Element {id: 'id-a0d52c37910b48b3a80c8b8be102d877'}) SET n:ApplicationComponent"
+    ));
+    assert!(cypher
+        .contains("MERGE (s)-[r:REALIZATION {id: 'id-d92ce6e3a2ee45ce8269fa7a0399a882'}]->(t)"));
+    assert!(!cypher.contains("Business Role"));
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("export")
+        .arg(&target_file)
+        .arg(&exported)
+        .arg("--format")
+        .arg("cypher")
+        .output()?;
+    assert!(output.status.success());
+    let cypher = fs::read_to_string(&exported)?;
+    assert!(cypher.contains("SET n:BusinessRole, n.type = 'BusinessRole', n.layer = 'Business', n.name = 'Business Role';"));
+    Ok(())
+}
+
+#[test]
+fn test_export_and_import_json_model() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let json_file = temp_dir.path().join("model.json");
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("export")
+        .arg("tests/test1.archimate")
+        .arg(&json_file)
+        .arg("--format")
+        .arg("json")
+        .output()?;
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_file)?)?;
+    let views = json["folders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|folder| folder["type"] == "diagrams")
+        .unwrap();
+    assert_eq!(views["views"][0]["name"], "Default View");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&json_file)
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .output()?;
+    assert!(output.status.success());
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains("name=\"Default View\""));
+    assert!(content.contains("name=\"Application Component\""));
+    Ok(())
+}
+
+#[test]
+fn test_export_sqlite() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let script = temp_dir.path().join("model.sql");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--export-sqlite")
+        .arg(&script)
+        .output()?;
+    assert!(output.status.success());
+    let sql = fs::read_to_string(&script)?;
+    assert!(sql.contains("CREATE TABLE IF NOT EXISTS view_objects"));
+    assert!(sql.contains(
+        "'id-a0d52c37910b48b3a80c8b8be102d877', 'ApplicationComponent', 'Application Component'"
+    ));
+    assert!(sql.contains("'Business Role'"));
+
+    // Without the sqlite3 shell a database is refused before the target
+    // is touched
+    let empty_path = TempDir::new()?;
+    let before = fs::read_to_string(&target_file)?;
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--all")
+        .arg("--export-sqlite")
+        .arg(temp_dir.path().join("model.db"))
+        .env("PATH", empty_path.path())
+        .output()?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)?.contains("the sqlite3 shell could not be run"));
+    assert_eq!(fs::read_to_string(&target_file)?, before);
+
+    let database = temp_dir.path().join("model.db");
+    for _ in 0..2 {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+            .arg("export")
+            .arg("tests/test1.archimate")
+            .arg(&database)
+            .arg("--format")
+            .arg("sqlite")
+            .output()?;
+        assert!(output.status.success());
+    }
+    let output = std::process::Command::new("sqlite3")
+        .arg(&database)
+        .arg("SELECT v.name, count(*) FROM view_objects o JOIN views v ON v.id = o.view_id AND v.model_id = o.model_id WHERE o.concept_id = 'id-a0d52c37910b48b3a80c8b8be102d877' GROUP BY v.name ORDER BY v.name;")
+        .output()?;
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "Default View|1\nDefault_View|1\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_emit_jarchi() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let script = temp_dir.path().join("import.ajs");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--emit-jarchi")
+        .arg(&script)
+        .output()?;
+    assert!(output.status.success());
+    // The target is left for Archi to change
+    assert_eq!(
+        fs::read_to_string(&target_file)?,
+        fs::read_to_string("tests/test2.archimate")?
+    );
+    let script = fs::read_to_string(&script)?;
+    assert!(script
+        .contains("model.createElement(\"application-component\", \"Application Component\")"));
+    assert!(script.contains("model.createRelationship(\"realization-relationship\""));
+    assert!(script.contains("model.createArchimateView(\"Default View\")"));
+    Ok(())
+}
+
+#[test]
+fn test_import_report() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let report = temp_dir.path().join("report.html");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--report")
+        .arg(&report)
+        .output()?;
+    assert!(output.status.success());
+    let html = fs::read_to_string(&report)?;
+    assert!(html.contains("<tr><td>Default View</td><td>imported</td></tr>"));
+    assert!(html.contains("<h2>New elements (2)</h2>"));
+    assert!(html.contains("<h3>Application Component (1)</h3>"));
+    assert!(html.contains("<code>id-a0d52c37910b48b3a80c8b8be102d877</code>"));
+    assert!(html.contains("<h2>New relations (1)</h2>\n<h3>Realization Relationship (1)</h3>"));
+    // The target's own concepts are not new
+    assert!(!html.contains("Business Role"));
+    // The imported view is drawn
+    assert!(html.contains("<h3>Default View</h3>\n<div class=\"diagram\">\n<svg "));
+    assert!(html.contains("<title>Default View</title>"));
+    Ok(())
+}
+
+#[test]
+fn test_import_markdown_report() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    let report = temp_dir.path().join("report.md");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg("tests/test1.archimate")
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Default View")
+        .arg("--into-folder")
+        .arg("Imported")
+        .arg("--report-md")
+        .arg(&report)
+        .output()?;
+    assert!(output.status.success());
+    let markdown = fs::read_to_string(&report)?;
+    assert!(markdown.contains("| Default View | imported |"));
+    assert!(markdown.contains("## Folders created (1)\n\n- Views[diagrams] > Imported\n"));
+    assert!(markdown.contains("## Elements imported (2)"));
+    assert!(markdown.contains(
+        "| Realization Relationship | Relations[relations] |  | `id-d92ce6e3a2ee45ce8269fa7a0399a882` |"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_import_lenient_exchange_format() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = TempDir::new()?;
+    let target_file = temp_dir.path().join("target.archimate");
+    fs::copy("tests/test2.archimate", &target_file)?;
+    // Another tool's take on the exchange format
+    let source_file = temp_dir.path().join("vendor.xml");
+    fs::write(
+        &source_file,
+        fs::read_to_string("tests/exchange.xml")?
+            .replace("/archimate/3.0/", "/archimate/3.1/")
+            .replace(r#"xsi:type="Serving""#, r#"xsi:type="ServingRelationship""#)
+            .replace(r#" w="120" h="55"/>"#, "/>"),
+    )?;
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_archi-view-importer"))
+        .arg("import")
+        .arg(&source_file)
+        .arg(&target_file)
+        .arg("--view")
+        .arg("Exchange View")
+        .arg("--lenient-exchange")
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("Warning: source file: the model is in the namespace"));
+    assert!(stdout.contains(
+        "Warning: source file: relationship id-exchange-serving has the type ServingRelationship instead of Serving"
+    ));
+    assert!(
+        stdout.contains("Warning: source file: diagram node id-exchange-node-1 has no w attribute")
+    );
+    let content = fs::read_to_string(&target_file)?;
+    assert!(content.contains(r#"xsi:type="archimate:ServingRelationship""#));
+    assert!(content.contains(r#"<bounds x="24" y="24" width="-1" height="-1"/>"#));
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! JavaScript bindings of the importer, for running imports in the browser
+//! on models the user drops onto a page. Models are passed and returned as
+//! XML text; archives are unpacked by the page.
+
+use archi_view_importer::api::{load_model, ConflictPolicy, MatchBy, NameCollision};
+use archi_view_importer::ImportOptions;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The options of `importViews`, named as the `import` command's options.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Options {
+    views: Vec<String>,
+    folders: Vec<String>,
+    match_by: Option<String>,
+    update: bool,
+    keep_duplicates: bool,
+    recursive: bool,
+    /// The seed of an earlier import, as a string, since JavaScript numbers
+    /// can't hold every seed.
+    seed: Option<String>,
+    on_conflict: Option<String>,
+    on_name_collision: Option<String>,
+    include_model_relations: bool,
+    no_relations: bool,
+    visual_only: bool,
+    create_stubs: bool,
+    into_folder: Option<String>,
+    reuse_by_name: bool,
+    fresh_ids: bool,
+    provenance: Option<String>,
+}
+
+impl TryFrom<Options> for ImportOptions {
+    type Error = String;
+
+    fn try_from(options: Options) -> Result<Self, Self::Error> {
+        let mut import_options = ImportOptions::new()
+            .views(options.views)
+            .folders(options.folders)
+            .update(options.update)
+            .keep_duplicates(options.keep_duplicates)
+            .recursive(options.recursive)
+            .include_model_relations(options.include_model_relations)
+            .no_relations(options.no_relations)
+            .visual_only(options.visual_only)
+            .create_stubs(options.create_stubs)
+            .reuse_by_name(options.reuse_by_name)
+            .fresh_ids(options.fresh_ids);
+        if let Some(match_by) = options.match_by {
+            import_options = import_options.match_by(match_by.parse::<MatchBy>()?);
+        }
+        if let Some(seed) = options.seed {
+            let seed = seed
+                .parse()
+                .map_err(|_| format!("invalid seed '{}'", seed))?;
+            import_options = import_options.seed(seed);
+        }
+        if let Some(policy) = options.on_conflict {
+            import_options = import_options.on_conflict(policy.parse::<ConflictPolicy>()?);
+        }
+        if let Some(policy) = options.on_name_collision {
+            import_options = import_options.on_name_collision(policy.parse::<NameCollision>()?);
+        }
+        if let Some(folder) = options.into_folder {
+            import_options = import_options.into_folder(folder);
+        }
+        if let Some(provenance) = options.provenance {
+            import_options = import_options.provenance(provenance);
+        }
+        Ok(import_options)
+    }
+}
+
+#[derive(Serialize)]
+struct ViewSummary {
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    view_type: String,
+    folder: String,
+}
+
+#[derive(Serialize)]
+struct ViewResult {
+    name: String,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ImportResult {
+    model: String,
+    views: Vec<ViewResult>,
+    elements: usize,
+    relations: usize,
+    seed: String,
+}
+
+fn js_error(e: impl std::fmt::Display) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+/// The views of a model, as `{ id, name, type, folder }` objects ordered by
+/// folder and name.
+#[wasm_bindgen(js_name = listViews)]
+pub fn list_views(model: &str) -> Result<JsValue, JsError> {
+    let model = load_model(model).map_err(js_error)?;
+    let views: Vec<ViewSummary> = model
+        .views()
+        .map(|view| ViewSummary {
+            id: view.id.to_string(),
+            name: view.name.to_string(),
+            view_type: view.xsi_type.to_string(),
+            folder: view
+                .folder_path
+                .iter()
+                .map(|folder| &*folder.name)
+                .collect::<Vec<_>>()
+                .join(" > "),
+        })
+        .collect();
+    serde_wasm_bindgen::to_value(&views).map_err(js_error)
+}
+
+/// How the target differs from the source, as the `diff --json` command
+/// prints it. `matchBy` is `"id"` (the default) or `"name"`.
+#[wasm_bindgen]
+pub fn diff(source: &str, target: &str, match_by: Option<String>) -> Result<JsValue, JsError> {
+    let match_by = match match_by {
+        Some(match_by) => match_by.parse::<MatchBy>().map_err(js_error)?,
+        None => MatchBy::Id,
+    };
+    let diff = archi_view_importer::diff(source, target, match_by).map_err(js_error)?;
+    serde_wasm_bindgen::to_value(&diff).map_err(js_error)
+}
+
+/// Imports views from the source model into the target model and returns
+/// `{ model, views, elements, relations, seed }`, `model` being the merged
+/// target XML. Without `views` or `folders` in the options, every view the
+/// target lacks is imported.
+#[wasm_bindgen(js_name = importViews)]
+pub fn import_views(source: &str, target: &str, options: JsValue) -> Result<JsValue, JsError> {
+    let options: Options = if options.is_undefined() || options.is_null() {
+        Options::default()
+    } else {
+        serde_wasm_bindgen::from_value(options).map_err(js_error)?
+    };
+    let options = ImportOptions::try_from(options).map_err(js_error)?;
+    let report = archi_view_importer::import(source, target, &options).map_err(js_error)?;
+    let result = ImportResult {
+        model: report.model,
+        views: report
+            .views
+            .into_iter()
+            .map(|view| ViewResult {
+                name: view.name,
+                error: view.error,
+            })
+            .collect(),
+        elements: report.elements,
+        relations: report.relations,
+        seed: report.seed.to_string(),
+    };
+    serde_wasm_bindgen::to_value(&result).map_err(js_error)
+}